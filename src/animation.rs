@@ -1,18 +1,29 @@
-use std::time::Duration;
+use std::{
+	f32::consts::{FRAC_PI_2, TAU},
+	time::Duration,
+};
 
 use bevy::{
 	pbr::{NotShadowCaster, NotShadowReceiver},
 	prelude::*,
+	utils::{HashMap, HashSet},
+};
+use bevy_easings::{
+	Ease, EaseFunction, EasingChainComponent, EasingComponent, EasingType,
 };
-use bevy_easings::{Ease, EaseFunction, EasingType};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-	control::{Action, ControlEvent},
-	level::{ChangeEvent, Coords, Id, LevelEntity},
-	materials::Materials,
-	meshes::Meshes,
-	models::Models,
-	update::NextActor,
+	assets::{GameAssets, MaterialKind, MeshKind, ModelKind},
+	audio::{self, AudioTracks},
+	control::{direction_action, Action, ControlEvent},
+	level::{
+		self, Change, ChangeEvent, CharacterColor, Coords, Id, Level,
+		LevelEntity, LevelObject, Offset, Tile,
+	},
+	photo_mode::HiddenInPhotoMode,
+	settings::Settings,
+	update::{LevelStatusEvent, NextActor, SeekRecapEvent},
 };
 
 /// Component for animating an object in a level.
@@ -30,6 +41,317 @@ pub struct Portal {
 	pub coords: Coords,
 }
 
+/// Component for animating a tile's model in a level.
+#[derive(Component)]
+#[require(Transform, Visibility)]
+pub struct TileModel {
+	pub coords: Coords,
+	/// The model's resting transform.
+	pub base: Transform,
+}
+
+/// Component for animating the stairs in a level.
+#[derive(Component)]
+pub struct Stairs {
+	pub coords: Coords,
+}
+
+/// How far below the floor the bottom of a pit is drawn.
+const PIT_DEPTH: f32 = 0.9;
+
+/// How far below the floor the surface of water is drawn.
+const WATER_DEPTH: f32 = 0.15;
+
+/// How far below the floor the surface of lava is drawn.
+const LAVA_DEPTH: f32 = 0.2;
+
+/// How far below the floor the bed of a platform's track is drawn.
+const TRACK_DEPTH: f32 = 0.3;
+
+/// The height of the center of a platform, which is flush with the floor.
+const PLATFORM_Z: f32 = -0.1;
+
+/// The marker drawn on top of `tile` at `coords`, for tiles that look like
+/// floor but aren't. The stairs' marker also needs a [`Stairs`] component to
+/// be animated.
+pub fn tile_marker(
+	assets: &GameAssets,
+	tile: Tile,
+	coords: Coords,
+) -> Option<impl Bundle> {
+	let marker = |mesh, material, z| {
+		(
+			Mesh3d(assets.mesh(mesh)),
+			MeshMaterial3d(assets.material(material)),
+			coords.transform(z),
+		)
+	};
+	match tile {
+		Tile::Stairs => {
+			Some(marker(MeshKind::Stairs, MaterialKind::Stairs, 0.0))
+		}
+		Tile::Ice => Some(marker(MeshKind::Highlight, MaterialKind::Ice, 0.01)),
+		Tile::Pit => {
+			Some(marker(MeshKind::Highlight, MaterialKind::Pit, -PIT_DEPTH))
+		}
+		Tile::Track { .. } => Some(marker(
+			MeshKind::Highlight,
+			MaterialKind::Track,
+			-TRACK_DEPTH,
+		)),
+		Tile::Turntable { .. } => {
+			Some(marker(MeshKind::Highlight, MaterialKind::Turntable, 0.01))
+		}
+		Tile::Cracked { stepped_on: false } => {
+			Some(marker(MeshKind::Highlight, MaterialKind::Cracks, 0.01))
+		}
+		Tile::Cracked { stepped_on: true } => {
+			Some(marker(MeshKind::Highlight, MaterialKind::DeepCracks, 0.01))
+		}
+		Tile::Water { .. } => Some(marker(
+			MeshKind::Highlight,
+			MaterialKind::Water,
+			-WATER_DEPTH,
+		)),
+		Tile::Lava { bridged: false } => {
+			Some(marker(MeshKind::Highlight, MaterialKind::Lava, -LAVA_DEPTH))
+		}
+		Tile::Lava { bridged: true } => {
+			Some(marker(MeshKind::Highlight, MaterialKind::Crust, 0.01))
+		}
+		Tile::Floor { .. } | Tile::Wall | Tile::Void => None,
+	}
+}
+
+/// Component for the marker drawn on top of a tile in a level. See
+/// [`tile_marker`].
+#[derive(Component)]
+pub struct TileMarker {
+	pub coords: Coords,
+}
+
+/// Spawns the model and marker of `tile` at `coords` in the level, whichever
+/// it has, with its model turned `quarter_turns` times.
+pub fn spawn_tile(
+	commands: &mut Commands,
+	assets: &GameAssets,
+	tile: Tile,
+	coords: Coords,
+	quarter_turns: u32,
+) {
+	if let Some((scene, z)) = assets.tile(tile) {
+		let mut transform = coords.transform(z);
+		transform.rotate_z(quarter_turns as f32 * FRAC_PI_2);
+		commands.spawn((
+			LevelEntity,
+			TileModel {
+				coords,
+				base: transform,
+			},
+			SceneRoot(scene),
+			transform,
+		));
+	}
+	if let Some(marker) = tile_marker(assets, tile, coords) {
+		let mut marker =
+			commands.spawn((LevelEntity, TileMarker { coords }, marker));
+		if tile == Tile::Stairs {
+			marker.insert(Stairs { coords });
+		}
+	}
+}
+
+/// Respawns the model and marker of each tile that changes kind, like a pit
+/// filled by a prop or restored by undoing. Cracked floor that collapses
+/// crumbles away into the pit.
+pub fn animate_tile_changes(
+	mut commands: Commands,
+	mut change_events: EventReader<ChangeEvent>,
+	assets: Res<GameAssets>,
+	tile_query: Query<(Entity, &TileModel)>,
+	marker_query: Query<(Entity, &TileMarker)>,
+) {
+	for change in change_events.read() {
+		let eased = eased(change);
+		for (coords, tile_change) in &change.tile_changes {
+			if eased
+				&& matches!(tile_change.from, Tile::Cracked { .. })
+				&& tile_change.to == Tile::Pit
+			{
+				rubble(&mut commands, &assets, *coords);
+			}
+			let models = tile_query
+				.iter()
+				.filter(|(_, tile)| tile.coords == *coords)
+				.map(|(entity, _)| entity);
+			let markers = marker_query
+				.iter()
+				.filter(|(_, marker)| marker.coords == *coords)
+				.map(|(entity, _)| entity);
+			for entity in models.chain(markers) {
+				commands.entity(entity).despawn_recursive();
+			}
+			spawn_tile(&mut commands, &assets, tile_change.to, *coords, 0);
+		}
+	}
+}
+
+/// How many chunks collapsing floor breaks into along each side.
+const RUBBLE_DIVISIONS: usize = 3;
+
+/// Drops chunks of floor from `coords` into the pit opening beneath them,
+/// tumbling and shrinking as they fall.
+fn rubble(commands: &mut Commands, assets: &GameAssets, coords: Coords) {
+	let once = |duration| EasingType::Once { duration };
+	let size = 1.0 / RUBBLE_DIVISIONS as f32;
+	for idx in 0..RUBBLE_DIVISIONS * RUBBLE_DIVISIONS {
+		let (row, col) = (idx / RUBBLE_DIVISIONS, idx % RUBBLE_DIVISIONS);
+		let offset = Vec3::new(
+			(col as f32 + 0.5) * size - 0.5,
+			0.5 - (row as f32 + 0.5) * size,
+			0.0,
+		);
+		let start = coords.transform(-0.5 * size).with_scale(Vec3::splat(size));
+		let start = start.with_translation(start.translation + offset);
+		// Chunks farther from the middle give way a little later.
+		let delay = ANIMATION_DURATION.mul_f32(0.25 + offset.length());
+		let angle =
+			TAU * idx as f32 / (RUBBLE_DIVISIONS * RUBBLE_DIVISIONS) as f32;
+		let fallen = start
+			.with_translation(
+				start.translation - 0.5 * offset - PIT_DEPTH * Vec3::Z,
+			)
+			.with_rotation(Quat::from_euler(EulerRot::XYZ, angle, angle, 0.0))
+			.with_scale(Vec3::ZERO);
+		commands.spawn((
+			LevelEntity,
+			Mesh3d(assets.mesh(MeshKind::Rubble)),
+			MeshMaterial3d(assets.material(MaterialKind::Rubble)),
+			DespawnTimer::from_duration(delay + 2 * ANIMATION_DURATION),
+			start
+				.ease_to(start, EaseFunction::QuadraticIn, once(delay))
+				.ease_to(
+					fallen,
+					EaseFunction::QuadraticIn,
+					once(2 * ANIMATION_DURATION),
+				),
+		));
+	}
+}
+
+/// A platform's model. Its entity also needs a [`PlatformModel`] component
+/// to be animated.
+pub fn platform(assets: &GameAssets, coords: Coords) -> impl Bundle {
+	(
+		Mesh3d(assets.mesh(MeshKind::Platform)),
+		MeshMaterial3d(assets.material(MaterialKind::Platform)),
+		coords.transform(PLATFORM_Z),
+	)
+}
+
+/// The model of an open portal of `color`. Its entity also needs a
+/// [`Portal`] component to be animated, and a transform.
+fn portal(assets: &GameAssets, color: CharacterColor) -> impl Bundle {
+	(
+		NotShadowCaster,
+		NotShadowReceiver,
+		Mesh3d(assets.mesh(MeshKind::Portal)),
+		MeshMaterial3d(assets.material(MaterialKind::Character(color))),
+	)
+}
+
+/// The model of the platform with the given index in
+/// [`Level::platforms`](crate::level::Level::platforms).
+#[derive(Component)]
+pub struct PlatformModel {
+	pub idx: usize,
+}
+
+/// Moves platforms along their tracks.
+pub fn animate_platforms(
+	mut commands: Commands,
+	mut change_events: EventReader<ChangeEvent>,
+	platform_query: Query<(Entity, &Transform, &PlatformModel)>,
+) {
+	for change in change_events.read() {
+		let eased = eased(change);
+		for (entity, from, platform) in &platform_query {
+			let Some(step) = change.platform_steps.get(&platform.idx) else {
+				continue;
+			};
+			let to = step.to_coords.transform(PLATFORM_Z);
+			if eased {
+				Glide.ease(&mut commands, entity, *from, to, Duration::ZERO);
+			} else {
+				snap(&mut commands, entity, to);
+			}
+		}
+	}
+}
+
+/// Spawns the entity for `level_object`, returning its ID.
+pub fn spawn_object(
+	commands: &mut Commands,
+	assets: &GameAssets,
+	level_object: &LevelObject,
+) -> Entity {
+	let mut entity = commands.spawn((
+		LevelEntity,
+		Object {
+			id: level_object.id,
+			rotates: level_object.object.is_character(),
+		},
+		level_object.coords.transform(0.5),
+	));
+	match level_object.object {
+		level::Object::Character(c) => entity.with_children(|child_builder| {
+			character_body(
+				child_builder,
+				assets,
+				level_object.id,
+				c.color,
+				Transform::from_rotation(Quat::from_rotation_z(
+					level_object.angle,
+				)),
+			);
+		}),
+		level::Object::Prop(descriptor) => {
+			entity.with_children(|child_builder| {
+				child_builder.spawn((
+					ObjectBody,
+					SceneRoot(assets.model(ModelKind::Prop(descriptor.model))),
+				));
+			})
+		}
+	};
+	entity.id()
+}
+
+/// Spawns the [body](ObjectBody) of the character with the given `id` and
+/// `color` under `child_builder`, with the body's own transform. The model
+/// hangs from the body by a [`Submersion`], so it can sink into a portal
+/// whichever way the body turns.
+fn character_body(
+	child_builder: &mut ChildBuilder,
+	assets: &GameAssets,
+	id: Id,
+	color: CharacterColor,
+	transform: Transform,
+) {
+	child_builder
+		.spawn((ObjectBody, transform))
+		.with_children(|body| {
+			body.spawn((
+				Submersion {
+					id,
+					submerged: false,
+				},
+				Mesh3d(assets.mesh(MeshKind::Character)),
+				MeshMaterial3d(assets.material(MaterialKind::Character(color))),
+			));
+		});
+}
+
 /// Marks the "body" of an object's animation. Making an `ObjectBody` entity a
 /// child of an [`Object`] entity allows setting the body's rotation
 /// independently from the rotation of UI elements (such as turn indicators)
@@ -48,8 +370,7 @@ pub struct ChoiceIndicator;
 /// Add indicators for pending actions and next actor.
 pub fn add_indicators(
 	mut commands: Commands,
-	models: Res<Models>,
-	materials: Res<Materials>,
+	assets: Res<GameAssets>,
 	mut next_actors: EventReader<NextActor>,
 	mut control_events: EventReader<ControlEvent>,
 	object_query: Query<(Entity, &Object, &Transform)>,
@@ -58,7 +379,7 @@ pub fn add_indicators(
 	let transform = Transform::from_translation(0.5 * Vec3::Z);
 
 	// Next actor
-	for NextActor { id: actor_id, .. } in next_actors.read() {
+	for next_actor in next_actors.read() {
 		// Clear any existing choosing indicators.
 		for entity in &choosing_query {
 			commands.entity(entity).despawn_recursive();
@@ -66,19 +387,53 @@ pub fn add_indicators(
 		// Spawn a new choosing indicator.
 		let indicator = commands
 			.spawn((
-				Mesh3d(models.question_mesh.clone()),
-				MeshMaterial3d(materials.indicator.clone()),
+				Mesh3d(assets.mesh(MeshKind::Question)),
+				MeshMaterial3d(assets.material(MaterialKind::Indicator)),
 				transform,
 				NotShadowCaster,
 				NotShadowReceiver,
 				ChoosingIndicator,
+				HiddenInPhotoMode,
 			))
 			.id();
+		// If the actor is restricted to some directions, surround the
+		// indicator with arrows, graying out the unavailable directions.
+		let allowed = Offset::DIRECTIONS.map(|offset| {
+			direction_action(next_actor, offset, false).is_some()
+		});
+		if allowed.contains(&false) {
+			for (offset, allowed) in Offset::DIRECTIONS.into_iter().zip(allowed)
+			{
+				let material = if allowed {
+					assets.material(MaterialKind::Indicator)
+				} else {
+					assets.material(MaterialKind::DisabledIndicator)
+				};
+				let arrow = commands
+					.spawn((
+						Mesh3d(assets.mesh(MeshKind::Arrow)),
+						MeshMaterial3d(material),
+						Transform::from_translation(
+							0.35 * Vec3::new(
+								offset.col as f32,
+								-offset.row as f32,
+								0.0,
+							),
+						)
+						.with_rotation(Quat::from_rotation_z(offset.angle()))
+						.with_scale(Vec3::splat(0.3)),
+						NotShadowCaster,
+						NotShadowReceiver,
+					))
+					.id();
+				commands.entity(indicator).add_child(arrow);
+			}
+		}
 		// Make the indicator a child of the next actor.
 		let actor = object_query
 			.iter()
 			.find_map(|(entity, object, _)| {
-				(object.id == *actor_id).then_some(entity)
+				(object.id == next_actor.id).then_some(entity)
 			})
 			.unwrap();
 		commands.entity(actor).add_child(indicator);
@@ -91,23 +446,26 @@ pub fn add_indicators(
 		};
 		// Get the mesh and transform for the pending action indicator.
 		let (mesh, transform) = match action {
-			Action::Wait => (models.wait_mesh.clone(), transform),
+			Action::Wait => (assets.mesh(MeshKind::Wait), transform),
 			Action::Push(offset) => (
-				models.arrow_mesh.clone(),
+				assets.mesh(MeshKind::Arrow),
 				transform.with_rotation(Quat::from_rotation_z(offset.angle())),
 			),
-			Action::Summon(_offset) => (models.summon_mesh.clone(), transform),
-			Action::Return => (models.return_mesh.clone(), transform),
+			Action::Summon(_offset) => {
+				(assets.mesh(MeshKind::Summon), transform)
+			}
+			Action::Return => (assets.mesh(MeshKind::Return), transform),
 		};
 		// Spawn the indicator.
 		let indicator = commands
 			.spawn((
 				Mesh3d(mesh),
-				MeshMaterial3d(materials.indicator.clone()),
+				MeshMaterial3d(assets.material(MaterialKind::Indicator)),
 				transform,
 				NotShadowCaster,
 				NotShadowReceiver,
 				ChoiceIndicator,
+				HiddenInPhotoMode,
 			))
 			.id();
 		// Make the indicator a child of the pending actor.
@@ -136,87 +494,413 @@ pub fn clear_indicators(
 
 const ANIMATION_DURATION: Duration = Duration::from_millis(200);
 
+/// Changes affecting more than this many objects skip easing and snap straight
+/// to their final state, so that large changes don't spawn hundreds of easing
+/// components and despawn timers in a single frame.
+const MAX_EASED_OBJECTS: usize = 64;
+
+/// Whether `change` is small enough to animate smoothly.
+fn eased(change: &Change) -> bool {
+	change.returnings.len()
+		+ change.moves.len()
+		+ change.summonings.len()
+		+ change.creations.len()
+		+ change.destructions.len()
+		+ change.platform_steps.len()
+		<= MAX_EASED_OBJECTS
+}
+
+/// How far from a closing portal its ripple spreads, in tiles.
+const RIPPLE_RADIUS: i32 = 2;
+
+/// How high the ripple from a closing portal lifts the tiles next to it.
+const RIPPLE_HEIGHT: f32 = 0.12;
+
+/// How long the ripple from a closing portal takes to spread one tile.
+const RIPPLE_DELAY: Duration = Duration::from_millis(60);
+
+/// Sends a ripple out through the tiles around `portal_coords`, lifting each
+/// one briefly, less the farther it is from the portal.
+fn ripple(
+	commands: &mut Commands,
+	portal_coords: Coords,
+	tile_query: &Query<(Entity, &TileModel)>,
+) {
+	let once = |duration| EasingType::Once { duration };
+	for (entity, tile) in tile_query {
+		let distance = (tile.coords.row - portal_coords.row)
+			.abs()
+			.max((tile.coords.col - portal_coords.col).abs());
+		if distance == 0 || distance > RIPPLE_RADIUS {
+			continue;
+		}
+		let lifted = tile.base.with_translation(
+			tile.base.translation + RIPPLE_HEIGHT / distance as f32 * Vec3::Z,
+		);
+		let delay = RIPPLE_DELAY * distance as u32;
+		commands
+			.entity(entity)
+			.remove::<EasingChainComponent<Transform>>()
+			.insert(
+				tile.base
+					.ease_to(tile.base, EaseFunction::QuadraticOut, once(delay))
+					.ease_to(
+						lifted,
+						EaseFunction::QuadraticOut,
+						once(ANIMATION_DURATION / 2),
+					)
+					.ease_to(
+						tile.base,
+						EaseFunction::BounceOut,
+						once(ANIMATION_DURATION * 2),
+					),
+			);
+	}
+}
+
+/// Animates characters returning to the past. Their portals snap shut, and
+/// unless motion is reduced, send a ripple through the tiles around them.
 pub fn animate_returnings(
 	mut commands: Commands,
 	mut change_events: EventReader<ChangeEvent>,
+	settings: Res<Settings>,
 	object_query: Query<(Entity, &Object)>,
 	portal_query: Query<(Entity, &Portal)>,
+	tile_query: Query<(Entity, &TileModel)>,
 ) {
 	for change in change_events.read() {
+		let eased = eased(change);
 		for returning in change.returnings.values() {
 			let returner_transform = returning.returner.coords.transform(0.5);
 			let portal_transform = returning
 				.returner
 				.coords
-				.transform(0.5 * crate::meshes::PORTAL_HEIGHT);
+				.transform(0.5 * crate::assets::PORTAL_HEIGHT);
 			// Despawn returning character.
 			for (entity, object) in &object_query {
 				if object.id == returning.returner.id {
-					commands.entity(entity).insert((
-						DespawnTimer::from_duration(ANIMATION_DURATION),
-						returner_transform.with_scale(Vec3::ONE).ease_to(
-							returner_transform.with_scale(Vec3::ZERO),
-							EaseFunction::CubicIn,
-							EasingType::Once {
-								duration: ANIMATION_DURATION,
-							},
-						),
-					));
+					if eased {
+						commands.entity(entity).insert((
+							DespawnTimer::from_duration(ANIMATION_DURATION),
+							returner_transform.with_scale(Vec3::ONE).ease_to(
+								returner_transform.with_scale(Vec3::ZERO),
+								EaseFunction::CubicIn,
+								EasingType::Once {
+									duration: ANIMATION_DURATION,
+								},
+							),
+						));
+					} else {
+						commands.entity(entity).despawn_recursive();
+					}
 					break;
 				}
 			}
-			// Despawn closed portal.
+			// Despawn closed portal, overshooting a little before snapping
+			// shut.
 			for (entity, portal) in &portal_query {
 				if portal.coords == returning.returner.coords {
-					commands.entity(entity).insert((
-						DespawnTimer::from_duration(ANIMATION_DURATION),
-						portal_transform.with_scale(Vec3::ONE).ease_to(
-							portal_transform.with_scale(Vec3::ZERO),
-							EaseFunction::CubicIn,
-							EasingType::Once {
-								duration: ANIMATION_DURATION,
-							},
-						),
-					));
+					if eased {
+						commands.entity(entity).insert((
+							DespawnTimer::from_duration(ANIMATION_DURATION),
+							portal_transform.with_scale(Vec3::ONE).ease_to(
+								portal_transform.with_scale(Vec3::ZERO),
+								EaseFunction::BackIn,
+								EasingType::Once {
+									duration: ANIMATION_DURATION,
+								},
+							),
+						));
+						if !settings.reduce_motion {
+							ripple(&mut commands, portal.coords, &tile_query);
+						}
+					} else {
+						commands.entity(entity).despawn_recursive();
+					}
+					break;
+				}
+			}
+		}
+	}
+}
+
+/// How long a move waits for each move it depends on. See
+/// [`move_stagger_steps`].
+const STAGGER_DELAY: Duration = Duration::from_millis(100);
+
+/// For each move in `change`, how many moves in other directions must get out
+/// of the way before it, counting along the chain of moves into tiles being
+/// vacated. Moves in the same direction as the move ahead of them can't visibly
+/// overlap it, so they don't add a step. Moves in a cycle don't wait at all.
+fn move_stagger_steps(change: &Change) -> HashMap<Id, u32> {
+	let moves_by_from: HashMap<Coords, (Id, &level::Move)> = change
+		.moves
+		.iter()
+		.map(|(id, mv)| (mv.from_coords, (*id, mv)))
+		.collect();
+	let direction = |mv: &level::Move| {
+		(
+			mv.to_coords.row - mv.from_coords.row,
+			mv.to_coords.col - mv.from_coords.col,
+		)
+	};
+	change
+		.moves
+		.iter()
+		.map(|(id, mv)| {
+			let mut steps = 0;
+			let mut visited = HashSet::from([*id]);
+			let mut current = mv;
+			while let Some((next_id, next)) =
+				moves_by_from.get(&current.to_coords)
+			{
+				if !visited.insert(*next_id) {
+					// Loops move all at once.
+					steps = 0;
 					break;
 				}
+				if direction(next) != direction(current) {
+					steps += 1;
+				}
+				current = next;
 			}
+			(*id, steps)
+		})
+		.collect()
+}
+
+/// How long each character's part of a turn gets when turns are replayed
+/// [per actor](TurnReplay::PerActor), including a brief pause before the next
+/// character's part.
+const ACTOR_REPLAY_DELAY: Duration = Duration::from_millis(400);
+
+/// How the changes in a turn play out.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TurnReplay {
+	/// Everything happens at once, as it does in the rules.
+	#[default]
+	Simultaneous,
+	/// Each character's part of the turn plays out in turn, in character
+	/// order, to help show what each action did. The outcome is the same.
+	PerActor,
+}
+
+/// For each move and summoning in `change`, how long to wait for characters
+/// earlier in line under `replay`. Objects without a character of their own
+/// are credited to the nearest character moving in behind them, which pushed
+/// them there. Only characters causing a change get a place in line.
+fn replay_delays(
+	change: &Change,
+	level: &Level,
+	replay: TurnReplay,
+) -> HashMap<Id, Duration> {
+	if replay == TurnReplay::Simultaneous {
+		return HashMap::new();
+	}
+	let moves_by_to: HashMap<Coords, Id> = change
+		.moves
+		.iter()
+		.map(|(id, mv)| (mv.to_coords, *id))
+		.collect();
+	let actor = |id: Id| {
+		let mut current = id;
+		let mut visited = HashSet::from([id]);
+		while !level.has_character(&current) {
+			let Some(next) = change
+				.moves
+				.get(&current)
+				.and_then(|mv| moves_by_to.get(&mv.from_coords))
+			else {
+				break;
+			};
+			if !visited.insert(*next) {
+				break;
+			}
+			current = *next;
+		}
+		current
+	};
+	let actors: Vec<(Id, Id)> = change
+		.moves
+		.keys()
+		.chain(change.summonings.keys())
+		.map(|id| (*id, actor(*id)))
+		.collect();
+	// Character IDs are in character order.
+	let mut line: Vec<Id> = actors.iter().map(|(_, actor)| *actor).collect();
+	line.sort();
+	line.dedup();
+	actors
+		.into_iter()
+		.map(|(id, actor)| {
+			let place = line.binary_search(&actor).unwrap_or_default();
+			(id, ACTOR_REPLAY_DELAY * place as u32)
+		})
+		.collect()
+}
+
+/// How objects travel from tile to tile.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MovementStyle {
+	/// Slide smoothly along the floor.
+	#[default]
+	Glide,
+	/// Hop from tile to tile in a small arc.
+	Step,
+}
+
+impl MovementStyle {
+	/// The strategy that animates moves in this style.
+	fn strategy(self) -> &'static dyn MoveAnimation {
+		match self {
+			MovementStyle::Glide => &Glide,
+			MovementStyle::Step => &Step,
+		}
+	}
+}
+
+/// A way of animating an object's move from one transform to another.
+trait MoveAnimation: Sync {
+	/// Eases `entity` from `from` to `to` after waiting for `delay`.
+	fn ease(
+		&self,
+		commands: &mut Commands,
+		entity: Entity,
+		from: Transform,
+		to: Transform,
+		delay: Duration,
+	);
+}
+
+/// Moves in a single smooth motion.
+struct Glide;
+
+impl MoveAnimation for Glide {
+	fn ease(
+		&self,
+		commands: &mut Commands,
+		entity: Entity,
+		from: Transform,
+		to: Transform,
+		delay: Duration,
+	) {
+		let once = |duration| EasingType::Once { duration };
+		let mut entity = commands.entity(entity);
+		entity.remove::<EasingChainComponent<Transform>>();
+		if delay.is_zero() {
+			entity.insert(from.ease_to(
+				to,
+				EaseFunction::CubicInOut,
+				once(ANIMATION_DURATION),
+			));
+		} else {
+			entity.remove::<EasingComponent<Transform>>().insert(
+				from.ease_to(from, EaseFunction::CubicInOut, once(delay))
+					.ease_to(
+						to,
+						EaseFunction::CubicInOut,
+						once(ANIMATION_DURATION),
+					),
+			);
+		}
+	}
+}
+
+/// How high an object hops when moving in the [step](MovementStyle::Step)
+/// style.
+const STEP_HEIGHT: f32 = 0.25;
+
+/// Moves in a hop, rising to [`STEP_HEIGHT`] halfway between the tiles.
+struct Step;
+
+impl MoveAnimation for Step {
+	fn ease(
+		&self,
+		commands: &mut Commands,
+		entity: Entity,
+		from: Transform,
+		to: Transform,
+		delay: Duration,
+	) {
+		let peak = Transform {
+			translation: from.translation.lerp(to.translation, 0.5)
+				+ STEP_HEIGHT * Vec3::Z,
+			rotation: from.rotation.slerp(to.rotation, 0.5),
+			scale: to.scale,
+		};
+		let once = |duration| EasingType::Once { duration };
+		let half = ANIMATION_DURATION / 2;
+		let mut entity = commands.entity(entity);
+		entity.remove::<EasingComponent<Transform>>();
+		if delay.is_zero() {
+			entity.insert(
+				from.ease_to(peak, EaseFunction::QuadraticOut, once(half))
+					.ease_to(to, EaseFunction::QuadraticIn, once(half)),
+			);
+		} else {
+			entity.insert(
+				from.ease_to(from, EaseFunction::QuadraticOut, once(delay))
+					.ease_to(peak, EaseFunction::QuadraticOut, once(half))
+					.ease_to(to, EaseFunction::QuadraticIn, once(half)),
+			);
 		}
 	}
 }
 
+/// Snaps `entity` to `to`, canceling any easing still in progress from an
+/// earlier change.
+fn snap(commands: &mut Commands, entity: Entity, to: Transform) {
+	commands
+		.entity(entity)
+		.remove::<(EasingComponent<Transform>, EasingChainComponent<Transform>)>(
+		)
+		.insert(to);
+}
+
 pub fn animate_moves(
 	mut commands: Commands,
 	mut change_events: EventReader<ChangeEvent>,
+	level: Res<Level>,
+	settings: Res<Settings>,
 	object_query: Query<(Entity, &Children, &Transform, &Object)>,
 	body_query: Query<(Entity, &Transform), With<ObjectBody>>,
 ) {
+	let style = settings.movement_style.strategy();
 	for change in change_events.read() {
+		let eased = eased(change);
+		let stagger_steps = move_stagger_steps(change);
+		let replay_delays = replay_delays(change, &level, settings.turn_replay);
 		for (parent, children, from, object) in &object_query {
 			let Some(mv) = change.moves.get(&object.id) else {
 				continue;
 			};
-			commands.entity(parent).insert(from.ease_to(
-				mv.to_coords.transform(0.5),
-				EaseFunction::CubicInOut,
-				EasingType::Once {
-					duration: ANIMATION_DURATION,
-				},
-			));
+			// Make moves into tiles being vacated wait their turn, so that
+			// objects don't appear to pass through each other.
+			let delay = STAGGER_DELAY * stagger_steps[&object.id]
+				+ replay_delays.get(&object.id).copied().unwrap_or_default();
+			let to = mv.to_coords.transform(0.5);
+			// Objects turning in place, like on a turntable, stay put.
+			if mv.from_coords != mv.to_coords {
+				if eased {
+					style.ease(&mut commands, parent, *from, to, delay);
+				} else {
+					snap(&mut commands, parent, to);
+				}
+			}
 			// Rotating the parent entity directly would cause indicators to
 			// rotate as well. Instead, rotate just the child "body" entity.
 			if object.rotates {
 				for child in children {
 					if let Ok((body, from)) = body_query.get(*child) {
-						commands.entity(body).insert(from.ease_to(
-							Transform::from_rotation(Quat::from_rotation_z(
-								mv.to_angle,
-							)),
-							EaseFunction::CubicInOut,
-							EasingType::Once {
-								duration: ANIMATION_DURATION,
-							},
-						));
+						let to = Transform::from_rotation(
+							Quat::from_rotation_z(mv.to_angle),
+						);
+						if eased {
+							// Bodies only turn, so they don't need to hop.
+							Glide.ease(&mut commands, body, *from, to, delay);
+						} else {
+							snap(&mut commands, body, to);
+						}
 					}
 				}
 			}
@@ -224,66 +908,483 @@ pub fn animate_moves(
 	}
 }
 
+/// How long the streak from a summoner takes to cross each tile on its way to
+/// where its summon lands.
+const STREAK_TILE_DURATION: Duration = Duration::from_millis(40);
+
+/// After waiting for `delay`, sends a streak of `color` from `from` to `to`,
+/// tracing a summon's path to the farthest open tile. Returns how long until
+/// it gets there.
+fn streak(
+	commands: &mut Commands,
+	assets: &GameAssets,
+	color: CharacterColor,
+	from: Coords,
+	to: Coords,
+	delay: Duration,
+) -> Duration {
+	let distance = (to.row - from.row).abs().max((to.col - from.col).abs());
+	let travel = STREAK_TILE_DURATION * distance as u32;
+	if travel.is_zero() {
+		return delay;
+	}
+	let start = from.transform(0.5);
+	let end = to.transform(0.5);
+	// Stretch the streak along its path.
+	let direction = (end.translation - start.translation).normalize().abs();
+	let scale = Vec3::ONE + 2.0 * direction;
+	let once = |duration| EasingType::Once { duration };
+	let hidden = start.with_scale(Vec3::ZERO);
+	// The streak pops up at the summoner and then flies to the landing tile.
+	let visible = start.with_scale(scale);
+	let pop = once(STREAK_TILE_DURATION);
+	let flight = if delay.is_zero() {
+		hidden
+			.ease_to(visible, EaseFunction::QuadraticOut, pop)
+			.ease_to(
+				end.with_scale(scale),
+				EaseFunction::QuadraticIn,
+				once(travel),
+			)
+	} else {
+		hidden
+			.ease_to(hidden, EaseFunction::QuadraticOut, once(delay))
+			.ease_to(visible, EaseFunction::QuadraticOut, pop)
+			.ease_to(
+				end.with_scale(scale),
+				EaseFunction::QuadraticIn,
+				once(travel),
+			)
+	};
+	let arrival = delay + STREAK_TILE_DURATION + travel;
+	commands.spawn((
+		LevelEntity,
+		Mesh3d(assets.mesh(MeshKind::Streak)),
+		MeshMaterial3d(assets.material(MaterialKind::Character(color))),
+		NotShadowCaster,
+		NotShadowReceiver,
+		DespawnTimer::from_duration(arrival),
+		hidden,
+		flight,
+	));
+	arrival
+}
+
+/// Grows `entity` from nothing to `transform` after waiting for `delay`.
+fn grow(entity: &mut EntityCommands, transform: Transform, delay: Duration) {
+	let hidden = transform.with_scale(Vec3::ZERO);
+	let once = |duration| EasingType::Once { duration };
+	if delay.is_zero() {
+		entity.insert(hidden.ease_to(
+			transform,
+			EaseFunction::CubicIn,
+			once(ANIMATION_DURATION),
+		));
+	} else {
+		entity.insert(
+			hidden
+				.ease_to(hidden, EaseFunction::CubicIn, once(delay))
+				.ease_to(
+					transform,
+					EaseFunction::CubicIn,
+					once(ANIMATION_DURATION),
+				),
+		);
+	}
+}
+
+/// Animates summonings. A streak flies from each summoner to where its summon
+/// lands, and then the summon and its portal grow into place.
 pub fn animate_summonings(
 	mut commands: Commands,
 	mut change_events: EventReader<ChangeEvent>,
-	meshes: Res<Meshes>,
-	materials: Res<Materials>,
+	level: Res<Level>,
+	settings: Res<Settings>,
+	assets: Res<GameAssets>,
 ) {
 	for change in change_events.read() {
-		for summoning in change.summonings.values() {
+		let eased = eased(change);
+		let initial_scale = if eased { Vec3::ZERO } else { Vec3::ONE };
+		let replay_delays = replay_delays(change, &level, settings.turn_replay);
+		for (summoner_id, summoning) in &change.summonings {
 			let summon_transform = summoning.summon.coords.transform(0.5);
 			let portal_transform = summoning
 				.summon
 				.coords
-				.transform(0.5 * crate::meshes::PORTAL_HEIGHT);
+				.transform(0.5 * crate::assets::PORTAL_HEIGHT);
+			let summoner_coords = level
+				.iter_level_objects()
+				.find(|level_object| level_object.id == *summoner_id)
+				.map(|level_object| level_object.coords);
+			let wait =
+				replay_delays.get(summoner_id).copied().unwrap_or_default();
+			let delay = match summoner_coords {
+				Some(from) if eased => streak(
+					&mut commands,
+					&assets,
+					summoning.portal_color,
+					from,
+					summoning.summon.coords,
+					wait,
+				),
+				_ => wait,
+			};
 			// Spawn summoned character.
-			commands
-				.spawn((
-					LevelEntity,
-					Object {
-						id: summoning.summon.id,
-						rotates: true,
-					},
-					summon_transform.with_scale(Vec3::ZERO).ease_to(
-						summon_transform.with_scale(Vec3::ONE),
-						EaseFunction::CubicIn,
+			let mut summon = commands.spawn((
+				LevelEntity,
+				Object {
+					id: summoning.summon.id,
+					rotates: true,
+				},
+				summon_transform.with_scale(initial_scale),
+			));
+			if eased {
+				grow(&mut summon, summon_transform, delay);
+			}
+			summon.with_children(|child_builder| {
+				character_body(
+					child_builder,
+					&assets,
+					summoning.summon.id,
+					summoning.summon.character.color,
+					Transform::from_rotation(Quat::from_rotation_y(
+						summoning.summon.angle,
+					)),
+				);
+			});
+			// Spawn opened portal.
+			let mut portal = commands.spawn((
+				LevelEntity,
+				Portal {
+					coords: summoning.summon.coords,
+				},
+				portal(&assets, summoning.portal_color),
+				portal_transform.with_scale(initial_scale),
+			));
+			if eased {
+				grow(&mut portal, portal_transform, delay);
+			}
+		}
+	}
+}
+
+pub fn animate_creations_and_destructions(
+	mut commands: Commands,
+	mut change_events: EventReader<ChangeEvent>,
+	assets: Res<GameAssets>,
+	level: Res<Level>,
+	object_query: Query<(Entity, &Object)>,
+) {
+	for change in change_events.read() {
+		let eased = eased(change);
+		for destroyed in change.destructions.values() {
+			if eased && change.crushed.contains(&destroyed.id) {
+				debris(&mut commands, &assets, destroyed.coords);
+			}
+			let transform = destroyed.coords.transform(0.5);
+			// Props lost to water sink out of sight, except floating ones,
+			// which the bridge they make takes the place of. Anything lost to
+			// lava sinks, including props that leave a crust behind.
+			let tile = level.tile_at(destroyed.coords);
+			let sink = |depth| {
+				transform.with_translation(
+					transform.translation - (1.0 + depth) * Vec3::Z,
+				)
+			};
+			let gone = match tile {
+				Tile::Water { bridged: false } => sink(WATER_DEPTH),
+				Tile::Lava { .. } => sink(LAVA_DEPTH),
+				_ => transform.with_scale(Vec3::ZERO),
+			};
+			for (entity, object) in &object_query {
+				if object.id == destroyed.id {
+					if eased && tile != (Tile::Water { bridged: true }) {
+						commands.entity(entity).insert((
+							DespawnTimer::from_duration(ANIMATION_DURATION),
+							transform.ease_to(
+								gone,
+								EaseFunction::CubicIn,
+								EasingType::Once {
+									duration: ANIMATION_DURATION,
+								},
+							),
+						));
+					} else {
+						commands.entity(entity).despawn_recursive();
+					}
+					break;
+				}
+			}
+		}
+		for created in change.creations.values() {
+			let entity = spawn_object(&mut commands, &assets, created);
+			if eased {
+				let transform = created.coords.transform(0.5);
+				commands.entity(entity).insert((
+					transform.with_scale(Vec3::ZERO),
+					transform.with_scale(Vec3::ZERO).ease_to(
+						transform,
+						EaseFunction::CubicOut,
 						EasingType::Once {
 							duration: ANIMATION_DURATION,
 						},
 					),
-				))
-				.with_children(|child_builder| {
-					child_builder.spawn((
-						ObjectBody,
-						Mesh3d(meshes.character.clone()),
-						MeshMaterial3d(
-							materials.characters
-								[summoning.summon.character.color.idx()]
-							.clone(),
-						),
-						Transform::from_rotation(Quat::from_rotation_y(
-							summoning.summon.angle,
-						)),
-					));
-				});
-			// Spawn opened portal.
+				));
+			}
+		}
+	}
+}
+
+/// How many splinters fly from a crushed object.
+const DEBRIS_COUNT: usize = 8;
+
+/// How far splinters fly from a crushed object, in tiles.
+const DEBRIS_SPREAD: f32 = 0.6;
+
+/// How high splinters fly from a crushed object before falling.
+const DEBRIS_HEIGHT: f32 = 0.5;
+
+/// Sends splinters flying out from an object crushed at `coords`, which fall
+/// and shrink away.
+fn debris(commands: &mut Commands, assets: &GameAssets, coords: Coords) {
+	let start = coords.transform(0.5);
+	let once = |duration| EasingType::Once { duration };
+	for idx in 0..DEBRIS_COUNT {
+		// Spread the splinters evenly around the object, spinning each a bit
+		// differently.
+		let angle = TAU * idx as f32 / DEBRIS_COUNT as f32;
+		let direction = Vec3::new(angle.cos(), angle.sin(), 0.0);
+		let spin = Quat::from_euler(EulerRot::XYZ, angle, 2.0 * angle, angle);
+		let peak = start
+			.with_translation(
+				start.translation
+					+ 0.5 * DEBRIS_SPREAD * direction
+					+ DEBRIS_HEIGHT * Vec3::Z,
+			)
+			.with_rotation(spin);
+		let landed = start
+			.with_translation(
+				start.translation + DEBRIS_SPREAD * direction - 0.45 * Vec3::Z,
+			)
+			.with_rotation(spin * spin)
+			.with_scale(Vec3::ZERO);
+		commands.spawn((
+			LevelEntity,
+			Mesh3d(assets.mesh(MeshKind::Debris)),
+			MeshMaterial3d(assets.material(MaterialKind::Debris)),
+			DespawnTimer::from_duration(2 * ANIMATION_DURATION),
+			start
+				.ease_to(
+					peak,
+					EaseFunction::QuadraticOut,
+					once(ANIMATION_DURATION / 2),
+				)
+				.ease_to(
+					landed,
+					EaseFunction::QuadraticIn,
+					once(ANIMATION_DURATION * 3 / 2),
+				),
+		));
+	}
+}
+
+/// How long the beam of light over activated stairs takes to rise, and then
+/// to narrow away.
+const EXIT_BEAM_DURATION: Duration = Duration::from_millis(500);
+
+/// How high the beam of light over activated stairs rises.
+const EXIT_BEAM_HEIGHT: f32 = 3.0;
+
+/// Lights up the stairs when a character reaches them and sends up a beam of
+/// light from each, with a sound cue. Dims them again if that's undone.
+pub fn animate_exit(
+	mut commands: Commands,
+	mut status_events: EventReader<LevelStatusEvent>,
+	assets: Res<GameAssets>,
+	tracks: Res<AudioTracks>,
+	// Stairs staged for thumbnails aren't level entities.
+	mut stairs_query: Query<
+		(&Stairs, &mut MeshMaterial3d<StandardMaterial>),
+		With<LevelEntity>,
+	>,
+) {
+	let Some(status) = status_events.read().last() else {
+		return;
+	};
+	for (stairs, mut material) in &mut stairs_query {
+		if !status.complete {
+			material.0 = assets.material(MaterialKind::Stairs);
+			continue;
+		}
+		material.0 = assets.material(MaterialKind::ActiveStairs);
+		// Cylinders are upright along their local y-axis.
+		let base = stairs
+			.coords
+			.transform(0.0)
+			.with_rotation(Quat::from_rotation_x(FRAC_PI_2));
+		let risen = base
+			.with_translation(
+				base.translation + 0.5 * EXIT_BEAM_HEIGHT * Vec3::Z,
+			)
+			.with_scale(Vec3::new(1.0, EXIT_BEAM_HEIGHT, 1.0));
+		let once = EasingType::Once {
+			duration: EXIT_BEAM_DURATION,
+		};
+		commands.spawn((
+			LevelEntity,
+			Mesh3d(assets.mesh(MeshKind::ExitBeam)),
+			MeshMaterial3d(assets.material(MaterialKind::ExitBeam)),
+			NotShadowCaster,
+			NotShadowReceiver,
+			DespawnTimer::from_duration(2 * EXIT_BEAM_DURATION),
+			base.with_scale(Vec3::new(1.0, 0.0, 1.0))
+				.ease_to(risen, EaseFunction::QuadraticOut, once)
+				.ease_to(
+					risen.with_scale(Vec3::new(0.0, EXIT_BEAM_HEIGHT, 0.0)),
+					EaseFunction::QuadraticIn,
+					once,
+				),
+		));
+	}
+	if status.complete {
+		audio::play_cue(&mut commands, &tracks, "exit_open");
+	}
+}
+
+/// How far a character standing on its open portal sinks into it.
+const SUBMERGE_DEPTH: f32 = 0.35;
+
+/// Component for a character's model, sinking it partway through the floor
+/// while the character stands on its own open portal, so the floor hides its
+/// lower part. This is a sign that the character can return right now.
+#[derive(Component)]
+#[require(Transform, Visibility)]
+pub struct Submersion {
+	id: Id,
+	submerged: bool,
+}
+
+/// Sinks characters into their portals as they become able to return, and
+/// raises them back out when they no longer can.
+pub fn animate_submersions(
+	mut commands: Commands,
+	level: Res<Level>,
+	settings: Res<Settings>,
+	mut submersion_query: Query<(Entity, &mut Submersion, &Transform)>,
+) {
+	let returners: HashSet<Id> = level
+		.iter_level_objects()
+		.filter(|level_object| match level_object.object {
+			level::Object::Character(character) => {
+				character.can_return()
+					&& character.portal_coords == Some(level_object.coords)
+			}
+			level::Object::Prop(_) => false,
+		})
+		.map(|level_object| level_object.id)
+		.collect();
+	for (entity, mut submersion, from) in &mut submersion_query {
+		let submerged = returners.contains(&submersion.id);
+		if submersion.submerged == submerged {
+			continue;
+		}
+		submersion.submerged = submerged;
+		let depth = if submerged { SUBMERGE_DEPTH } else { 0.0 };
+		let to = Transform::from_translation(-depth * Vec3::Z);
+		if settings.reduce_motion {
+			snap(&mut commands, entity, to);
+		} else {
+			commands.entity(entity).insert(from.ease_to(
+				to,
+				EaseFunction::QuadraticInOut,
+				EasingType::Once {
+					duration: 2 * ANIMATION_DURATION,
+				},
+			));
+		}
+	}
+}
+
+/// The entities besides tile models that [`respawn_after_seek`] rebuilds.
+type Rebuilt = Or<(With<Object>, With<Portal>, With<TileMarker>)>;
+
+/// Rebuilds the tiles, objects, and portals of the level from the level itself
+/// after a seek of more than one turn. Every turn of a seek is animated in the
+/// same frame, and since commands are deferred, entities spawned for one turn
+/// can't be found to despawn for a later one.
+pub fn respawn_after_seek(
+	mut commands: Commands,
+	mut recap_events: EventReader<SeekRecapEvent>,
+	level: Res<Level>,
+	assets: Res<GameAssets>,
+	entity_query: Query<Entity, Rebuilt>,
+	tile_query: Query<(Entity, &TileModel)>,
+) {
+	if recap_events.read().count() == 0 {
+		return;
+	}
+	// Keep each tile model's turn, so floors don't visibly reshuffle.
+	let mut quarter_turns = HashMap::new();
+	for (entity, tile) in &tile_query {
+		let (angle, _, _) = tile.base.rotation.to_euler(EulerRot::ZYX);
+		let turns = (angle / FRAC_PI_2).round().rem_euclid(4.0) as u32;
+		quarter_turns.insert(tile.coords, turns);
+		commands.entity(entity).despawn_recursive();
+	}
+	for entity in &entity_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	for row in 0..level.height() {
+		for col in 0..level.width() {
+			let coords = Coords::new(row as i32, col as i32);
+			let turns = quarter_turns.get(&coords).copied().unwrap_or_default();
+			spawn_tile(
+				&mut commands,
+				&assets,
+				level.tile_at(coords),
+				coords,
+				turns,
+			);
+		}
+	}
+	for level_object in level.iter_level_objects() {
+		spawn_object(&mut commands, &assets, level_object);
+	}
+	for (_, character) in level.characters_by_id() {
+		if let Some(coords) = character.portal_coords {
 			commands.spawn((
 				LevelEntity,
-				Portal {
-					coords: summoning.summon.coords,
-				},
+				Portal { coords },
+				portal(&assets, character.color),
+				coords.transform(0.5 * crate::assets::PORTAL_HEIGHT),
+			));
+		}
+	}
+}
+
+const RECAP_DURATION: Duration = Duration::from_millis(600);
+
+/// Briefly highlights every tile changed by a multi-turn seek.
+pub fn animate_recaps(
+	mut commands: Commands,
+	mut recap_events: EventReader<SeekRecapEvent>,
+	assets: Res<GameAssets>,
+) {
+	for recap in recap_events.read() {
+		for coords in &recap.changed_coords {
+			let transform = coords.transform(0.01);
+			commands.spawn((
+				LevelEntity,
+				Mesh3d(assets.mesh(MeshKind::Highlight)),
+				MeshMaterial3d(assets.material(MaterialKind::Highlight)),
 				NotShadowCaster,
 				NotShadowReceiver,
-				Mesh3d(meshes.portal.clone()),
-				MeshMaterial3d(
-					materials.characters[summoning.portal_color.idx()].clone(),
-				),
-				portal_transform.with_scale(Vec3::ZERO).ease_to(
-					portal_transform.with_scale(Vec3::ONE),
+				DespawnTimer::from_duration(RECAP_DURATION),
+				transform.ease_to(
+					transform.with_scale(Vec3::ZERO),
 					EaseFunction::CubicIn,
 					EasingType::Once {
-						duration: ANIMATION_DURATION,
+						duration: RECAP_DURATION,
 					},
 				),
 			));
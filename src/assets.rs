@@ -0,0 +1,473 @@
+use bevy::{
+	asset::LoadState,
+	gltf::{Gltf, GltfMesh},
+	prelude::*,
+	utils::HashMap,
+};
+
+use crate::level::{CharacterColor, Object, Tile};
+
+pub const PORTAL_HEIGHT: f32 = 0.1;
+
+/// The number of heat colors for tile weights. See [`MaterialKind::Pressure`].
+pub const PRESSURE_LEVELS: u8 = 4;
+
+/// A mesh, either built in code or loaded from a model.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MeshKind {
+	Character,
+	Portal,
+	Highlight,
+	Backdrop,
+	Stairs,
+	ExitBeam,
+	Debris,
+	/// A chunk of collapsing floor, as a unit cube.
+	Rubble,
+	Platform,
+	Streak,
+	/// Shown over a character choosing its action.
+	Question,
+	Wait,
+	Arrow,
+	Summon,
+	Return,
+}
+
+/// A material.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaterialKind {
+	Character(CharacterColor),
+	Indicator,
+	DisabledIndicator,
+	Highlight,
+	Backdrop,
+	Grid,
+	Danger,
+	Stairs,
+	/// Stairs once a character has reached them.
+	ActiveStairs,
+	ExitBeam,
+	Ice,
+	/// The bottom of a pit.
+	Pit,
+	/// Splinters of crushed props.
+	Debris,
+	/// The bed of a platform's track.
+	Track,
+	Platform,
+	Turntable,
+	/// The surface of water.
+	Water,
+	/// The surface of lava.
+	Lava,
+	/// The cooled crust of bridged lava.
+	Crust,
+	/// The cracks in cracked floor.
+	Cracks,
+	/// The cracks in cracked floor that's been stepped on.
+	DeepCracks,
+	/// Chunks of collapsing floor.
+	Rubble,
+	/// The heat color for a tile's weight, from 1 up to [`PRESSURE_LEVELS`].
+	Pressure(u8),
+}
+
+/// A model's scene.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ModelKind {
+	Wall,
+	Floor,
+	/// A prop, by model path.
+	Prop(&'static str),
+}
+
+/// Every mesh, material, and model scene the game draws with, by kind.
+///
+/// Each kind starts out with a built-in asset, which a theme or mod can
+/// replace with [`set_mesh`](GameAssets::set_mesh) and friends, or with
+/// [`load_mesh`](GameAssets::load_mesh) for a mesh from a model file. Meshes
+/// from model files are blank until they load; loading finishes once each has
+/// loaded or failed to, and failures are logged and kept in
+/// [`failures`](GameAssets::failures) rather than stalling the game.
+#[derive(Resource)]
+pub struct GameAssets {
+	meshes: HashMap<MeshKind, Handle<Mesh>>,
+	materials: HashMap<MaterialKind, Handle<StandardMaterial>>,
+	models: HashMap<ModelKind, Handle<Scene>>,
+	/// Model files whose meshes haven't loaded yet, with the kind of mesh
+	/// each provides and its path.
+	pending: HashMap<Handle<Gltf>, (MeshKind, String)>,
+	/// The number of meshes ever requested from model files.
+	requested: usize,
+	failures: Vec<String>,
+}
+
+impl GameAssets {
+	pub fn load(
+		asset_server: &AssetServer,
+		mesh_assets: &mut Assets<Mesh>,
+		material_assets: &mut Assets<StandardMaterial>,
+	) -> Self {
+		let mut assets = Self {
+			meshes: HashMap::new(),
+			materials: HashMap::new(),
+			models: HashMap::new(),
+			pending: HashMap::new(),
+			requested: 0,
+			failures: Vec::new(),
+		};
+		assets.load_built_in_meshes(mesh_assets);
+		assets.load_built_in_materials(material_assets);
+		for (kind, path) in [
+			(MeshKind::Question, "models/question.glb"),
+			(MeshKind::Wait, "models/wait.glb"),
+			(MeshKind::Arrow, "models/arrow.glb"),
+			(MeshKind::Summon, "models/summon.glb"),
+			(MeshKind::Return, "models/return.glb"),
+		] {
+			assets.load_mesh(asset_server, kind, path);
+		}
+		let scene0 = GltfAssetLabel::Scene(0);
+		assets.set_model(
+			ModelKind::Wall,
+			asset_server.load(scene0.from_asset("models/wall.glb")),
+		);
+		assets.set_model(
+			ModelKind::Floor,
+			asset_server.load(scene0.from_asset("models/stone.glb")),
+		);
+		for prop in Object::PROPS {
+			if let Object::Prop(descriptor) = prop {
+				assets.set_model(
+					ModelKind::Prop(descriptor.model),
+					asset_server.load(scene0.from_asset(descriptor.model)),
+				);
+			}
+		}
+		assets
+	}
+
+	fn load_built_in_meshes(&mut self, mesh_assets: &mut Assets<Mesh>) {
+		let mut add = |kind, mesh: Mesh| {
+			self.meshes.insert(kind, mesh_assets.add(mesh));
+		};
+		add(
+			MeshKind::Character,
+			Mesh::from(Extrusion::new(
+				Triangle2d::new(
+					Vec2::new(-0.5, -0.5),
+					0.5 * Vec2::X,
+					Vec2::new(-0.5, 0.5),
+				),
+				1.0,
+			)),
+		);
+		add(
+			MeshKind::Portal,
+			Mesh::from(Extrusion::new(Circle { radius: 0.5 }, PORTAL_HEIGHT)),
+		);
+		add(MeshKind::Highlight, Mesh::from(Rectangle::new(1.0, 1.0)));
+		// A unit square that fades from white at the top to gray at the
+		// bottom, to be tinted by the sky color.
+		add(
+			MeshKind::Backdrop,
+			Mesh::from(Rectangle::new(1.0, 1.0)).with_inserted_attribute(
+				Mesh::ATTRIBUTE_COLOR,
+				vec![
+					[1.0, 1.0, 1.0, 1.0],
+					[1.0, 1.0, 1.0, 1.0],
+					[0.3, 0.3, 0.3, 1.0],
+					[0.3, 0.3, 0.3, 1.0],
+				],
+			),
+		);
+		add(MeshKind::Stairs, Mesh::from(Cuboid::new(0.8, 0.8, 0.1)));
+		add(MeshKind::ExitBeam, Mesh::from(Cylinder::new(0.35, 1.0)));
+		add(MeshKind::Debris, Mesh::from(Cuboid::new(0.2, 0.08, 0.08)));
+		add(MeshKind::Rubble, Mesh::from(Cuboid::new(1.0, 1.0, 1.0)));
+		add(MeshKind::Platform, Mesh::from(Cuboid::new(0.9, 0.9, 0.2)));
+		add(MeshKind::Streak, Mesh::from(Sphere::new(0.1)));
+	}
+
+	fn load_built_in_materials(
+		&mut self,
+		material_assets: &mut Assets<StandardMaterial>,
+	) {
+		let mut add = |kind, material: StandardMaterial| {
+			self.materials.insert(kind, material_assets.add(material));
+		};
+		for color in (0..CharacterColor::COUNT).map(CharacterColor::from) {
+			add(MaterialKind::Character(color), color.color().into());
+		}
+		add(MaterialKind::Indicator, Color::WHITE.into());
+		add(
+			MaterialKind::DisabledIndicator,
+			Color::srgb(0.3, 0.3, 0.3).into(),
+		);
+		add(
+			MaterialKind::Highlight,
+			StandardMaterial {
+				base_color: Color::srgba(1.0, 1.0, 1.0, 0.4),
+				alpha_mode: AlphaMode::Blend,
+				unlit: true,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Backdrop,
+			StandardMaterial {
+				unlit: true,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Grid,
+			StandardMaterial {
+				base_color: Color::srgba(1.0, 1.0, 1.0, 0.35),
+				alpha_mode: AlphaMode::Blend,
+				unlit: true,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Danger,
+			StandardMaterial {
+				base_color: Color::srgba(1.0, 0.1, 0.1, 0.4),
+				alpha_mode: AlphaMode::Blend,
+				unlit: true,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Stairs,
+			StandardMaterial {
+				base_color: Color::srgb(0.9, 0.75, 0.3),
+				emissive: LinearRgba::rgb(0.3, 0.25, 0.1),
+				..default()
+			},
+		);
+		add(
+			MaterialKind::ActiveStairs,
+			StandardMaterial {
+				base_color: Color::srgb(1.0, 0.9, 0.5),
+				emissive: LinearRgba::rgb(1.2, 1.0, 0.4),
+				..default()
+			},
+		);
+		add(
+			MaterialKind::ExitBeam,
+			StandardMaterial {
+				base_color: Color::srgba(1.0, 0.9, 0.5, 0.5),
+				emissive: LinearRgba::rgb(2.0, 1.6, 0.6),
+				alpha_mode: AlphaMode::Blend,
+				unlit: true,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Ice,
+			StandardMaterial {
+				base_color: Color::srgba(0.7, 0.9, 1.0, 0.6),
+				alpha_mode: AlphaMode::Blend,
+				perceptual_roughness: 0.1,
+				reflectance: 0.8,
+				..default()
+			},
+		);
+		add(MaterialKind::Pit, Color::srgb(0.05, 0.05, 0.05).into());
+		add(MaterialKind::Debris, Color::srgb(0.55, 0.38, 0.2).into());
+		add(MaterialKind::Track, Color::srgb(0.2, 0.2, 0.22).into());
+		add(
+			MaterialKind::Platform,
+			StandardMaterial {
+				base_color: Color::srgb(0.5, 0.52, 0.55),
+				metallic: 0.6,
+				perceptual_roughness: 0.4,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Turntable,
+			Color::srgba(0.8, 0.5, 0.2, 0.6).into(),
+		);
+		add(
+			MaterialKind::Water,
+			StandardMaterial {
+				base_color: Color::srgba(0.1, 0.35, 0.6, 0.85),
+				alpha_mode: AlphaMode::Blend,
+				perceptual_roughness: 0.05,
+				reflectance: 0.6,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Lava,
+			StandardMaterial {
+				base_color: Color::srgb(0.9, 0.3, 0.05),
+				emissive: LinearRgba::rgb(4.0, 0.9, 0.1),
+				perceptual_roughness: 0.6,
+				..default()
+			},
+		);
+		add(
+			MaterialKind::Crust,
+			Color::srgba(0.15, 0.1, 0.08, 0.8).into(),
+		);
+		add(
+			MaterialKind::Cracks,
+			Color::srgba(0.1, 0.08, 0.06, 0.4).into(),
+		);
+		add(
+			MaterialKind::DeepCracks,
+			Color::srgba(0.1, 0.08, 0.06, 0.75).into(),
+		);
+		add(MaterialKind::Rubble, Color::srgb(0.45, 0.43, 0.4).into());
+		// From yellow for the lightest load to red for the heaviest.
+		for level in 1..=PRESSURE_LEVELS {
+			let heat = (level - 1) as f32 / (PRESSURE_LEVELS - 1) as f32;
+			add(
+				MaterialKind::Pressure(level),
+				StandardMaterial {
+					base_color: Color::srgba(1.0, 0.9 * (1.0 - heat), 0.1, 0.5),
+					alpha_mode: AlphaMode::Blend,
+					unlit: true,
+					..default()
+				},
+			);
+		}
+	}
+
+	/// The mesh of `kind`, or a blank one if it hasn't loaded.
+	pub fn mesh(&self, kind: MeshKind) -> Handle<Mesh> {
+		self.meshes.get(&kind).cloned().unwrap_or_default()
+	}
+
+	/// The material of `kind`.
+	pub fn material(&self, kind: MaterialKind) -> Handle<StandardMaterial> {
+		self.materials.get(&kind).cloned().unwrap_or_default()
+	}
+
+	/// The scene of `kind`, or an empty one if there's no such model.
+	pub fn model(&self, kind: ModelKind) -> Handle<Scene> {
+		self.models.get(&kind).cloned().unwrap_or_default()
+	}
+
+	/// The scene for `tile`, with the height to place it at, or `None` if
+	/// the tile isn't drawn.
+	pub fn tile(&self, tile: Tile) -> Option<(Handle<Scene>, f32)> {
+		match tile {
+			Tile::Floor { .. }
+			| Tile::Stairs
+			| Tile::Ice
+			| Tile::Turntable { .. }
+			| Tile::Cracked { .. }
+			| Tile::Lava { bridged: true } => Some((self.model(ModelKind::Floor), -0.5)),
+			Tile::Wall => Some((self.model(ModelKind::Wall), 0.5)),
+			// Only wooden crates float, so they're what bridges water, riding
+			// low in it.
+			Tile::Water { bridged: true } => {
+				let Object::Prop(raft) = Object::WOODEN_CRATE else {
+					return None;
+				};
+				Some((self.model(ModelKind::Prop(raft.model)), -0.4))
+			}
+			Tile::Void
+			| Tile::Pit
+			| Tile::Track { .. }
+			| Tile::Water { bridged: false }
+			| Tile::Lava { bridged: false } => None,
+		}
+	}
+
+	/// Uses `mesh` for meshes of `kind`, from then on.
+	pub fn set_mesh(&mut self, kind: MeshKind, mesh: Handle<Mesh>) {
+		self.meshes.insert(kind, mesh);
+	}
+
+	/// Uses `material` for materials of `kind`, from then on.
+	pub fn set_material(
+		&mut self,
+		kind: MaterialKind,
+		material: Handle<StandardMaterial>,
+	) {
+		self.materials.insert(kind, material);
+	}
+
+	/// Uses `scene` for models of `kind`, from then on.
+	pub fn set_model(&mut self, kind: ModelKind, scene: Handle<Scene>) {
+		self.models.insert(kind, scene);
+	}
+
+	/// Starts loading the first mesh of the model file at `path` into meshes
+	/// of `kind`. Until it loads, the current mesh of `kind` is kept, if any.
+	pub fn load_mesh(
+		&mut self,
+		asset_server: &AssetServer,
+		kind: MeshKind,
+		path: impl Into<String>,
+	) {
+		let path = path.into();
+		self.pending
+			.insert(asset_server.load(path.clone()), (kind, path));
+		self.requested += 1;
+	}
+
+	/// Whether every mesh requested from a model file has loaded or failed
+	/// to.
+	pub fn is_loaded(&self) -> bool {
+		self.pending.is_empty()
+	}
+
+	/// How many meshes requested from model files have loaded or failed to,
+	/// out of how many were requested.
+	pub fn progress(&self) -> (usize, usize) {
+		(self.requested - self.pending.len(), self.requested)
+	}
+
+	/// Why each model file that failed to load did.
+	pub fn failures(&self) -> &[String] {
+		&self.failures
+	}
+}
+
+/// Moves each mesh requested from a model file into [`GameAssets`] once its
+/// model loads, and gives up on it if its model fails to.
+pub fn load_pending_meshes(
+	asset_server: Res<AssetServer>,
+	mut assets: ResMut<GameAssets>,
+	gltf_assets: Res<Assets<Gltf>>,
+	gltf_mesh_assets: Res<Assets<GltfMesh>>,
+) {
+	if assets.pending.is_empty() {
+		return;
+	}
+	let assets = &mut *assets;
+	assets.pending.retain(|handle, (kind, path)| {
+		if let Some(gltf) = gltf_assets.get(handle) {
+			let mesh = gltf
+				.meshes
+				.first()
+				.and_then(|mesh| gltf_mesh_assets.get(mesh))
+				.and_then(|mesh| mesh.primitives.first());
+			match mesh {
+				Some(primitive) => {
+					assets.meshes.insert(*kind, primitive.mesh.clone());
+				}
+				None => {
+					let failure = format!("{path}: model has no mesh");
+					warn!("could not load {kind:?} mesh from {failure}");
+					assets.failures.push(failure);
+				}
+			}
+			return false;
+		}
+		if let LoadState::Failed(err) = asset_server.load_state(handle.id()) {
+			let failure = format!("{path}: {err}");
+			warn!("could not load {kind:?} mesh from {failure}");
+			assets.failures.push(failure);
+			return false;
+		}
+		true
+	});
+}
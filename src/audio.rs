@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	level::{Level, LevelEntity},
+	settings::Settings,
+};
+
+/// Registered audio tracks, as (key, asset path, bus) triples.
+const TRACKS: [(&str, &str, Bus); 23] = [
+	("meadow", "audio/music/meadow.ogg", Bus::Music),
+	("caverns", "audio/music/caverns.ogg", Bus::Music),
+	("expanse", "audio/music/expanse.ogg", Bus::Music),
+	("birdsong", "audio/ambience/birdsong.ogg", Bus::Ambience),
+	("dripping", "audio/ambience/dripping.ogg", Bus::Ambience),
+	("tick", "audio/cues/tick.ogg", Bus::Ui),
+	("planning", "audio/cues/planning.ogg", Bus::Ui),
+	("exit_open", "audio/cues/exit_open.ogg", Bus::Sfx),
+	("nudge", "audio/cues/nudge.ogg", Bus::Ui),
+	("move_up", "audio/cues/move_up.ogg", Bus::Sfx),
+	("move_down", "audio/cues/move_down.ogg", Bus::Sfx),
+	("move_left", "audio/cues/move_left.ogg", Bus::Sfx),
+	("move_right", "audio/cues/move_right.ogg", Bus::Sfx),
+	("blocked", "audio/cues/blocked.ogg", Bus::Sfx),
+	("fanfare", "audio/cues/fanfare.ogg", Bus::Sfx),
+	("voice_green", "audio/voice/green.ogg", Bus::Ui),
+	("voice_red", "audio/voice/red.ogg", Bus::Ui),
+	("voice_blue", "audio/voice/blue.ogg", Bus::Ui),
+	("voice_yellow", "audio/voice/yellow.ogg", Bus::Ui),
+	("voice_magenta", "audio/voice/magenta.ogg", Bus::Ui),
+	("voice_cyan", "audio/voice/cyan.ogg", Bus::Ui),
+	("voice_black", "audio/voice/black.ogg", Bus::Ui),
+	("voice_white", "audio/voice/white.ogg", Bus::Ui),
+];
+
+/// A channel of audio with its own volume, set in the [`Mixer`].
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+	Music,
+	Ambience,
+	/// Sounds of things happening in the level, like moves and pushes.
+	Sfx,
+	/// Interface feedback, like the planning tick and actor announcements.
+	Ui,
+}
+
+/// The volume of one [`Bus`].
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct BusLevel {
+	/// The volume multiplier, from 0 (silent) to 1 (full).
+	pub volume: f32,
+	pub muted: bool,
+}
+
+impl Default for BusLevel {
+	fn default() -> BusLevel {
+		BusLevel {
+			volume: 1.0,
+			muted: false,
+		}
+	}
+}
+
+impl BusLevel {
+	fn gain(self) -> f32 {
+		if self.muted {
+			0.0
+		} else {
+			self.volume.clamp(0.0, 1.0)
+		}
+	}
+}
+
+/// Per-bus volume settings.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct Mixer {
+	pub music: BusLevel,
+	pub ambience: BusLevel,
+	pub sfx: BusLevel,
+	pub ui: BusLevel,
+}
+
+impl Mixer {
+	/// The volume to play `bus` at, accounting for mutes.
+	pub fn gain(&self, bus: Bus) -> f32 {
+		match bus {
+			Bus::Music => self.music,
+			Bus::Ambience => self.ambience,
+			Bus::Sfx => self.sfx,
+			Bus::Ui => self.ui,
+		}
+		.gain()
+	}
+}
+
+/// How long music stays dipped for the level completion fanfare.
+const FANFARE_DURATION: Duration = Duration::from_millis(2500);
+
+/// The music volume multiplier while ducked.
+const DUCKED_GAIN: f32 = 0.3;
+
+/// Temporarily lowers the music so a cue like the victory fanfare stands out.
+#[derive(Resource)]
+pub struct Ducking(Timer);
+
+impl Default for Ducking {
+	fn default() -> Ducking {
+		// Start finished, so music isn't ducked until something asks.
+		let mut timer = Timer::new(Duration::ZERO, TimerMode::Once);
+		timer.tick(Duration::ZERO);
+		Ducking(timer)
+	}
+}
+
+impl Ducking {
+	/// Ducks the music for `duration`, or longer if it's already ducked for
+	/// longer.
+	fn duck(&mut self, duration: Duration) {
+		if self.0.remaining() < duration {
+			self.0 = Timer::new(duration, TimerMode::Once);
+		}
+	}
+
+	fn gain(&self, bus: Bus) -> f32 {
+		if bus == Bus::Music && !self.0.finished() {
+			DUCKED_GAIN
+		} else {
+			1.0
+		}
+	}
+}
+
+/// The music and ambience to play, as keys into the [`AudioTracks`] registry.
+/// Either may be unset to defer to a fallback.
+#[derive(Clone, Copy, Default)]
+pub struct Soundtrack {
+	pub music: Option<&'static str>,
+	pub ambience: Option<&'static str>,
+}
+
+/// The registered key equal to `name`, if there is one.
+pub fn track_key(name: &str) -> Option<&'static str> {
+	TRACKS
+		.into_iter()
+		.map(|(key, _, _)| key)
+		.find(|key| *key == name)
+}
+
+/// The bus the track registered as `key` plays on.
+fn track_bus(key: &str) -> Option<Bus> {
+	TRACKS
+		.into_iter()
+		.find_map(|(track, _, bus)| (track == key).then_some(bus))
+}
+
+/// Loaded audio tracks, keyed by name. Registered tracks whose files are
+/// missing from the assets directory are left out, so they stay silent.
+#[derive(Resource)]
+pub struct AudioTracks {
+	tracks: HashMap<&'static str, Handle<AudioSource>>,
+}
+
+impl AudioTracks {
+	pub fn load(asset_server: &mut AssetServer) -> Self {
+		let assets_dir = FileAssetReader::get_base_path().join("assets");
+		let (present, missing): (Vec<_>, Vec<_>) = TRACKS
+			.into_iter()
+			.partition(|(_, path, _)| assets_dir.join(path).is_file());
+		if !missing.is_empty() {
+			info!(
+				"{} audio tracks are missing; they won't play",
+				missing.len()
+			);
+		}
+		Self {
+			tracks: present
+				.into_iter()
+				.map(|(key, path, _)| (key, asset_server.load(path)))
+				.collect(),
+		}
+	}
+
+	/// The track for the first of `keys` that's loaded, warning about any
+	/// unregistered keys along the way. Keys of missing tracks are skipped
+	/// quietly.
+	fn resolve(
+		&self,
+		keys: impl IntoIterator<Item = Option<&'static str>>,
+	) -> Option<Handle<AudioSource>> {
+		keys.into_iter().flatten().find_map(|key| {
+			let track = self.tracks.get(key).cloned();
+			if track.is_none() && track_bus(key).is_none() {
+				warn!("no audio track registered as \"{key}\"");
+			}
+			track
+		})
+	}
+}
+
+/// Starts looping the level's music and ambience. Each falls back to the
+/// default for the level's theme if the level doesn't set it or sets an
+/// unregistered track.
+///
+/// Audio starts paused, to be unpaused by [`apply_mixer`] once its volume is
+/// set.
+pub fn play_level_audio(
+	mut commands: Commands,
+	level: Res<Level>,
+	tracks: Res<AudioTracks>,
+) {
+	let soundtrack = level.soundtrack();
+	let theme = level.lighting().soundtrack;
+	for (track, bus) in [
+		(tracks.resolve([soundtrack.music, theme.music]), Bus::Music),
+		(
+			tracks.resolve([soundtrack.ambience, theme.ambience]),
+			Bus::Ambience,
+		),
+	] {
+		let Some(track) = track else {
+			continue;
+		};
+		commands.spawn((
+			LevelEntity,
+			AudioPlayer::new(track),
+			PlaybackSettings::LOOP.paused(),
+			bus,
+		));
+	}
+}
+
+/// Plays the track registered as `key` once, as a sound cue, on the track's
+/// bus.
+pub fn play_cue(commands: &mut Commands, tracks: &AudioTracks, key: &str) {
+	let key = track_key(key);
+	let (Some(track), Some(bus)) =
+		(tracks.resolve([key]), key.and_then(track_bus))
+	else {
+		return;
+	};
+	commands.spawn((
+		AudioPlayer::new(track),
+		PlaybackSettings::DESPAWN.paused(),
+		bus,
+	));
+}
+
+/// Plays the fanfare when a level is completed, ducking the music under it.
+pub fn play_fanfare(
+	mut commands: Commands,
+	tracks: Res<AudioTracks>,
+	mut ducking: ResMut<Ducking>,
+) {
+	play_cue(&mut commands, &tracks, "fanfare");
+	ducking.duck(FANFARE_DURATION);
+}
+
+/// Sets the volume of playing audio from its bus's level in the mixer and any
+/// ducking, and starts audio that's ready to play.
+pub fn apply_mixer(
+	time: Res<Time>,
+	settings: Res<Settings>,
+	mut ducking: ResMut<Ducking>,
+	sink_query: Query<(Ref<AudioSink>, &Bus)>,
+) {
+	let was_ducked = !ducking.0.finished();
+	ducking.0.tick(time.delta());
+	let ducking_changed = was_ducked || !ducking.0.finished();
+	for (sink, bus) in &sink_query {
+		if settings.is_changed() || ducking_changed || sink.is_added() {
+			sink.set_volume(settings.mixer.gain(*bus) * ducking.gain(*bus));
+		}
+		if sink.is_added() {
+			sink.play();
+		}
+	}
+}
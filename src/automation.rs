@@ -0,0 +1,187 @@
+use std::{
+	fmt::Write as _,
+	fs::File,
+	io::{self, LineWriter, Write},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+	campaign::LevelCompleted,
+	level::{ChangeEvent, Coords, Id, Level},
+	update::LevelStatusEvent,
+};
+
+/// Environment variable naming the file or named pipe to write automation
+/// events to. Events go to stdout if it's not set.
+const EVENTS_VAR: &str = "CAUSAL_OOPS_EVENTS";
+
+/// A channel of structured game events for external tools, like end-to-end
+/// tests and recorders, to assert on without scraping the screen. Only built
+/// with the `automation` feature.
+///
+/// Each event is a JSON object on its own line, with an `event` field giving
+/// its kind:
+///
+/// - `change`: the contents of a change to the level, from a turn, undo, or
+///   redo. Each of `moves`, `creations`, `destructions`, `summonings`, and
+///   `returnings` lists the objects involved by ID, with their coordinates as
+///   `[row, col]` where relevant, and `crushed` and `blocked` list IDs.
+/// - `turn`: the level's turn and state hash, after the frame's changes. The
+///   hash is a hexadecimal string, since it may not fit in a JSON number.
+/// - `exit`: whether the level's exit has activated, with a character on the
+///   stairs, or deactivated again.
+/// - `complete`: the statistics of a completed level: its `turns`, `par` (or
+///   `null`), `summonings`, and `returnings`.
+#[derive(Resource)]
+pub struct AutomationLog(Box<dyn Write + Send + Sync>);
+
+impl Default for AutomationLog {
+	fn default() -> AutomationLog {
+		let Some(path) = std::env::var_os(EVENTS_VAR) else {
+			return AutomationLog(Box::new(io::stdout()));
+		};
+		match File::create(&path) {
+			Ok(file) => AutomationLog(Box::new(LineWriter::new(file))),
+			Err(err) => {
+				warn!(
+					"could not open automation events at {}: {err}",
+					path.to_string_lossy(),
+				);
+				AutomationLog(Box::new(io::stdout()))
+			}
+		}
+	}
+}
+
+impl AutomationLog {
+	/// Writes one event line, warning if the channel is closed.
+	fn emit(&mut self, event: &str) {
+		if let Err(err) = writeln!(self.0, "{event}") {
+			warn!("could not write automation event: {err}");
+		}
+	}
+}
+
+/// An event of the given `kind` as a JSON object with `fields`, whose values
+/// are already JSON.
+fn event_json(kind: &str, fields: &[(&str, String)]) -> String {
+	let mut json = format!(r#"{{"event":"{kind}""#);
+	for (name, value) in fields {
+		write!(json, r#","{name}":{value}"#).unwrap();
+	}
+	json.push('}');
+	json
+}
+
+/// `coords` as a JSON array.
+fn coords_json(coords: Coords) -> String {
+	format!("[{},{}]", coords.row, coords.col)
+}
+
+/// A JSON array of `items` converted by `to_json`, sorted by ID so that events
+/// are deterministic.
+fn id_list<'a, T>(
+	items: impl IntoIterator<Item = (&'a Id, T)>,
+	to_json: impl Fn(Id, T) -> String,
+) -> String {
+	let mut items: Vec<(&Id, T)> = items.into_iter().collect();
+	items.sort_by_key(|(id, _)| id.0);
+	let items: Vec<String> = items
+		.into_iter()
+		.map(|(id, item)| to_json(*id, item))
+		.collect();
+	format!("[{}]", items.join(","))
+}
+
+/// A JSON object with an object's ID and where it is.
+fn placed_json(id: Id, coords: Coords) -> String {
+	format!(r#"{{"id":{},"at":{}}}"#, id.0, coords_json(coords))
+}
+
+/// Writes automation events for each change to the level, its turn and state
+/// hash whenever it changes, its exit activating or deactivating, and its
+/// completion.
+pub fn emit_automation_events(
+	mut log: ResMut<AutomationLog>,
+	level: Res<Level>,
+	mut change_events: EventReader<ChangeEvent>,
+	mut status_events: EventReader<LevelStatusEvent>,
+	mut completed_events: EventReader<LevelCompleted>,
+) {
+	for change in change_events.read() {
+		let moves = id_list(&change.moves, |id, mv| {
+			format!(
+				r#"{{"id":{},"from":{},"to":{}}}"#,
+				id.0,
+				coords_json(mv.from_coords),
+				coords_json(mv.to_coords),
+			)
+		});
+		let creations = id_list(&change.creations, |id, object| {
+			placed_json(id, object.coords)
+		});
+		let destructions = id_list(&change.destructions, |id, object| {
+			placed_json(id, object.coords)
+		});
+		let summonings = id_list(&change.summonings, |id, summoning| {
+			placed_json(id, summoning.summon.coords)
+		});
+		let returnings = id_list(&change.returnings, |id, returning| {
+			placed_json(id, returning.returner.coords)
+		});
+		let crushed =
+			id_list(change.crushed.iter().map(|id| (id, ())), |id, ()| {
+				id.0.to_string()
+			});
+		let blocked =
+			id_list(change.blocked.iter().map(|id| (id, ())), |id, ()| {
+				id.0.to_string()
+			});
+		log.emit(&event_json(
+			"change",
+			&[
+				("moves", moves),
+				("creations", creations),
+				("destructions", destructions),
+				("summonings", summonings),
+				("returnings", returnings),
+				("crushed", crushed),
+				("blocked", blocked),
+			],
+		));
+	}
+	if level.is_changed() {
+		log.emit(&event_json(
+			"turn",
+			&[
+				("turn", level.turn().to_string()),
+				("state_hash", format!(r#""{:016x}""#, level.state_hash())),
+				("complete", level.is_complete().to_string()),
+			],
+		));
+	}
+	for status in status_events.read() {
+		log.emit(&event_json(
+			"exit",
+			&[("active", status.complete.to_string())],
+		));
+	}
+	for completed in completed_events.read() {
+		log.emit(&event_json(
+			"complete",
+			&[
+				("turns", completed.turns.to_string()),
+				(
+					"par",
+					completed.par.map_or_else(
+						|| "null".to_string(),
+						|par| par.to_string(),
+					),
+				),
+				("summonings", completed.summonings.to_string()),
+				("returnings", completed.returnings.to_string()),
+			],
+		));
+	}
+}
@@ -0,0 +1,102 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+	animation,
+	control::Action,
+	level::{CharacterColor, Id, Level},
+};
+
+/// Radius of the ring drawn under scripted characters.
+const RING_RADIUS: f32 = 0.45;
+
+/// Height above the floor at which rings are drawn.
+const RING_HEIGHT: f32 = 0.05;
+
+/// Records each character's actions and, when enabled, replays them for
+/// summoned characters so that replaying a stretch of time after undoing
+/// doesn't require re-entering their actions by hand.
+///
+/// Actions are keyed by turn and character color rather than ID, since a
+/// summon gets a fresh ID each time it's summoned but the same color.
+#[derive(Resource, Default)]
+pub struct Autopilot {
+	enabled: bool,
+	scripts: HashMap<(usize, CharacterColor), Action>,
+}
+
+impl Autopilot {
+	/// Records that the characters with the given IDs took the given actions
+	/// on `level`'s current turn. Must be called before the turn is executed.
+	pub fn record(&mut self, level: &Level, actions: &[(Id, Action)]) {
+		for (id, action) in actions {
+			let color = level.character_by_id(id).color;
+			self.scripts.insert((level.turn(), color), *action);
+		}
+	}
+
+	/// Forgets all recorded actions, e.g. when changing levels.
+	pub fn clear(&mut self) {
+		self.scripts.clear();
+	}
+
+	/// The recorded action of the character with the given `id` for the
+	/// current turn, if the autopilot should act for it.
+	fn script(&self, level: &Level, id: &Id) -> Option<Action> {
+		if !self.enabled || !level.has_character(id) || !level.is_summoned(id) {
+			return None;
+		}
+		let color = level.character_by_id(id).color;
+		self.scripts.get(&(level.turn(), color)).copied()
+	}
+
+	/// Whether the autopilot will act for the character with the given `id`
+	/// this turn.
+	pub fn is_scripted(&self, level: &Level, id: &Id) -> bool {
+		self.script(level, id).is_some()
+	}
+
+	/// The `idx`-th character and its recorded action, if the autopilot should
+	/// act for it.
+	pub fn scripted_action(
+		&self,
+		level: &Level,
+		idx: usize,
+	) -> Option<(Id, Action)> {
+		let (&id, _) = level.characters_by_id().nth(idx)?;
+		self.script(level, &id).map(|action| (id, action))
+	}
+}
+
+/// Turns the autopilot on or off when O is pressed.
+pub fn toggle_autopilot(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut autopilot: ResMut<Autopilot>,
+) {
+	if keys.just_pressed(KeyCode::KeyO) {
+		autopilot.enabled = !autopilot.enabled;
+	}
+}
+
+/// Draws a ring under each character the autopilot is controlling, to
+/// distinguish scripted characters from player-controlled ones.
+pub fn draw_scripted_rings(
+	autopilot: Res<Autopilot>,
+	level: Res<Level>,
+	object_query: Query<(&animation::Object, &GlobalTransform)>,
+	mut gizmos: Gizmos,
+) {
+	if !autopilot.enabled {
+		return;
+	}
+	for (object, transform) in &object_query {
+		if autopilot.is_scripted(&level, &object.id) {
+			let mut position = transform.translation();
+			position.z = RING_HEIGHT;
+			gizmos.circle(
+				Isometry3d::from_translation(position),
+				RING_RADIUS,
+				Color::srgb(0.3, 0.8, 1.0),
+			);
+		}
+	}
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use bevy::{
+	pbr::{NotShadowCaster, NotShadowReceiver},
+	prelude::*,
+};
+
+use crate::{
+	assets::{GameAssets, MaterialKind, MeshKind},
+	level::Level,
+	settings::Settings,
+};
+
+/// Distance from the camera to the backdrop.
+const BACKDROP_DISTANCE: f32 = 900.0;
+/// Side length of the backdrop, large enough to fill the view.
+const BACKDROP_SIZE: f32 = 2000.0;
+
+/// A gradient sky that slowly cycles through a sequence of colors.
+#[derive(Clone, Copy)]
+pub struct Sky {
+	/// Colors to cycle through, evenly spaced over the cycle.
+	pub colors: &'static [Color],
+	/// How long one full cycle takes.
+	pub cycle: Duration,
+}
+
+impl Sky {
+	/// The sky color `elapsed` into the cycle.
+	fn color_at(&self, elapsed: Duration) -> Color {
+		let count = self.colors.len();
+		let phase = (elapsed.as_secs_f32() / self.cycle.as_secs_f32()).fract()
+			* count as f32;
+		let idx = phase as usize % count;
+		let from = LinearRgba::from(self.colors[idx]);
+		let to = LinearRgba::from(self.colors[(idx + 1) % count]);
+		from.mix(&to, phase.fract()).into()
+	}
+}
+
+/// Marker component for the backdrop entity.
+#[derive(Component)]
+pub struct Backdrop;
+
+/// The backdrop's transform relative to its camera.
+fn backdrop_transform() -> Transform {
+	Transform::from_translation(-BACKDROP_DISTANCE * Vec3::Z)
+		.with_scale(Vec3::splat(BACKDROP_SIZE))
+}
+
+/// A backdrop, to be spawned as a child of a camera so it always fills the
+/// view.
+pub fn backdrop_bundle(assets: &GameAssets) -> impl Bundle {
+	(
+		Backdrop,
+		Mesh3d(assets.mesh(MeshKind::Backdrop)),
+		MeshMaterial3d(assets.material(MaterialKind::Backdrop)),
+		backdrop_transform(),
+		NotShadowCaster,
+		NotShadowReceiver,
+	)
+}
+
+/// Tints the backdrop according to the level's sky and the time, or its fixed
+/// background color if it has one.
+pub fn animate_backdrop(
+	time: Res<Time>,
+	settings: Res<Settings>,
+	level: Res<Level>,
+	assets: Res<GameAssets>,
+	mut material_assets: ResMut<Assets<StandardMaterial>>,
+) {
+	let lighting = level.lighting();
+	let sky = lighting.sky;
+	let color = match lighting.clear_color {
+		Some(color) => color,
+		None if settings.animated_backdrop => sky.color_at(time.elapsed()),
+		None => sky.colors[0],
+	};
+	if let Some(material) =
+		material_assets.get_mut(&assets.material(MaterialKind::Backdrop))
+	{
+		material.base_color = color;
+	}
+}
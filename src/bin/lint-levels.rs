@@ -0,0 +1,24 @@
+//! Lints every level file in a directory, like a campaign before release.
+//! See [`check::lint`].
+//!
+//! Usage: `cargo run --bin lint-levels -- <dir>`
+
+use std::path::Path;
+
+use causal_oops::check;
+
+fn main() {
+	let args: Vec<String> = std::env::args().collect();
+	let Some(dir) = args.get(1) else {
+		eprintln!("usage: lint-levels <dir>");
+		std::process::exit(2);
+	};
+	match check::lint(Path::new(dir)) {
+		Ok(true) => {}
+		Ok(false) => std::process::exit(1),
+		Err(err) => {
+			eprintln!("{dir}: {err}");
+			std::process::exit(2);
+		}
+	}
+}
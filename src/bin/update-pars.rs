@@ -0,0 +1,22 @@
+//! Writes the optimal move count of every level file in a directory back to
+//! the file as its par. See [`check::update_pars`].
+//!
+//! Usage: `cargo run --release --bin update-pars -- [dir]`, where `dir`
+//! defaults to the bundled levels in `assets/levels`.
+
+use std::path::Path;
+
+use causal_oops::check;
+
+fn main() {
+	let args: Vec<String> = std::env::args().collect();
+	let dir = args.get(1).map_or("assets/levels", String::as_str);
+	match check::update_pars(Path::new(dir)) {
+		Ok(true) => {}
+		Ok(false) => std::process::exit(1),
+		Err(err) => {
+			eprintln!("{dir}: {err}");
+			std::process::exit(2);
+		}
+	}
+}
@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+
+use crate::{
+	assets::GameAssets,
+	history,
+	level::{Level, LevelEntity},
+	states::GameState,
+	thumbnails,
+};
+
+/// Where each branch is staged while comparing them, left then right. They're
+/// apart from each other and from where thumbnails are staged.
+const STAGING_OFFSETS: [Vec3; 2] = [
+	Vec3::new(-10_000.0, 0.0, 0.0),
+	Vec3::new(-20_000.0, 0.0, 0.0),
+];
+
+/// Width and height of each branch's view, in pixels.
+const VIEW_PIXELS: u32 = 512;
+
+/// Width and height of each branch's view on screen.
+const VIEW_SIZE: Val = Val::Px(384.0);
+
+/// Lines of play through the current level.
+///
+/// Taking a turn after undoing discards the undone turns, so before that
+/// happens, the line they were part of is kept as the other branch, which can
+/// be compared side by side with the current one and continued instead.
+#[derive(Resource, Default)]
+pub struct Branches {
+	/// The level as of its last change, to tell when a turn discards history.
+	previous: Option<Level>,
+	/// The most recently discarded line of play, at the end of its history.
+	other: Option<Level>,
+	/// The roots of the staged branches, while comparing them.
+	stages: Vec<Entity>,
+}
+
+impl Branches {
+	/// The other branch, if it's a line of play through `level`.
+	fn other_of(&self, level: &Level) -> Option<&Level> {
+		self.other.as_ref().filter(|other| {
+			other.rewound().state_hash() == level.rewound().state_hash()
+		})
+	}
+}
+
+/// Keeps the line of play a turn is about to discard as the other branch.
+pub fn track_branches(level: Res<Level>, mut branches: ResMut<Branches>) {
+	if !level.is_changed() {
+		return;
+	}
+	if let Some(previous) = branches.previous.take() {
+		let turn = level.turn();
+		let previous_hashes: Vec<u64> = history::turns(&previous)
+			.map(|turn| turn.state_hash)
+			.collect();
+		// The previous level was at the last turn, and at least the turn being
+		// replaced was still there to redo.
+		let diverged = turn > 0
+			&& !level.can_redo()
+			&& previous.turn() + 1 == turn
+			&& previous_hashes.len() >= turn
+			&& (previous_hashes.len() > turn
+				|| history::turns(&level).last().map(|turn| turn.state_hash)
+					!= previous_hashes.last().copied());
+		if diverged {
+			let mut other = previous;
+			while other.redo().is_some() {}
+			branches.other = Some(other);
+		}
+	}
+	branches.previous = Some(level.clone());
+}
+
+/// Marker component for the branch comparison.
+#[derive(Component)]
+pub struct BranchComparison;
+
+/// Opens the branch comparison when K is pressed, if there's another branch
+/// through the current level.
+pub fn open_branch_comparison(
+	keys: Res<ButtonInput<KeyCode>>,
+	level: Res<Level>,
+	branches: Res<Branches>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::KeyK) && branches.other_of(&level).is_some() {
+		next_state.set(GameState::ComparingBranches);
+	}
+}
+
+/// Stages the current and other branches, each with a camera rendering it to
+/// an image, and shows the images side by side.
+pub fn spawn_branch_comparison(
+	mut commands: Commands,
+	level: Res<Level>,
+	mut branches: ResMut<Branches>,
+	mut images: ResMut<Assets<Image>>,
+	assets: Res<GameAssets>,
+) {
+	let Some(other) = branches.other_of(&level).cloned() else {
+		return;
+	};
+	let mut views = Vec::new();
+	for (idx, (branch, label)) in
+		[(&*level, "This line"), (&other, "Other line")]
+			.into_iter()
+			.enumerate()
+	{
+		let image = images.add(thumbnails::render_target(VIEW_PIXELS));
+		let root = thumbnails::stage(
+			&mut commands,
+			branch,
+			STAGING_OFFSETS[idx],
+			&image,
+			&assets,
+		);
+		branches.stages.push(root);
+		let status = if branch.is_complete() {
+			" (complete)"
+		} else {
+			""
+		};
+		views.push((image, format!("{label}: turn {}{status}", branch.turn())));
+	}
+	commands
+		.spawn((
+			BranchComparison,
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Percent(10.0),
+				left: Val::Percent(10.0),
+				width: Val::Percent(80.0),
+				padding: UiRect::all(Val::Px(12.0)),
+				row_gap: Val::Px(8.0),
+				flex_direction: FlexDirection::Column,
+				align_items: AlignItems::Center,
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+		))
+		.with_children(|dialog| {
+			dialog
+				.spawn(Node {
+					column_gap: Val::Px(12.0),
+					..default()
+				})
+				.with_children(|row| {
+					for (image, label) in views {
+						row.spawn(Node {
+							flex_direction: FlexDirection::Column,
+							align_items: AlignItems::Center,
+							row_gap: Val::Px(4.0),
+							..default()
+						})
+						.with_children(|view| {
+							view.spawn((
+								ImageNode::new(image),
+								Node {
+									width: VIEW_SIZE,
+									height: VIEW_SIZE,
+									..default()
+								},
+							));
+							view.spawn((
+								Text::new(label),
+								TextFont {
+									font_size: 16.0,
+									..default()
+								},
+							));
+						});
+					}
+				});
+			dialog.spawn((
+				Text::new(
+					"Enter: continue the other line   Esc: keep this line",
+				),
+				TextFont {
+					font_size: 14.0,
+					..default()
+				},
+			));
+		});
+}
+
+/// Despawns the branch comparison and its stages.
+pub fn despawn_branch_comparison(
+	mut commands: Commands,
+	mut branches: ResMut<Branches>,
+	comparison_query: Query<Entity, With<BranchComparison>>,
+) {
+	for entity in &comparison_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	for root in branches.stages.drain(..) {
+		commands.entity(root).despawn_recursive();
+	}
+}
+
+/// Handles branch comparison input: Enter switches to the other branch,
+/// keeping the current one as the other branch in its place, and K or Escape
+/// returns to the current branch.
+pub fn compare_branches(
+	mut commands: Commands,
+	keys: Res<ButtonInput<KeyCode>>,
+	mut level: ResMut<Level>,
+	mut branches: ResMut<Branches>,
+	mut next_state: ResMut<NextState<GameState>>,
+	level_entities: Query<Entity, With<LevelEntity>>,
+) {
+	if keys.just_pressed(KeyCode::Escape) || keys.just_pressed(KeyCode::KeyK) {
+		next_state.set(GameState::Playing);
+		return;
+	}
+	if !keys.just_pressed(KeyCode::Enter) {
+		return;
+	}
+	let Some(other) = branches.other_of(&level).cloned() else {
+		next_state.set(GameState::Playing);
+		return;
+	};
+	for entity in &level_entities {
+		commands.entity(entity).despawn_recursive();
+	}
+	branches.other = Some(std::mem::replace(&mut *level, other));
+	branches.previous = Some(level.clone());
+	next_state.set(GameState::SpawningLevel);
+}
@@ -0,0 +1,366 @@
+use std::fmt::Write;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+	campaign::{Campaign, PlayLevel},
+	level_asset::{LevelAsset, Levels, Mechanic},
+	locale,
+	progress::Progress,
+	settings::Settings,
+	sharing,
+	states::GameState,
+	thumbnails::Thumbnails,
+};
+
+/// Width and height of the selected level's thumbnail in the browser.
+const THUMBNAIL_SIZE: Val = Val::Px(160.0);
+
+/// Which levels the browser lists.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Filter {
+	#[default]
+	All,
+	/// Levels requiring a mechanic the player hasn't seen yet.
+	Unseen,
+	/// Levels requiring the given mechanic.
+	Uses(Mechanic),
+}
+
+impl Filter {
+	/// The filter after this one in the cycle.
+	fn next(self) -> Filter {
+		match self {
+			Filter::All => Filter::Unseen,
+			Filter::Unseen => Filter::Uses(Mechanic::ALL[0]),
+			Filter::Uses(mechanic) => {
+				let idx = Mechanic::ALL.iter().position(|m| *m == mechanic);
+				match idx.and_then(|idx| Mechanic::ALL.get(idx + 1)) {
+					Some(next) => Filter::Uses(*next),
+					None => Filter::All,
+				}
+			}
+		}
+	}
+
+	fn describe(self) -> String {
+		match self {
+			Filter::All => "all".to_string(),
+			Filter::Unseen => "mechanics I haven't seen".to_string(),
+			Filter::Uses(mechanic) => format!("uses {}", mechanic.name()),
+		}
+	}
+
+	fn matches(self, asset: &LevelAsset, progress: &Progress) -> bool {
+		let mechanics = &asset.tags.mechanics;
+		match self {
+			Filter::All => true,
+			Filter::Unseen => mechanics
+				.iter()
+				.any(|mechanic| !progress.seen_mechanics.contains(mechanic)),
+			Filter::Uses(mechanic) => mechanics.contains(&mechanic),
+		}
+	}
+}
+
+/// The order the browser lists levels in.
+#[derive(Clone, Copy, Default)]
+enum Sort {
+	#[default]
+	Bundled,
+	Difficulty,
+	Name,
+	Author,
+}
+
+impl Sort {
+	/// The sort order after this one in the cycle.
+	fn next(self) -> Sort {
+		match self {
+			Sort::Bundled => Sort::Difficulty,
+			Sort::Difficulty => Sort::Name,
+			Sort::Name => Sort::Author,
+			Sort::Author => Sort::Bundled,
+		}
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Sort::Bundled => "default",
+			Sort::Difficulty => "difficulty",
+			Sort::Name => "name",
+			Sort::Author => "author",
+		}
+	}
+}
+
+/// The state of the level browser.
+#[derive(Resource, Default)]
+pub struct LevelBrowser {
+	filter: Filter,
+	sort: Sort,
+	/// The index of the selected level in the filtered, sorted list.
+	selected: usize,
+}
+
+impl LevelBrowser {
+	/// The levels to list, with their indices. Names are sorted as shown in
+	/// `language`.
+	fn listed<'a>(
+		&self,
+		levels: &'a Levels,
+		level_assets: &'a Assets<LevelAsset>,
+		progress: &Progress,
+		language: &str,
+	) -> Vec<(usize, &'a LevelAsset)> {
+		let mut listed: Vec<_> = levels
+			.loaded(level_assets)
+			.filter(|(_, asset)| self.filter.matches(asset, progress))
+			.collect();
+		match self.sort {
+			Sort::Bundled => {}
+			Sort::Difficulty => {
+				listed.sort_by_key(|(_, asset)| asset.tags.difficulty)
+			}
+			Sort::Name => listed.sort_by(|(_, a), (_, b)| {
+				a.localized_name(language).cmp(b.localized_name(language))
+			}),
+			Sort::Author => listed
+				.sort_by(|(_, a), (_, b)| a.tags.author.cmp(&b.tags.author)),
+		}
+		listed
+	}
+}
+
+/// Marker component for level browser entities.
+#[derive(Component)]
+pub struct LevelBrowserDialog;
+
+/// Marker component for the level browser's list of levels.
+#[derive(Component)]
+pub struct BrowserList;
+
+/// Marker component for the thumbnail of the selected level in the browser.
+#[derive(Component)]
+pub struct BrowserThumbnail;
+
+/// Opens the level browser when B is pressed.
+pub fn open_level_browser(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::KeyB) {
+		next_state.set(GameState::LevelBrowser);
+	}
+}
+
+/// Spawns the level browser: the list of levels, with a thumbnail of the
+/// selected level beside it.
+pub fn spawn_level_browser(mut commands: Commands) {
+	commands
+		.spawn((
+			LevelBrowserDialog,
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Percent(20.0),
+				left: Val::Percent(20.0),
+				width: Val::Percent(60.0),
+				padding: UiRect::all(Val::Px(12.0)),
+				column_gap: Val::Px(12.0),
+				justify_content: JustifyContent::SpaceBetween,
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+		))
+		.with_children(|dialog| {
+			dialog.spawn((
+				BrowserList,
+				Text::default(),
+				TextFont {
+					font_size: 16.0,
+					..default()
+				},
+			));
+			dialog.spawn((
+				BrowserThumbnail,
+				ImageNode::default(),
+				Node {
+					width: THUMBNAIL_SIZE,
+					height: THUMBNAIL_SIZE,
+					flex_shrink: 0.0,
+					..default()
+				},
+				Visibility::Hidden,
+			));
+		});
+}
+
+/// Despawns the level browser.
+pub fn despawn_level_browser(
+	mut commands: Commands,
+	dialog_query: Query<Entity, With<LevelBrowserDialog>>,
+) {
+	for entity in &dialog_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// The levels the browser lists, and what it shows about them.
+#[derive(SystemParam)]
+pub struct BrowserCatalog<'w> {
+	levels: Res<'w, Levels>,
+	level_assets: Res<'w, Assets<LevelAsset>>,
+	campaign: Res<'w, Campaign>,
+	progress: Res<'w, Progress>,
+	settings: Res<'w, Settings>,
+	thumbnails: Res<'w, Thumbnails>,
+}
+
+/// Handles browser input: up and down select a level, F and S cycle the filter
+/// and sort order, Enter plays the selected level if it's unlocked, and B or
+/// Escape returns to the current level. Shows the selected level's thumbnail
+/// once it's available. C opens the community levels, which
+/// [`sharing::open_community_levels`] handles.
+pub fn browse_levels(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut browser: ResMut<LevelBrowser>,
+	catalog: BrowserCatalog,
+	mut play_level_events: EventWriter<PlayLevel>,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut list_query: Query<(&mut Text, Ref<BrowserList>)>,
+	mut thumbnail_query: Query<
+		(&mut ImageNode, &mut Visibility),
+		With<BrowserThumbnail>,
+	>,
+) {
+	let BrowserCatalog {
+		levels,
+		level_assets,
+		campaign,
+		progress,
+		settings,
+		thumbnails,
+	} = catalog;
+	if keys.just_pressed(KeyCode::Escape) || keys.just_pressed(KeyCode::KeyB) {
+		next_state.set(GameState::Playing);
+		return;
+	}
+	if keys.just_pressed(KeyCode::KeyF) {
+		browser.filter = browser.filter.next();
+		browser.selected = 0;
+	}
+	if keys.just_pressed(KeyCode::KeyS) {
+		browser.sort = browser.sort.next();
+		browser.selected = 0;
+	}
+	let language = locale::language(&settings);
+	let listed = browser.listed(&levels, &level_assets, &progress, &language);
+	if keys.just_pressed(KeyCode::ArrowUp) {
+		browser.selected = browser.selected.saturating_sub(1);
+	}
+	if keys.just_pressed(KeyCode::ArrowDown) {
+		browser.selected =
+			(browser.selected + 1).min(listed.len().saturating_sub(1));
+	}
+	if keys.just_pressed(KeyCode::Enter) {
+		if let Some(&(idx, _)) = listed.get(browser.selected) {
+			if campaign.is_unlocked(idx, &progress) {
+				play_level_events.send(PlayLevel(idx));
+				next_state.set(GameState::Playing);
+				return;
+			}
+		}
+	}
+
+	for (mut text, list) in &mut list_query {
+		if browser.is_changed() || list.is_added() {
+			**text = browser_text(
+				&browser,
+				&listed,
+				&campaign,
+				&progress,
+				&language,
+				sharing::is_enabled(&settings),
+			);
+		}
+	}
+
+	let thumbnail = listed
+		.get(browser.selected)
+		.and_then(|(idx, _)| levels.id(*idx))
+		.and_then(|id| thumbnails.get(id));
+	for (mut image_node, mut visibility) in &mut thumbnail_query {
+		match thumbnail {
+			Some(image) => {
+				if image_node.image != *image {
+					image_node.image = image.clone();
+				}
+				visibility.set_if_neq(Visibility::Inherited);
+			}
+			None => {
+				visibility.set_if_neq(Visibility::Hidden);
+			}
+		}
+	}
+}
+
+fn browser_text(
+	browser: &LevelBrowser,
+	listed: &[(usize, &LevelAsset)],
+	campaign: &Campaign,
+	progress: &Progress,
+	language: &str,
+	sharing: bool,
+) -> String {
+	let mut text = format!(
+		"Levels\n\
+		Filter: {} (F)   Sort: {} (S)\n\n",
+		browser.filter.describe(),
+		browser.sort.name(),
+	);
+	if listed.is_empty() {
+		writeln!(text, "No matching levels").unwrap();
+	}
+	for (row, (idx, asset)) in listed.iter().enumerate() {
+		let marker = if row == browser.selected { ">" } else { " " };
+		let tags = &asset.tags;
+		let difficulty = match tags.difficulty {
+			0 => "unrated".to_string(),
+			difficulty => format!("difficulty {difficulty}"),
+		};
+		let mechanics: Vec<String> = tags
+			.mechanics
+			.iter()
+			.map(|mechanic| {
+				if progress.seen_mechanics.contains(mechanic) {
+					mechanic.name().to_string()
+				} else {
+					format!("{} (new)", mechanic.name())
+				}
+			})
+			.collect();
+		let name = asset.localized_name(language);
+		write!(text, "{marker} {name} - {difficulty}").unwrap();
+		if !mechanics.is_empty() {
+			write!(text, " - {}", mechanics.join(", ")).unwrap();
+		}
+		if let Some(author) = &tags.author {
+			write!(text, " - by {author}").unwrap();
+		}
+		if let Some(pack) = campaign.pack(*idx) {
+			write!(text, " - from {}", pack.title).unwrap();
+			if let Some(author) = &pack.author {
+				write!(text, " by {author}").unwrap();
+			}
+		}
+		if !campaign.is_unlocked(*idx, progress) {
+			write!(text, " (locked)").unwrap();
+		}
+		writeln!(text).unwrap();
+	}
+	write!(text, "\nEnter: play   Esc: close").unwrap();
+	if sharing {
+		write!(text, "   C: community levels").unwrap();
+	}
+	text
+}
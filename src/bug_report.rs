@@ -0,0 +1,155 @@
+use std::{
+	fmt::Write,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+	input::{
+		keyboard::{Key, KeyboardInput},
+		ButtonState,
+	},
+	prelude::*,
+};
+
+use crate::{
+	level::Level, replay::Replay, settings::Settings, states::GameState,
+};
+
+/// Maximum length of the player's message, in characters.
+const MAX_MESSAGE_LEN: usize = 500;
+
+/// The player's message for the bug report being written.
+#[derive(Resource, Default)]
+pub struct BugReport {
+	message: String,
+}
+
+/// Marker component for bug report dialog entities.
+#[derive(Component)]
+pub struct BugReportDialog;
+
+/// Opens the bug report dialog when F9 is pressed.
+pub fn open_bug_report(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::F9) {
+		next_state.set(GameState::BugReport);
+	}
+}
+
+/// Spawns the bug report dialog.
+pub fn spawn_bug_report_dialog(
+	mut commands: Commands,
+	mut report: ResMut<BugReport>,
+) {
+	report.message.clear();
+	commands.spawn((
+		BugReportDialog,
+		Text(dialog_text("")),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Percent(30.0),
+			left: Val::Percent(20.0),
+			width: Val::Percent(60.0),
+			padding: UiRect::all(Val::Px(12.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+	));
+}
+
+/// Despawns the bug report dialog.
+pub fn despawn_bug_report_dialog(
+	mut commands: Commands,
+	dialog_query: Query<Entity, With<BugReportDialog>>,
+) {
+	for entity in &dialog_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+fn dialog_text(message: &str) -> String {
+	format!(
+		"Bug report\n\n\
+		Describe the problem (optional):\n\
+		> {message}_\n\n\
+		Enter: save report   Esc: cancel"
+	)
+}
+
+/// Edits the message as the player types. Enter saves the report to a file
+/// and Escape cancels; either returns to play.
+pub fn edit_bug_report(
+	mut keyboard_events: EventReader<KeyboardInput>,
+	mut report: ResMut<BugReport>,
+	level: Res<Level>,
+	settings: Res<Settings>,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut dialog_query: Query<&mut Text, With<BugReportDialog>>,
+) {
+	for event in keyboard_events.read() {
+		if event.state != ButtonState::Pressed {
+			continue;
+		}
+		match &event.logical_key {
+			Key::Enter => {
+				save_report(&report.message, &level, &settings);
+				next_state.set(GameState::Playing);
+				return;
+			}
+			Key::Escape => {
+				next_state.set(GameState::Playing);
+				return;
+			}
+			Key::Backspace => {
+				report.message.pop();
+			}
+			Key::Space => report.message.push(' '),
+			Key::Character(text) => report.message.push_str(text),
+			_ => {}
+		}
+		if report.message.chars().count() > MAX_MESSAGE_LEN {
+			report.message.pop();
+		}
+	}
+	if report.is_changed() {
+		for mut text in &mut dialog_query {
+			**text = dialog_text(&report.message);
+		}
+	}
+}
+
+/// Writes a bug report with the player's `message`, the current level and its
+/// history, and the player's settings to a file in the working directory.
+fn save_report(message: &str, level: &Level, settings: &Settings) {
+	let seconds = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or_default();
+	let path = format!("bug-report-{seconds}.txt");
+
+	let mut report = String::new();
+	writeln!(report, "# Message\n{message}\n").unwrap();
+	writeln!(
+		report,
+		"# Level (checksum {:016x})\n{}",
+		level.checksum(),
+		level.to_text()
+	)
+	.unwrap();
+	writeln!(report, "# Replay ({} turns)", level.turn()).unwrap();
+	writeln!(report, "{}", Replay::record(level).to_text()).unwrap();
+	let settings = ron::ser::to_string_pretty(settings, default())
+		.unwrap_or_else(|err| format!("unavailable: {err}"));
+	writeln!(report, "\n# Settings\n{settings}").unwrap();
+
+	match std::fs::write(&path, report) {
+		Ok(()) => info!("saved bug report to {path}"),
+		Err(err) => warn!("could not save bug report to {path}: {err}"),
+	}
+}
@@ -0,0 +1,98 @@
+use bevy::{prelude::*, render::camera::ScalingMode};
+
+use crate::{level::Level, overview::OverviewWindow, settings::Settings};
+
+/// Number of tiles kept in view around the characters when framing them.
+const MARGIN: f32 = 2.0;
+
+/// How quickly the camera approaches its target framing, as the fraction of
+/// the remaining distance per second, roughly.
+const FRAMING_RATE: f32 = 3.0;
+
+/// Marker component for the camera rendering to the primary window.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// The camera transform and view size, in tiles, that frame the tiles from
+/// `min` to `max` (inclusive), given as (column, row) coordinates.
+pub fn framing(min: Vec2, max: Vec2) -> (Transform, Vec2) {
+	let size = max - min + Vec2::ONE;
+	let target = Vec3::new(0.5 * (min.x + max.x), -0.5 * (min.y + max.y), 1.0);
+	let transform = Transform::from_translation(Vec3::new(
+		target.x,
+		target.y - 0.5 * (size.y + 1.0),
+		size.x.max(size.y),
+	))
+	.looking_at(target, Vec3::Z);
+	(transform, size)
+}
+
+/// The orthographic scaling mode that shows at least `size` tiles.
+pub fn scaling_mode(size: Vec2) -> ScalingMode {
+	ScalingMode::AutoMin {
+		min_width: size.x,
+		min_height: size.y,
+	}
+}
+
+/// If dynamic framing is enabled, or the level overview is open in its own
+/// window, smoothly frames the area occupied by characters, plus a margin,
+/// instead of the whole level. Otherwise, smoothly returns to framing the
+/// whole level, so turning dynamic framing off doesn't leave the camera
+/// wherever it last was.
+pub fn frame_characters(
+	settings: Res<Settings>,
+	level: Res<Level>,
+	time: Res<Time>,
+	overview_query: Query<(), With<OverviewWindow>>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection),
+		With<MainCamera>,
+	>,
+) {
+	let level_max =
+		Vec2::new(level.width() as f32, level.height() as f32) - Vec2::ONE;
+	let (min, max) = if settings.dynamic_camera || !overview_query.is_empty() {
+		let Some((min, max)) = level
+			.iter_level_objects()
+			.filter(|level_object| level_object.object.is_character())
+			.map(|level_object| {
+				let coords = level_object.coords;
+				Vec2::new(coords.col as f32, coords.row as f32)
+			})
+			.fold(None, |bounds: Option<(Vec2, Vec2)>, coords| {
+				Some(bounds.map_or((coords, coords), |(min, max)| {
+					(min.min(coords), max.max(coords))
+				}))
+			})
+		else {
+			return;
+		};
+		(
+			(min - Vec2::splat(MARGIN)).max(Vec2::ZERO),
+			(max + Vec2::splat(MARGIN)).min(level_max),
+		)
+	} else {
+		(Vec2::ZERO, level_max)
+	};
+	let (target_transform, target_size) = framing(min, max);
+
+	let t = 1.0 - (-FRAMING_RATE * time.delta_secs()).exp();
+	for (mut transform, mut projection) in &mut camera_query {
+		transform.translation =
+			transform.translation.lerp(target_transform.translation, t);
+		transform.rotation =
+			transform.rotation.slerp(target_transform.rotation, t);
+		if let Projection::Orthographic(orthographic) = &mut *projection {
+			if let ScalingMode::AutoMin {
+				min_width,
+				min_height,
+			} = orthographic.scaling_mode
+			{
+				let size =
+					Vec2::new(min_width, min_height).lerp(target_size, t);
+				orthographic.scaling_mode = scaling_mode(size);
+			}
+		}
+	}
+}
@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+	history, level::Level, level_asset::Levels, mutators, progress::Progress,
+	settings::Settings, solution::SolutionComparison, states::GameState,
+};
+
+/// Where the campaign manifest is read from.
+const MANIFEST_PATH: &str = "assets/campaign.ron";
+
+/// The ordered list of levels making up the campaign, along with any level
+/// packs, which are played as campaigns of their own.
+#[derive(Resource, Deserialize, Default)]
+pub struct Campaign {
+	/// Paths of the level files, relative to the assets directory.
+	levels: Vec<String>,
+	#[serde(skip)]
+	packs: Vec<PackCampaign>,
+}
+
+/// The levels of a [level pack](crate::pack::LevelPack), in order.
+pub struct PackCampaign {
+	pub title: String,
+	pub author: Option<String>,
+	/// The index in [`Levels`] of the pack's first level.
+	first: usize,
+	/// The keys recording each level's completion in [`Progress`].
+	keys: Vec<String>,
+}
+
+impl Campaign {
+	/// Loads the campaign from [`MANIFEST_PATH`], leaving it empty if the
+	/// manifest is missing or invalid.
+	pub fn load() -> Campaign {
+		let text = match std::fs::read_to_string(MANIFEST_PATH) {
+			Ok(text) => text,
+			Err(err) => {
+				error!("could not read {MANIFEST_PATH}: {err}");
+				return Campaign::default();
+			}
+		};
+		ron::from_str(&text).unwrap_or_else(|err| {
+			error!("invalid campaign in {MANIFEST_PATH}: {err}");
+			Campaign::default()
+		})
+	}
+
+	/// Paths of the campaign's level files, in order.
+	pub fn level_paths(&self) -> &[String] {
+		&self.levels
+	}
+
+	/// Adds a pack of `count` levels, the first of which is at index `first`
+	/// in [`Levels`]. `file` is the name of the pack's file, which identifies
+	/// its levels in the player's progress.
+	pub fn add_pack(
+		&mut self,
+		title: String,
+		author: Option<String>,
+		file: &str,
+		first: usize,
+		count: usize,
+	) {
+		self.packs.push(PackCampaign {
+			title,
+			author,
+			first,
+			keys: (0..count).map(|idx| format!("{file}#{idx}")).collect(),
+		});
+	}
+
+	/// The pack the `idx`-th level belongs to, if any.
+	pub fn pack(&self, idx: usize) -> Option<&PackCampaign> {
+		self.packs.iter().find(|pack| {
+			(pack.first..pack.first + pack.keys.len()).contains(&idx)
+		})
+	}
+
+	/// The progress keys of the sequence of levels the `idx`-th level belongs
+	/// to, either the campaign or a pack, with the index of its first level.
+	/// Levels in neither, like loose user levels, have no sequence.
+	fn sequence(&self, idx: usize) -> Option<(usize, &[String])> {
+		if idx < self.levels.len() {
+			return Some((0, &self.levels));
+		}
+		self.pack(idx)
+			.map(|pack| (pack.first, pack.keys.as_slice()))
+	}
+
+	/// The key recording the `idx`-th level's completion in [`Progress`], if
+	/// it's in a sequence.
+	fn progress_key(&self, idx: usize) -> Option<&str> {
+		let (first, keys) = self.sequence(idx)?;
+		Some(&keys[idx - first])
+	}
+
+	/// Whether the `idx`-th level is unlocked: the first level of a sequence
+	/// always is, and completing a level unlocks the next one. Levels outside
+	/// any sequence are always unlocked.
+	pub fn is_unlocked(&self, idx: usize, progress: &Progress) -> bool {
+		let Some((first, keys)) = self.sequence(idx) else {
+			return true;
+		};
+		let completed = |idx: usize| {
+			keys.get(idx)
+				.is_some_and(|key| progress.completed_levels.contains(key))
+		};
+		let idx = idx - first;
+		idx == 0 || completed(idx) || completed(idx - 1)
+	}
+
+	/// The index of the level after the `idx`-th in its sequence, if there is
+	/// one.
+	fn next(&self, idx: usize) -> Option<usize> {
+		let (first, keys) = self.sequence(idx)?;
+		(idx + 1 < first + keys.len()).then_some(idx + 1)
+	}
+}
+
+/// A request to play the campaign level with the given index.
+#[derive(Event)]
+pub struct PlayLevel(pub usize);
+
+/// Sent when the player completes a level, with statistics on the turns that
+/// completed it.
+#[derive(Event, Clone, Copy)]
+pub struct LevelCompleted {
+	/// The number of turns taken.
+	pub turns: usize,
+	/// The level's par, if it has one.
+	pub par: Option<usize>,
+	/// The number of characters summoned from the future.
+	pub summonings: usize,
+	/// The number of characters that returned to the past.
+	pub returnings: usize,
+}
+
+impl LevelCompleted {
+	/// The statistics of `level`'s current timeline.
+	fn of(level: &Level) -> LevelCompleted {
+		let taken = || history::taken(level).map(|turn| turn.change);
+		LevelCompleted {
+			turns: level.turn(),
+			par: level.meta().par,
+			summonings: taken().map(|change| change.summonings.len()).sum(),
+			returnings: taken().map(|change| change.returnings.len()).sum(),
+		}
+	}
+}
+
+/// Completes the level when a character reaches the stairs, recording the
+/// completion if it's a campaign or pack level, and starts its
+/// [outro](crate::outro) ahead of the results.
+pub fn check_level_complete(
+	level: Res<Level>,
+	levels: Res<Levels>,
+	campaign: Res<Campaign>,
+	comparison: Res<SolutionComparison>,
+	mut progress: ResMut<Progress>,
+	mut completed_events: EventWriter<LevelCompleted>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !level.is_changed() || comparison.is_watching() || !level.is_complete() {
+		return;
+	}
+	if let Some(key) =
+		levels.current().and_then(|idx| campaign.progress_key(idx))
+	{
+		progress.complete_level(key);
+	}
+	let completed = LevelCompleted::of(&level);
+	info!("level complete in {} turns", completed.turns);
+	completed_events.send(completed);
+	next_state.set(GameState::LevelOutro);
+}
+
+/// Marker component for level completion dialog entities.
+#[derive(Component)]
+pub struct LevelCompleteDialog;
+
+/// Spawns the level completion dialog, with the player's score: the level's
+/// [score](crate::level::LevelMeta::score) for their turn count, scaled by
+/// the active mutators' multiplier.
+pub fn spawn_level_complete_dialog(
+	mut commands: Commands,
+	level: Res<Level>,
+	levels: Res<Levels>,
+	campaign: Res<Campaign>,
+	settings: Res<Settings>,
+) {
+	let current = levels.current();
+	let next = current.and_then(|idx| campaign.next(idx));
+	let in_sequence =
+		current.is_some_and(|idx| campaign.sequence(idx).is_some());
+	let controls = match (in_sequence, next) {
+		(_, Some(_)) => "Enter: next level   Esc: keep playing",
+		(true, None) => "Campaign complete!\n\nEsc: keep playing",
+		(false, None) => "Esc: keep playing",
+	};
+	let meta = level.meta();
+	let par = match meta.par {
+		Some(par) => format!(" (par {par})"),
+		None => String::new(),
+	};
+	let score = meta.score(level.turn()) as f32
+		* mutators::score_multiplier(&settings.mutators);
+	commands.spawn((
+		LevelCompleteDialog,
+		Text(format!(
+			"Level complete in {} turns{par}!\nScore: {}\n\n{controls}",
+			level.turn(),
+			score.round(),
+		)),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Percent(60.0),
+			left: Val::Percent(30.0),
+			width: Val::Percent(40.0),
+			padding: UiRect::all(Val::Px(12.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+	));
+}
+
+/// Despawns the level completion dialog.
+pub fn despawn_level_complete_dialog(
+	mut commands: Commands,
+	dialog_query: Query<Entity, With<LevelCompleteDialog>>,
+) {
+	for entity in &dialog_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// Advances to the next campaign level when Enter is pressed, or returns to
+/// the completed level when Escape is pressed.
+pub fn advance_campaign(
+	keys: Res<ButtonInput<KeyCode>>,
+	levels: Res<Levels>,
+	campaign: Res<Campaign>,
+	mut play_level_events: EventWriter<PlayLevel>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::Escape) {
+		next_state.set(GameState::Playing);
+	} else if keys.just_pressed(KeyCode::Enter) {
+		if let Some(next) = levels.current().and_then(|idx| campaign.next(idx))
+		{
+			play_level_events.send(PlayLevel(next));
+			next_state.set(GameState::Playing);
+		}
+	}
+}
@@ -0,0 +1,331 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{
+	ldtk,
+	level::Level,
+	level_asset::{self, LevelAsset},
+	playtest::{self, Search},
+	solution, tiled,
+};
+
+/// Command-line flag that checks every level in a directory headlessly,
+/// printing a [report](LevelReport) for each. See [`run`].
+pub const CHECK_FLAG: &str = "--check-levels";
+
+/// Flag following the directory that also checks each level's solution.
+pub const SOLVE_FLAG: &str = "--solve";
+
+/// The result of checking one level file.
+#[derive(Serialize)]
+pub struct LevelReport {
+	/// The level's file name, relative to the checked directory.
+	pub file: String,
+	/// Why the file couldn't be parsed, if it couldn't.
+	pub parse_error: Option<String>,
+	/// The ways the level breaks [its invariants](Level::validate).
+	pub invalid: Vec<String>,
+	/// Whether the level's solution completes it, if solutions were checked
+	/// and the level parsed.
+	pub solution: Option<SolutionReport>,
+}
+
+impl LevelReport {
+	/// Whether the level passed every check.
+	pub fn passed(&self) -> bool {
+		self.parse_error.is_none()
+			&& self.invalid.is_empty()
+			&& self
+				.solution
+				.as_ref()
+				.is_none_or(|solution| *solution == SolutionReport::Completes)
+	}
+}
+
+/// The result of checking a level's solution.
+#[derive(Serialize, PartialEq, Eq)]
+pub enum SolutionReport {
+	/// The solution plays through and completes the level.
+	Completes,
+	/// The solution plays through but leaves the level incomplete.
+	Incomplete,
+	/// The solution couldn't be played.
+	Error(String),
+}
+
+/// The result of linting one level file. See [`lint`].
+#[derive(Serialize)]
+pub struct LintReport {
+	/// The level's [check](LevelReport). Its solution is only checked if it
+	/// has one.
+	pub check: LevelReport,
+	/// Whether a search found the level solvable, if it parsed and is valid.
+	pub solvability: Option<Solvability>,
+	/// The level's par, as authored or as the length of its solution.
+	pub par: Option<usize>,
+	/// An estimate of a fair par: the fewest turns the search completed the
+	/// level in.
+	pub par_estimate: Option<usize>,
+}
+
+impl LintReport {
+	/// Whether the level passed its check and is solvable, either by its own
+	/// solution or as far as the search can tell.
+	pub fn passed(&self) -> bool {
+		let solved = self.check.solution == Some(SolutionReport::Completes);
+		self.check.passed()
+			&& (solved || self.solvability != Some(Solvability::Unsolvable))
+	}
+}
+
+/// What a search for a level's [shortest
+/// solution](playtest::shortest_solution) found.
+#[derive(Serialize, PartialEq, Eq)]
+pub enum Solvability {
+	Solvable,
+	/// The search ran out of states to try.
+	Unsolvable,
+	/// The search gave up before finding a solution.
+	Unknown,
+}
+
+/// The result of updating one level file's par. See [`update_pars`].
+#[derive(Serialize)]
+pub struct ParReport {
+	/// The level's file name, relative to the searched directory.
+	pub file: String,
+	/// Why the level couldn't be searched or its par written, if it
+	/// couldn't.
+	pub error: Option<String>,
+	/// The level's par beforehand, as authored or as the length of its
+	/// solution.
+	pub par: Option<usize>,
+	/// The fewest turns the search completed the level in, if it did.
+	pub optimal: Option<usize>,
+	/// Whether the file was rewritten with the optimal par.
+	pub updated: bool,
+}
+
+impl ParReport {
+	/// Whether the level's par is now its optimal move count.
+	pub fn passed(&self) -> bool {
+		self.error.is_none() && self.optimal.is_some()
+	}
+}
+
+/// Checks every level file in `dir`: that it parses, that it's
+/// [valid](Level::validate), and, if `solve` is true, that its solution
+/// completes it. Prints the reports to stdout in RON, one per file in name
+/// order, and returns whether every level passed.
+///
+/// Meant for authors of level packs, to check many levels at once. Tiled maps
+/// and LDtk projects are checked along with `.level.ron` files.
+pub fn run(dir: &Path, solve: bool) -> std::io::Result<bool> {
+	let reports: Vec<LevelReport> = level_asset::level_files(dir)?
+		.into_iter()
+		.map(|file| check(dir, file, solve))
+		.collect();
+	print_reports(&reports)?;
+	Ok(reports.iter().all(LevelReport::passed))
+}
+
+/// Lints every level file in `dir` before release: [checks](run) each one,
+/// along with its solution if it has one, then searches for its shortest
+/// solution to tell whether it's solvable and to estimate its par. Prints the
+/// reports to stdout in RON, one per file in name order, and returns whether
+/// every level passed.
+///
+/// Backs the `lint-levels` tool. The search can take a while on big levels.
+pub fn lint(dir: &Path) -> std::io::Result<bool> {
+	let reports: Vec<LintReport> = level_asset::level_files(dir)?
+		.into_iter()
+		.map(|file| lint_file(dir, file))
+		.collect();
+	print_reports(&reports)?;
+	Ok(reports.iter().all(LintReport::passed))
+}
+
+/// Searches for the shortest solution of every `.level.ron` file in `dir` and
+/// writes its length back to the file as the level's par wherever the file
+/// says otherwise, so pars stay honest as mechanics change. Prints the reports
+/// to stdout in RON, one per file in name order, and returns whether every
+/// level's par is now optimal.
+///
+/// Backs the `update-pars` tool. Levels the search gives up on or finds
+/// unsolvable are left alone, and so are Tiled maps and LDtk projects, which
+/// don't have pars. Like [`lint`], the search can take a while on big levels.
+pub fn update_pars(dir: &Path) -> std::io::Result<bool> {
+	let reports: Vec<ParReport> = level_asset::level_files(dir)?
+		.into_iter()
+		.filter(|file| file.ends_with(".level.ron"))
+		.map(|file| update_par(dir, file))
+		.collect();
+	print_reports(&reports)?;
+	Ok(reports.iter().all(ParReport::passed))
+}
+
+/// Prints `reports` to stdout in RON.
+fn print_reports(reports: &impl Serialize) -> std::io::Result<()> {
+	let text = ron::ser::to_string_pretty(reports, Default::default())
+		.map_err(std::io::Error::other)?;
+	println!("{text}");
+	Ok(())
+}
+
+fn check(dir: &Path, file: String, solve: bool) -> LevelReport {
+	let asset = match read(&dir.join(&file)) {
+		Ok(asset) => asset,
+		Err(err) => {
+			return LevelReport {
+				file,
+				parse_error: Some(err),
+				invalid: Vec::new(),
+				solution: None,
+			}
+		}
+	};
+	let invalid = match asset.start() {
+		Ok(level) => level.validate().iter().map(ToString::to_string).collect(),
+		Err(err) => vec![err.to_string()],
+	};
+	let solution = solve.then(|| check_solution(asset.to_level()));
+	LevelReport {
+		file,
+		parse_error: None,
+		invalid,
+		solution,
+	}
+}
+
+fn lint_file(dir: &Path, file: String) -> LintReport {
+	let asset = read(&dir.join(&file)).ok();
+	let has_solution =
+		asset.as_ref().is_some_and(|asset| asset.solution.is_some());
+	let check = check(dir, file, has_solution);
+	let level = asset
+		.filter(|_| check.parse_error.is_none() && check.invalid.is_empty())
+		.map(|asset| asset.to_level());
+	let search = level.as_ref().map(playtest::shortest_solution);
+	LintReport {
+		solvability: search.as_ref().map(|search| match search {
+			Search::Solved(_) => Solvability::Solvable,
+			Search::Unsolvable => Solvability::Unsolvable,
+			Search::GaveUp => Solvability::Unknown,
+		}),
+		par: level.as_ref().and_then(|level| level.meta().par),
+		par_estimate: match search {
+			Some(Search::Solved(turns)) => Some(turns),
+			_ => None,
+		},
+		check,
+	}
+}
+
+fn update_par(dir: &Path, file: String) -> ParReport {
+	let mut report = ParReport {
+		file,
+		error: None,
+		par: None,
+		optimal: None,
+		updated: false,
+	};
+	let path = dir.join(&report.file);
+	let asset = match read(&path) {
+		Ok(asset) => asset,
+		Err(err) => {
+			report.error = Some(err);
+			return report;
+		}
+	};
+	let invalid = match asset.start() {
+		Ok(level) => level.validate().iter().map(ToString::to_string).collect(),
+		Err(err) => vec![err.to_string()],
+	};
+	if !invalid.is_empty() {
+		report.error = Some(invalid.join("; "));
+		return report;
+	}
+	let level = asset.to_level();
+	report.par = level.meta().par;
+	let optimal = match playtest::shortest_solution(&level) {
+		Search::Solved(turns) => turns,
+		Search::Unsolvable => {
+			report.error = Some("the search found no solution".into());
+			return report;
+		}
+		Search::GaveUp => {
+			report.error = Some("the search gave up".into());
+			return report;
+		}
+	};
+	report.optimal = Some(optimal);
+	if asset.par == Some(optimal) {
+		return report;
+	}
+	let result = std::fs::read_to_string(&path)
+		.and_then(|text| std::fs::write(&path, with_par(&text, optimal)));
+	match result {
+		Ok(()) => report.updated = true,
+		Err(err) => report.error = Some(err.to_string()),
+	}
+	report
+}
+
+/// The text of a `.level.ron` file with its par set to `par`, replacing the
+/// file's `par` field if it has one or adding one at the end otherwise. The
+/// rest of the file is left as written, rather than reserialized, to keep
+/// authors' formatting.
+fn with_par(text: &str, par: usize) -> String {
+	let field = format!("par: Some({par}),");
+	let mut lines: Vec<String> = text.lines().map(String::from).collect();
+	let existing = lines
+		.iter_mut()
+		.find(|line| line.trim_start().starts_with("par:"));
+	if let Some(line) = existing {
+		let indent = line.len() - line.trim_start().len();
+		*line = format!("{}{field}", &line[..indent]);
+	} else if let Some(close) = lines.iter().rposition(|line| line == ")") {
+		lines.insert(close, format!("\t{field}"));
+	}
+	let mut text = lines.join("\n");
+	text.push('\n');
+	text
+}
+
+/// Parses the level file at `path` and migrates it to the current format
+/// without validating it, since validation problems are reported separately.
+fn read(path: &Path) -> Result<LevelAsset, String> {
+	let bytes = std::fs::read(path).map_err(|err| err.to_string())?;
+	let extension = path.extension().and_then(|extension| extension.to_str());
+	if let Some(extension @ ("tmx" | "ldtk")) = extension {
+		let text = String::from_utf8(bytes)
+			.map_err(|_| format!("{extension} file is not UTF-8"))?;
+		let name = path
+			.file_stem()
+			.map_or_else(String::new, |stem| stem.to_string_lossy().into());
+		let map = if extension == "tmx" {
+			tiled::import_map(&text).map_err(|err| err.to_string())?
+		} else {
+			ldtk::import_project(&text).map_err(|err| err.to_string())?
+		};
+		Ok(LevelAsset {
+			name,
+			map,
+			..Default::default()
+		})
+	} else {
+		ron::de::from_bytes::<LevelAsset>(&bytes)
+			.map_err(|err| err.to_string())?
+			.migrated()
+			.map_err(|err| err.to_string())
+	}
+}
+
+fn check_solution(level: Level) -> SolutionReport {
+	match solution::play_solution(level) {
+		Ok(level) if level.is_complete() => SolutionReport::Completes,
+		Ok(_) => SolutionReport::Incomplete,
+		Err(err) => SolutionReport::Error(err.to_string()),
+	}
+}
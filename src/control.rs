@@ -1,21 +1,30 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use bevy::{
-	input::{keyboard::KeyboardInput, ButtonState},
+	ecs::system::SystemParam,
+	input::{
+		gamepad::GamepadButtonStateChangedEvent, keyboard::KeyboardInput,
+		ButtonState,
+	},
 	prelude::*,
 	utils::HashMap,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{
-	level::{Id, Offset},
+	audio::{self, AudioTracks},
+	level::{Coords, Id, Level, Object, Offset},
+	states::GameState,
 	update::NextActor,
 };
 
 /// An abstraction over keys and gamepad buttons.
-#[derive(Clone, Copy)]
-enum GameButton {
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GameButton {
 	Undo,
 	Redo,
+	UndoAll,
+	RedoAll,
 	Up,
 	Left,
 	Down,
@@ -24,10 +33,61 @@ enum GameButton {
 	Act,
 }
 
+/// The input device the player most recently used.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ActiveDevice {
+	#[default]
+	Keyboard,
+	Gamepad(Entity),
+}
+
 /// Maps keys to game buttons.
-struct KeyboardBindings(HashMap<KeyCode, GameButton>);
+#[derive(Resource)]
+pub struct KeyboardBindings(HashMap<KeyCode, GameButton>);
 
 impl KeyboardBindings {
+	/// The bindings of the profile named `name` among `profiles`, or the
+	/// default bindings if `name` is `None` or there's no such profile.
+	pub fn profile(
+		profiles: &[BindingProfile],
+		name: Option<&str>,
+	) -> KeyboardBindings {
+		let Some(name) = name else {
+			return KeyboardBindings::default();
+		};
+		let Some(profile) =
+			profiles.iter().find(|profile| profile.name == name)
+		else {
+			warn!("no key binding profile named {name:?}");
+			return KeyboardBindings::default();
+		};
+		KeyboardBindings(
+			profile
+				.keys
+				.iter()
+				.filter_map(|(key, button)| {
+					let code = key_code(key);
+					if code.is_none() {
+						warn!(
+							"unknown key {key:?} in binding profile {name:?}"
+						);
+					}
+					code.map(|code| (code, *button))
+				})
+				.collect(),
+		)
+	}
+
+	/// A display name for a key bound to `button`, if there is one. If several
+	/// keys are bound, the shortest name is chosen.
+	pub fn glyph(&self, button: GameButton) -> Option<String> {
+		self.0
+			.iter()
+			.filter(|(_, bound)| **bound == button)
+			.map(|(key, _)| key_name(*key))
+			.min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+	}
+
 	/// Converts keyboard input events into game button events.
 	fn adapt<'s, 'k>(
 		&'s self,
@@ -49,6 +109,8 @@ impl Default for KeyboardBindings {
 		KeyboardBindings(HashMap::from([
 			(KeyCode::KeyZ, GameButton::Undo),
 			(KeyCode::KeyX, GameButton::Redo),
+			(KeyCode::Home, GameButton::UndoAll),
+			(KeyCode::End, GameButton::RedoAll),
 			(KeyCode::KeyW, GameButton::Up),
 			(KeyCode::ArrowUp, GameButton::Up),
 			(KeyCode::KeyA, GameButton::Left),
@@ -63,8 +125,240 @@ impl Default for KeyboardBindings {
 	}
 }
 
+/// A named set of key bindings, like "left-handed" or "laptop", which players
+/// define in their settings and switch between from the pause menu.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BindingProfile {
+	pub name: String,
+	/// The game button each key is bound to. Keys are named as in
+	/// [`KeyCode`], like `KeyZ` or `ArrowUp`.
+	pub keys: BTreeMap<String, GameButton>,
+}
+
+/// Keys that binding profiles can bind.
+const BINDABLE_KEYS: [KeyCode; 82] = [
+	KeyCode::KeyA,
+	KeyCode::KeyB,
+	KeyCode::KeyC,
+	KeyCode::KeyD,
+	KeyCode::KeyE,
+	KeyCode::KeyF,
+	KeyCode::KeyG,
+	KeyCode::KeyH,
+	KeyCode::KeyI,
+	KeyCode::KeyJ,
+	KeyCode::KeyK,
+	KeyCode::KeyL,
+	KeyCode::KeyM,
+	KeyCode::KeyN,
+	KeyCode::KeyO,
+	KeyCode::KeyP,
+	KeyCode::KeyQ,
+	KeyCode::KeyR,
+	KeyCode::KeyS,
+	KeyCode::KeyT,
+	KeyCode::KeyU,
+	KeyCode::KeyV,
+	KeyCode::KeyW,
+	KeyCode::KeyX,
+	KeyCode::KeyY,
+	KeyCode::KeyZ,
+	KeyCode::Digit0,
+	KeyCode::Digit1,
+	KeyCode::Digit2,
+	KeyCode::Digit3,
+	KeyCode::Digit4,
+	KeyCode::Digit5,
+	KeyCode::Digit6,
+	KeyCode::Digit7,
+	KeyCode::Digit8,
+	KeyCode::Digit9,
+	KeyCode::Numpad0,
+	KeyCode::Numpad1,
+	KeyCode::Numpad2,
+	KeyCode::Numpad3,
+	KeyCode::Numpad4,
+	KeyCode::Numpad5,
+	KeyCode::Numpad6,
+	KeyCode::Numpad7,
+	KeyCode::Numpad8,
+	KeyCode::Numpad9,
+	KeyCode::NumpadEnter,
+	KeyCode::NumpadAdd,
+	KeyCode::NumpadSubtract,
+	KeyCode::NumpadMultiply,
+	KeyCode::NumpadDivide,
+	KeyCode::NumpadDecimal,
+	KeyCode::ArrowUp,
+	KeyCode::ArrowLeft,
+	KeyCode::ArrowDown,
+	KeyCode::ArrowRight,
+	KeyCode::Space,
+	KeyCode::Enter,
+	KeyCode::Backspace,
+	KeyCode::ShiftLeft,
+	KeyCode::ShiftRight,
+	KeyCode::ControlLeft,
+	KeyCode::ControlRight,
+	KeyCode::AltLeft,
+	KeyCode::AltRight,
+	KeyCode::Home,
+	KeyCode::End,
+	KeyCode::PageUp,
+	KeyCode::PageDown,
+	KeyCode::Insert,
+	KeyCode::Delete,
+	KeyCode::Comma,
+	KeyCode::Period,
+	KeyCode::Slash,
+	KeyCode::Semicolon,
+	KeyCode::Quote,
+	KeyCode::BracketLeft,
+	KeyCode::BracketRight,
+	KeyCode::Backslash,
+	KeyCode::Minus,
+	KeyCode::Equal,
+	KeyCode::Backquote,
+];
+
+/// The bindable key named `name`, as in [`KeyCode`].
+fn key_code(name: &str) -> Option<KeyCode> {
+	BINDABLE_KEYS
+		.into_iter()
+		.find(|key| format!("{key:?}") == name)
+}
+
+/// A short display name for `key`.
+fn key_name(key: KeyCode) -> String {
+	let name = match key {
+		KeyCode::ArrowUp => "Up",
+		KeyCode::ArrowLeft => "Left",
+		KeyCode::ArrowDown => "Down",
+		KeyCode::ArrowRight => "Right",
+		KeyCode::ShiftLeft | KeyCode::ShiftRight => "Shift",
+		KeyCode::ControlLeft | KeyCode::ControlRight => "Ctrl",
+		KeyCode::AltLeft | KeyCode::AltRight => "Alt",
+		_ => {
+			// Strip prefixes like "Key" in "KeyZ" and "Digit" in "Digit1".
+			let name = format!("{key:?}");
+			return name
+				.strip_prefix("Key")
+				.or_else(|| name.strip_prefix("Digit"))
+				.unwrap_or(&name)
+				.to_string();
+		}
+	};
+	name.to_string()
+}
+
+/// Maps gamepad buttons to game buttons, separately for each gamepad.
+///
+/// Bindings are remembered by device name, so a gamepad that's disconnected
+/// and reconnected gets its bindings back, even though it's a new entity.
+#[derive(Resource)]
+pub struct GamepadBindings {
+	/// Bindings for gamepads seen for the first time.
+	defaults: HashMap<GamepadButton, GameButton>,
+	/// Bindings of each gamepad seen this session, by device name.
+	by_device: HashMap<String, HashMap<GamepadButton, GameButton>>,
+	/// Device names of the connected gamepads.
+	connected: HashMap<Entity, String>,
+}
+
+impl GamepadBindings {
+	/// Records that `gamepad` connected with the device name `name`, giving it
+	/// the bindings it had last time, or the defaults if it's new.
+	pub fn connect(&mut self, gamepad: Entity, name: &str) {
+		if !self.by_device.contains_key(name) {
+			self.by_device
+				.insert(name.to_string(), self.defaults.clone());
+		}
+		self.connected.insert(gamepad, name.to_string());
+	}
+
+	/// Records that `gamepad` disconnected. Its bindings are kept in case it
+	/// reconnects.
+	pub fn disconnect(&mut self, gamepad: Entity) {
+		self.connected.remove(&gamepad);
+	}
+
+	/// A connected gamepad, if there are any.
+	pub fn any_connected(&self) -> Option<Entity> {
+		self.connected.keys().copied().min()
+	}
+
+	/// The bindings of `gamepad`, or the defaults if it isn't connected.
+	fn bindings(&self, gamepad: Entity) -> &HashMap<GamepadButton, GameButton> {
+		self.connected
+			.get(&gamepad)
+			.and_then(|name| self.by_device.get(name))
+			.unwrap_or(&self.defaults)
+	}
+
+	/// Converts a gamepad button event into a game button event.
+	fn adapt(
+		&self,
+		event: &GamepadButtonStateChangedEvent,
+	) -> Option<(GameButton, ButtonState)> {
+		self.bindings(event.entity)
+			.get(&event.button)
+			.map(|button| (*button, event.state))
+	}
+
+	/// A display name for a button of `gamepad` bound to `button`, if there is
+	/// one.
+	pub fn glyph(&self, gamepad: Entity, button: GameButton) -> Option<String> {
+		self.bindings(gamepad)
+			.iter()
+			.filter(|(_, bound)| **bound == button)
+			.map(|(gamepad_button, _)| gamepad_button_name(*gamepad_button))
+			.min()
+	}
+}
+
+impl Default for GamepadBindings {
+	fn default() -> GamepadBindings {
+		let defaults = HashMap::from([
+			(GamepadButton::LeftTrigger, GameButton::Undo),
+			(GamepadButton::RightTrigger, GameButton::Redo),
+			(GamepadButton::LeftTrigger2, GameButton::UndoAll),
+			(GamepadButton::RightTrigger2, GameButton::RedoAll),
+			(GamepadButton::DPadUp, GameButton::Up),
+			(GamepadButton::DPadLeft, GameButton::Left),
+			(GamepadButton::DPadDown, GameButton::Down),
+			(GamepadButton::DPadRight, GameButton::Right),
+			(GamepadButton::South, GameButton::Wait),
+			(GamepadButton::West, GameButton::Act),
+		]);
+		GamepadBindings {
+			defaults,
+			by_device: HashMap::new(),
+			connected: HashMap::new(),
+		}
+	}
+}
+
+/// A short display name for `button`, using Xbox-style labels.
+fn gamepad_button_name(button: GamepadButton) -> String {
+	match button {
+		GamepadButton::South => "(A)".to_string(),
+		GamepadButton::East => "(B)".to_string(),
+		GamepadButton::West => "(X)".to_string(),
+		GamepadButton::North => "(Y)".to_string(),
+		GamepadButton::LeftTrigger => "LB".to_string(),
+		GamepadButton::RightTrigger => "RB".to_string(),
+		GamepadButton::LeftTrigger2 => "LT".to_string(),
+		GamepadButton::RightTrigger2 => "RT".to_string(),
+		GamepadButton::DPadUp => "D-Up".to_string(),
+		GamepadButton::DPadLeft => "D-Left".to_string(),
+		GamepadButton::DPadDown => "D-Down".to_string(),
+		GamepadButton::DPadRight => "D-Right".to_string(),
+		other => format!("{other:?}"),
+	}
+}
+
 /// An action that can be performed by a character.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Action {
 	Wait,
 	Push(Offset),
@@ -72,11 +366,105 @@ pub enum Action {
 	Return,
 }
 
+/// The action, if any, that `actor` takes when the direction button for
+/// `offset` is pressed, given whether the Act button is held.
+pub fn direction_action(
+	actor: &NextActor,
+	offset: Offset,
+	act_button_held: bool,
+) -> Option<Action> {
+	if actor.character.sliding {
+		// Sliding characters can only continue in the direction they face.
+		(offset == actor.facing).then_some(Action::Push(offset))
+	} else if actor.character.can_summon() && act_button_held {
+		Some(Action::Summon(offset))
+	} else if actor.character.can_push() {
+		Some(Action::Push(offset))
+	} else {
+		None
+	}
+}
+
+/// The control event for `actor` when the direction button for `offset` is
+/// pressed, or why there isn't one. See [`direction_action`].
+fn direction_event(
+	actor: &NextActor,
+	offset: Offset,
+	act_button_held: bool,
+) -> Result<Option<ControlEvent>, RejectionReason> {
+	if let Some(action) = direction_action(actor, offset, act_button_held) {
+		Ok(Some(ControlEvent::Act((actor.id, action))))
+	} else if !actor.character.sliding {
+		Ok(None)
+	} else if act_button_held && actor.character.portal_coords.is_none() {
+		Err(RejectionReason::SummonWhileSliding)
+	} else {
+		Err(RejectionReason::TurnWhileSliding)
+	}
+}
+
+/// Why a button press was refused instead of becoming an action.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RejectionReason {
+	/// A sliding character can only continue in the direction it faces.
+	TurnWhileSliding,
+	/// A sliding character can't hold still.
+	WaitWhileSliding,
+	/// A sliding character can't open a portal.
+	SummonWhileSliding,
+	/// A sliding character can't return through its portal.
+	ReturnWhileSliding,
+	/// The most portals
+	/// [`Rules::max_open_portals`](crate::level::Rules::max_open_portals)
+	/// allows are already open.
+	PortalLimit,
+}
+
+impl RejectionReason {
+	/// A short explanation for the player.
+	pub fn message(&self) -> &'static str {
+		match self {
+			RejectionReason::TurnWhileSliding => {
+				"This character can't change direction while sliding"
+			}
+			RejectionReason::WaitWhileSliding => {
+				"This character can't wait while sliding"
+			}
+			RejectionReason::SummonWhileSliding => {
+				"This character can't summon while sliding"
+			}
+			RejectionReason::ReturnWhileSliding => {
+				"This character can't return while sliding"
+			}
+			RejectionReason::PortalLimit => {
+				"No more portals can be open at once"
+			}
+		}
+	}
+}
+
+/// Sent when a button press is refused, so that the input isn't silently
+/// eaten.
+#[derive(Event)]
+pub struct ControlRejected(pub RejectionReason);
+
 #[derive(Event)]
 pub enum ControlEvent {
 	Act((Id, Action)),
 	Undo,
 	Redo,
+	/// Undo back to the start of the level's history.
+	UndoAll,
+	/// Redo forward to the end of the level's history.
+	RedoAll,
+	/// Undo or redo to the given turn, or as near it as possible.
+	Seek(usize),
+	/// Place an object at (or remove the object from) the given coordinates,
+	/// outside the normal rules.
+	Edit {
+		coords: Coords,
+		object: Option<Object>,
+	},
 }
 
 /// Local state for the control system, for handling multi-input/multi-frame
@@ -88,21 +476,78 @@ pub struct ControlState {
 	act_button_held: bool,
 }
 
+/// `event` if it's `available`. Otherwise, plays a nudge sound so that the
+/// button press isn't met with silence.
+fn unless_unavailable(
+	available: bool,
+	event: ControlEvent,
+	commands: &mut Commands,
+	tracks: &AudioTracks,
+) -> Option<ControlEvent> {
+	if available {
+		Some(event)
+	} else {
+		audio::play_cue(commands, tracks, "nudge");
+		None
+	}
+}
+
+/// The raw input the [`control`] system reads, and how it's bound.
+#[derive(SystemParam)]
+pub struct ControlInput<'w, 's> {
+	keyboard_bindings: Res<'w, KeyboardBindings>,
+	gamepad_bindings: Res<'w, GamepadBindings>,
+	active_device: ResMut<'w, ActiveDevice>,
+	keyboard_events: EventReader<'w, 's, KeyboardInput>,
+	gamepad_events: EventReader<'w, 's, GamepadButtonStateChangedEvent>,
+	game_state: Res<'w, State<GameState>>,
+}
+
+/// The events the [`control`] system produces.
+#[derive(SystemParam)]
+pub struct ControlOutput<'w> {
+	control_events: EventWriter<'w, ControlEvent>,
+	rejected_events: EventWriter<'w, ControlRejected>,
+}
+
 /// Consumes keyboard/gamepad input and produces higher-level control events to
 /// be consumed by the update and animation systems.
 pub fn control(
+	mut commands: Commands,
 	mut state: Local<ControlState>,
-	mut keyboard_events: EventReader<KeyboardInput>,
+	level: Res<Level>,
+	tracks: Res<AudioTracks>,
+	mut input: ControlInput,
 	mut next_actors: EventReader<NextActor>,
-	mut control_events: EventWriter<ControlEvent>,
+	mut output: ControlOutput,
 ) {
-	// TODO: Make this a resource and support custom input bindings.
-	let keybinds = KeyboardBindings::default();
+	// Drop input from before play last paused or the level changed, so none
+	// of it is replayed on resuming.
+	if input.game_state.is_changed() {
+		state.input_buffer.clear();
+		state.act_button_held = false;
+		input.keyboard_events.clear();
+		input.gamepad_events.clear();
+	}
 	// Buffer inputs so that update and animation systems can run after each
-	// control event.
-	state
-		.input_buffer
-		.extend(keybinds.adapt(&mut keyboard_events.read()));
+	// control event. Also keep track of the most recently used device.
+	let buffered = state.input_buffer.len();
+	state.input_buffer.extend(
+		input
+			.keyboard_bindings
+			.adapt(&mut input.keyboard_events.read()),
+	);
+	if state.input_buffer.len() > buffered {
+		input.active_device.set_if_neq(ActiveDevice::Keyboard);
+	}
+	for event in input.gamepad_events.read() {
+		if let Some(button) = input.gamepad_bindings.adapt(event) {
+			state.input_buffer.push_back(button);
+			input
+				.active_device
+				.set_if_neq(ActiveDevice::Gamepad(event.entity));
+		}
+	}
 
 	// Set the next actor if there is one. There should be at most one next
 	// actor per frame.
@@ -122,75 +567,99 @@ pub fn control(
 	while let Some((button, button_state)) = state.input_buffer.pop_front() {
 		// Get the next control event and/or update internal state.
 		let control_event = match (button, button_state) {
-			(GameButton::Undo, ButtonState::Pressed) => {
-				Some(ControlEvent::Undo)
+			(GameButton::Undo, ButtonState::Pressed) => Ok(unless_unavailable(
+				level.can_undo(),
+				ControlEvent::Undo,
+				&mut commands,
+				&tracks,
+			)),
+			(GameButton::Redo, ButtonState::Pressed) => Ok(unless_unavailable(
+				level.can_redo(),
+				ControlEvent::Redo,
+				&mut commands,
+				&tracks,
+			)),
+			(GameButton::UndoAll, ButtonState::Pressed) => {
+				Ok(unless_unavailable(
+					level.can_undo(),
+					ControlEvent::UndoAll,
+					&mut commands,
+					&tracks,
+				))
 			}
-			(GameButton::Redo, ButtonState::Pressed) => {
-				Some(ControlEvent::Redo)
+			(GameButton::RedoAll, ButtonState::Pressed) => {
+				Ok(unless_unavailable(
+					level.can_redo(),
+					ControlEvent::RedoAll,
+					&mut commands,
+					&tracks,
+				))
 			}
 			(GameButton::Up, ButtonState::Pressed) => {
-				if actor.character.can_summon() && state.act_button_held {
-					act(Action::Summon(Offset::UP))
-				} else if actor.character.can_push() {
-					act(Action::Push(Offset::UP))
-				} else {
-					None
-				}
+				direction_event(&actor, Offset::UP, state.act_button_held)
 			}
 			(GameButton::Left, ButtonState::Pressed) => {
-				if actor.character.can_summon() && state.act_button_held {
-					act(Action::Summon(Offset::LEFT))
-				} else if actor.character.can_push() {
-					act(Action::Push(Offset::LEFT))
-				} else {
-					None
-				}
+				direction_event(&actor, Offset::LEFT, state.act_button_held)
 			}
 			(GameButton::Down, ButtonState::Pressed) => {
-				if actor.character.can_summon() && state.act_button_held {
-					act(Action::Summon(Offset::DOWN))
-				} else if actor.character.can_push() {
-					act(Action::Push(Offset::DOWN))
-				} else {
-					None
-				}
+				direction_event(&actor, Offset::DOWN, state.act_button_held)
 			}
 			(GameButton::Right, ButtonState::Pressed) => {
-				if actor.character.can_summon() && state.act_button_held {
-					act(Action::Summon(Offset::RIGHT))
-				} else if actor.character.can_push() {
-					act(Action::Push(Offset::RIGHT))
+				direction_event(&actor, Offset::RIGHT, state.act_button_held)
+			}
+			(GameButton::Wait, ButtonState::Pressed) => {
+				if actor.character.can_wait() {
+					Ok(act(Action::Wait))
 				} else {
-					None
+					Err(RejectionReason::WaitWhileSliding)
 				}
 			}
-			(GameButton::Wait, ButtonState::Pressed) => act(Action::Wait),
 			(GameButton::Act, ButtonState::Pressed) => {
 				// The Act button is contextual. If the actor has the ability to
 				// return, it's the return button. If it has the ability to
 				// summon, it's a modifier button.
-				if !state.act_button_held {
-					state.act_button_held = true;
-					actor
-						.character
-						.can_return()
-						.then(|| act(Action::Return))
-						.flatten()
+				if state.act_button_held {
+					Ok(None)
 				} else {
-					None
+					state.act_button_held = true;
+					if actor.character.can_return() {
+						Ok(act(Action::Return))
+					} else if actor.character.sliding
+						&& actor.character.portal_coords.is_some()
+					{
+						Err(RejectionReason::ReturnWhileSliding)
+					} else {
+						Ok(None)
+					}
 				}
 			}
 			(GameButton::Act, ButtonState::Released) => {
 				state.act_button_held = false;
-				None
+				Ok(None)
+			}
+			_ => Ok(None),
+		};
+		// Refuse summons past the portal limit, rather than letting them
+		// fizzle.
+		let control_event = match control_event {
+			Ok(Some(ControlEvent::Act((_, Action::Summon(_)))))
+				if level.portal_room() == Some(0) =>
+			{
+				Err(RejectionReason::PortalLimit)
 			}
-			_ => None,
+			control_event => control_event,
 		};
+		// Nudge and say why on a refused press, so it doesn't feel eaten.
+		let control_event = control_event.unwrap_or_else(|reason| {
+			audio::play_cue(&mut commands, &tracks, "nudge");
+			output.rejected_events.send(ControlRejected(reason));
+			None
+		});
 		// If there was a control event, emit it, reset the next actor, and
 		// return so that the update and animation systems can respond.
 		if let Some(control_event) = control_event {
 			state.next_actor = None;
-			control_events.send(control_event);
+			output.control_events.send(control_event);
 			return;
 		}
 	}
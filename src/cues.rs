@@ -0,0 +1,83 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+	audio::{self, AudioTracks},
+	level::{ChangeEvent, CharacterColor, Level, Offset},
+	settings::Settings,
+	update::NextActor,
+};
+
+/// The cue for a character moving with the given unit `offset`.
+fn move_cue(offset: Offset) -> Option<&'static str> {
+	match offset {
+		Offset::UP => Some("move_up"),
+		Offset::DOWN => Some("move_down"),
+		Offset::LEFT => Some("move_left"),
+		Offset::RIGHT => Some("move_right"),
+		_ => None,
+	}
+}
+
+/// The cue announcing a character of the given `color`.
+fn color_cue(color: CharacterColor) -> &'static str {
+	match color {
+		CharacterColor::Green => "voice_green",
+		CharacterColor::Red => "voice_red",
+		CharacterColor::Blue => "voice_blue",
+		CharacterColor::Yellow => "voice_yellow",
+		CharacterColor::Magenta => "voice_magenta",
+		CharacterColor::Cyan => "voice_cyan",
+		CharacterColor::Black => "voice_black",
+		CharacterColor::White => "voice_white",
+	}
+}
+
+/// Plays a cue for each direction characters moved in during a change, and
+/// the "blocked" cue if any character's push was blocked, so the result of a
+/// turn can be followed by ear.
+pub fn cue_moves(
+	mut commands: Commands,
+	mut change_events: EventReader<ChangeEvent>,
+	level: Res<Level>,
+	tracks: Res<AudioTracks>,
+) {
+	for change in change_events.read() {
+		let mut cues = HashSet::new();
+		for (id, mv) in &change.moves {
+			if !level
+				.characters_by_id()
+				.any(|(character_id, _)| character_id == id)
+			{
+				continue;
+			}
+			let offset = Offset::new(
+				(mv.to_coords.row - mv.from_coords.row).signum(),
+				(mv.to_coords.col - mv.from_coords.col).signum(),
+			);
+			cues.extend(move_cue(offset));
+		}
+		if !change.blocked.is_empty() {
+			cues.insert("blocked");
+		}
+		for cue in cues {
+			audio::play_cue(&mut commands, &tracks, cue);
+		}
+	}
+}
+
+/// Announces the color of the next character to act, if enabled in
+/// [`Settings`].
+pub fn announce_actor(
+	mut commands: Commands,
+	mut next_actors: EventReader<NextActor>,
+	settings: Res<Settings>,
+	tracks: Res<AudioTracks>,
+) {
+	let Some(actor) = next_actors.read().last() else {
+		return;
+	};
+	if settings.announce_actor {
+		let cue = color_cue(actor.character.color);
+		audio::play_cue(&mut commands, &tracks, cue);
+	}
+}
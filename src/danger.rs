@@ -0,0 +1,68 @@
+use bevy::{
+	pbr::{NotShadowCaster, NotShadowReceiver},
+	prelude::*,
+};
+
+use crate::{
+	assets::{GameAssets, MaterialKind, MeshKind},
+	level::{ChangeEvent, Level, LevelEntity},
+	photo_mode::HiddenInPhotoMode,
+	states::GameState,
+};
+
+/// Height above the floor at which danger highlights are drawn.
+const DANGER_HEIGHT: f32 = 0.015;
+
+/// Whether tiles that would be lethal or destructive next turn are
+/// highlighted.
+#[derive(Resource, Default)]
+pub struct ShowDanger(bool);
+
+/// Marker component for danger highlight entities.
+#[derive(Component)]
+pub struct DangerHighlight;
+
+/// Shows or hides the danger preview when H is pressed.
+pub fn toggle_danger_preview(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut show_danger: ResMut<ShowDanger>,
+) {
+	if keys.just_pressed(KeyCode::KeyH) {
+		show_danger.0 = !show_danger.0;
+	}
+}
+
+/// Recomputes the danger highlights whenever the level changes, a level
+/// starts, or the preview is toggled. See [`Level::danger_coords`].
+pub fn update_danger_preview(
+	mut commands: Commands,
+	show_danger: Res<ShowDanger>,
+	level: Res<Level>,
+	state: Res<State<GameState>>,
+	mut change_events: EventReader<ChangeEvent>,
+	assets: Res<GameAssets>,
+	highlight_query: Query<Entity, With<DangerHighlight>>,
+) {
+	let changed = change_events.read().count() > 0;
+	if !changed && !show_danger.is_changed() && !state.is_changed() {
+		return;
+	}
+	for entity in &highlight_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	if !show_danger.0 {
+		return;
+	}
+	for coords in level.danger_coords() {
+		commands.spawn((
+			LevelEntity,
+			DangerHighlight,
+			HiddenInPhotoMode,
+			Mesh3d(assets.mesh(MeshKind::Highlight)),
+			MeshMaterial3d(assets.material(MaterialKind::Danger)),
+			NotShadowCaster,
+			NotShadowReceiver,
+			coords.transform(DANGER_HEIGHT),
+		));
+	}
+}
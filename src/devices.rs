@@ -0,0 +1,112 @@
+use bevy::{
+	input::gamepad::{
+		GamepadButtonStateChangedEvent, GamepadConnection,
+		GamepadConnectionEvent,
+	},
+	prelude::*,
+};
+
+use crate::{
+	control::{ActiveDevice, GamepadBindings},
+	states::GameState,
+};
+
+/// Tracks gamepads as they connect and disconnect.
+///
+/// A newly connected gamepad becomes the active device. If the active gamepad
+/// disconnects, another connected gamepad (or the keyboard) takes over, and
+/// play pauses until the player reconnects or picks another device.
+pub fn handle_gamepad_connections(
+	mut connection_events: EventReader<GamepadConnectionEvent>,
+	mut gamepad_bindings: ResMut<GamepadBindings>,
+	mut active_device: ResMut<ActiveDevice>,
+	state: Res<State<GameState>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	for event in connection_events.read() {
+		match &event.connection {
+			GamepadConnection::Connected { name, .. } => {
+				info!("gamepad connected: {name}");
+				gamepad_bindings.connect(event.gamepad, name);
+				*active_device = ActiveDevice::Gamepad(event.gamepad);
+				if *state.get() == GameState::DeviceDisconnected {
+					next_state.set(GameState::Playing);
+				}
+			}
+			GamepadConnection::Disconnected => {
+				info!("gamepad disconnected");
+				gamepad_bindings.disconnect(event.gamepad);
+				if *active_device != ActiveDevice::Gamepad(event.gamepad) {
+					continue;
+				}
+				*active_device = gamepad_bindings
+					.any_connected()
+					.map_or(ActiveDevice::Keyboard, ActiveDevice::Gamepad);
+				if *state.get() == GameState::Playing {
+					next_state.set(GameState::DeviceDisconnected);
+				}
+			}
+		}
+	}
+}
+
+/// Marker component for the disconnected controller prompt.
+#[derive(Component)]
+pub struct DisconnectedPrompt;
+
+/// Spawns the disconnected controller prompt.
+pub fn spawn_disconnected_prompt(mut commands: Commands) {
+	commands.spawn((
+		DisconnectedPrompt,
+		Text::new(
+			"Controller disconnected\n\n\
+			Reconnect it, or press Enter or Start on another device to \
+			continue.",
+		),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Percent(40.0),
+			left: Val::Percent(30.0),
+			width: Val::Percent(40.0),
+			padding: UiRect::all(Val::Px(12.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+	));
+}
+
+/// Despawns the disconnected controller prompt.
+pub fn despawn_disconnected_prompt(
+	mut commands: Commands,
+	prompt_query: Query<Entity, With<DisconnectedPrompt>>,
+) {
+	for entity in &prompt_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// Resumes play with the keyboard when Enter is pressed, or with a gamepad
+/// when its Start button is pressed. These buttons aren't bound to any game
+/// buttons, so resuming doesn't also move a character.
+pub fn resume_with_any_device(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut gamepad_events: EventReader<GamepadButtonStateChangedEvent>,
+	mut active_device: ResMut<ActiveDevice>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	let pressed_start = gamepad_events.read().find(|event| {
+		event.button == GamepadButton::Start && event.state.is_pressed()
+	});
+	if let Some(event) = pressed_start {
+		*active_device = ActiveDevice::Gamepad(event.entity);
+	} else if keys.just_pressed(KeyCode::Enter) {
+		*active_device = ActiveDevice::Keyboard;
+	} else {
+		return;
+	}
+	next_state.set(GameState::Playing);
+}
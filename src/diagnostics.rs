@@ -0,0 +1,125 @@
+use std::time::Instant;
+
+use bevy::{
+	diagnostic::{
+		Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore,
+		EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+	},
+	prelude::*,
+};
+use bevy_easings::EasingComponent;
+
+use crate::{
+	animation::{ChoiceIndicator, ChoosingIndicator},
+	photo_mode::HiddenInPhotoMode,
+	rng::GameRng,
+};
+
+/// Time spent in the gameplay system chain each frame, in milliseconds.
+pub const GAMEPLAY_TIME: DiagnosticPath =
+	DiagnosticPath::const_new("gameplay_time");
+
+/// Makes the diagnostic for [`GAMEPLAY_TIME`].
+pub fn gameplay_time_diagnostic() -> Diagnostic {
+	Diagnostic::new(GAMEPLAY_TIME).with_suffix("ms")
+}
+
+/// When the gameplay system chain started running this frame, if it has.
+#[derive(Resource, Default)]
+pub struct GameplayTimer(Option<Instant>);
+
+/// Marks the start of the gameplay system chain.
+pub fn start_gameplay_timer(mut timer: ResMut<GameplayTimer>) {
+	timer.0 = Some(Instant::now());
+}
+
+/// Marks the end of the gameplay system chain and records its duration.
+pub fn stop_gameplay_timer(
+	mut timer: ResMut<GameplayTimer>,
+	mut diagnostics: Diagnostics,
+) {
+	if let Some(start) = timer.0.take() {
+		diagnostics.add_measurement(&GAMEPLAY_TIME, || {
+			1000.0 * start.elapsed().as_secs_f64()
+		});
+	}
+}
+
+/// Marker component for the diagnostics overlay text.
+#[derive(Component)]
+pub struct DiagnosticsOverlay;
+
+/// Spawns the (initially hidden) diagnostics overlay.
+pub fn spawn_diagnostics_overlay(mut commands: Commands) {
+	commands.spawn((
+		DiagnosticsOverlay,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Px(4.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+		Visibility::Hidden,
+	));
+}
+
+/// Shows or hides the diagnostics overlay when F3 is pressed.
+pub fn toggle_diagnostics_overlay(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut overlay_query: Query<&mut Visibility, With<DiagnosticsOverlay>>,
+) {
+	if !keys.just_pressed(KeyCode::F3) {
+		return;
+	}
+	for mut visibility in &mut overlay_query {
+		*visibility = match *visibility {
+			Visibility::Hidden => Visibility::Visible,
+			_ => Visibility::Hidden,
+		};
+	}
+}
+
+/// Refreshes the diagnostics overlay text while it's visible.
+pub fn update_diagnostics_overlay(
+	store: Res<DiagnosticsStore>,
+	rng: Res<GameRng>,
+	easing_query: Query<(), With<EasingComponent<Transform>>>,
+	choosing_query: Query<(), With<ChoosingIndicator>>,
+	choice_query: Query<(), With<ChoiceIndicator>>,
+	mut overlay_query: Query<
+		(&mut Text, &Visibility),
+		With<DiagnosticsOverlay>,
+	>,
+) {
+	let smoothed = |path: &DiagnosticPath| {
+		store
+			.get(path)
+			.and_then(|diagnostic| diagnostic.smoothed())
+			.unwrap_or(f64::NAN)
+	};
+	for (mut text, visibility) in &mut overlay_query {
+		if *visibility == Visibility::Hidden {
+			continue;
+		}
+		**text = format!(
+			"FPS: {:.0}\n\
+			Entities: {:.0}\n\
+			Gameplay: {:.2} ms\n\
+			Easings: {}\n\
+			Indicators: {}\n\
+			Seed: {}",
+			smoothed(&FrameTimeDiagnosticsPlugin::FPS),
+			smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+			smoothed(&GAMEPLAY_TIME),
+			easing_query.iter().count(),
+			choosing_query.iter().count() + choice_query.iter().count(),
+			rng.seed(),
+		);
+	}
+}
@@ -0,0 +1,171 @@
+use std::{
+	thread,
+	time::{Duration, Instant},
+};
+
+use bevy::{
+	prelude::*,
+	window::{PrimaryWindow, WindowResizeConstraints},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{settings::Settings, timeline::TimelineStrip};
+
+/// Environment variables Steam sets on the Steam Deck.
+const STEAM_DECK_VARS: [&str; 2] = ["SteamDeck", "SteamOS"];
+
+/// A set of display choices suited to a kind of screen.
+#[derive(
+	Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug,
+)]
+pub enum DisplayPreset {
+	#[default]
+	Desktop,
+	/// For small 16:10 screens, like the Steam Deck's 1280×800 one: a larger
+	/// HUD, hints with gamepad glyphs, the resolution capped at 800p, and the
+	/// frame rate capped to save battery. The timeline moves to the top of the
+	/// screen, since the narrower screen has no room for it between the
+	/// control hints and mutators along the bottom.
+	Handheld,
+}
+
+impl DisplayPreset {
+	const ALL: [DisplayPreset; 2] =
+		[DisplayPreset::Desktop, DisplayPreset::Handheld];
+
+	/// The preset suited to the device the game is running on, for choosing
+	/// one at first launch.
+	pub fn detect() -> DisplayPreset {
+		let is_steam_deck = STEAM_DECK_VARS
+			.iter()
+			.any(|var| std::env::var(var).is_ok_and(|value| value == "1"));
+		if is_steam_deck {
+			DisplayPreset::Handheld
+		} else {
+			DisplayPreset::Desktop
+		}
+	}
+
+	fn name(&self) -> &'static str {
+		match self {
+			DisplayPreset::Desktop => "desktop",
+			DisplayPreset::Handheld => "handheld",
+		}
+	}
+
+	/// The preset after this one, wrapping around.
+	fn next(&self) -> DisplayPreset {
+		let idx = DisplayPreset::ALL.iter().position(|p| p == self).unwrap();
+		DisplayPreset::ALL[(idx + 1) % DisplayPreset::ALL.len()]
+	}
+
+	/// How much to scale the UI by.
+	fn ui_scale(&self) -> f32 {
+		match self {
+			DisplayPreset::Desktop => 1.0,
+			DisplayPreset::Handheld => 1.25,
+		}
+	}
+
+	/// The largest window size, in logical pixels, if capped.
+	fn max_resolution(&self) -> Option<Vec2> {
+		match self {
+			DisplayPreset::Desktop => None,
+			DisplayPreset::Handheld => Some(Vec2::new(1280.0, 800.0)),
+		}
+	}
+
+	/// The highest frame rate, if capped. The Steam Deck's display can
+	/// refresh at 40 Hz, so this paces frames evenly there.
+	fn max_frame_rate(&self) -> Option<f64> {
+		match self {
+			DisplayPreset::Desktop => None,
+			DisplayPreset::Handheld => Some(40.0),
+		}
+	}
+
+	/// Whether control hints show gamepad glyphs even while the keyboard is
+	/// the active device. Handhelds' built-in controls may be mapped to keys.
+	pub fn gamepad_glyphs(&self) -> bool {
+		*self == DisplayPreset::Handheld
+	}
+
+	/// Where the timeline is anchored.
+	fn timeline_node(&self, node: &mut Node) {
+		match self {
+			DisplayPreset::Desktop => {
+				node.top = Val::Auto;
+				node.bottom = Val::Px(4.0);
+				node.left = Val::Percent(30.0);
+				node.width = Val::Percent(40.0);
+			}
+			DisplayPreset::Handheld => {
+				node.top = Val::Px(4.0);
+				node.bottom = Val::Auto;
+				node.left = Val::Percent(20.0);
+				node.width = Val::Percent(60.0);
+			}
+		}
+	}
+}
+
+/// Switches to the next display preset when F5 is pressed.
+pub fn cycle_display_preset(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut settings: ResMut<Settings>,
+) {
+	if keys.just_pressed(KeyCode::F5) {
+		let preset = settings.display_preset().next();
+		settings.display_preset = Some(preset);
+		settings.save();
+		info!("display preset: {}", preset.name());
+	}
+}
+
+/// Applies the display preset's UI scale, resolution cap, and timeline
+/// anchoring at startup and whenever the setting changes.
+pub fn apply_display_preset(
+	settings: Res<Settings>,
+	mut ui_scale: ResMut<UiScale>,
+	mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+	mut timeline_query: Query<&mut Node, With<TimelineStrip>>,
+) {
+	if !settings.is_changed() {
+		return;
+	}
+	let preset = settings.display_preset();
+	ui_scale.0 = preset.ui_scale();
+	for mut window in &mut window_query {
+		let Some(max) = preset.max_resolution() else {
+			window.resize_constraints = WindowResizeConstraints::default();
+			continue;
+		};
+		window.resize_constraints = WindowResizeConstraints {
+			max_width: max.x,
+			max_height: max.y,
+			..default()
+		};
+		let size = window.size().min(max);
+		window.resolution.set(size.x, size.y);
+	}
+	for mut node in &mut timeline_query {
+		preset.timeline_node(&mut node);
+	}
+}
+
+/// Sleeps off the rest of each frame under the display preset's frame rate
+/// cap, if it has one.
+pub fn limit_frame_rate(
+	settings: Res<Settings>,
+	mut last_frame: Local<Option<Instant>>,
+) {
+	if let (Some(max), Some(last)) =
+		(settings.display_preset().max_frame_rate(), *last_frame)
+	{
+		let frame = Duration::from_secs_f64(1.0 / max);
+		if let Some(rest) = frame.checked_sub(last.elapsed()) {
+			thread::sleep(rest);
+		}
+	}
+	*last_frame = Some(Instant::now());
+}
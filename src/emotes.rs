@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_easings::{
+	Ease, EaseFunction, EasingChainComponent, EasingComponent, EasingType,
+};
+
+use crate::{
+	animation,
+	camera::MainCamera,
+	level::{ChangeEvent, Coords, Level, LevelEntity},
+	photo_mode::HiddenInPhotoMode,
+};
+
+/// How long an emote floats before disappearing.
+const EMOTE_DURATION: Duration = Duration::from_millis(900);
+
+/// How far an emote rises over its lifetime, in pixels.
+const EMOTE_RISE: f32 = 30.0;
+
+/// Height above a character's tile at which its emotes appear.
+const EMOTE_HEIGHT: f32 = 1.2;
+
+/// How high a surprised character hops.
+const HOP_HEIGHT: f32 = 0.15;
+
+/// How long each half of a hop takes.
+const HOP_DURATION: Duration = Duration::from_millis(90);
+
+/// A floating reaction above a tile that rises and fades out.
+#[derive(Component)]
+pub struct Emote {
+	/// The point in the world the emote floats above.
+	anchor: Vec3,
+	timer: Timer,
+}
+
+/// Spawns an emote showing `text` above `coords`.
+fn spawn_emote(commands: &mut Commands, coords: Coords, text: &str) {
+	commands.spawn((
+		LevelEntity,
+		HiddenInPhotoMode,
+		Emote {
+			anchor: coords.transform(EMOTE_HEIGHT).translation,
+			timer: Timer::new(EMOTE_DURATION, TimerMode::Once),
+		},
+		Text(text.to_string()),
+		TextFont {
+			font_size: 24.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			..default()
+		},
+		// Hidden until positioned.
+		Visibility::Hidden,
+	));
+}
+
+/// Reacts to each new turn: characters whose pushes were blocked hop in
+/// surprise, and returning characters sigh with relief. Undone turns get no
+/// reactions. This is purely cosmetic and doesn't affect the level.
+pub fn add_emotes(
+	mut commands: Commands,
+	mut last_turn: Local<usize>,
+	level: Res<Level>,
+	mut change_events: EventReader<ChangeEvent>,
+	object_query: Query<(Entity, &animation::Object)>,
+) {
+	let forward = level.turn() > *last_turn;
+	*last_turn = level.turn();
+	for change in change_events.read() {
+		if !forward {
+			continue;
+		}
+		for (entity, object) in &object_query {
+			if !change.blocked.contains(&object.id) {
+				continue;
+			}
+			let Some(coords) = level
+				.iter_level_objects()
+				.find(|level_object| level_object.id == object.id)
+				.map(|level_object| level_object.coords)
+			else {
+				continue;
+			};
+			// Hop from where the character is standing, overriding any
+			// animation still in progress.
+			let from = coords.transform(0.5);
+			let hop =
+				from.with_translation(from.translation + HOP_HEIGHT * Vec3::Z);
+			commands
+				.entity(entity)
+				.remove::<(EasingComponent<Transform>, EasingChainComponent<Transform>)>(
+				)
+				.insert(
+					from.ease_to(
+						hop,
+						EaseFunction::QuadraticOut,
+						EasingType::Once {
+							duration: HOP_DURATION,
+						},
+					)
+					.ease_to(
+						from,
+						EaseFunction::QuadraticIn,
+						EasingType::Once {
+							duration: HOP_DURATION,
+						},
+					),
+				);
+			spawn_emote(&mut commands, coords, "!");
+		}
+		for returning in change.returnings.values() {
+			spawn_emote(&mut commands, returning.returner.coords, "phew");
+		}
+	}
+}
+
+/// Keeps emotes above their anchors as they rise and fade, despawning them
+/// once they expire.
+pub fn float_emotes(
+	mut commands: Commands,
+	time: Res<Time>,
+	camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+	mut emote_query: Query<(
+		Entity,
+		&mut Emote,
+		&mut Node,
+		&mut TextColor,
+		&mut Visibility,
+	)>,
+) {
+	let Ok((camera, camera_transform)) = camera_query.get_single() else {
+		return;
+	};
+	for (entity, mut emote, mut node, mut color, mut visibility) in
+		&mut emote_query
+	{
+		if emote.timer.tick(time.delta()).finished() {
+			commands.entity(entity).despawn_recursive();
+			continue;
+		}
+		let Ok(position) =
+			camera.world_to_viewport(camera_transform, emote.anchor)
+		else {
+			continue;
+		};
+		let progress = emote.timer.fraction();
+		node.left = Val::Px(position.x);
+		node.top = Val::Px(position.y - EMOTE_RISE * progress);
+		color.0 = Color::WHITE.with_alpha(1.0 - progress * progress);
+		*visibility = Visibility::Inherited;
+	}
+}
@@ -0,0 +1,193 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{level::Level, level_asset, photo_mode::HiddenInPhotoMode};
+
+/// Where the player's featured-level results are stored.
+const RESULTS_PATH: &str = "featured.ron";
+
+const SECONDS_PER_WEEK: u64 = 7 * 24 * 60 * 60;
+
+/// Number of turn squares per row in a share string.
+const SHARE_ROW_LEN: usize = 5;
+
+/// The number of weeks since the Unix epoch.
+pub fn current_week() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs() / SECONDS_PER_WEEK)
+		.unwrap_or_default()
+}
+
+/// The level featured in the given `week`. The
+/// [embedded campaign levels](level_asset::embedded_level) take turns being
+/// featured, one per week.
+pub fn featured_level(week: u64) -> Level {
+	let idx = week as usize % level_asset::EMBEDDED_LEVEL_COUNT;
+	level_asset::embedded_level(idx)
+		.expect("embedded levels are valid")
+		.to_level()
+}
+
+/// The outcome of an attempt at a featured level.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct FeaturedResult {
+	pub week: u64,
+	pub turns: usize,
+	pub undos: usize,
+}
+
+impl FeaturedResult {
+	/// Whether this result beats `other`: fewer turns, then fewer undos.
+	fn beats(&self, other: &FeaturedResult) -> bool {
+		(self.turns, self.undos) < (other.turns, other.undos)
+	}
+
+	/// A spoiler-free summary for sharing, like:
+	///
+	/// ```text
+	/// Causal Oops #2912: 7 turns, 2 undos
+	/// 🟩🟩🟩🟩🟩
+	/// 🟩🟩
+	/// 🟥🟥
+	/// ```
+	pub fn share_string(&self) -> String {
+		let mut lines = vec![format!(
+			"Causal Oops #{}: {} turns, {} undos",
+			self.week, self.turns, self.undos
+		)];
+		let turns: Vec<&str> = vec!["🟩"; self.turns];
+		lines.extend(turns.chunks(SHARE_ROW_LEN).map(|row| row.concat()));
+		let undos: Vec<&str> = vec!["🟥"; self.undos];
+		lines.extend(undos.chunks(SHARE_ROW_LEN).map(|row| row.concat()));
+		lines.join("\n")
+	}
+}
+
+/// The player's progress on the current week's featured level.
+#[derive(Resource, Default)]
+pub struct FeaturedRun {
+	/// The week of the featured level being played, if one is being played.
+	week: Option<u64>,
+	/// Turns undone so far in this attempt.
+	undos: usize,
+	/// The level's turn as of the last update, for counting undos.
+	last_turn: usize,
+	/// The best recorded result for the current week.
+	best: Option<FeaturedResult>,
+}
+
+impl FeaturedRun {
+	/// Starts a new attempt at the featured level of `week`.
+	pub fn start(&mut self, week: u64) {
+		self.week = Some(week);
+		self.undos = 0;
+		self.last_turn = 0;
+		self.best = load_best().filter(|best| best.week == week);
+	}
+
+	/// Stops tracking featured-level progress.
+	pub fn stop(&mut self) {
+		self.week = None;
+	}
+}
+
+/// Loads the best recorded result from [`RESULTS_PATH`], if there is one.
+fn load_best() -> Option<FeaturedResult> {
+	let text = std::fs::read_to_string(RESULTS_PATH).ok()?;
+	ron::from_str(&text)
+		.map_err(|err| warn!("invalid results in {RESULTS_PATH}: {err}"))
+		.ok()
+}
+
+/// Saves `result` to [`RESULTS_PATH`].
+fn save_best(result: &FeaturedResult) {
+	let saved = ron::to_string(result)
+		.map_err(|err| err.to_string())
+		.and_then(|text| {
+			std::fs::write(RESULTS_PATH, text).map_err(|err| err.to_string())
+		});
+	if let Err(err) = saved {
+		warn!("could not save results to {RESULTS_PATH}: {err}");
+	}
+}
+
+/// Marker component for the featured level text.
+#[derive(Component)]
+pub struct FeaturedText;
+
+/// Spawns the (initially empty) featured level text.
+pub fn spawn_featured_text(mut commands: Commands) {
+	commands.spawn((
+		FeaturedText,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Px(4.0),
+			left: Val::Percent(40.0),
+			..default()
+		},
+	));
+}
+
+/// Counts undos during a featured level attempt. Pressing Enter once the level
+/// is complete records the result, keeping it if it's the player's best, and
+/// shows a share string (also written to the log).
+pub fn track_featured_run(
+	keys: Res<ButtonInput<KeyCode>>,
+	level: Res<Level>,
+	mut run: ResMut<FeaturedRun>,
+	mut text_query: Query<&mut Text, With<FeaturedText>>,
+) {
+	let Some(week) = run.week else {
+		for mut text in &mut text_query {
+			if !text.is_empty() {
+				text.clear();
+			}
+		}
+		return;
+	};
+	if level.turn() < run.last_turn {
+		run.undos += run.last_turn - level.turn();
+	}
+	run.last_turn = level.turn();
+
+	let result = FeaturedResult {
+		week,
+		turns: level.turn(),
+		undos: run.undos,
+	};
+	let complete = level.is_complete();
+	let mut status = format!(
+		"Featured level #{week}\nTurns: {}   Undos: {}",
+		result.turns, result.undos,
+	);
+	// Only finished runs can be recorded.
+	if complete {
+		status.push_str("\nEnter: record result");
+	}
+	let is_best = match run.best {
+		Some(best) => result.beats(&best),
+		None => true,
+	};
+	if keys.just_pressed(KeyCode::Enter) && complete && is_best {
+		save_best(&result);
+		run.best = Some(result);
+		info!("featured level result:\n{}", result.share_string());
+	}
+	if let Some(best) = run.best {
+		status.push_str(&format!("\nBest:\n{}", best.share_string()));
+	}
+	for mut text in &mut text_query {
+		if **text != status {
+			**text = status.clone();
+		}
+	}
+}
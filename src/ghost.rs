@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+	level::{CharacterColor, Coords, Level, Object},
+	replay::Replay,
+};
+
+/// How long a ghost takes to glide from one tile to the next.
+const GHOST_MOVE_DURATION: Duration = Duration::from_millis(200);
+
+/// Radius of a ghost.
+const GHOST_RADIUS: f32 = 0.3;
+
+/// Opacity of a ghost.
+const GHOST_ALPHA: f32 = 0.3;
+
+/// The character positions in one turn of an attempt.
+type Frame = Vec<(CharacterColor, Coords)>;
+
+/// Tracks the timing of the current attempt at a level and, after a restart,
+/// plays back the previous attempt's characters alongside the new attempt at
+/// the pace they originally moved, like a racing ghost.
+#[derive(Resource, Default)]
+pub struct Ghost {
+	enabled: bool,
+	/// When the current attempt started, in elapsed app time.
+	started: Duration,
+	/// The time into the current attempt at which each turn in its timeline
+	/// was last reached.
+	reached: Vec<Duration>,
+	/// The previous attempt's frames, each with the time into the attempt at
+	/// which it was reached.
+	previous: Vec<(Duration, Frame)>,
+}
+
+impl Ghost {
+	/// Ends the attempt at `level` and starts a new attempt at `next_level`
+	/// at time `now`. If `next_level` is a restart of `level`, the old
+	/// attempt becomes the ghost.
+	pub fn restart(
+		&mut self,
+		level: &Level,
+		next_level: &Level,
+		now: Duration,
+	) {
+		self.previous.clear();
+		let start = level.rewound();
+		if start.state_hash() == next_level.state_hash() {
+			let mut frames = vec![frame(&start)];
+			match Replay::record(level).play_with(start, |level| {
+				frames.push(frame(level));
+			}) {
+				Ok(_) => {
+					self.previous =
+						self.reached.iter().copied().zip(frames).collect();
+				}
+				Err(desync) => {
+					warn!("could not replay ghost past turn {}", desync.turn);
+				}
+			}
+		}
+		self.started = now;
+		self.reached.clear();
+	}
+}
+
+/// The positions of `level`'s characters.
+fn frame(level: &Level) -> Frame {
+	level
+		.iter_level_objects()
+		.filter_map(|level_object| match level_object.object {
+			Object::Character(character) => {
+				Some((character.color, level_object.coords))
+			}
+			Object::Prop(_) => None,
+		})
+		.collect()
+}
+
+/// Shows or hides the ghost when G is pressed.
+pub fn toggle_ghost(keys: Res<ButtonInput<KeyCode>>, mut ghost: ResMut<Ghost>) {
+	if keys.just_pressed(KeyCode::KeyG) {
+		ghost.enabled = !ghost.enabled;
+	}
+}
+
+/// Records when each turn of the current attempt is reached. Undoing rewinds
+/// the record, so it always matches the level's current timeline.
+pub fn track_attempt(
+	time: Res<Time>,
+	level: Res<Level>,
+	mut ghost: ResMut<Ghost>,
+) {
+	let len = level.turn() + 1;
+	if ghost.reached.len() != len {
+		let elapsed = time.elapsed().saturating_sub(ghost.started);
+		ghost.reached.truncate(len);
+		ghost.reached.resize(len, elapsed);
+	}
+}
+
+/// Draws the previous attempt's characters where they were at this point in
+/// that attempt.
+pub fn draw_ghost(ghost: Res<Ghost>, time: Res<Time>, mut gizmos: Gizmos) {
+	if !ghost.enabled || ghost.previous.is_empty() {
+		return;
+	}
+	let elapsed = time.elapsed().saturating_sub(ghost.started);
+	// The latest frame the previous attempt had reached by now.
+	let idx = ghost
+		.previous
+		.partition_point(|(reached, _)| *reached <= elapsed)
+		.max(1)
+		- 1;
+	let (reached, to) = &ghost.previous[idx];
+	let from = &ghost.previous[idx.saturating_sub(1)].1;
+	let progress = (elapsed.saturating_sub(*reached).as_secs_f32()
+		/ GHOST_MOVE_DURATION.as_secs_f32())
+	.min(1.0);
+	for (color, to_coords) in to {
+		let to_position = to_coords.transform(0.5).translation;
+		let position = from
+			.iter()
+			.find(|(from_color, _)| from_color == color)
+			.map_or(to_position, |(_, from_coords)| {
+				from_coords
+					.transform(0.5)
+					.translation
+					.lerp(to_position, progress)
+			});
+		gizmos.sphere(
+			Isometry3d::from_translation(position),
+			GHOST_RADIUS,
+			color.color().with_alpha(GHOST_ALPHA),
+		);
+	}
+}
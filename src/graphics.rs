@@ -0,0 +1,97 @@
+use bevy::{
+	core_pipeline::{experimental::taa::TemporalAntiAliasing, fxaa::Fxaa},
+	prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// A method of smoothing jagged edges.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AntiAliasing {
+	Off,
+	/// Multisample anti-aliasing with 2 samples per pixel.
+	Msaa2,
+	/// Multisample anti-aliasing with 4 samples per pixel.
+	#[default]
+	Msaa4,
+	/// Fast approximate anti-aliasing, a cheap post-process.
+	Fxaa,
+	/// Temporal anti-aliasing, which also smooths edges that shimmer in
+	/// motion, at the cost of some blur.
+	Taa,
+}
+
+impl AntiAliasing {
+	const ALL: [AntiAliasing; 5] = [
+		AntiAliasing::Off,
+		AntiAliasing::Msaa2,
+		AntiAliasing::Msaa4,
+		AntiAliasing::Fxaa,
+		AntiAliasing::Taa,
+	];
+
+	fn name(&self) -> &'static str {
+		match self {
+			AntiAliasing::Off => "off",
+			AntiAliasing::Msaa2 => "MSAA 2x",
+			AntiAliasing::Msaa4 => "MSAA 4x",
+			AntiAliasing::Fxaa => "FXAA",
+			AntiAliasing::Taa => "TAA",
+		}
+	}
+
+	/// The method after this one, wrapping around.
+	fn next(&self) -> AntiAliasing {
+		let idx = AntiAliasing::ALL.iter().position(|aa| aa == self).unwrap();
+		AntiAliasing::ALL[(idx + 1) % AntiAliasing::ALL.len()]
+	}
+
+	/// Enables this method on `camera`, replacing any other method. FXAA and
+	/// TAA require MSAA to be off.
+	fn apply(&self, camera: &mut EntityCommands) {
+		camera.remove::<(Fxaa, TemporalAntiAliasing)>();
+		match self {
+			AntiAliasing::Off => {
+				camera.insert(Msaa::Off);
+			}
+			AntiAliasing::Msaa2 => {
+				camera.insert(Msaa::Sample2);
+			}
+			AntiAliasing::Msaa4 => {
+				camera.insert(Msaa::Sample4);
+			}
+			AntiAliasing::Fxaa => {
+				camera.insert((Msaa::Off, Fxaa::default()));
+			}
+			AntiAliasing::Taa => {
+				camera.insert((Msaa::Off, TemporalAntiAliasing::default()));
+			}
+		}
+	}
+}
+
+/// Switches to the next anti-aliasing method when F7 is pressed.
+pub fn cycle_anti_aliasing(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut settings: ResMut<Settings>,
+) {
+	if keys.just_pressed(KeyCode::F7) {
+		settings.anti_aliasing = settings.anti_aliasing.next();
+		info!("anti-aliasing: {}", settings.anti_aliasing.name());
+	}
+}
+
+/// Applies the anti-aliasing setting to new cameras, and to all cameras when
+/// the setting changes.
+pub fn apply_anti_aliasing(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	camera_query: Query<(Entity, Ref<Camera3d>)>,
+) {
+	for (entity, camera) in &camera_query {
+		if settings.is_changed() || camera.is_added() {
+			settings.anti_aliasing.apply(&mut commands.entity(entity));
+		}
+	}
+}
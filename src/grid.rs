@@ -0,0 +1,203 @@
+use bevy::{
+	pbr::{NotShadowCaster, NotShadowReceiver},
+	prelude::*,
+	render::{
+		mesh::{Indices, PrimitiveTopology},
+		render_asset::RenderAssetUsages,
+	},
+	utils::HashSet,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	assets::{GameAssets, MaterialKind},
+	level::{Coords, Level, LevelEntity, Tile},
+	photo_mode::HiddenInPhotoMode,
+	settings::Settings,
+};
+
+/// Height above the floor at which the grid overlay is drawn.
+const GRID_HEIGHT: f32 = 0.02;
+
+/// Width of grid lines, in tiles.
+const LINE_WIDTH: f32 = 0.03;
+
+/// Side length of grid dots, in tiles.
+const DOT_SIZE: f32 = 0.08;
+
+/// How tile boundaries are marked on the floor.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GridOverlay {
+	#[default]
+	Off,
+	/// Thin lines along the edges of floor tiles.
+	Lines,
+	/// Dots at the corners of floor tiles.
+	Dots,
+}
+
+impl GridOverlay {
+	/// The style after this one, wrapping around.
+	fn next(&self) -> GridOverlay {
+		match self {
+			GridOverlay::Off => GridOverlay::Lines,
+			GridOverlay::Lines => GridOverlay::Dots,
+			GridOverlay::Dots => GridOverlay::Off,
+		}
+	}
+}
+
+/// Marker component for the grid overlay entity.
+#[derive(Component)]
+pub struct GridOverlayMesh;
+
+/// A single mesh marking the boundaries of `level`'s floor tiles in the given
+/// `style`, or `None` if the style is [`GridOverlay::Off`].
+fn grid_mesh(level: &Level, style: GridOverlay) -> Option<Mesh> {
+	let is_floor = |row: usize, col: usize| {
+		row < level.height()
+			&& col < level.width()
+			&& matches!(
+				level.tile_at(Coords::new(row as i32, col as i32)),
+				Tile::Floor { .. }
+					| Tile::Stairs | Tile::Ice
+					| Tile::Turntable { .. }
+					| Tile::Cracked { .. }
+					| Tile::Lava { bridged: true }
+			)
+	};
+	// Corners are indexed from the top-left corner of the top-left tile.
+	let corner =
+		|row: usize, col: usize| Vec2::new(col as f32 - 0.5, 0.5 - row as f32);
+	// Axis-aligned rectangles to draw, as (min, max) pairs.
+	let mut rects = Vec::new();
+	match style {
+		GridOverlay::Off => return None,
+		GridOverlay::Lines => {
+			let half = Vec2::splat(0.5 * LINE_WIDTH);
+			for row in 0..level.height() {
+				for col in 0..level.width() {
+					if !is_floor(row, col) {
+						continue;
+					}
+					// Each tile draws its top and left edges, plus its bottom
+					// and right edges where no floor tile will draw them.
+					let mut edges = vec![
+						(corner(row, col), corner(row, col + 1)),
+						(corner(row, col), corner(row + 1, col)),
+					];
+					if !is_floor(row + 1, col) {
+						edges.push((
+							corner(row + 1, col),
+							corner(row + 1, col + 1),
+						));
+					}
+					if !is_floor(row, col + 1) {
+						edges.push((
+							corner(row, col + 1),
+							corner(row + 1, col + 1),
+						));
+					}
+					rects.extend(edges.into_iter().map(|(from, to)| {
+						(from.min(to) - half, from.max(to) + half)
+					}));
+				}
+			}
+		}
+		GridOverlay::Dots => {
+			let half = Vec2::splat(0.5 * DOT_SIZE);
+			let mut corners = HashSet::new();
+			for row in 0..level.height() {
+				for col in 0..level.width() {
+					if is_floor(row, col) {
+						corners.extend([
+							(row, col),
+							(row, col + 1),
+							(row + 1, col),
+							(row + 1, col + 1),
+						]);
+					}
+				}
+			}
+			rects.extend(corners.into_iter().map(|(row, col)| {
+				let center = corner(row, col);
+				(center - half, center + half)
+			}));
+		}
+	}
+
+	let mut positions = Vec::new();
+	let mut indices = Vec::new();
+	for (min, max) in rects {
+		let base = positions.len() as u32;
+		positions.extend([
+			[min.x, min.y, GRID_HEIGHT],
+			[max.x, min.y, GRID_HEIGHT],
+			[max.x, max.y, GRID_HEIGHT],
+			[min.x, max.y, GRID_HEIGHT],
+		]);
+		indices.extend([base, base + 1, base + 2, base, base + 2, base + 3]);
+	}
+	let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+	Some(
+		Mesh::new(
+			PrimitiveTopology::TriangleList,
+			RenderAssetUsages::RENDER_WORLD,
+		)
+		.with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+		.with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+		.with_inserted_indices(Indices::U32(indices)),
+	)
+}
+
+/// Spawns the grid overlay for the current level in the style from the
+/// settings.
+pub fn spawn_grid_overlay(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	level: Res<Level>,
+	assets: Res<GameAssets>,
+	mut mesh_assets: ResMut<Assets<Mesh>>,
+) {
+	if let Some(mesh) = grid_mesh(&level, settings.grid_overlay) {
+		commands.spawn((
+			LevelEntity,
+			GridOverlayMesh,
+			HiddenInPhotoMode,
+			Mesh3d(mesh_assets.add(mesh)),
+			MeshMaterial3d(assets.material(MaterialKind::Grid)),
+			NotShadowCaster,
+			NotShadowReceiver,
+		));
+	}
+}
+
+/// Switches to the next grid overlay style when F8 is pressed.
+pub fn cycle_grid_overlay(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut settings: ResMut<Settings>,
+) {
+	if keys.just_pressed(KeyCode::F8) {
+		settings.grid_overlay = settings.grid_overlay.next();
+	}
+}
+
+/// Rebuilds the grid overlay when its style changes.
+pub fn respawn_grid_overlay(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	level: Res<Level>,
+	assets: Res<GameAssets>,
+	mesh_assets: ResMut<Assets<Mesh>>,
+	overlay_query: Query<Entity, With<GridOverlayMesh>>,
+	mut style: Local<GridOverlay>,
+) {
+	if *style == settings.grid_overlay {
+		return;
+	}
+	*style = settings.grid_overlay;
+	for entity in &overlay_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	spawn_grid_overlay(commands, settings, level, assets, mesh_assets);
+}
@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+	control::{
+		ActiveDevice, ControlRejected, GameButton, GamepadBindings,
+		KeyboardBindings,
+	},
+	level::Level,
+	photo_mode::HiddenInPhotoMode,
+	settings::Settings,
+	update::NextActor,
+};
+
+/// Control hints, as labels and the buttons they describe.
+const HINTS: [(&str, &[GameButton]); 6] = [
+	(
+		"Move",
+		&[
+			GameButton::Up,
+			GameButton::Left,
+			GameButton::Down,
+			GameButton::Right,
+		],
+	),
+	("Wait", &[GameButton::Wait]),
+	("Act", &[GameButton::Act]),
+	("Undo", &[GameButton::Undo]),
+	("Redo", &[GameButton::Redo]),
+	("Jump", &[GameButton::UndoAll, GameButton::RedoAll]),
+];
+
+/// The color of hints for buttons that can be used right now.
+const AVAILABLE_COLOR: Color = Color::WHITE;
+
+/// The color of hints for buttons that would do nothing right now.
+const UNAVAILABLE_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
+
+/// How long a rejection tooltip stays up, fading out as it goes.
+const TOOLTIP_DURATION: Duration = Duration::from_millis(1500);
+
+/// The color of rejection tooltips.
+const TOOLTIP_COLOR: Color = Color::srgb(1.0, 0.55, 0.45);
+
+/// Marker component for the row of control hints.
+#[derive(Component)]
+pub struct ControlHints;
+
+/// Component for the control hint describing the `idx`-th entry in
+/// [`HINTS`]. Hints track hovering, so that, for example, hovering the Undo
+/// hint can preview the undo.
+#[derive(Component)]
+pub struct ControlHint(usize);
+
+impl ControlHint {
+	/// The buttons this hint describes.
+	pub fn buttons(&self) -> &'static [GameButton] {
+		HINTS[self.0].1
+	}
+}
+
+/// Spawns the control hints along the bottom of the screen.
+pub fn spawn_control_hints(mut commands: Commands) {
+	let font = TextFont {
+		font_size: 14.0,
+		..default()
+	};
+	commands
+		.spawn((
+			ControlHints,
+			HiddenInPhotoMode,
+			Node {
+				position_type: PositionType::Absolute,
+				bottom: Val::Px(4.0),
+				left: Val::Px(4.0),
+				flex_direction: FlexDirection::Row,
+				..default()
+			},
+		))
+		.with_children(|hints| {
+			for idx in 0..HINTS.len() {
+				hints.spawn((
+					ControlHint(idx),
+					Text::default(),
+					font.clone(),
+					TextColor(AVAILABLE_COLOR),
+					Interaction::default(),
+				));
+			}
+		});
+}
+
+/// The device whose glyphs hints show: the active device, or a gamepad if the
+/// [display preset](crate::display::DisplayPreset::gamepad_glyphs) calls for
+/// it. With no gamepad connected, that shows the default gamepad bindings.
+fn glyph_device(
+	active_device: ActiveDevice,
+	gamepad_bindings: &GamepadBindings,
+	settings: &Settings,
+) -> ActiveDevice {
+	match active_device {
+		ActiveDevice::Keyboard
+			if settings.display_preset().gamepad_glyphs() =>
+		{
+			ActiveDevice::Gamepad(
+				gamepad_bindings
+					.any_connected()
+					.unwrap_or(Entity::PLACEHOLDER),
+			)
+		}
+		device => device,
+	}
+}
+
+/// Whether pressing any of `buttons` would do something in `level`.
+fn is_available(buttons: &[GameButton], level: &Level) -> bool {
+	buttons.iter().any(|button| match button {
+		GameButton::Undo | GameButton::UndoAll => level.can_undo(),
+		GameButton::Redo | GameButton::RedoAll => level.can_redo(),
+		_ => true,
+	})
+}
+
+/// Rewrites the control hints using the glyphs of the currently bound keys or
+/// gamepad buttons, whenever the bindings, active device, or settings change,
+/// and grays out hints for buttons that would do nothing, like Undo at the
+/// start of a level.
+pub fn update_control_hints(
+	keyboard_bindings: Res<KeyboardBindings>,
+	gamepad_bindings: Res<GamepadBindings>,
+	active_device: Res<ActiveDevice>,
+	settings: Res<Settings>,
+	level: Res<Level>,
+	mut hint_query: Query<(&ControlHint, &mut Text, &mut TextColor)>,
+) {
+	if !keyboard_bindings.is_changed()
+		&& !gamepad_bindings.is_changed()
+		&& !active_device.is_changed()
+		&& !settings.is_changed()
+		&& !level.is_changed()
+	{
+		return;
+	}
+	let glyph = |button: GameButton| {
+		match glyph_device(*active_device, &gamepad_bindings, &settings) {
+			ActiveDevice::Keyboard => keyboard_bindings.glyph(button),
+			ActiveDevice::Gamepad(gamepad) => {
+				gamepad_bindings.glyph(gamepad, button)
+			}
+		}
+		.unwrap_or_else(|| "-".to_string())
+	};
+	for (hint, mut text, mut color) in &mut hint_query {
+		let (label, buttons) = HINTS[hint.0];
+		let glyphs: Vec<String> = buttons.iter().copied().map(glyph).collect();
+		let separator = if hint.0 > 0 { "   " } else { "" };
+		**text = format!("{separator}{label}: {}", glyphs.join("/"));
+		color.0 = if is_available(buttons, &level) {
+			AVAILABLE_COLOR
+		} else {
+			UNAVAILABLE_COLOR
+		};
+	}
+}
+
+/// Marker component for the prompt shown when the next actor is stuck or a
+/// character has been lost.
+#[derive(Component)]
+pub struct StuckPrompt;
+
+/// Spawns the (initially hidden) stuck prompt above the control hints.
+pub fn spawn_stuck_prompt(mut commands: Commands) {
+	commands.spawn((
+		StuckPrompt,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 20.0,
+			..default()
+		},
+		TextColor(Color::srgb(1.0, 0.85, 0.3)),
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(24.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// The bindings, device and settings that decide which glyph shows a button.
+#[derive(SystemParam)]
+pub struct Glyphs<'w> {
+	keyboard_bindings: Res<'w, KeyboardBindings>,
+	gamepad_bindings: Res<'w, GamepadBindings>,
+	active_device: Res<'w, ActiveDevice>,
+	settings: Res<'w, Settings>,
+}
+
+impl Glyphs<'_> {
+	/// The glyph of the key or gamepad button bound to `button`, or "-" if
+	/// it's unbound.
+	fn glyph(&self, button: GameButton) -> String {
+		match glyph_device(
+			*self.active_device,
+			&self.gamepad_bindings,
+			&self.settings,
+		) {
+			ActiveDevice::Keyboard => self.keyboard_bindings.glyph(button),
+			ActiveDevice::Gamepad(gamepad) => {
+				self.gamepad_bindings.glyph(gamepad, button)
+			}
+		}
+		.unwrap_or_else(|| "-".to_string())
+	}
+}
+
+/// Shows the stuck prompt whenever a character has been lost, like to a pit,
+/// with the glyph for the Undo button, or otherwise whenever the next actor
+/// can't do anything but wait, with the glyph for the Wait button. Hides it
+/// otherwise.
+pub fn update_stuck_prompt(
+	level: Res<Level>,
+	glyphs: Glyphs,
+	mut next_actors: EventReader<NextActor>,
+	mut stuck: Local<bool>,
+	mut prompt_query: Query<&mut Text, With<StuckPrompt>>,
+) {
+	let next_actor = next_actors.read().last();
+	if let Some(actor) = next_actor {
+		*stuck = actor.stuck;
+	} else if !level.is_changed() {
+		return;
+	}
+	let prompt = if level.has_lost_character() {
+		format!(
+			"A character was lost! Undo ({}) to try again.",
+			glyphs.glyph(GameButton::Undo),
+		)
+	} else if *stuck {
+		format!(
+			"Boxed in! The only option is to wait ({}).",
+			glyphs.glyph(GameButton::Wait),
+		)
+	} else {
+		String::new()
+	};
+	for mut text in &mut prompt_query {
+		**text = prompt.clone();
+	}
+}
+
+/// Component for the tooltip explaining why the last button press was
+/// refused, with the time left until it's gone.
+#[derive(Component)]
+pub struct RejectionTooltip(Timer);
+
+/// Spawns the (initially hidden) rejection tooltip above the stuck prompt.
+pub fn spawn_rejection_tooltip(mut commands: Commands) {
+	let mut timer = Timer::new(TOOLTIP_DURATION, TimerMode::Once);
+	timer.tick(TOOLTIP_DURATION);
+	commands.spawn((
+		RejectionTooltip(timer),
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		TextColor(TOOLTIP_COLOR.with_alpha(0.0)),
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(50.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// Briefly shows why a button press was refused, like a direction press for a
+/// sliding character, so that the input never feels eaten. See
+/// [`ControlRejected`].
+pub fn show_rejection_tooltip(
+	time: Res<Time>,
+	mut rejected_events: EventReader<ControlRejected>,
+	mut tooltip_query: Query<(
+		&mut RejectionTooltip,
+		&mut Text,
+		&mut TextColor,
+	)>,
+) {
+	let rejection = rejected_events.read().last();
+	for (mut tooltip, mut text, mut color) in &mut tooltip_query {
+		if let Some(ControlRejected(reason)) = rejection {
+			tooltip.0.reset();
+			**text = reason.message().to_string();
+		} else if tooltip.0.finished() {
+			continue;
+		}
+		tooltip.0.tick(time.delta());
+		color.0 = TOOLTIP_COLOR.with_alpha(1.0 - tooltip.0.fraction().powi(3));
+		if tooltip.0.finished() {
+			text.clear();
+		}
+	}
+}
@@ -0,0 +1,60 @@
+use crate::{
+	control::Action,
+	level::{Cause, Change, Id, Level},
+};
+
+/// A turn in a level's log: what caused it, what it changed, and the level's
+/// [state hash](Level::state_hash) after it.
+#[derive(Clone, Copy)]
+pub struct TurnSummary<'a> {
+	/// The turn's index, counting from 0.
+	pub turn: usize,
+	pub cause: &'a Cause,
+	pub change: &'a Change,
+	pub state_hash: u64,
+	/// Whether the turn has been undone. Undone turns stay in the log, so they
+	/// can be redone, until a new turn replaces them.
+	pub undone: bool,
+}
+
+/// Every turn in `level`'s log, including undone turns that could still be
+/// redone.
+pub fn turns(level: &Level) -> impl Iterator<Item = TurnSummary<'_>> {
+	level.records().enumerate().map(
+		move |(turn, (cause, change, state_hash))| TurnSummary {
+			turn,
+			cause,
+			change,
+			state_hash,
+			undone: turn >= level.turn(),
+		},
+	)
+}
+
+/// The turns taken to reach `level`'s current turn.
+pub fn taken(level: &Level) -> impl Iterator<Item = TurnSummary<'_>> {
+	turns(level).take(level.turn())
+}
+
+/// Every action the object with the given `id` has taken, as (turn, action)
+/// pairs. Only characters act. Includes undone turns that could still be
+/// redone.
+pub fn object_actions(level: &Level, id: Id) -> Vec<(usize, Action)> {
+	// Each turn's actions are in the order of the characters present at the
+	// time, so replay the log to keep track of who was present.
+	let mut replayed = level.rewound();
+	let mut actions = Vec::new();
+	for summary in turns(level) {
+		if let Cause::Actions(turn_actions) = summary.cause {
+			let action =
+				replayed.characters_by_id().zip(turn_actions).find_map(
+					|((actor, _), action)| (*actor == id).then_some(*action),
+				);
+			if let Some(action) = action {
+				actions.push((summary.turn, action));
+			}
+		}
+		replayed.apply(summary.change);
+	}
+	actions
+}
@@ -0,0 +1,141 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+	control::Action,
+	history,
+	level::{Id, Level, Offset},
+	photo_mode::HiddenInPhotoMode,
+	sandbox::{self, Sandbox},
+	update::NextActor,
+};
+
+/// The most history entries the panel lists, counting back from the latest.
+const MAX_LISTED: usize = 20;
+
+/// Which character's action history is being inspected, if any.
+#[derive(Resource, Default)]
+pub struct Inspector {
+	character: Option<Id>,
+	/// The character up next, which I inspects.
+	next_actor: Option<Id>,
+}
+
+/// Marker component for the action history panel.
+#[derive(Component)]
+pub struct HistoryPanel;
+
+/// Spawns the (initially hidden) action history panel.
+pub fn spawn_history_panel(mut commands: Commands) {
+	commands.spawn((
+		HistoryPanel,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Px(40.0),
+			right: Val::Px(4.0),
+			padding: UiRect::all(Val::Px(8.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+		Visibility::Hidden,
+	));
+}
+
+/// Handles inspector input: clicking a character (outside sandbox mode) or
+/// pressing I inspects it, and clicking elsewhere, pressing I again, or
+/// pressing Escape stops inspecting.
+pub fn inspect_characters(
+	keys: Res<ButtonInput<KeyCode>>,
+	mouse_buttons: Res<ButtonInput<MouseButton>>,
+	level: Res<Level>,
+	sandbox: Res<Sandbox>,
+	mut inspector: ResMut<Inspector>,
+	mut next_actors: EventReader<NextActor>,
+	cursor: sandbox::Cursor,
+) {
+	if let Some(actor) = next_actors.read().last() {
+		inspector.next_actor = Some(actor.id);
+	}
+	if keys.just_pressed(KeyCode::KeyI) {
+		inspector.character = match inspector.character {
+			Some(_) => None,
+			None => inspector.next_actor,
+		};
+	}
+	if keys.just_pressed(KeyCode::Escape) {
+		inspector.character = None;
+	}
+	if mouse_buttons.just_pressed(MouseButton::Left) && !sandbox.is_enabled() {
+		inspector.character = cursor
+			.coords()
+			.and_then(|coords| level.character_id_at(coords));
+	}
+}
+
+/// Lists the inspected character's actions in the history panel, updating it
+/// as the level changes.
+pub fn update_history_panel(
+	level: Res<Level>,
+	inspector: Res<Inspector>,
+	mut panel_query: Query<(&mut Text, &mut Visibility), With<HistoryPanel>>,
+) {
+	if !level.is_changed() && !inspector.is_changed() {
+		return;
+	}
+	for (mut text, mut visibility) in &mut panel_query {
+		let Some(id) = inspector.character else {
+			*visibility = Visibility::Hidden;
+			continue;
+		};
+		*visibility = Visibility::Inherited;
+		**text = history_text(&level, id);
+	}
+}
+
+fn history_text(level: &Level, id: Id) -> String {
+	let history = history::object_actions(level, id);
+	let mut text = format!("Character #{} history\n", id.0);
+	if history.is_empty() {
+		write!(text, "\nNo actions yet").unwrap();
+	}
+	let skipped = history.len().saturating_sub(MAX_LISTED);
+	if skipped > 0 {
+		write!(text, "\n({skipped} earlier turns)").unwrap();
+	}
+	for (turn, action) in &history[skipped..] {
+		write!(text, "\nTurn {}: {}", turn + 1, describe_action(*action))
+			.unwrap();
+		if *turn >= level.turn() {
+			write!(text, " (undone)").unwrap();
+		}
+	}
+	text
+}
+
+fn describe_action(action: Action) -> String {
+	match action {
+		Action::Wait => "wait".to_string(),
+		Action::Push(offset) => format!("move {}", direction_name(offset)),
+		Action::Summon(offset) => {
+			format!("summon {}", direction_name(offset))
+		}
+		Action::Return => "return".to_string(),
+	}
+}
+
+fn direction_name(offset: Offset) -> &'static str {
+	match offset {
+		Offset::UP => "up",
+		Offset::LEFT => "left",
+		Offset::DOWN => "down",
+		Offset::RIGHT => "right",
+		_ => "?",
+	}
+}
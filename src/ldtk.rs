@@ -0,0 +1,455 @@
+use std::fmt::{self, Display};
+
+use bevy::{
+	asset::{io::Reader, AssetLoader, LoadContext},
+	prelude::*,
+	utils::HashMap,
+};
+
+use crate::{
+	level::CharacterColor,
+	level_asset::{LevelAsset, LevelAssetError},
+	tiled,
+};
+
+/// An error importing an LDtk project.
+#[derive(Debug)]
+pub enum LdtkError {
+	Io(std::io::Error),
+	/// The project doesn't follow the subset of the LDtk format the importer
+	/// understands.
+	Format(String),
+	/// The project imported but isn't a playable level.
+	Level(LevelAssetError),
+}
+
+impl Display for LdtkError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LdtkError::Io(err) => write!(f, "could not read project: {err}"),
+			LdtkError::Format(msg) => write!(f, "invalid project: {msg}"),
+			LdtkError::Level(err) => write!(f, "{err}"),
+		}
+	}
+}
+
+impl std::error::Error for LdtkError {}
+
+impl From<std::io::Error> for LdtkError {
+	fn from(err: std::io::Error) -> Self {
+		LdtkError::Io(err)
+	}
+}
+
+fn format_error(msg: impl Into<String>) -> LdtkError {
+	LdtkError::Format(msg.into())
+}
+
+/// Converts the first level of an [LDtk](https://ldtk.io/) project into a
+/// [`make_level`](crate::level::make_level) map.
+///
+/// The importer understands projects with their levels embedded in the
+/// project file, where every layer has the same grid size:
+///
+/// - IntGrid layers are mapped to tiles by the identifiers of their values,
+///   using the same names as the [Tiled importer](tiled::import_map): `wall`,
+///   `stairs`, `ice`, and so on. Values without an identifier are floor, as
+///   are cells no IntGrid layer sets, so only walls and other special tiles
+///   need painting. Upper layers draw over lower ones. Auto-layers and tile
+///   layers are only decoration, so they're ignored.
+/// - Entities are mapped by their identifiers, ignoring case: `Character`, or
+///   the name of a prop in snake case, like `Wooden_crate`. Characters get
+///   colors in the order they appear, from the lowest layer up. Entities are
+///   placed on the grid cell LDtk reports for them.
+pub fn import_project(json: &str) -> Result<String, LdtkError> {
+	let project = Parser::new(json).parse_document()?;
+	let missing = |what: &str| format_error(format!("missing {what}"));
+
+	// Tile classes by layer definition and IntGrid value.
+	let mut classes: HashMap<(i64, i64), String> = HashMap::new();
+	let layer_defs = project
+		.get("defs")
+		.and_then(|defs| defs.get("layers"))
+		.and_then(Json::as_array)
+		.ok_or_else(|| missing("layer definitions"))?;
+	for layer_def in layer_defs {
+		let uid = layer_def.number("uid")? as i64;
+		let values = layer_def.get("intGridValues").and_then(Json::as_array);
+		for value in values.unwrap_or_default() {
+			if let Some(identifier) =
+				value.get("identifier").and_then(Json::as_str)
+			{
+				classes.insert(
+					(uid, value.number("value")? as i64),
+					identifier.to_lowercase(),
+				);
+			}
+		}
+	}
+
+	let level = project
+		.get("levels")
+		.and_then(Json::as_array)
+		.and_then(<[Json]>::first)
+		.ok_or_else(|| format_error("project has no levels"))?;
+	let Some(layers) = level.get("layerInstances").and_then(Json::as_array)
+	else {
+		return Err(format_error("external level files are unsupported"));
+	};
+	let mut grid_size = None;
+	for layer in layers {
+		let size = layer.number("__gridSize")?;
+		if grid_size.is_some_and(|grid_size| grid_size != size) {
+			return Err(format_error("layers have different grid sizes"));
+		}
+		grid_size = Some(size);
+	}
+	let Some(grid_size) = grid_size.filter(|size| *size > 0.0) else {
+		return Err(format_error("level has no layers"));
+	};
+	let width = (level.number("pxWid")? / grid_size) as usize;
+	let height = (level.number("pxHei")? / grid_size) as usize;
+	if width == 0 || height == 0 {
+		return Err(format_error("level is empty"));
+	}
+
+	let mut tiles = vec![b'.'; width * height];
+	let mut objects: HashMap<(usize, usize), u8> = HashMap::new();
+	let mut character_count = 0;
+	// LDtk lists layers from the top down.
+	for layer in layers.iter().rev() {
+		match layer.get("__type").and_then(Json::as_str) {
+			Some("IntGrid") => {
+				let uid = layer.number("layerDefUid")? as i64;
+				let cells = layer
+					.get("intGridCsv")
+					.and_then(Json::as_array)
+					.ok_or_else(|| missing("IntGrid cells"))?;
+				for (idx, cell) in cells.iter().enumerate() {
+					let value = cell
+						.as_number()
+						.ok_or_else(|| format_error("invalid IntGrid cell"))?;
+					let value = value as i64;
+					if value == 0 {
+						continue;
+					}
+					let Some(tile) = tiles.get_mut(idx) else {
+						return Err(format_error("IntGrid layer is too large"));
+					};
+					let class = classes
+						.get(&(uid, value))
+						.map_or("floor", String::as_str);
+					*tile = tiled::tile_symbol(class).ok_or_else(|| {
+						format_error(format!("unknown tile class \"{class}\""))
+					})?;
+				}
+			}
+			Some("Entities") => {
+				let entities = layer
+					.get("entityInstances")
+					.and_then(Json::as_array)
+					.ok_or_else(|| missing("entities"))?;
+				for entity in entities {
+					let identifier = entity
+						.get("__identifier")
+						.and_then(Json::as_str)
+						.ok_or_else(|| missing("entity identifier"))?
+						.to_lowercase();
+					let symbol = if identifier == "character" {
+						if character_count == CharacterColor::COUNT {
+							return Err(format_error("too many characters"));
+						}
+						character_count += 1;
+						b'0' + character_count as u8 - 1
+					} else {
+						tiled::prop_symbol(&identifier).ok_or_else(|| {
+							format_error(format!(
+								"unknown entity \"{identifier}\""
+							))
+						})?
+					};
+					let grid = entity
+						.get("__grid")
+						.and_then(Json::as_array)
+						.ok_or_else(|| missing("entity position"))?;
+					let [Some(col), Some(row)] = [0, 1]
+						.map(|idx| grid.get(idx).and_then(Json::as_number))
+					else {
+						return Err(format_error("invalid entity position"));
+					};
+					if col < 0.0
+						|| row < 0.0 || col as usize >= width
+						|| row as usize >= height
+					{
+						return Err(format_error("entity outside the level"));
+					}
+					let coords = (row as usize, col as usize);
+					if objects.insert(coords, symbol).is_some() {
+						return Err(format_error(format!(
+							"more than one entity at row {}, column {}",
+							coords.0, coords.1
+						)));
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	let mut map = String::new();
+	for row in 0..height {
+		for col in 0..width {
+			map.push(tiles[row * width + col] as char);
+			map.push(objects.get(&(row, col)).copied().unwrap_or(b' ') as char);
+		}
+		map.push('\n');
+	}
+	Ok(map)
+}
+
+/// A JSON value. Objects keep their keys in order.
+enum Json {
+	/// `true`, `false`, or `null`, which LDtk projects only use for settings
+	/// the importer doesn't need.
+	Literal,
+	Number(f64),
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(String, Json)>),
+}
+
+impl Json {
+	/// The value of `key`, if this is an object with that key.
+	fn get(&self, key: &str) -> Option<&Json> {
+		match self {
+			Json::Object(entries) => entries
+				.iter()
+				.find(|(entry_key, _)| entry_key == key)
+				.map(|(_, value)| value),
+			_ => None,
+		}
+	}
+
+	fn as_str(&self) -> Option<&str> {
+		match self {
+			Json::String(string) => Some(string),
+			_ => None,
+		}
+	}
+
+	fn as_number(&self) -> Option<f64> {
+		match self {
+			Json::Number(number) => Some(*number),
+			_ => None,
+		}
+	}
+
+	fn as_array(&self) -> Option<&[Json]> {
+		match self {
+			Json::Array(values) => Some(values),
+			_ => None,
+		}
+	}
+
+	/// The number at `key`, or an error if there isn't one.
+	fn number(&self, key: &str) -> Result<f64, LdtkError> {
+		self.get(key)
+			.and_then(Json::as_number)
+			.ok_or_else(|| format_error(format!("missing or invalid {key}")))
+	}
+}
+
+/// A parser for just enough JSON to read LDtk projects.
+struct Parser<'a> {
+	text: &'a str,
+	pos: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn new(text: &'a str) -> Parser<'a> {
+		Parser { text, pos: 0 }
+	}
+
+	/// Parses the whole text as a single value.
+	fn parse_document(&mut self) -> Result<Json, LdtkError> {
+		let value = self.parse_value()?;
+		self.skip_whitespace();
+		if self.pos < self.text.len() {
+			return Err(self.error("trailing characters"));
+		}
+		Ok(value)
+	}
+
+	fn error(&self, msg: &str) -> LdtkError {
+		format_error(format!("{msg} at byte {} of the JSON", self.pos))
+	}
+
+	fn skip_whitespace(&mut self) {
+		let rest = &self.text[self.pos..];
+		let len = rest.len() - rest.trim_start().len();
+		self.pos += len;
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.text[self.pos..].chars().next()
+	}
+
+	/// Consumes `token` if the text continues with it.
+	fn eat(&mut self, token: &str) -> bool {
+		self.skip_whitespace();
+		let found = self.text[self.pos..].starts_with(token);
+		if found {
+			self.pos += token.len();
+		}
+		found
+	}
+
+	fn expect(&mut self, token: &str) -> Result<(), LdtkError> {
+		if self.eat(token) {
+			Ok(())
+		} else {
+			Err(self.error(&format!("expected \"{token}\"")))
+		}
+	}
+
+	fn parse_value(&mut self) -> Result<Json, LdtkError> {
+		self.skip_whitespace();
+		match self.peek() {
+			Some('{') => {
+				self.pos += 1;
+				let mut entries = Vec::new();
+				if !self.eat("}") {
+					loop {
+						self.skip_whitespace();
+						let key = self.parse_string()?;
+						self.expect(":")?;
+						entries.push((key, self.parse_value()?));
+						if self.eat("}") {
+							break;
+						}
+						self.expect(",")?;
+					}
+				}
+				Ok(Json::Object(entries))
+			}
+			Some('[') => {
+				self.pos += 1;
+				let mut values = Vec::new();
+				if !self.eat("]") {
+					loop {
+						values.push(self.parse_value()?);
+						if self.eat("]") {
+							break;
+						}
+						self.expect(",")?;
+					}
+				}
+				Ok(Json::Array(values))
+			}
+			Some('"') => Ok(Json::String(self.parse_string()?)),
+			Some('-' | '0'..='9') => {
+				let text = self.text;
+				let rest = &text[self.pos..];
+				let is_numeric = |c: char| {
+					matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9')
+				};
+				let len = rest.find(|c| !is_numeric(c)).unwrap_or(rest.len());
+				let number = rest[..len]
+					.parse()
+					.map_err(|_| self.error("invalid number"))?;
+				self.pos += len;
+				Ok(Json::Number(number))
+			}
+			_ if ["true", "false", "null"]
+				.iter()
+				.any(|token| self.eat(token)) =>
+			{
+				Ok(Json::Literal)
+			}
+			_ => Err(self.error("expected a value")),
+		}
+	}
+
+	fn parse_string(&mut self) -> Result<String, LdtkError> {
+		if self.peek() != Some('"') {
+			return Err(self.error("expected a string"));
+		}
+		self.pos += 1;
+		let mut string = String::new();
+		let text = self.text;
+		let mut chars = text[self.pos..].char_indices();
+		while let Some((idx, c)) = chars.next() {
+			match c {
+				'"' => {
+					self.pos += idx + 1;
+					return Ok(string);
+				}
+				'\\' => {
+					let escaped = match chars.next().map(|(_, c)| c) {
+						Some('b') => '\u{8}',
+						Some('f') => '\u{c}',
+						Some('n') => '\n',
+						Some('r') => '\r',
+						Some('t') => '\t',
+						Some('u') => {
+							let hex: String = chars
+								.by_ref()
+								.take(4)
+								.map(|(_, c)| c)
+								.collect();
+							// Surrogate pairs aren't combined, since LDtk only
+							// escapes characters like this for names.
+							u32::from_str_radix(&hex, 16)
+								.ok()
+								.and_then(char::from_u32)
+								.unwrap_or(char::REPLACEMENT_CHARACTER)
+						}
+						Some(c) => c,
+						None => break,
+					};
+					string.push(escaped);
+				}
+				c => string.push(c),
+			}
+		}
+		Err(self.error("unterminated string"))
+	}
+}
+
+/// Loads the first level of LDtk projects as [`LevelAsset`]s, named after
+/// their files.
+#[derive(Default)]
+pub struct LdtkLevelLoader;
+
+impl AssetLoader for LdtkLevelLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = LdtkError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		load_context: &mut LoadContext<'_>,
+	) -> Result<LevelAsset, LdtkError> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let json = String::from_utf8(bytes)
+			.map_err(|_| format_error("project is not UTF-8"))?;
+		let name = load_context
+			.path()
+			.file_stem()
+			.map_or_else(String::new, |stem| stem.to_string_lossy().into());
+		LevelAsset {
+			name,
+			map: import_project(&json)?,
+			..default()
+		}
+		.validated()
+		.map_err(LdtkError::Level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["ldtk"]
+	}
+}
@@ -1,7 +1,8 @@
 use std::{
 	cmp::Ordering,
 	collections::BTreeSet,
-	fmt::{Debug, Write},
+	fmt::{self, Debug, Display, Write},
+	hash::Hash,
 	ops::{Add, AddAssign, Mul, Neg},
 	sync::Arc,
 };
@@ -11,7 +12,13 @@ use bevy::{
 	utils::{HashMap, HashSet},
 };
 
-use crate::control::Action;
+use crate::{
+	audio::Soundtrack,
+	control::Action,
+	lighting::Lighting,
+	locale::{self, LevelText, Translations},
+	tile_behavior::TurnPhase,
+};
 
 /// Marker component for entities that should be despawned when the level is
 /// despawned. Note that level entities are despawned recursively, so it's best
@@ -57,6 +64,10 @@ impl Offset {
 	pub const LEFT: Offset = Offset::new(0, -1);
 	pub const RIGHT: Offset = Offset::new(0, 1);
 
+	/// The four unit offsets.
+	pub const DIRECTIONS: [Offset; 4] =
+		[Offset::UP, Offset::LEFT, Offset::DOWN, Offset::RIGHT];
+
 	pub const fn new(row: i32, col: i32) -> Offset {
 		Offset { row, col }
 	}
@@ -65,6 +76,21 @@ impl Offset {
 	pub fn angle(&self) -> f32 {
 		(-self.row as f32).atan2(self.col as f32)
 	}
+
+	/// The unit offset nearest to `angle` relative to [`Offset::RIGHT`].
+	pub fn from_angle(angle: f32) -> Offset {
+		Offset::new(-angle.sin().round() as i32, angle.cos().round() as i32)
+	}
+
+	/// `self` turned a quarter turn, clockwise as seen from above if
+	/// `clockwise`, otherwise counterclockwise.
+	pub fn quarter_turn(self, clockwise: bool) -> Offset {
+		if clockwise {
+			Offset::new(self.col, -self.row)
+		} else {
+			Offset::new(-self.col, self.row)
+		}
+	}
 }
 
 impl Ord for Offset {
@@ -137,10 +163,178 @@ pub enum Tile {
 		portal_color: Option<CharacterColor>,
 	},
 	Wall,
+	/// The level's exit.
+	Stairs,
+	/// Empty space outside the level, for giving levels irregular outlines.
+	/// Nothing can enter it, and summons can't be cast across it.
+	Void,
+	/// Slippery floor. Characters that step onto ice slide until they step
+	/// off it.
+	Ice,
+	/// A hole in the floor, destroying anything that moves into it. A prop
+	/// that falls in fills the pit, leaving floor.
+	Pit,
+	/// Part of a [platform](Platform)'s track. Nothing can enter the track
+	/// except where the platform is.
+	Track {
+		platform: bool,
+	},
+	/// Floor that turns whatever stands on it a quarter turn at the end of
+	/// each turn.
+	Turntable {
+		clockwise: bool,
+	},
+	/// Floor that gives way after use, collapsing into a [pit](Tile::Pit)
+	/// once whatever stepped onto it leaves it empty.
+	Cracked {
+		stepped_on: bool,
+	},
+	/// Open water, which characters can't enter. Props pushed in are lost,
+	/// except that one that [floats](ObjectDescriptor::floats) bridges the
+	/// water, which can then be walked over like floor.
+	Water {
+		bridged: bool,
+	},
+	/// Molten rock, destroying anything that moves into it. A prop that isn't
+	/// [flammable](ObjectDescriptor::flammable) bridges the lava as it cools
+	/// around it, leaving a crust that can be walked over like floor, while
+	/// flammable props burn up and characters are lost.
+	Lava {
+		bridged: bool,
+	},
+}
+
+impl Tile {
+	/// Whether objects are stopped from moving onto this tile.
+	pub fn blocks_movement(self) -> bool {
+		matches!(
+			self,
+			Tile::Wall | Tile::Void | Tile::Track { platform: false }
+		)
+	}
+
+	/// Whether `object` is stopped from moving onto this tile. Characters
+	/// can't enter open water, though props can be pushed into it.
+	fn blocks(self, object: &Object) -> bool {
+		self.blocks_movement()
+			|| (object.is_character() && self == Tile::Water { bridged: false })
+	}
+
+	/// This tile's symbol in [level text](Level::to_text).
+	fn symbol(self) -> u8 {
+		match self {
+			Tile::Floor { portal_color: None } => b'.',
+			Tile::Floor {
+				portal_color: Some(color),
+			} => PORTAL_SYMBOL + color.idx() as u8,
+			Tile::Wall => b'#',
+			Tile::Stairs => b'>',
+			Tile::Void => b'_',
+			Tile::Ice => b'~',
+			Tile::Pit => b'*',
+			Tile::Track { platform: false } => b'=',
+			Tile::Track { platform: true } => b'+',
+			Tile::Turntable { clockwise: true } => b')',
+			Tile::Turntable { clockwise: false } => b'(',
+			Tile::Cracked { stepped_on: false } => b'%',
+			Tile::Cracked { stepped_on: true } => b'&',
+			Tile::Water { bridged: false } => b'w',
+			Tile::Water { bridged: true } => b'W',
+			Tile::Lava { bridged: false } => b'l',
+			Tile::Lava { bridged: true } => b'L',
+		}
+	}
+
+	/// The tile with the given symbol in [level text](Level::to_text).
+	/// Unknown symbols are floors.
+	fn from_symbol(symbol: u8) -> Tile {
+		match symbol {
+			b'#' => Tile::Wall,
+			b'>' => Tile::Stairs,
+			b'_' => Tile::Void,
+			b'~' => Tile::Ice,
+			b'*' => Tile::Pit,
+			b'=' => Tile::Track { platform: false },
+			b'+' => Tile::Track { platform: true },
+			b')' => Tile::Turntable { clockwise: true },
+			b'(' => Tile::Turntable { clockwise: false },
+			b'%' => Tile::Cracked { stepped_on: false },
+			b'&' => Tile::Cracked { stepped_on: true },
+			b'w' => Tile::Water { bridged: false },
+			b'W' => Tile::Water { bridged: true },
+			b'l' => Tile::Lava { bridged: false },
+			b'L' => Tile::Lava { bridged: true },
+			b'a'..=b'h' => Tile::Floor {
+				portal_color: Some(CharacterColor::from(
+					symbol - PORTAL_SYMBOL,
+				)),
+			},
+			_ => Tile::Floor { portal_color: None },
+		}
+	}
+}
+
+/// The symbol of a floor with a portal of the first color. Portals of later
+/// colors follow in alphabetical order.
+const PORTAL_SYMBOL: u8 = b'a';
+
+/// A platform that moves one step along its track each turn, carrying
+/// whatever stands on it. The track's tiles are [`Tile::Track`]s, with the
+/// platform's tile marked.
+#[derive(Clone)]
+pub struct Platform {
+	/// The track's tiles, in order.
+	track: Arc<[Coords]>,
+	/// Whether the track is a loop, which the platform circles. Otherwise the
+	/// platform shuttles between the track's ends.
+	looped: bool,
+	position: PlatformPosition,
+}
+
+/// Where a [`Platform`] is on its track.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct PlatformPosition {
+	/// The index of the platform's tile in its track.
+	idx: usize,
+	/// Whether the platform is heading toward the start of its track.
+	backward: bool,
+}
+
+impl Platform {
+	/// The coordinates of the platform's tile.
+	pub fn coords(&self) -> Coords {
+		self.track[self.position.idx]
+	}
+
+	/// Where the platform will be after its next step. Platforms on
+	/// single-tile tracks stay put.
+	fn next_position(&self) -> PlatformPosition {
+		let PlatformPosition { idx, backward } = self.position;
+		let last = self.track.len() - 1;
+		if last == 0 {
+			return self.position;
+		}
+		if self.looped {
+			return PlatformPosition {
+				idx: (idx + 1) % self.track.len(),
+				backward: false,
+			};
+		}
+		// Turn around at the ends of the track.
+		let backward = if backward { idx != 0 } else { idx == last };
+		PlatformPosition {
+			idx: if backward { idx - 1 } else { idx + 1 },
+			backward,
+		}
+	}
 }
 
+/// The symbol of a sliding character of the first color. Sliding characters
+/// of later colors follow in alphabetical order.
+const SLIDING_SYMBOL: u8 = b'a';
+
 /// An object identifier. Enables correlating object animations across frames.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct Id(pub u32);
 
 /// Distinguishes between characters and links them to their return portals.
@@ -212,12 +406,17 @@ impl Character {
 		!self.sliding
 	}
 
+	/// Sliding characters can't stop mid-slide, so they can't wait.
+	pub fn can_wait(&self) -> bool {
+		!self.sliding
+	}
+
 	pub fn can_summon(&self) -> bool {
-		self.portal_coords.is_none()
+		!self.sliding && self.portal_coords.is_none()
 	}
 
 	pub fn can_return(&self) -> bool {
-		self.portal_coords.is_some()
+		!self.sliding && self.portal_coords.is_some()
 	}
 }
 
@@ -225,23 +424,127 @@ impl Character {
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Object {
 	Character(Character),
-	WoodenCrate,
-	SteelCrate,
-	StoneBlock,
+	/// A non-character object, defined by its capabilities.
+	Prop(&'static ObjectDescriptor),
 }
 
 impl Object {
+	pub const WOODEN_CRATE: Object = Object::Prop(&ObjectDescriptor {
+		name: "Wooden crate",
+		symbol: b'X',
+		model: "models/wooden-crate.glb",
+		pushable: true,
+		weight: 1,
+		is_crate: true,
+		flammable: true,
+		floats: true,
+		fragile: true,
+	});
+
+	pub const STEEL_CRATE: Object = Object::Prop(&ObjectDescriptor {
+		name: "Steel crate",
+		symbol: b'Y',
+		model: "models/steel-crate.glb",
+		pushable: true,
+		weight: 2,
+		is_crate: true,
+		flammable: false,
+		floats: false,
+		fragile: false,
+	});
+
+	pub const STONE_BLOCK: Object = Object::Prop(&ObjectDescriptor {
+		name: "Stone block",
+		symbol: b'Z',
+		model: "models/sandstone-block.glb",
+		pushable: true,
+		weight: 3,
+		is_crate: false,
+		flammable: false,
+		floats: false,
+		fragile: false,
+	});
+
+	/// Every kind of prop.
+	pub const PROPS: [Object; 3] = [
+		Object::WOODEN_CRATE,
+		Object::STEEL_CRATE,
+		Object::STONE_BLOCK,
+	];
+
+	/// The prop with the given ASCII map `symbol`, if there is one.
+	pub fn prop_with_symbol(symbol: u8) -> Option<Object> {
+		Object::PROPS.into_iter().find(|prop| {
+			matches!(prop, Object::Prop(descriptor) if descriptor.symbol == symbol)
+		})
+	}
+
+	pub fn is_character(&self) -> bool {
+		matches!(self, Object::Character(_))
+	}
+
+	fn pushable(&self) -> bool {
+		match self {
+			Object::Character(_) => true,
+			Object::Prop(descriptor) => descriptor.pushable,
+		}
+	}
+
 	fn weight(&self) -> i32 {
 		match self {
-			Object::Character { .. } => 1,
-			Object::WoodenCrate => 1,
-			Object::SteelCrate => 2,
-			Object::StoneBlock => 3,
+			Object::Character(_) => 1,
+			Object::Prop(descriptor) => descriptor.weight,
+		}
+	}
+}
+
+/// The capabilities of a kind of prop. New kinds of props only need a new
+/// descriptor (and a model).
+#[derive(PartialEq, Eq, Debug)]
+pub struct ObjectDescriptor {
+	pub name: &'static str,
+	/// The object's symbol in ASCII level maps.
+	pub symbol: u8,
+	/// The path to the object's model in the assets directory.
+	pub model: &'static str,
+	/// Whether the object can be pushed at all.
+	pub pushable: bool,
+	/// The push strength needed to move the object.
+	pub weight: i32,
+	/// Whether the object counts as a crate for rules that affect crates.
+	pub is_crate: bool,
+	/// Whether the object burns up in [lava](Tile::Lava) rather than bridging
+	/// it.
+	pub flammable: bool,
+	pub floats: bool,
+	/// Whether heavier objects crush the object under the crushing rule. See
+	/// [`Rules::crushing`].
+	pub fragile: bool,
+}
+
+impl ObjectDescriptor {
+	/// A short summary of the object's capabilities, like "weight 1, floats".
+	pub fn summary(&self) -> String {
+		let mut traits = vec![if self.pushable {
+			format!("weight {}", self.weight)
+		} else {
+			"immovable".to_string()
+		}];
+		if self.flammable {
+			traits.push("flammable".to_string());
+		}
+		if self.floats {
+			traits.push("floats".to_string());
 		}
+		if self.fragile {
+			traits.push("fragile".to_string());
+		}
+		traits.join(", ")
 	}
 }
 
 /// An [`Object`] along with data relating that object to a [`Level`].
+#[derive(Clone, Copy)]
 pub struct LevelObject {
 	pub id: Id,
 	pub object: Object,
@@ -270,15 +573,98 @@ pub struct LevelCharacter {
 	pub angle: f32,
 }
 
+/// Optional gameplay rules that can vary from level to level.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Rules {
+	/// Whether two adjacent characters pushing into each other swap places
+	/// instead of blocking each other.
+	pub swap_places: bool,
+	/// Whether moving backward in time is disallowed.
+	pub no_undo: bool,
+	/// Whether crates weigh twice as much as usual.
+	pub heavy_crates: bool,
+	/// Whether summons land on the adjacent tile instead of the farthest one.
+	pub adjacent_summons: bool,
+	/// Whether a turn with a summoning can't be undone until the summoner
+	/// returns. See [`Level::undo_floor`].
+	pub summons_lock_history: bool,
+	/// Whether an object pushed into a lighter, fragile one crushes it rather
+	/// than pushing it along.
+	pub crushing: bool,
+	/// The most portals that can be open at once, if limited. Summons that
+	/// would open more fizzle, lowest character ID first to summon.
+	pub max_open_portals: Option<usize>,
+}
+
+/// Descriptive information about a level.
+#[derive(Clone, Default)]
+pub struct LevelMeta {
+	/// The name to display for the level.
+	pub name: String,
+	pub author: Option<String>,
+	pub description: Option<String>,
+	/// The number of turns a good solution takes, if known.
+	pub par: Option<usize>,
+	/// The name and description in other languages.
+	pub translations: Translations<LevelText>,
+}
+
+impl LevelMeta {
+	/// The highest possible [score](LevelMeta::score).
+	pub const MAX_SCORE: u32 = 1000;
+
+	/// The score for completing the level in `turns` turns: full marks at or
+	/// under par, falling off in proportion to the turns over par. Levels
+	/// without a par always get full marks.
+	pub fn score(&self, turns: usize) -> u32 {
+		match self.par {
+			Some(par) if turns > par => {
+				(LevelMeta::MAX_SCORE as usize * par / turns) as u32
+			}
+			_ => LevelMeta::MAX_SCORE,
+		}
+	}
+
+	/// The level's name in `language`, if it's been translated, or else its
+	/// untranslated name.
+	pub fn localized_name(&self, language: &str) -> &str {
+		locale::resolve(&self.translations, language)
+			.and_then(|text| text.name.as_deref())
+			.unwrap_or(&self.name)
+	}
+
+	/// The level's description in `language`, if it's been translated, or
+	/// else its untranslated description.
+	pub fn localized_description(&self, language: &str) -> Option<&str> {
+		locale::resolve(&self.translations, language)
+			.and_then(|text| text.description.as_deref())
+			.or(self.description.as_deref())
+	}
+}
+
 /// The complete state of a level at a single point in time.
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct Level {
 	width: usize,
 	height: usize,
+	rules: Rules,
+	lighting: Lighting,
+	/// Music and ambience for this level, overriding its lighting's defaults.
+	soundtrack: Soundtrack,
+	/// A shortest known solution, as each turn's actions in character ID
+	/// order.
+	solution: Option<Arc<[Vec<Action>]>>,
+	meta: Arc<LevelMeta>,
 	tiles: Vec<Tile>,
+	/// The platforms moving along the level's tracks, in the row-major order
+	/// of their tracks' first tiles.
+	platforms: Vec<Platform>,
 	objects_by_id: HashMap<Id, LevelObject>,
 	object_ids_by_coords: HashMap<Coords, Id>,
 	character_ids: BTreeSet<Id>,
+	/// Props hidden inside other objects, keyed by the IDs of the objects
+	/// hiding them. See [`Level::with_contents`].
+	contents: Arc<HashMap<Id, Object>>,
 	next_object_id: Id,
 	/// History of the level's state, for seeking backward and forward in time.
 	history: Vec<BiChange>,
@@ -296,6 +682,135 @@ impl Level {
 		self.height
 	}
 
+	/// The gameplay rules in effect.
+	pub fn rules(&self) -> Rules {
+		self.rules
+	}
+
+	/// Replaces the gameplay rules in effect.
+	pub fn set_rules(&mut self, rules: Rules) {
+		self.rules = rules;
+	}
+
+	/// This level with `rules` in effect.
+	pub fn with_rules(self, rules: Rules) -> Level {
+		Level { rules, ..self }
+	}
+
+	/// How the level is lit.
+	pub fn lighting(&self) -> &Lighting {
+		&self.lighting
+	}
+
+	/// This level with its lighting set to `lighting`.
+	pub fn with_lighting(self, lighting: Lighting) -> Level {
+		Level { lighting, ..self }
+	}
+
+	/// The level's own music and ambience, if any.
+	pub fn soundtrack(&self) -> Soundtrack {
+		self.soundtrack
+	}
+
+	/// This level with its music and ambience set to `soundtrack`.
+	pub fn with_soundtrack(self, soundtrack: Soundtrack) -> Level {
+		Level { soundtrack, ..self }
+	}
+
+	/// A shortest known solution to the level, if any, as each turn's actions
+	/// in character ID order.
+	pub fn solution(&self) -> Option<&[Vec<Action>]> {
+		self.solution.as_deref()
+	}
+
+	/// The level's name, author, and so on.
+	pub fn meta(&self) -> &LevelMeta {
+		&self.meta
+	}
+
+	/// This level with its descriptive information set to `meta`.
+	pub fn with_meta(self, meta: LevelMeta) -> Level {
+		Level {
+			meta: Arc::new(meta),
+			..self
+		}
+	}
+
+	/// This level with each of `contents` hidden inside the object at its
+	/// coordinates. When an object with contents is crushed, its contents
+	/// spill out onto the tile past it, if that tile is open, or are lost
+	/// with it otherwise. Contents for empty coordinates are ignored.
+	pub fn with_contents(
+		self,
+		contents: impl IntoIterator<Item = (Coords, Object)>,
+	) -> Level {
+		let contents = contents
+			.into_iter()
+			.filter_map(|(coords, object)| {
+				Some((*self.object_ids_by_coords.get(&coords)?, object))
+			})
+			.collect();
+		Level {
+			contents: Arc::new(contents),
+			..self
+		}
+	}
+
+	/// This level with each of `characters` added at its coordinates, facing
+	/// its angle. Characters for occupied or out-of-bounds coordinates are
+	/// ignored.
+	///
+	/// Object IDs are reassigned so that characters still act in color order,
+	/// so this is meant for levels fresh from a map, before anything else
+	/// refers to their objects by ID, like [contents](Level::with_contents).
+	pub fn with_characters(
+		mut self,
+		characters: impl IntoIterator<Item = (Coords, Character, f32)>,
+	) -> Level {
+		let mut level_objects: Vec<LevelObject> =
+			self.objects_by_id.values().copied().collect();
+		level_objects.sort_by_key(|level_object| level_object.id);
+		for (coords, character, angle) in characters {
+			if self.in_bounds(coords)
+				&& !level_objects.iter().any(|lo| lo.coords == coords)
+			{
+				level_objects.push(LevelObject {
+					id: Id(u32::MAX),
+					object: Object::Character(character),
+					coords,
+					angle,
+				});
+			}
+		}
+		// As in make_level, characters come first, in color order.
+		level_objects.sort_by_key(|level_object| match level_object.object {
+			Object::Character(character) => (false, Some(character.color)),
+			Object::Prop(_) => (true, None),
+		});
+		self.objects_by_id.clear();
+		self.object_ids_by_coords.clear();
+		self.character_ids.clear();
+		self.next_object_id = Id(0);
+		for mut level_object in level_objects {
+			level_object.id = self.new_object_id();
+			self.spawn(level_object);
+		}
+		self
+	}
+
+	/// The prop hidden inside the object with the given `id`, if any.
+	pub fn contents(&self, id: &Id) -> Option<Object> {
+		self.contents.get(id).copied()
+	}
+
+	/// This level with its shortest known solution set to `solution`.
+	pub fn with_solution(self, solution: Vec<Vec<Action>>) -> Level {
+		Level {
+			solution: Some(solution.into()),
+			..self
+		}
+	}
+
 	/// The index of the tile at `coords`.
 	fn tile_idx(&self, coords: Coords) -> usize {
 		coords.row as usize * self.width + coords.col as usize
@@ -368,27 +883,464 @@ impl Level {
 			.map(|id| (id, self.character_by_id(id)))
 	}
 
+	/// The object at `coords` with its ID and position, if any.
+	pub fn level_object_at(&self, coords: Coords) -> Option<&LevelObject> {
+		self.object_ids_by_coords
+			.get(&coords)
+			.map(|id| &self.objects_by_id[id])
+	}
+
+	/// The ID of the character at `coords`, if any.
+	pub fn character_id_at(&self, coords: Coords) -> Option<Id> {
+		self.object_ids_by_coords
+			.get(&coords)
+			.copied()
+			.filter(|id| self.character_ids.contains(id))
+	}
+
+	/// Whether there's a character with the given `id` in the level.
+	pub fn has_character(&self, id: &Id) -> bool {
+		self.character_ids.contains(id)
+	}
+
+	/// Whether the character with the given `id` was summoned from the future
+	/// during the turns taken so far, rather than being present from the start.
+	pub fn is_summoned(&self, id: &Id) -> bool {
+		self.history[..self.turn].iter().any(|bi_change| {
+			bi_change
+				.forward
+				.summonings
+				.values()
+				.any(|summoning| summoning.summon.id == *id)
+		})
+	}
+
+	/// Problems with the level that would make it unplayable, or that would
+	/// break the simulation if it were played. An empty list means the level
+	/// is valid.
+	pub fn validate(&self) -> Vec<LevelError> {
+		// Without a full grid, tiles can't be looked up reliably.
+		if self.tiles.len() != self.width * self.height {
+			return vec![LevelError::NotRectangular];
+		}
+		let mut errors = Vec::new();
+		if self.character_ids.is_empty() {
+			errors.push(LevelError::NoCharacters);
+		}
+		for row in 0..self.height {
+			for col in 0..self.width {
+				let coords = Coords::new(row as i32, col as i32);
+				if self.is_on_edge(coords)
+					&& !matches!(self.tile_at(coords), Tile::Wall | Tile::Void)
+				{
+					errors.push(LevelError::OpenEdge(coords));
+				}
+			}
+		}
+		let mut objects: Vec<&LevelObject> =
+			self.objects_by_id.values().collect();
+		objects.sort_by_key(|level_object| level_object.id);
+		let mut color_coords: HashMap<CharacterColor, Coords> = HashMap::new();
+		for level_object in objects {
+			let coords = level_object.coords;
+			match self.tile_at(coords) {
+				Tile::Wall => errors.push(LevelError::ObjectOnWall(coords)),
+				Tile::Void => errors.push(LevelError::ObjectInVoid(coords)),
+				Tile::Track { platform: false } => {
+					errors.push(LevelError::ObjectOffPlatform(coords))
+				}
+				Tile::Water { bridged: false } => {
+					errors.push(LevelError::ObjectInWater(coords))
+				}
+				Tile::Lava { bridged: false } => {
+					errors.push(LevelError::ObjectInLava(coords))
+				}
+				Tile::Floor { .. }
+				| Tile::Stairs
+				| Tile::Ice
+				| Tile::Pit
+				| Tile::Track { platform: true }
+				| Tile::Turntable { .. }
+				| Tile::Cracked { .. }
+				| Tile::Water { bridged: true }
+				| Tile::Lava { bridged: true } => {}
+			}
+			if let Object::Character(character) = level_object.object {
+				if let Some(other) =
+					color_coords.insert(character.color, coords)
+				{
+					errors.push(LevelError::DuplicateColor(other, coords));
+				}
+			}
+		}
+		errors.extend(self.trace_tracks().1);
+		let has_stairs = self.tiles.contains(&Tile::Stairs);
+		if has_stairs && !self.characters_can_reach_stairs() {
+			errors.push(LevelError::UnreachableStairs);
+		}
+		errors
+	}
+
+	/// Whether the stairs can be reached from any character's position
+	/// without passing through walls, the void, or pits. Objects are ignored,
+	/// since they might be moved out of the way, and so are platforms'
+	/// positions, since tracks can be ridden. Open water and lava count as
+	/// passable, since a prop might bridge them.
+	fn characters_can_reach_stairs(&self) -> bool {
+		let mut visited: HashSet<Coords> = self
+			.character_ids
+			.iter()
+			.map(|id| self.objects_by_id[id].coords)
+			.collect();
+		let mut frontier: Vec<Coords> = visited.iter().copied().collect();
+		while let Some(coords) = frontier.pop() {
+			match self.tile_at(coords) {
+				Tile::Stairs => return true,
+				Tile::Wall | Tile::Void | Tile::Pit => continue,
+				Tile::Floor { .. }
+				| Tile::Ice
+				| Tile::Track { .. }
+				| Tile::Turntable { .. }
+				| Tile::Cracked { .. }
+				| Tile::Water { .. }
+				| Tile::Lava { .. } => {}
+			}
+			for offset in Offset::DIRECTIONS {
+				let neighbor = coords + offset;
+				if self.in_bounds(neighbor) && visited.insert(neighbor) {
+					frontier.push(neighbor);
+				}
+			}
+		}
+		false
+	}
+
+	/// Whether the level is complete: a character is on the stairs.
+	pub fn is_complete(&self) -> bool {
+		self.has_character_on_stairs()
+	}
+
+	/// Whether any character is standing on stairs.
+	pub fn has_character_on_stairs(&self) -> bool {
+		self.character_ids.iter().any(|id| {
+			self.tile_at(self.objects_by_id[id].coords) == Tile::Stairs
+		})
+	}
+
 	/// Number of characters in the level.
 	pub fn character_count(&self) -> usize {
 		self.character_ids.len()
 	}
 
+	/// The number of turns taken so far, not counting undone turns.
+	pub fn turn(&self) -> usize {
+		self.turn
+	}
+
+	/// Whether a character was destroyed on a turn up to the current one, like
+	/// by falling into a pit, so that the attempt is lost unless undone. Edits
+	/// that remove characters don't count.
+	pub fn has_lost_character(&self) -> bool {
+		self.history[..self.turn].iter().any(|bi_change| {
+			matches!(bi_change.cause, Cause::Actions(_))
+				&& bi_change
+					.forward
+					.destructions
+					.values()
+					.any(|level_object| level_object.object.is_character())
+		})
+	}
+
+	/// The cause and change of each turn in the level's history, including
+	/// undone turns that could still be redone, along with the level's
+	/// [state hash](Level::state_hash) after that turn. See
+	/// [`history`](crate::history) for the public view of it.
+	pub(crate) fn records(
+		&self,
+	) -> impl Iterator<Item = (&Cause, &Change, u64)> {
+		self.history.iter().map(|bi_change| {
+			(&bi_change.cause, &*bi_change.forward, bi_change.state_hash)
+		})
+	}
+
+	/// A hash of the level's tiles and objects, for checking whether two
+	/// copies of a level are in the same state. Object IDs aren't included,
+	/// since they can differ between copies. Like the
+	/// [checksum](Level::checksum), it's stable across runs and builds (the
+	/// FNV-1a hash of the level's current [text](Level::to_text)), so it can
+	/// be saved and checked by another build.
+	pub fn state_hash(&self) -> u64 {
+		fnv1a(&self.to_text())
+	}
+
+	/// A checksum of the level's tiles and starting objects, including any
+	/// hidden contents, for checking that a replay, score, or saved solution
+	/// was recorded on exactly this level. Unlike the
+	/// [state hash](Level::state_hash), it doesn't depend on the turns taken,
+	/// and it's stable across runs and builds (the FNV-1a hash of the
+	/// [text](Level::to_text) of the level at its start, followed by a line
+	/// for each hidden object).
+	pub fn checksum(&self) -> u64 {
+		let start = self.rewound();
+		let mut text = start.to_text();
+		let mut contents: Vec<(Coords, char)> = start
+			.contents
+			.iter()
+			.filter_map(|(id, object)| {
+				let symbol = match object {
+					Object::Character(c) => {
+						(b'0' + c.color.idx() as u8) as char
+					}
+					Object::Prop(descriptor) => descriptor.symbol as char,
+				};
+				let coords = start.objects_by_id.get(id)?.coords;
+				Some((coords, symbol))
+			})
+			.collect();
+		contents.sort_by_key(|(coords, _)| (coords.row, coords.col));
+		for (Coords { row, col }, symbol) in contents {
+			writeln!(text, "{row} {col} contains {symbol}").unwrap();
+		}
+		fnv1a(&text)
+	}
+
+	/// The [mutations](Mutation) that apply to the level's starting layout:
+	/// moving a prop onto an open neighboring tile, building a wall on an open
+	/// tile, or opening up a wall that isn't on the edge of the level.
+	pub fn mutations(&self) -> Vec<Mutation> {
+		let start = self.rewound();
+		let mut mutations = Vec::new();
+		for row in 0..start.height as i32 {
+			for col in 0..start.width as i32 {
+				let coords = Coords::new(row, col);
+				if start.tile_at(coords) == Tile::Wall
+					&& !start.is_on_edge(coords)
+				{
+					mutations.push(Mutation::RemoveWall(coords));
+				} else if start.is_open(coords) {
+					mutations.push(Mutation::AddWall(coords));
+				}
+				if let Some(Object::Prop(_)) = start.object_at(coords) {
+					for offset in Offset::DIRECTIONS {
+						let to = coords + offset;
+						if start.in_bounds(to) && start.is_open(to) {
+							mutations
+								.push(Mutation::MoveProp { from: coords, to });
+						}
+					}
+				}
+			}
+		}
+		mutations
+	}
+
+	/// A copy of this level as it was at the start, with `mutation` applied
+	/// and no history.
+	pub fn mutated(&self, mutation: Mutation) -> Level {
+		let mut level = self.rewound();
+		match mutation {
+			Mutation::MoveProp { from, to } => {
+				if let Some(id) = level.object_ids_by_coords.get(&from).copied()
+				{
+					let level_object = level.objects_by_id[&id];
+					level.remove(&id);
+					level.spawn(LevelObject {
+						coords: to,
+						..level_object
+					});
+				}
+			}
+			Mutation::AddWall(coords) => level.set_tile_at(coords, Tile::Wall),
+			Mutation::RemoveWall(coords) => {
+				level.set_tile_at(coords, Tile::Floor { portal_color: None });
+			}
+		}
+		level
+	}
+
+	/// A copy of this level as it was at the start, with no history.
+	pub fn rewound(&self) -> Level {
+		let mut level = self.clone();
+		while level.turn > 0 {
+			let change = level.history[level.turn - 1].reverse.clone();
+			level.apply(&change);
+			level.turn -= 1;
+		}
+		level.history.clear();
+		level
+	}
+
+	/// The direction the object with the given `id` is facing.
+	pub fn facing(&self, id: &Id) -> Offset {
+		Offset::from_angle(self.objects_by_id[id].angle)
+	}
+
+	/// The strength the object with the given `id` contributes to a push.
+	/// Sliding characters move but can't push anything.
+	fn push_strength(&self, id: &Id) -> i32 {
+		match self.objects_by_id[id].object {
+			Object::Character(character) if character.can_push() => 1,
+			_ => 0,
+		}
+	}
+
+	/// Whether the character with the given `id` has no choice but to wait,
+	/// assuming no other character moves: it can't push in any direction, and it
+	/// can neither summon nor return. Sliding characters are never stuck, since
+	/// they always continue sliding.
+	pub fn is_stuck(&self, id: &Id) -> bool {
+		let character = self.character_by_id(id);
+		if character.sliding {
+			return false;
+		}
+		let coords = self.objects_by_id[id].coords;
+		let can_push = Offset::DIRECTIONS.into_iter().any(|offset| {
+			// Check for a blocked push rather than a move, since a character
+			// on a platform moves regardless.
+			!self
+				.preview(vec![(*id, Action::Push(offset))])
+				.blocked
+				.contains(id)
+		});
+		let can_summon = character.can_summon()
+			&& self.portal_room() != Some(0)
+			&& Offset::DIRECTIONS
+				.into_iter()
+				.any(|offset| self.summon_tile(coords, offset).is_some());
+		let can_return =
+			character.can_return() && character.portal_coords == Some(coords);
+		!can_push && !can_summon && !can_return
+	}
+
+	/// The coordinates of every tile that would be lethal or destructive next
+	/// turn: tiles whose behavior destroys objects that enter them, plus tiles
+	/// where something would be destroyed even if every character waited.
+	pub fn danger_coords(&self) -> HashSet<Coords> {
+		let mut coords: HashSet<Coords> = (0..self.height)
+			.flat_map(|row| {
+				(0..self.width)
+					.map(move |col| Coords::new(row as i32, col as i32))
+			})
+			.filter(|&coords| {
+				self.tile_at(coords).behavior().is_lethal(self, coords)
+			})
+			.collect();
+		let waits = self
+			.character_ids
+			.iter()
+			.map(|&id| (id, Action::Wait))
+			.collect();
+		coords.extend(
+			self.preview(waits)
+				.destructions
+				.values()
+				.map(|destroyed| destroyed.coords),
+		);
+		coords
+	}
+
+	/// The total weight of the objects on each tile that has any, under the
+	/// level's rules, as on pressure plates or under a crushing push.
+	pub fn tile_weights(&self) -> HashMap<Coords, i32> {
+		let mut weights = HashMap::new();
+		for level_object in self.objects_by_id.values() {
+			*weights.entry(level_object.coords).or_insert(0) +=
+				self.weight(&level_object.object);
+		}
+		weights
+	}
+
+	/// The weight of `object` under the level's rules.
+	fn weight(&self, object: &Object) -> i32 {
+		match object {
+			Object::Prop(descriptor)
+				if descriptor.is_crate && self.rules.heavy_crates =>
+			{
+				2 * object.weight()
+			}
+			_ => object.weight(),
+		}
+	}
+
 	/// Updates the level by making the `actors` act, returning the resulting
-	/// (possibly trivial) [`Change`].
+	/// (possibly trivial) [`Change`]. See [`Level::preview`] for how actions
+	/// are resolved.
+	pub fn update(&mut self, actors: Vec<(Id, Action)>) -> ChangeEvent {
+		let cause = Cause::Actions(
+			self.character_ids
+				.iter()
+				.map(|id| {
+					actors
+						.iter()
+						.find(|(actor, _)| actor == id)
+						.map_or(Action::Wait, |(_, action)| *action)
+				})
+				.collect(),
+		);
+		let mut scratch = self.without_history();
+		let change = scratch.resolve(actors);
+		// Keep any IDs allocated while resolving the turn.
+		self.next_object_id = scratch.next_object_id;
+		self.apply(&change);
+		self.record(change, cause)
+	}
+
+	/// The (possibly trivial) [`Change`] that making the `actors` act would
+	/// cause, without modifying the level or its history.
 	///
 	/// Actions are resolved in three phases: (1) return, (2) push, and (3)
-	/// summon. Actions within each phase are simultaneous.
+	/// summon. Actions within each phase are simultaneous. Between pushing and
+	/// summoning, each [platform](Platform) takes a step, carrying whatever is
+	/// on it. Since pushes are resolved first, objects can step onto a platform
+	/// and ride it in the same turn, but not onto one that's just arriving.
+	/// Tiles act after each phase; see [`TurnPhase`]. Sliding characters
+	/// always continue in the direction they're facing, regardless of their
+	/// chosen action.
 	///
 	/// Any two summoners must summon into disjoint coordinates. This
 	/// precondition will generally be trivially satisfied since there should be
 	/// at most one summoner per update.
-	pub fn update(&mut self, actors: Vec<(Id, Action)>) -> ChangeEvent {
+	pub fn preview(&self, actors: Vec<(Id, Action)>) -> Change {
+		self.without_history().resolve(actors)
+	}
+
+	/// A copy of this level without its history, for working out hypothetical
+	/// turns.
+	fn without_history(&self) -> Level {
+		Level {
+			width: self.width,
+			height: self.height,
+			rules: self.rules,
+			lighting: self.lighting,
+			soundtrack: self.soundtrack,
+			solution: self.solution.clone(),
+			meta: self.meta.clone(),
+			tiles: self.tiles.clone(),
+			platforms: self.platforms.clone(),
+			objects_by_id: self.objects_by_id.clone(),
+			object_ids_by_coords: self.object_ids_by_coords.clone(),
+			character_ids: self.character_ids.clone(),
+			contents: self.contents.clone(),
+			next_object_id: self.next_object_id,
+			history: Vec::new(),
+			turn: self.turn,
+		}
+	}
+
+	/// Resolves the turn in which the `actors` act, applying the results
+	/// without affecting history and returning them as a [`Change`].
+	fn resolve(&mut self, actors: Vec<(Id, Action)>) -> Change {
 		// Map pushers and summoners to their offsets.
 		let (pushers, summoners, returners) = {
 			let mut pushers = HashMap::new();
 			let mut summoners = HashMap::new();
 			let mut returners = HashSet::new();
 			for (id, action) in actors {
+				let action = if self.character_by_id(&id).sliding {
+					Action::Push(self.facing(&id))
+				} else {
+					action
+				};
 				match action {
 					Action::Push(offset) => {
 						pushers.insert(id, offset);
@@ -407,19 +1359,141 @@ impl Level {
 
 		let returnings = self.get_returnings(returners);
 		self.apply_returnings(&returnings);
+		let mut change = Change {
+			returnings,
+			..Change::default()
+		};
+		self.run_tile_phase(TurnPhase::PreMove, &mut change);
 
-		let moves = self.get_moves(pushers);
+		let pusher_ids: Vec<Id> = pushers.keys().copied().collect();
+		let (moves, crushed) = self.get_moves(pushers);
+		let destructions = crushed
+			.iter()
+			.map(|id| (*id, self.objects_by_id[id]))
+			.collect();
 		self.apply_moves(&moves);
+		for id in &crushed {
+			self.remove(id);
+		}
+		let creations = self.spill_contents(&destructions, &moves);
+		let blocked = pusher_ids
+			.into_iter()
+			.filter(|id| !moves.contains_key(id))
+			.collect();
+		change.merge(Change {
+			moves,
+			creations,
+			destructions,
+			blocked,
+			crushed,
+			..Change::default()
+		});
+		let (platform_steps, carried) = self.get_platform_steps();
+		self.apply_moves(&carried);
+		self.apply_platform_steps(&platform_steps);
+		change.merge(Change {
+			moves: carried,
+			platform_steps,
+			..Change::default()
+		});
+		self.run_tile_phase(TurnPhase::PostMove, &mut change);
 
 		let summonings = self.get_summonings(summoners);
 		self.apply_summonings(&summonings);
-
-		// Add the change to the turn history and then return it.
-		let change = Change {
-			returnings,
-			moves,
+		change.merge(Change {
 			summonings,
+			..Change::default()
+		});
+		self.run_tile_phase(TurnPhase::EndOfTurn, &mut change);
+
+		change
+	}
+
+	/// Runs each tile's [`TileBehavior`](crate::tile_behavior::TileBehavior)
+	/// hook for `phase`, in row-major order, applying the resulting effects and
+	/// merging them into `change`.
+	fn run_tile_phase(&mut self, phase: TurnPhase, change: &mut Change) {
+		for row in 0..self.height {
+			for col in 0..self.width {
+				let coords = Coords::new(row as i32, col as i32);
+				let effects =
+					self.tile_at(coords).behavior().run(phase, self, coords);
+				self.apply(&effects);
+				change.merge(effects);
+			}
+		}
+	}
+
+	/// Edits the level outside the normal rules by placing `object` at
+	/// `coords`, or by removing the object at `coords` if `object` is `None`.
+	/// The edit is recorded in history, so it can be undone like a turn.
+	/// Returns `None` if the edit isn't possible.
+	///
+	/// Objects can only be placed onto empty floor. Placed characters get the
+	/// first available color. The last character can't be removed.
+	pub fn edit(
+		&mut self,
+		coords: Coords,
+		object: Option<Object>,
+	) -> Option<ChangeEvent> {
+		if !self.in_bounds(coords)
+			|| !matches!(self.tile_at(coords), Tile::Floor { .. })
+		{
+			return None;
+		}
+		let mut creations = HashMap::new();
+		let mut destructions = HashMap::new();
+		match (object, self.object_ids_by_coords.get(&coords).copied()) {
+			(Some(object), None) => {
+				let object = match object {
+					Object::Character(character) => {
+						Object::Character(Character {
+							color: *self.get_available_colors().first()?,
+							..character
+						})
+					}
+					object => object,
+				};
+				let id = self.new_object_id();
+				creations.insert(
+					id,
+					LevelObject {
+						id,
+						object,
+						coords,
+						angle: 0.0,
+					},
+				);
+			}
+			(None, Some(id)) => {
+				if self.character_ids.contains(&id)
+					&& self.character_count() == 1
+				{
+					return None;
+				}
+				destructions.insert(id, self.objects_by_id[&id]);
+			}
+			_ => return None,
+		}
+		let change = Change {
+			creations,
+			destructions,
+			..Change::default()
 		};
+		self.apply(&change);
+		Some(self.record(change, Cause::Edit { coords, object }))
+	}
+
+	/// Applies `change`, recorded from another copy of this level, and adds it
+	/// to the turn history as though it were caused by `cause`.
+	pub fn replay(&mut self, change: Change, cause: Cause) -> ChangeEvent {
+		self.apply(&change);
+		self.record(change, cause)
+	}
+
+	/// Adds `change`, which has already been applied, to the turn history and
+	/// returns it as a [`ChangeEvent`].
+	fn record(&mut self, change: Change, cause: Cause) -> ChangeEvent {
 		let reverse = Arc::new(change.clone().reverse());
 		let change = Arc::new(change);
 		// Truncate history to remove any future states. This is a no-op if the
@@ -428,6 +1502,8 @@ impl Level {
 		self.history.push(BiChange {
 			forward: change.clone(),
 			reverse,
+			cause,
+			state_hash: self.state_hash(),
 		});
 		self.turn += 1;
 		ChangeEvent(change)
@@ -455,8 +1531,12 @@ impl Level {
 			.collect()
 	}
 
-	/// Computes the set of [`Move`]s resulting from the given `pushers`.
-	fn get_moves(&self, pushers: HashMap<Id, Offset>) -> HashMap<Id, Move> {
+	/// Computes the set of [`Move`]s resulting from the given `pushers`, along
+	/// with the objects they crush.
+	fn get_moves(
+		&self,
+		pushers: HashMap<Id, Offset>,
+	) -> (HashMap<Id, Move>, HashSet<Id>) {
 		// Build the set of teams, keyed by starting coordinates. Teams may not
 		// be maximal; i.e. some teams may be subsumed by larger ones.
 		let mut teams: HashMap<Coords, Team> = pushers
@@ -468,15 +1548,21 @@ impl Level {
 					start: pusher.coords,
 					offset,
 					count: 1,
-					strength: 1,
+					strength: self.push_strength(id),
 					blocked: false,
+					crushes: false,
 				};
+				// The last object in line, which would crush the next.
+				let mut last = pusher.object;
 				// Consider tiles in the direction of the backmost pusher.
 				let mut coords = pusher.coords + offset;
 				loop {
-					// Block just the starting pusher of teams facing a wall, to
-					// allow non-pushers to be claimed by other teams.
-					if let Tile::Wall = self.tile_at(coords) {
+					// Block just the starting pusher of teams facing a wall or
+					// the edge of the map, or moving a character into open
+					// water, to allow non-pushers to be claimed by other teams.
+					if !self.in_bounds(coords)
+						|| self.tile_at(coords).blocks(&last)
+					{
 						return (
 							pusher.coords,
 							Team {
@@ -485,6 +1571,7 @@ impl Level {
 								count: 1,
 								strength: -1,
 								blocked: true,
+								crushes: false,
 							},
 						);
 					}
@@ -495,8 +1582,16 @@ impl Level {
 					// or be orthogonal to the current team.
 					if let Some(&other_offset) = pushers.get(other_id) {
 						if other_offset == offset {
-							// Contributing; add strength.
-							team.strength += 2;
+							// Contributing; add strength and offset the
+							// contributor's own weight.
+							team.strength += 1 + self.push_strength(other_id);
+						} else if other_offset == -offset
+							&& self.rules.swap_places
+							&& team.count == 1
+						{
+							// Swapping: the two pushers are adjacent and will
+							// trade places, so neither joins the other's team.
+							break;
 						} else if other_offset == -offset {
 							// Opposing: block the starting pusher.
 							return (
@@ -507,6 +1602,7 @@ impl Level {
 									count: 1,
 									strength: -1,
 									blocked: true,
+									crushes: false,
 								},
 							);
 						} else {
@@ -515,11 +1611,16 @@ impl Level {
 							break;
 						}
 					}
-					// The team's strength must remain at or above zero for its
-					// entire length.
 					let other = &self.objects_by_id[other_id].object;
-					team.strength -= other.weight();
-					if team.strength < 0 {
+					if self.crushes(&last, other) {
+						// The team moves into the crushed object's tile.
+						team.crushes = true;
+						break;
+					}
+					// The team's strength must remain at or above zero for its
+					// entire length, and every object in it must be pushable.
+					team.strength -= self.weight(other);
+					if team.strength < 0 || !other.pushable() {
 						return (
 							pusher.coords,
 							Team {
@@ -528,12 +1629,14 @@ impl Level {
 								count: 1,
 								strength: -1,
 								blocked: true,
+								crushes: false,
 							},
 						);
 					}
 					// Welcome to the team.
 					team.count += 1;
 					coords += offset;
+					last = *other;
 				}
 				(pusher.coords, team)
 			})
@@ -647,16 +1750,78 @@ impl Level {
 			}
 		}
 
-		// Move the objects in unblocked teams.
+		// Move the objects in unblocked teams, crushing whatever they're
+		// pushed into.
 		let mut moves = HashMap::new();
+		let mut crushed = HashSet::new();
 		for team in teams.values().filter(|team| !team.blocked) {
 			for coords in team.coords() {
 				let id = self.object_ids_by_coords[&coords];
 				let mv = self.get_move(id, team.offset);
 				moves.insert(id, mv);
 			}
+			if team.crushes {
+				let coords = team.start + team.count as i32 * team.offset;
+				crushed.insert(self.object_ids_by_coords[&coords]);
+			}
 		}
-		moves
+		// An object pushed out of the way by another team escapes crushing.
+		crushed.retain(|id| !moves.contains_key(id));
+		(moves, crushed)
+	}
+
+	/// Spawns the contents of the `crushed` objects onto the tiles past them,
+	/// in the direction of the `moves` that crushed them, and returns them.
+	/// Contents with nowhere to go are lost.
+	fn spill_contents(
+		&mut self,
+		crushed: &HashMap<Id, LevelObject>,
+		moves: &HashMap<Id, Move>,
+	) -> HashMap<Id, LevelObject> {
+		let mut crushed: Vec<&LevelObject> = crushed.values().collect();
+		crushed.sort_by_key(|level_object| level_object.id);
+		let mut creations = HashMap::new();
+		for level_object in crushed {
+			let Some(object) = self.contents(&level_object.id) else {
+				continue;
+			};
+			let Some(mv) = moves
+				.values()
+				.find(|mv| mv.to_coords == level_object.coords)
+			else {
+				continue;
+			};
+			let offset = Offset::new(
+				mv.to_coords.row - mv.from_coords.row,
+				mv.to_coords.col - mv.from_coords.col,
+			);
+			let coords = level_object.coords + offset;
+			if !self.in_bounds(coords)
+				|| self.tile_at(coords).blocks_movement()
+				|| self.object_ids_by_coords.contains_key(&coords)
+			{
+				continue;
+			}
+			let spilled = LevelObject {
+				id: self.new_object_id(),
+				object,
+				coords,
+				angle: 0.0,
+			};
+			self.spawn(spilled);
+			creations.insert(spilled.id, spilled);
+		}
+		creations
+	}
+
+	/// Whether `pushed` is crushed when `pusher` is pushed into it.
+	fn crushes(&self, pusher: &Object, pushed: &Object) -> bool {
+		let Object::Prop(descriptor) = pushed else {
+			return false;
+		};
+		self.rules.crushing
+			&& descriptor.fragile
+			&& self.weight(pusher) > self.weight(pushed)
 	}
 
 	/// Computes the list of colors not yet taken by any character. The results
@@ -683,14 +1848,19 @@ impl Level {
 		&mut self,
 		summoners: HashMap<Id, Offset>,
 	) -> HashMap<Id, Summoning> {
+		let mut summoners: Vec<(Id, Offset)> = summoners.into_iter().collect();
+		if let Some(room) = self.portal_room() {
+			summoners.sort_by_key(|(id, _)| *id);
+			summoners.truncate(room);
+		}
 		summoners
 			.into_iter()
 			.zip(self.get_available_colors())
 			.filter_map(|((summoner_id, offset), summon_color)| {
 				let summon_id = self.new_object_id();
 				let level_summoner = self.level_character_by_id(&summoner_id);
-				self.farthest_open_tile(level_summoner.coords, offset).map(
-					|coords| {
+				self.summon_tile(level_summoner.coords, offset)
+					.map(|coords| {
 						(
 							summoner_id,
 							Summoning {
@@ -708,13 +1878,188 @@ impl Level {
 								portal_color: level_summoner.character.color,
 							},
 						)
-					},
-				)
+					})
 			})
 			.collect()
 	}
 
-	/// The empty floor tile most distant from `start` incrementing by `offset`.
+	/// The number of portals currently open.
+	pub fn open_portals(&self) -> usize {
+		self.tiles
+			.iter()
+			.filter(|tile| {
+				matches!(
+					tile,
+					Tile::Floor {
+						portal_color: Some(_)
+					}
+				)
+			})
+			.count()
+	}
+
+	/// How many more portals can open under [`Rules::max_open_portals`], or
+	/// `None` if there's no limit.
+	pub fn portal_room(&self) -> Option<usize> {
+		self.rules
+			.max_open_portals
+			.map(|max| max.saturating_sub(self.open_portals()))
+	}
+
+	/// The level's moving platforms.
+	pub fn platforms(&self) -> &[Platform] {
+		&self.platforms
+	}
+
+	/// Traces each connected set of track tiles into a [`Platform`]. Tracks
+	/// that aren't a single line or loop with exactly one platform on them
+	/// are left out, with an error.
+	fn trace_tracks(&self) -> (Vec<Platform>, Vec<LevelError>) {
+		// Check the tile list itself, since maps with ragged rows are traced
+		// before they're validated.
+		let is_track = |coords: Coords| {
+			self.in_bounds(coords)
+				&& matches!(
+					self.tiles.get(self.tile_idx(coords)),
+					Some(Tile::Track { .. })
+				)
+		};
+		let neighbors = |coords: Coords| -> Vec<Coords> {
+			Offset::DIRECTIONS
+				.into_iter()
+				.map(|offset| coords + offset)
+				.filter(|neighbor| is_track(*neighbor))
+				.collect()
+		};
+		let mut platforms = Vec::new();
+		let mut errors = Vec::new();
+		let mut visited = HashSet::new();
+		for row in 0..self.height {
+			for col in 0..self.width {
+				let start = Coords::new(row as i32, col as i32);
+				if !is_track(start) || !visited.insert(start) {
+					continue;
+				}
+				let mut component = vec![start];
+				let mut frontier = vec![start];
+				while let Some(coords) = frontier.pop() {
+					for neighbor in neighbors(coords) {
+						if visited.insert(neighbor) {
+							component.push(neighbor);
+							frontier.push(neighbor);
+						}
+					}
+				}
+				if component.iter().any(|coords| neighbors(*coords).len() > 2) {
+					errors.push(LevelError::InvalidTrack(start));
+					continue;
+				}
+				// Lines are walked from their first end in reading order, and
+				// loops from their first tile.
+				let end = component
+					.iter()
+					.copied()
+					.filter(|coords| neighbors(*coords).len() < 2)
+					.min_by_key(|coords| (coords.row, coords.col));
+				let mut track = vec![end.unwrap_or(start)];
+				while let Some(next) = neighbors(track[track.len() - 1])
+					.into_iter()
+					.find(|neighbor| !track.contains(neighbor))
+				{
+					track.push(next);
+				}
+				let mut on_platform =
+					track.iter().enumerate().filter(|(_, coords)| {
+						self.tile_at(**coords) == Tile::Track { platform: true }
+					});
+				match (on_platform.next(), on_platform.next()) {
+					(Some((idx, _)), None) => platforms.push(Platform {
+						track: track.into(),
+						looped: end.is_none(),
+						position: PlatformPosition {
+							idx,
+							backward: false,
+						},
+					}),
+					_ => errors.push(LevelError::InvalidTrack(start)),
+				}
+			}
+		}
+		(platforms, errors)
+	}
+
+	/// Computes each platform's next step, along with the [`Move`]s of the
+	/// objects they carry.
+	fn get_platform_steps(
+		&self,
+	) -> (HashMap<usize, PlatformStep>, HashMap<Id, Move>) {
+		let mut steps = HashMap::new();
+		let mut carried = HashMap::new();
+		for (idx, platform) in self.platforms.iter().enumerate() {
+			let to = platform.next_position();
+			if to == platform.position {
+				continue;
+			}
+			let step = PlatformStep {
+				from_coords: platform.coords(),
+				to_coords: platform.track[to.idx],
+				from: platform.position,
+				to,
+			};
+			if let Some(level_object) = self.level_object_at(step.from_coords) {
+				carried.insert(
+					level_object.id,
+					Move {
+						from_coords: step.from_coords,
+						to_coords: step.to_coords,
+						from_angle: level_object.angle,
+						to_angle: level_object.angle,
+					},
+				);
+			}
+			steps.insert(idx, step);
+		}
+		(steps, carried)
+	}
+
+	/// Whether `coords` is within the level's grid.
+	pub fn in_bounds(&self, coords: Coords) -> bool {
+		coords.row >= 0
+			&& coords.row < self.height() as i32
+			&& coords.col >= 0
+			&& coords.col < self.width() as i32
+	}
+
+	/// Whether `coords` is on the outermost rows or columns of the level's
+	/// grid.
+	fn is_on_edge(&self, coords: Coords) -> bool {
+		coords.row == 0
+			|| coords.col == 0
+			|| coords.row == self.height as i32 - 1
+			|| coords.col == self.width as i32 - 1
+	}
+
+	/// Whether `coords` is an empty floor tile without a portal.
+	fn is_open(&self, coords: Coords) -> bool {
+		matches!(
+			(self.tile_at(coords), self.object_at(coords)),
+			(Tile::Floor { portal_color: None }, None)
+		)
+	}
+
+	/// Where a character summoning from `start` toward `offset` summons to, if
+	/// anywhere.
+	fn summon_tile(&self, start: Coords, offset: Offset) -> Option<Coords> {
+		if self.rules.adjacent_summons {
+			let coords = start + offset;
+			(self.in_bounds(coords) && self.is_open(coords)).then_some(coords)
+		} else {
+			self.farthest_open_tile(start, offset)
+		}
+	}
+
+	/// The empty floor tile most distant from `start` incrementing by `offset`,
+	/// stopping at the void.
 	fn farthest_open_tile(
 		&self,
 		start: Coords,
@@ -724,26 +2069,52 @@ impl Level {
 		let mut coords = start;
 		loop {
 			coords += offset;
-			if coords.row < 0
-				|| coords.row >= self.height() as i32
-				|| coords.col < 0
-				|| coords.col >= self.width() as i32
-			{
+			if !self.in_bounds(coords) || self.tile_at(coords) == Tile::Void {
 				break;
 			}
-			if let (Tile::Floor { portal_color: None }, None) =
-				(self.tile_at(coords), self.object_at(coords))
-			{
+			if self.is_open(coords) {
 				result = Some(coords);
 			}
 		}
 		result
 	}
 
-	/// If possible, moves to the previous level state and returns the resulting
-	/// [`ChangeEvent`].
+	/// The earliest turn that can be undone back to. Under
+	/// [`Rules::summons_lock_history`], undoing a turn with a summoning would
+	/// mean undoing across an open portal, so it's locked along with every
+	/// turn before it until the summoner returns and closes the portal.
+	pub fn undo_floor(&self) -> usize {
+		if !self.rules.summons_lock_history {
+			return 0;
+		}
+		self.history[..self.turn]
+			.iter()
+			.rposition(|bi_change| {
+				bi_change
+					.forward
+					.summonings
+					.keys()
+					.any(|summoner_id| self.has_character(summoner_id))
+			})
+			.map_or(0, |turn| turn + 1)
+	}
+
+	/// Whether [`Level::undo`] is possible: there's a turn to undo, it isn't
+	/// disallowed under [`Rules::no_undo`], and it isn't past the
+	/// [undo floor](Level::undo_floor).
+	pub fn can_undo(&self) -> bool {
+		self.turn > self.undo_floor() && !self.rules.no_undo
+	}
+
+	/// Whether [`Level::redo`] is possible: there's an undone turn to redo.
+	pub fn can_redo(&self) -> bool {
+		self.turn < self.history.len()
+	}
+
+	/// If [possible](Level::can_undo), moves to the previous level state and
+	/// returns the resulting [`ChangeEvent`].
 	pub fn undo(&mut self) -> Option<ChangeEvent> {
-		if self.turn > 0 {
+		if self.can_undo() {
 			let change = self.history[self.turn - 1].reverse.clone();
 			self.apply(&change);
 			self.turn -= 1;
@@ -753,10 +2124,24 @@ impl Level {
 		}
 	}
 
+	/// If [possible](Level::can_undo), the level state [`Level::undo`] would
+	/// move to, without history, leaving this level as it is. Compare it to a
+	/// [snapshot](Level::snapshot) of this level to see what undoing would
+	/// change.
+	pub fn undo_preview(&self) -> Option<Level> {
+		if !self.can_undo() {
+			return None;
+		}
+		let mut previous = self.without_history();
+		previous.apply(&self.history[self.turn - 1].reverse);
+		previous.turn -= 1;
+		Some(previous)
+	}
+
 	/// If possible, moves to the next level state and returns the resulting
 	/// [`ChangeEvent`].
 	pub fn redo(&mut self) -> Option<ChangeEvent> {
-		if self.turn < self.history.len() {
+		if self.can_redo() {
 			let change = self.history[self.turn].forward.clone();
 			self.apply(&change);
 			self.turn += 1;
@@ -766,11 +2151,97 @@ impl Level {
 		}
 	}
 
+	/// A snapshot of the level's current tiles and objects, for later
+	/// comparison using [`Level::changed_coords`].
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			tiles: self.tiles.clone(),
+			objects: self
+				.objects_by_id
+				.values()
+				.map(|level_object| (level_object.coords, level_object.object))
+				.collect(),
+		}
+	}
+
+	/// The coordinates whose tile or object differs from `before`, in
+	/// row-major order.
+	pub fn changed_coords(&self, before: &Snapshot) -> Vec<Coords> {
+		let mut changed = Vec::new();
+		for row in 0..self.height {
+			for col in 0..self.width {
+				let coords = Coords::new(row as i32, col as i32);
+				let idx = self.tile_idx(coords);
+				if self.tiles[idx] != before.tiles[idx]
+					|| self.object_at(coords)
+						!= before.objects.get(&coords).copied()
+				{
+					changed.push(coords);
+				}
+			}
+		}
+		changed
+	}
+
+	/// The paths each character has taken up to the current turn,
+	/// reconstructed from the level's history. Characters that haven't moved
+	/// have no trail.
+	pub fn trails(&self) -> Vec<Trail> {
+		// Characters present now, plus any that have since returned.
+		let mut colors: HashMap<Id, CharacterColor> = self
+			.characters_by_id()
+			.map(|(&id, character)| (id, character.color))
+			.collect();
+		for bi_change in &self.history[..self.turn] {
+			for summoning in bi_change.forward.summonings.values() {
+				colors.insert(
+					summoning.summon.id,
+					summoning.summon.character.color,
+				);
+			}
+		}
+		let mut paths: HashMap<Id, Vec<Coords>> = HashMap::new();
+		for bi_change in &self.history[..self.turn] {
+			for (id, mv) in &bi_change.forward.moves {
+				// Turning in place doesn't extend a trail.
+				if colors.contains_key(id) && mv.from_coords != mv.to_coords {
+					paths
+						.entry(*id)
+						.or_insert_with(|| vec![mv.from_coords])
+						.push(mv.to_coords);
+				}
+			}
+		}
+		let mut trails: Vec<Trail> = paths
+			.into_iter()
+			.map(|(id, coords)| Trail {
+				color: colors[&id],
+				coords,
+			})
+			.collect();
+		trails.sort_by_key(|trail| trail.color);
+		trails
+	}
+
 	/// Applies `change` to the level's state without affecting history.
-	fn apply(&mut self, change: &Change) {
+	pub(crate) fn apply(&mut self, change: &Change) {
+		for level_object in change.creations.values() {
+			self.spawn(*level_object);
+		}
 		self.apply_returnings(&change.returnings);
 		self.apply_moves(&change.moves);
+		self.apply_platform_steps(&change.platform_steps);
 		self.apply_summonings(&change.summonings);
+		for id in &change.slide_toggles {
+			let character = self.character_by_id_mut(id);
+			character.sliding = !character.sliding;
+		}
+		for (coords, tile_change) in &change.tile_changes {
+			self.set_tile_at(*coords, tile_change.to);
+		}
+		for id in change.destructions.keys() {
+			self.remove(id);
+		}
 	}
 
 	/// Applies `returnings` to the level's state without affecting history.
@@ -790,9 +2261,13 @@ impl Level {
 
 	/// Applies `moves` to the level's state without affecting history.
 	fn apply_moves(&mut self, moves: &HashMap<Id, Move>) {
-		// To make sure every target tile is open, first remove all movers.
-		for mv in moves.values() {
-			self.object_ids_by_coords.remove(&mv.from_coords);
+		// To make sure every target tile is open, first remove all movers. A
+		// mover's tile may already be taken by an object restored in its wake,
+		// like a crushed object when undoing.
+		for (id, mv) in moves {
+			if self.object_ids_by_coords.get(&mv.from_coords) == Some(id) {
+				self.object_ids_by_coords.remove(&mv.from_coords);
+			}
 		}
 		// Now place the movers into their new tiles.
 		for (id, mv) in moves.iter() {
@@ -804,6 +2279,15 @@ impl Level {
 		}
 	}
 
+	/// Applies platform `steps` to the level's state without affecting history.
+	fn apply_platform_steps(&mut self, steps: &HashMap<usize, PlatformStep>) {
+		for (idx, step) in steps {
+			self.set_tile_at(step.from_coords, Tile::Track { platform: false });
+			self.set_tile_at(step.to_coords, Tile::Track { platform: true });
+			self.platforms[*idx].position = step.to;
+		}
+	}
+
 	/// Applies `summonings` to the level's state without affecting history.
 	fn apply_summonings(&mut self, summonings: &HashMap<Id, Summoning>) {
 		for (summoner_id, summoning) in summonings {
@@ -855,6 +2339,16 @@ impl Level {
 		self.objects_by_id.insert(level_object.id, level_object);
 	}
 
+	/// Removes the object with the given `id`, if there is one.
+	fn remove(&mut self, id: &Id) {
+		if let Some(level_object) = self.objects_by_id.remove(id) {
+			if self.object_ids_by_coords.get(&level_object.coords) == Some(id) {
+				self.object_ids_by_coords.remove(&level_object.coords);
+			}
+			self.character_ids.remove(id);
+		}
+	}
+
 	/// Removes the object at `coords`, if there is one.
 	fn remove_at(&mut self, coords: Coords) {
 		if let Some(removed_id) = self.object_ids_by_coords.remove(&coords) {
@@ -885,25 +2379,12 @@ impl Debug for Level {
 			write!(f, "\n  ")?;
 			for col in 0..self.width {
 				let coords = Coords::new(row as i32, col as i32);
-				let tile = self.tile_at(coords);
-				let object = self.object_at(coords);
-				f.write_char(match tile {
-					Tile::Floor { portal_color } => {
-						if portal_color.is_some() {
-							'o'
-						} else {
-							'.'
-						}
-					}
-					Tile::Wall => '#',
-				})?;
-				f.write_char(match object {
+				f.write_char(self.tile_at(coords).symbol() as char)?;
+				f.write_char(match self.object_at(coords) {
 					Some(Object::Character(c)) => {
 						(b'0' + c.color.idx() as u8) as char
 					}
-					Some(Object::WoodenCrate) => 'X',
-					Some(Object::SteelCrate) => 'Y',
-					Some(Object::StoneBlock) => 'Z',
+					Some(Object::Prop(descriptor)) => descriptor.symbol as char,
 					None => ' ',
 				})?;
 			}
@@ -912,6 +2393,190 @@ impl Debug for Level {
 	}
 }
 
+/// The 64-bit FNV-1a hash of `text`.
+fn fnv1a(text: &str) -> u64 {
+	text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+	})
+}
+
+/// The line separating the map from object states in [level
+/// text](Level::to_text).
+const STATE_SEPARATOR: &str = "---";
+
+impl Level {
+	/// The level's current state as text, which [`Level::from_text`] reads
+	/// back into an equivalent level.
+	///
+	/// The text starts with the map, in the format of [`make_level`]. If any
+	/// object isn't in its starting state, a `---` line follows, then a line
+	/// for each such object with its row and column and then its state:
+	/// `angle` and the angle it's facing in radians, and `portal`, a row, and
+	/// a column if it's a character with an open portal. Platforms heading
+	/// back toward the start of their tracks get a line too, with their row
+	/// and column and then `backward`. For example:
+	///
+	/// ```text
+	/// # # # # # #
+	/// # a .0. > #
+	/// # # # # # #
+	/// ---
+	/// 1 2 angle 3.1415927 portal 1 1
+	/// ```
+	///
+	/// History, object IDs, and level metadata aren't included.
+	pub fn to_text(&self) -> String {
+		let mut text = self.map_text();
+		let states = self.state_text();
+		if !states.is_empty() {
+			writeln!(text, "{STATE_SEPARATOR}").unwrap();
+			text.push_str(&states);
+		}
+		text
+	}
+
+	/// The map part of the level's [text](Level::to_text), in the format of
+	/// [`make_level`].
+	pub fn map_text(&self) -> String {
+		let mut text = String::new();
+		for row in 0..self.height {
+			for col in 0..self.width {
+				let coords = Coords::new(row as i32, col as i32);
+				text.push(self.tile_at(coords).symbol() as char);
+				text.push(match self.object_at(coords) {
+					Some(Object::Character(c)) if c.sliding => {
+						(SLIDING_SYMBOL + c.color.idx() as u8) as char
+					}
+					Some(Object::Character(c)) => {
+						(b'0' + c.color.idx() as u8) as char
+					}
+					Some(Object::Prop(descriptor)) => descriptor.symbol as char,
+					None => ' ',
+				});
+			}
+			text.push('\n');
+		}
+		text
+	}
+
+	/// The object and platform states part of the level's
+	/// [text](Level::to_text), one line per state, or an empty string if
+	/// everything is in its starting state.
+	pub fn state_text(&self) -> String {
+		let mut level_objects: Vec<&LevelObject> =
+			self.objects_by_id.values().collect();
+		level_objects.sort_by_key(|lo| (lo.coords.row, lo.coords.col));
+		let mut states = String::new();
+		for level_object in level_objects {
+			let mut state = String::new();
+			if level_object.angle != 0.0 {
+				write!(state, " angle {}", level_object.angle).unwrap();
+			}
+			if let Object::Character(character) = level_object.object {
+				if let Some(portal) = character.portal_coords {
+					write!(state, " portal {} {}", portal.row, portal.col)
+						.unwrap();
+				}
+			}
+			if !state.is_empty() {
+				let Coords { row, col } = level_object.coords;
+				writeln!(states, "{row} {col}{state}").unwrap();
+			}
+		}
+		for platform in &self.platforms {
+			if platform.position.backward {
+				let Coords { row, col } = platform.coords();
+				writeln!(states, "{row} {col} backward").unwrap();
+			}
+		}
+		states
+	}
+
+	/// Reads a level from text written by [`Level::to_text`].
+	pub fn from_text(text: &str) -> Result<Level, LevelTextError> {
+		let lines: Vec<&str> = text.lines().collect();
+		let separator = lines
+			.iter()
+			.position(|line| line.trim() == STATE_SEPARATOR)
+			.unwrap_or(lines.len());
+		let state = lines.get(separator + 1..).unwrap_or_default().join("\n");
+		make_level(&lines[..separator].join("\n"))
+			.with_state(&state)
+			.map_err(|LevelTextError::InvalidState(line)| {
+				LevelTextError::InvalidState(separator + 1 + line)
+			})
+	}
+
+	/// This level with the object and platform states in `state` applied,
+	/// given in the format of the [state text](Level::state_text).
+	pub fn with_state(mut self, state: &str) -> Result<Level, LevelTextError> {
+		for (idx, line) in state.lines().enumerate() {
+			if line.trim().is_empty() {
+				continue;
+			}
+			self.read_object_state(line)
+				.ok_or(LevelTextError::InvalidState(idx + 1))?;
+		}
+		Ok(self)
+	}
+
+	/// Applies the object or platform state in `line` of [level
+	/// text](Level::to_text), or returns `None` if it's invalid.
+	fn read_object_state(&mut self, line: &str) -> Option<()> {
+		let mut words = line.split_whitespace();
+		let mut coords = || -> Option<Coords> {
+			let row = words.next()?.parse().ok()?;
+			let col = words.next()?.parse().ok()?;
+			Some(Coords::new(row, col))
+		};
+		let coords = coords()?;
+		while let Some(word) = words.next() {
+			if word == "backward" {
+				let platform = self
+					.platforms
+					.iter_mut()
+					.find(|platform| platform.coords() == coords)?;
+				platform.position.backward = true;
+				continue;
+			}
+			let id = *self.object_ids_by_coords.get(&coords)?;
+			let level_object = self.objects_by_id.get_mut(&id)?;
+			match (word, &mut level_object.object) {
+				("angle", _) => {
+					level_object.angle = words.next()?.parse().ok()?
+				}
+				("portal", Object::Character(character)) => {
+					let row = words.next()?.parse().ok()?;
+					let col = words.next()?.parse().ok()?;
+					character.portal_coords = Some(Coords::new(row, col));
+				}
+				_ => return None,
+			}
+		}
+		Some(())
+	}
+}
+
+/// An error reading [level text](Level::to_text).
+#[derive(Debug, PartialEq, Eq)]
+pub enum LevelTextError {
+	/// The object state on the given line, counting from 1, is invalid or
+	/// refers to an empty tile.
+	InvalidState(usize),
+}
+
+impl Display for LevelTextError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LevelTextError::InvalidState(line) => {
+				write!(f, "invalid object state on line {line}")
+			}
+		}
+	}
+}
+
+impl std::error::Error for LevelTextError {}
+
 /// A character's return to the past.
 #[derive(Clone)]
 pub struct Returning {
@@ -930,7 +2595,8 @@ impl Returning {
 	}
 }
 
-/// A movement of an object from one tile to another.
+/// A movement of an object from one tile to another. A move whose coordinates
+/// are the same, like a turntable's, only turns the object.
 #[derive(Clone, Copy)]
 pub struct Move {
 	pub from_coords: Coords,
@@ -950,6 +2616,42 @@ impl Move {
 	}
 }
 
+/// A step of a [`Platform`] along its track.
+#[derive(Clone, Copy)]
+pub struct PlatformStep {
+	pub from_coords: Coords,
+	pub to_coords: Coords,
+	from: PlatformPosition,
+	to: PlatformPosition,
+}
+
+impl PlatformStep {
+	fn reverse(self) -> PlatformStep {
+		PlatformStep {
+			from_coords: self.to_coords,
+			to_coords: self.from_coords,
+			from: self.to,
+			to: self.from,
+		}
+	}
+}
+
+/// A tile's change from one kind to another, like a pit filled by a prop.
+#[derive(Clone, Copy)]
+pub struct TileChange {
+	pub from: Tile,
+	pub to: Tile,
+}
+
+impl TileChange {
+	fn reverse(self) -> TileChange {
+		TileChange {
+			from: self.to,
+			to: self.from,
+		}
+	}
+}
+
 /// A character's summoning from the future.
 #[derive(Clone)]
 pub struct Summoning {
@@ -968,16 +2670,84 @@ impl Summoning {
 }
 
 /// A change from one [`Level`] state to another.
-#[derive(Clone)]
+///
+/// Creations are applied first and destructions last, so that reversing a
+/// change (which swaps creations and destructions) keeps its phases in the
+/// right order.
+#[derive(Clone, Default)]
 pub struct Change {
 	pub returnings: HashMap<Id, Returning>,
 	pub moves: HashMap<Id, Move>,
 	pub summonings: HashMap<Id, Summoning>,
+	/// Objects added to the level, other than by summoning.
+	pub creations: HashMap<Id, LevelObject>,
+	/// Objects removed from the level, other than by returning, in the state
+	/// they were in just before removal.
+	pub destructions: HashMap<Id, LevelObject>,
+	/// Characters that tried to push but were blocked. Applying a change
+	/// ignores this; it's only for presentation.
+	pub blocked: HashSet<Id>,
+	/// Characters that started or stopped sliding. Applied after summonings
+	/// and before destructions.
+	pub slide_toggles: HashSet<Id>,
+	/// Destroyed objects that were crushed. Like `blocked`, this is only for
+	/// presentation.
+	pub crushed: HashSet<Id>,
+	/// Steps taken by platforms, keyed by their indices in
+	/// [`Level::platforms`]. Objects they carry move as well.
+	pub platform_steps: HashMap<usize, PlatformStep>,
+	/// Tiles that changed kind, other than by portals opening and closing or
+	/// platforms moving. Applied after slide toggles and before destructions.
+	pub tile_changes: HashMap<Coords, TileChange>,
 }
 
 impl Change {
+	/// Folds `later`, which happened after this change, into this change. An
+	/// object moved by both changes moves straight from its first to its last
+	/// position.
+	fn merge(&mut self, later: Change) {
+		self.returnings.extend(later.returnings);
+		for (id, mv) in later.moves {
+			self.moves
+				.entry(id)
+				.and_modify(|earlier| {
+					earlier.to_coords = mv.to_coords;
+					earlier.to_angle = mv.to_angle;
+				})
+				.or_insert(mv);
+		}
+		self.summonings.extend(later.summonings);
+		self.creations.extend(later.creations);
+		self.destructions.extend(later.destructions);
+		self.blocked.extend(later.blocked);
+		self.crushed.extend(later.crushed);
+		for (idx, step) in later.platform_steps {
+			self.platform_steps
+				.entry(idx)
+				.and_modify(|earlier| {
+					earlier.to_coords = step.to_coords;
+					earlier.to = step.to;
+				})
+				.or_insert(step);
+		}
+		for (coords, tile_change) in later.tile_changes {
+			self.tile_changes
+				.entry(coords)
+				.and_modify(|earlier| earlier.to = tile_change.to)
+				.or_insert(tile_change);
+		}
+		// Toggling twice is the same as not toggling.
+		self.slide_toggles = self
+			.slide_toggles
+			.symmetric_difference(&later.slide_toggles)
+			.copied()
+			.collect();
+	}
+
 	fn reverse(self) -> Change {
 		Change {
+			creations: self.destructions,
+			destructions: self.creations,
 			returnings: self
 				.summonings
 				.into_iter()
@@ -993,10 +2763,38 @@ impl Change {
 				.into_iter()
 				.map(|(id, summon)| (id, summon.reverse()))
 				.collect(),
+			// Undoing a blocked push doesn't involve any pushing.
+			blocked: HashSet::new(),
+			// Nor does restoring a crushed object involve any crushing.
+			crushed: HashSet::new(),
+			slide_toggles: self.slide_toggles,
+			platform_steps: self
+				.platform_steps
+				.into_iter()
+				.map(|(idx, step)| (idx, step.reverse()))
+				.collect(),
+			tile_changes: self
+				.tile_changes
+				.into_iter()
+				.map(|(coords, tile_change)| (coords, tile_change.reverse()))
+				.collect(),
 		}
 	}
 }
 
+/// The tiles and objects of a [`Level`] at some point in time.
+pub struct Snapshot {
+	tiles: Vec<Tile>,
+	objects: HashMap<Coords, Object>,
+}
+
+/// The path a character has taken through a level.
+pub struct Trail {
+	pub color: CharacterColor,
+	/// The coordinates the character has occupied, from oldest to newest.
+	pub coords: Vec<Coords>,
+}
+
 /// A [`Change`] event. Note that `Change` itself can't be an [`Event`] because
 /// it's not [`Sync`].
 #[derive(Event, Deref)]
@@ -1012,6 +2810,8 @@ struct Team {
 	count: usize,
 	strength: i32,
 	blocked: bool,
+	/// Whether the team crushes the object just past its front.
+	crushes: bool,
 }
 
 impl Team {
@@ -1078,106 +2878,216 @@ impl PartialOrd for Team {
 	}
 }
 
-/// A bidirectional change, i.e. a pair inverse changes.
+/// A bidirectional change, i.e. a pair inverse changes, along with what
+/// caused it.
+#[derive(Clone)]
 struct BiChange {
 	forward: Arc<Change>,
 	reverse: Arc<Change>,
+	cause: Cause,
+	/// The level's [state hash](Level::state_hash) after the forward change.
+	state_hash: u64,
 }
 
-/// Makes a fresh copy of a simple test level.
-pub fn test_level() -> Level {
-	make_level(
-		r#"# # # # # # # # # 
-		   # . .0. . . . . # 
-		   # . . . . . . . # 
-		   # . . . . . . . # 
-		   # . .X.Y.Z. . . # 
-		   # . .X.Y. . . . # 
-		   # . .X. . . . . # 
-		   # . . . . . . . # 
-		   # # # # # # # # # "#,
-	)
-}
-
-/// Makes a fresh copy of a flat test level.
-pub fn test_level_short() -> Level {
-	make_level(
-		r#"# # # # # # # # # 
-		   # . .0. . . . . # 
-		   # # # # # # # # # "#,
-	)
-}
-
-/// Makes a fresh copy of a thin test level.
-pub fn test_level_thin() -> Level {
-	make_level(
-		r#"# # # 
-		   # .0# 
-		   # . # 
-		   # . # 
-		   # .X# 
-		   # .X# 
-		   # . # 
-		   # . # 
-		   # # # "#,
-	)
-}
-
-/// Makes a fresh copy of a large test level.
-pub fn test_level_large() -> Level {
-	make_level(
-		r#"# # # # # # # # # # # # # # # # # # # # # # 
-		   # . .0. . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . .X.Y.Z. . . . . . . . . . . . . . . . # 
-		   # . .X.Y. . . . . . . . . . . . . . . . . # 
-		   # . .X. . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # . . . . . . . . . . . . . . . . . . . . # 
-		   # # # # # # # # # # # # # # # # # # # # # # "#,
-	)
-}
-
-/// Makes a test level from a string. Each line is a level row, alternating
-/// between tiles and objects. Leading whitespace and blank lines are ignored.
-fn make_level(map: &str) -> Level {
-	let (mut width, mut height) = (0, 0);
-	let mut tiles = Vec::new();
-	let mut object_coords = Vec::new();
-	for (row, line) in map
-		.lines()
-		.map(|line| line.trim_start())
-		.filter(|line| !line.is_empty())
+/// What brought about a turn's [`Change`].
+#[derive(Clone)]
+pub enum Cause {
+	/// Each character's action that turn, in character order.
+	Actions(Vec<Action>),
+	/// An edit outside the normal rules. See [`Level::edit`].
+	Edit {
+		coords: Coords,
+		object: Option<Object>,
+	},
+}
+
+/// A problem found by [`Level::validate`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum LevelError {
+	/// The map's rows aren't all the same length.
+	NotRectangular,
+	NoCharacters,
+	/// The tile at the given coordinates is on the edge of the map but isn't
+	/// a wall or the void, so objects could leave the map from it.
+	OpenEdge(Coords),
+	/// There's an object inside a wall at the given coordinates.
+	ObjectOnWall(Coords),
+	/// There's an object in the void at the given coordinates.
+	ObjectInVoid(Coords),
+	/// There's an object on a track away from its platform at the given
+	/// coordinates.
+	ObjectOffPlatform(Coords),
+	/// There's an object in open water at the given coordinates.
+	ObjectInWater(Coords),
+	/// There's an object in unbridged lava at the given coordinates.
+	ObjectInLava(Coords),
+	/// The track including the given coordinates branches, or doesn't have
+	/// exactly one platform.
+	InvalidTrack(Coords),
+	/// The characters at the given coordinates have the same color.
+	DuplicateColor(Coords, Coords),
+	/// No character can reach the stairs.
+	UnreachableStairs,
+	/// The contents given for the object at the given coordinates aren't a
+	/// known prop, or there's no object there to hide them.
+	InvalidContents(Coords),
+	/// The starting character given for the given coordinates has an unknown
+	/// color, or its tile is out of bounds or already has an object.
+	InvalidCharacter(Coords),
+}
+
+impl Display for LevelError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LevelError::NotRectangular => {
+				write!(f, "rows are not all the same length")
+			}
+			LevelError::NoCharacters => write!(f, "there are no characters"),
+			LevelError::OpenEdge(coords) => {
+				write!(f, "open tile on the edge of the map at {coords:?}")
+			}
+			LevelError::ObjectOnWall(coords) => {
+				write!(f, "object inside a wall at {coords:?}")
+			}
+			LevelError::ObjectInVoid(coords) => {
+				write!(f, "object in the void at {coords:?}")
+			}
+			LevelError::ObjectOffPlatform(coords) => {
+				write!(f, "object on a track but not a platform at {coords:?}")
+			}
+			LevelError::ObjectInWater(coords) => {
+				write!(f, "object in open water at {coords:?}")
+			}
+			LevelError::ObjectInLava(coords) => {
+				write!(f, "object in lava at {coords:?}")
+			}
+			LevelError::InvalidTrack(coords) => write!(
+				f,
+				"track at {coords:?} isn't a line or loop with one platform"
+			),
+			LevelError::DuplicateColor(first, second) => write!(
+				f,
+				"characters at {first:?} and {second:?} have the same color"
+			),
+			LevelError::UnreachableStairs => {
+				write!(f, "no character can reach the stairs")
+			}
+			LevelError::InvalidContents(coords) => {
+				write!(f, "invalid contents for the object at {coords:?}")
+			}
+			LevelError::InvalidCharacter(coords) => {
+				write!(f, "invalid starting character at {coords:?}")
+			}
+		}
+	}
+}
+
+impl std::error::Error for LevelError {}
+
+/// A small change to a level's starting layout, for exploring variants of a
+/// puzzle. See [`Level::mutations`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mutation {
+	/// Moves the prop at `from` onto the open tile `to`, next to it.
+	MoveProp { from: Coords, to: Coords },
+	/// Builds a wall on an open floor tile.
+	AddWall(Coords),
+	/// Opens up a wall into floor.
+	RemoveWall(Coords),
+}
+
+impl Display for Mutation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Mutation::MoveProp { from, to } => {
+				write!(f, "moved the prop at {from:?} to {to:?}")
+			}
+			Mutation::AddWall(coords) => {
+				write!(f, "added a wall at {coords:?}")
+			}
+			Mutation::RemoveWall(coords) => {
+				write!(f, "removed the wall at {coords:?}")
+			}
+		}
+	}
+}
+
+/// Makes a large level crowded with crates and every color of character, for
+/// stress testing.
+pub fn stress_level() -> Level {
+	const WIDTH: usize = 40;
+	const HEIGHT: usize = 30;
+	let mut map = String::new();
+	for row in 0..HEIGHT {
+		for col in 0..WIDTH {
+			if row == 0 || col == 0 || row == HEIGHT - 1 || col == WIDTH - 1 {
+				map.push_str("# ");
+				continue;
+			}
+			map.push('.');
+			let color = row / 3;
+			map.push(
+				if col == 1 && row % 3 == 1 && color < CharacterColor::COUNT {
+					char::from(b'0' + color as u8)
+				} else if col > 2 && (row + col) % 3 == 0 {
+					if row % 2 == 0 {
+						'X'
+					} else {
+						'Y'
+					}
+				} else {
+					' '
+				},
+			);
+		}
+		map.push('\n');
+	}
+	make_level(&map)
+}
+
+/// Makes a level from a string. Each line is a level row, alternating
+/// between tiles and objects. Leading whitespace and blank lines are ignored,
+/// and a row's last object may be omitted if it's empty.
+///
+/// Tiles are `.` for floor, `#` for wall, `>` for stairs, `_` for the void,
+/// `~` for ice, `*` for pits, `=` for platform tracks, `+` for platforms on
+/// their tracks, `)` and `(` for clockwise and counterclockwise turntables,
+/// `%` and `&` for cracked floor before and after it's stepped on, `w` and `W`
+/// for open and bridged water, and `l` and `L` for open and bridged lava,
+/// while `a` through `h` are floors with open portals, in character color
+/// order. Unknown tiles are floor.
+///
+/// Objects are ` ` for none and `0` through `7` for characters, in color
+/// order, while `a` through `h` are sliding characters. Props use their
+/// [symbols](ObjectDescriptor::symbol), like `X` for a wooden crate.
+pub fn make_level(map: &str) -> Level {
+	let (mut width, mut height) = (0, 0);
+	let mut tiles = Vec::new();
+	let mut object_coords = Vec::new();
+	for (row, line) in map
+		.lines()
+		.map(|line| line.trim_start())
+		.filter(|line| !line.is_empty())
 		.enumerate()
 	{
 		height = height.max(row + 1);
-		for (col, tile_object) in line.as_bytes().chunks_exact(2).enumerate() {
+		for (col, tile_object) in line.as_bytes().chunks(2).enumerate() {
 			width = width.max(col + 1);
-			let (tile, object) = (tile_object[0], tile_object[1]);
-			tiles.push(match tile {
-				b'#' => Tile::Wall,
-				_ => Tile::Floor { portal_color: None },
-			});
+			let (tile, object) =
+				(tile_object[0], tile_object.get(1).copied().unwrap_or(b' '));
+			tiles.push(Tile::from_symbol(tile));
 			if let Some(object) = match object {
 				b'0'..=b'7' => Some(Object::Character(Character {
 					color: CharacterColor::from(object - b'0'),
 					sliding: false,
 					portal_coords: None,
 				})),
-				b'X' => Some(Object::WoodenCrate),
-				b'Y' => Some(Object::SteelCrate),
-				b'Z' => Some(Object::StoneBlock),
-				_ => None,
+				b'a'..=b'h' => Some(Object::Character(Character {
+					color: CharacterColor::from(object - SLIDING_SYMBOL),
+					sliding: true,
+					portal_coords: None,
+				})),
+				symbol => Object::prop_with_symbol(symbol),
 			} {
 				object_coords
 					.push((object, Coords::new(row as i32, col as i32)));
@@ -1200,10 +3110,17 @@ fn make_level(map: &str) -> Level {
 	let mut level = Level {
 		width,
 		height,
+		rules: Rules::default(),
+		lighting: Lighting::default(),
+		soundtrack: Soundtrack::default(),
+		solution: None,
+		meta: Arc::default(),
 		tiles,
+		platforms: Vec::new(),
 		objects_by_id: HashMap::new(),
 		object_ids_by_coords: HashMap::new(),
 		character_ids: BTreeSet::new(),
+		contents: Arc::default(),
 		next_object_id: Id(0),
 		history: Vec::new(),
 		turn: 0,
@@ -1217,12 +3134,14 @@ fn make_level(map: &str) -> Level {
 			angle: 0.0,
 		});
 	}
+	level.platforms = level.trace_tracks().0;
 	level
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::history;
 
 	const U: Action = Action::Push(Offset::UP);
 	const D: Action = Action::Push(Offset::DOWN);
@@ -1241,12 +3160,43 @@ mod tests {
 
 	/// Performs `actions` on `start` and asserts the result is equal to `end`.
 	fn test<const N: usize>(actions: [Action; N], start: &str, end: &str) {
+		test_with_rules(Rules::default(), actions, start, end);
+	}
+
+	/// Like [`test`], but with the given `rules` in effect.
+	fn test_with_rules<const N: usize>(
+		rules: Rules,
+		actions: [Action; N],
+		start: &str,
+		end: &str,
+	) {
 		let mut actual = make_level(start);
+		actual.rules = rules;
 		perform(&mut actual, actions);
 		let expected = make_level(end);
 		assert_eq!(actual, expected);
 	}
 
+	/// Makes a level from `map` where the character with the given `id` is
+	/// sliding.
+	fn make_sliding_level(map: &str, id: Id) -> Level {
+		let mut level = make_level(map);
+		level.character_by_id_mut(&id).sliding = true;
+		level
+	}
+
+	/// Like [`test`], but character 0 is sliding in both `start` and `end`.
+	fn test_sliding<const N: usize>(
+		actions: [Action; N],
+		start: &str,
+		end: &str,
+	) {
+		let mut actual = make_sliding_level(start, Id(0));
+		perform(&mut actual, actions);
+		let expected = make_sliding_level(end, Id(0));
+		assert_eq!(actual, expected);
+	}
+
 	// Push strength
 
 	#[test]
@@ -1279,6 +3229,1003 @@ mod tests {
 		test([R, R], ".0.1.Y. ", ". .0.1.Y");
 	}
 
+	#[test]
+	fn one_cannot_push_heavy_wooden_crate() {
+		let rules = Rules {
+			heavy_crates: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R], ".0.X. ", ".0.X. ");
+	}
+
+	#[test]
+	fn two_can_push_heavy_wooden_crate() {
+		let rules = Rules {
+			heavy_crates: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R, R], ".0.1.X. ", ". .0.1.X");
+	}
+
+	#[test]
+	fn stone_block_crushes_wooden_crate() {
+		let rules = Rules {
+			crushing: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R, R, R], ".0.1.2.Z.X# ", ". .0.1.2.Z# ");
+		// Without crushing, the crate adds too much weight.
+		test([R, R, R], ".0.1.2.Z.X. ", ".0.1.2.Z.X. ");
+	}
+
+	#[test]
+	fn characters_do_not_crush_crates() {
+		let rules = Rules {
+			crushing: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R], ".0.X# ", ".0.X# ");
+	}
+
+	#[test]
+	fn undoing_a_crush_restores_the_crushed_object() {
+		let start = ".0.1.2.Z.X# ";
+		let mut level = make_level(start);
+		level.rules.crushing = true;
+		perform(&mut level, [R, R, R]);
+		level.undo();
+		assert_eq!(level, make_level(start));
+		assert!(
+			level.object_at(Coords::new(0, 4)) == Some(Object::WOODEN_CRATE)
+		);
+	}
+
+	#[test]
+	fn crushed_crates_spill_their_contents() {
+		let crushing_level = |map| {
+			let mut level = make_level(map)
+				.with_contents([(Coords::new(0, 4), Object::STEEL_CRATE)]);
+			level.rules.crushing = true;
+			level
+		};
+		let start = ".0.1.2.Z.X. ";
+		let mut level = crushing_level(start);
+		perform(&mut level, [R, R, R]);
+		assert_eq!(level, make_level(". .0.1.2.Z.Y"));
+		level.undo();
+		assert_eq!(level, make_level(start));
+		// Contents with nowhere to go are lost with the crate.
+		let mut level = crushing_level(".0.1.2.Z.X# ");
+		perform(&mut level, [R, R, R]);
+		assert_eq!(level, make_level(". .0.1.2.Z# "));
+	}
+
+	// Sliding
+
+	#[test]
+	fn sliding_character_cannot_stop() {
+		test_sliding([Z], ".0. . ", ". .0. ");
+	}
+
+	#[test]
+	fn sliding_character_cannot_turn() {
+		test_sliding(
+			[U],
+			r#". . . 
+			   .0. . "#,
+			r#". . . 
+			   . .0. "#,
+		);
+	}
+
+	#[test]
+	fn sliding_character_cannot_push() {
+		test_sliding([R], ".0.X. ", ".0.X. ");
+		test_sliding([R, Z], ".0.1. ", ".0.1. ");
+	}
+
+	#[test]
+	fn sliding_character_stops_at_wall() {
+		test_sliding([R], ".0# ", ".0# ");
+	}
+
+	#[test]
+	fn sliding_character_does_not_contribute_strength() {
+		// A sliding 0 carries its own weight but adds no strength to 1's team.
+		let mut actual = make_sliding_level(".1.0.X. ", Id(0));
+		perform(&mut actual, [R, R]);
+		let expected = make_sliding_level(". .1.0.X", Id(0));
+		assert_eq!(actual, expected);
+
+		let mut actual = make_sliding_level(".1.0.X.X. ", Id(0));
+		perform(&mut actual, [R, R]);
+		let expected = make_sliding_level(".1.0.X.X. ", Id(0));
+		assert_eq!(actual, expected);
+	}
+
+	#[test]
+	fn sliding_character_can_be_pushed() {
+		let mut actual = make_sliding_level(".1.0. ", Id(0));
+		perform(&mut actual, [Z, R]);
+		let expected = make_sliding_level(". .1.0", Id(0));
+		assert_eq!(actual, expected);
+	}
+
+	// Trails
+
+	#[test]
+	fn trails_follow_moved_characters() {
+		let mut level = make_level(".0. . .X");
+		perform(&mut level, [R]);
+		perform(&mut level, [Z]);
+		perform(&mut level, [R]);
+		let trails = level.trails();
+		assert_eq!(trails.len(), 1);
+		assert_eq!(
+			trails[0].coords,
+			vec![Coords::new(0, 0), Coords::new(0, 1), Coords::new(0, 2)]
+		);
+	}
+
+	#[test]
+	fn trails_exclude_undone_turns() {
+		let mut level = make_level(".0. . ");
+		perform(&mut level, [R]);
+		perform(&mut level, [R]);
+		level.undo();
+		let trails = level.trails();
+		assert_eq!(
+			trails[0].coords,
+			vec![Coords::new(0, 0), Coords::new(0, 1)]
+		);
+	}
+
+	// Snapshots
+
+	#[test]
+	fn changed_coords_covers_moved_objects() {
+		let mut level = make_level(".0.X. . ");
+		let before = level.snapshot();
+		perform(&mut level, [R]);
+		perform(&mut level, [R]);
+		assert_eq!(
+			level.changed_coords(&before),
+			vec![
+				Coords::new(0, 0),
+				Coords::new(0, 1),
+				Coords::new(0, 2),
+				Coords::new(0, 3)
+			]
+		);
+		level.undo();
+		level.undo();
+		assert!(level.changed_coords(&before).is_empty());
+	}
+
+	// Editing
+
+	#[test]
+	fn edits_can_be_undone_and_redone() {
+		let mut level = make_level(".0. . ");
+		assert!(level
+			.edit(Coords::new(0, 1), Some(Object::STEEL_CRATE))
+			.is_some());
+		assert_eq!(level, make_level(".0.Y. "));
+		assert!(level.edit(Coords::new(0, 0), None).is_none());
+		assert!(level
+			.edit(
+				Coords::new(0, 2),
+				Some(Object::Character(Character {
+					color: CharacterColor::Green,
+					sliding: false,
+					portal_coords: None,
+				}))
+			)
+			.is_some());
+		assert_eq!(level, make_level(".0.Y.1"));
+		assert!(level.edit(Coords::new(0, 0), None).is_some());
+		assert_eq!(level, make_level(". .Y.1"));
+		level.undo();
+		level.undo();
+		assert_eq!(level, make_level(".0.Y. "));
+		level.redo();
+		level.redo();
+		assert_eq!(level, make_level(". .Y.1"));
+	}
+
+	#[test]
+	fn edits_only_place_on_empty_floor() {
+		let mut level = make_level(".0# .X");
+		// Wall
+		assert!(level
+			.edit(Coords::new(0, 1), Some(Object::WOODEN_CRATE))
+			.is_none());
+		// Occupied
+		assert!(level
+			.edit(Coords::new(0, 3), Some(Object::WOODEN_CRATE))
+			.is_none());
+		// Out of bounds
+		assert!(level
+			.edit(Coords::new(0, 4), Some(Object::WOODEN_CRATE))
+			.is_none());
+		assert_eq!(level, make_level(".0# .X"));
+	}
+
+	// Rules
+
+	#[test]
+	fn summons_land_adjacent_with_rule() {
+		let mut level = make_level(".0. . . ");
+		level.rules.adjacent_summons = true;
+		perform(&mut level, [Action::Summon(Offset::RIGHT)]);
+		assert!(matches!(
+			level.object_at(Coords::new(0, 1)),
+			Some(Object::Character(_))
+		));
+		assert!(level.object_at(Coords::new(0, 3)).is_none());
+	}
+
+	#[test]
+	fn summons_past_portal_limit_fizzle() {
+		let mut level = make_level(
+			".0. . \n\
+			 .1. . ",
+		);
+		level.rules.max_open_portals = Some(1);
+		let summon = Action::Summon(Offset::RIGHT);
+		perform(&mut level, [summon, summon]);
+		assert_eq!(level.open_portals(), 1);
+		assert_eq!(level.portal_room(), Some(0));
+		assert!(level.character_by_id(&Id(0)).portal_coords.is_some());
+		assert!(level.character_by_id(&Id(1)).portal_coords.is_none());
+	}
+
+	#[test]
+	fn undo_is_disallowed_with_rule() {
+		let mut level = make_level(".0. ");
+		level.rules.no_undo = true;
+		perform(&mut level, [R]);
+		assert!(level.undo().is_none());
+		assert_eq!(level, make_level(". .0"));
+	}
+
+	#[test]
+	fn undo_and_redo_availability_follows_history() {
+		let mut level = make_level(".0. ");
+		assert!(!level.can_undo() && !level.can_redo());
+		perform(&mut level, [R]);
+		assert!(level.can_undo() && !level.can_redo());
+		level.undo();
+		assert!(!level.can_undo() && level.can_redo());
+		level.rules.no_undo = true;
+		level.redo();
+		assert!(!level.can_undo());
+	}
+
+	#[test]
+	fn undo_preview_matches_undo_without_undoing() {
+		let mut level = make_level(".0.X. ");
+		assert!(level.undo_preview().is_none());
+		perform(&mut level, [R]);
+		let before = level.snapshot();
+		let previous = level.undo_preview().unwrap();
+		assert_eq!(previous, make_level(".0.X. "));
+		assert_eq!(
+			previous.changed_coords(&before),
+			[Coords::new(0, 0), Coords::new(0, 1), Coords::new(0, 2)],
+		);
+		assert_eq!(level.turn(), 1);
+		level.undo();
+		assert_eq!(level, previous);
+	}
+
+	#[test]
+	fn summons_lock_history_until_the_portal_closes() {
+		let mut level = make_level(". .0. \n. . . ");
+		level.rules.summons_lock_history = true;
+		perform(&mut level, [Action::Summon(Offset::RIGHT)]);
+		assert_eq!(level.undo_floor(), 1);
+		assert!(level.undo().is_none());
+		perform(&mut level, [Z, D]);
+		perform(&mut level, [R, Z]);
+		assert!(level.undo().is_some());
+		assert!(level.redo().is_some());
+		perform(&mut level, [Action::Return, Z]);
+		assert_eq!(level.undo_floor(), 0);
+		assert!(level.undo().is_some());
+	}
+
+	// Previews
+
+	#[test]
+	fn previews_do_not_modify_the_level() {
+		let level = make_level(".0.X. ");
+		let change = level.preview(vec![(Id(0), R)]);
+		assert_eq!(change.moves.len(), 2);
+		assert_eq!(level, make_level(".0.X. "));
+		assert_eq!(level.turn(), 0);
+	}
+
+	// Changes
+
+	#[test]
+	fn merged_moves_can_be_reversed() {
+		let mut level = make_level(".0. . ");
+		let mut change = Change::default();
+		for _ in 0..2 {
+			let moves =
+				HashMap::from([(Id(0), level.get_move(Id(0), Offset::RIGHT))]);
+			level.apply_moves(&moves);
+			change.merge(Change {
+				moves,
+				..Change::default()
+			});
+		}
+		assert_eq!(level, make_level(". . .0"));
+		level.apply(&change.reverse());
+		assert_eq!(level, make_level(".0. . "));
+	}
+
+	#[test]
+	fn blocked_pushes_are_recorded_but_not_reversed() {
+		let mut level = make_level(".0.Z");
+		let change = level.update(vec![(Id(0), R)]);
+		assert!(change.moves.is_empty());
+		assert!(change.blocked.contains(&Id(0)));
+		assert!(level.undo().unwrap().blocked.is_empty());
+	}
+
+	// Level files
+
+	#[test]
+	fn trailing_empty_objects_may_be_omitted() {
+		assert_eq!(make_level("# .0.\n# . ."), make_level("# .0. \n# . . "));
+	}
+
+	/// Reads the bundled level file with the given `name`.
+	fn read_level_file(name: &str) -> Level {
+		crate::level_asset::LevelAsset::read(&format!(
+			"assets/levels/{name}.level.ron"
+		))
+		.unwrap()
+		.to_level()
+	}
+
+	#[test]
+	fn embedded_levels_are_the_campaign_levels() {
+		use crate::{campaign::Campaign, level_asset};
+		let paths: Vec<&str> = (0..level_asset::EMBEDDED_LEVEL_COUNT)
+			.map(|idx| level_asset::embedded_level_path(idx).unwrap())
+			.collect();
+		assert_eq!(paths, Campaign::load().level_paths());
+		for idx in 0..level_asset::EMBEDDED_LEVEL_COUNT {
+			assert!(level_asset::embedded_level(idx).is_some());
+		}
+	}
+
+	#[test]
+	fn tutorial_demos_show_their_mechanics() {
+		use crate::{level_asset::Mechanic, tutorials};
+		for mechanic in [
+			Mechanic::Summoning,
+			Mechanic::Returning,
+			Mechanic::HeavyObjects,
+			Mechanic::Sliding,
+		] {
+			let (mut level, turns) = tutorials::demo(mechanic).unwrap();
+			for actions in turns {
+				assert_eq!(actions.len(), level.character_count());
+				let hash = level.state_hash();
+				let actors = level
+					.characters_by_id()
+					.map(|(id, _)| *id)
+					.zip(actions.iter().copied())
+					.collect();
+				level.update(actors);
+				assert_ne!(level.state_hash(), hash, "{mechanic:?} demo");
+			}
+		}
+		let (level, _) = tutorials::demo(Mechanic::Sliding).unwrap();
+		assert_eq!(tutorials::mechanics_in(&level), [Mechanic::Sliding]);
+	}
+
+	#[test]
+	fn bundled_solutions_are_parsed() {
+		let level = read_level_file("short");
+		let solution = level.solution().unwrap();
+		assert_eq!(solution.len(), 5);
+		assert!(solution.iter().all(|actions| actions == &[R]));
+	}
+
+	#[test]
+	fn bundled_solutions_reach_the_stairs() {
+		let mut level = read_level_file("short");
+		assert!(!level.has_character_on_stairs());
+		for actions in level.solution().unwrap().to_vec() {
+			perform(&mut level, [actions[0]]);
+		}
+		assert!(level.has_character_on_stairs());
+	}
+
+	#[test]
+	fn maps_can_have_sliding_characters() {
+		assert_eq!(make_level(".a. "), make_sliding_level(".0. ", Id(0)));
+		assert_eq!(make_sliding_level(".0. ", Id(0)).to_text(), ".a. \n");
+	}
+
+	#[test]
+	fn characters_slide_across_ice() {
+		let mut level = make_level(".0~ ~ . . ");
+		perform(&mut level, [R]);
+		assert!(level.character_by_id(&Id(0)).sliding);
+		perform(&mut level, [Z]);
+		perform(&mut level, [Z]);
+		assert_eq!(level.character_id_at(Coords::new(0, 3)), Some(Id(0)));
+		assert!(!level.character_by_id(&Id(0)).sliding);
+		perform(&mut level, [Z]);
+		assert_eq!(level.character_id_at(Coords::new(0, 3)), Some(Id(0)));
+		level.undo();
+		level.undo();
+		assert!(level.character_by_id(&Id(0)).sliding);
+	}
+
+	#[test]
+	fn tile_weights_follow_objects_and_rules() {
+		let mut level = make_level(".0.X.Y. ");
+		let weights = level.tile_weights();
+		assert_eq!(weights.len(), 3);
+		assert_eq!(weights[&Coords::new(0, 0)], 1);
+		assert_eq!(weights[&Coords::new(0, 1)], 1);
+		assert_eq!(weights[&Coords::new(0, 2)], 2);
+		level.rules.heavy_crates = true;
+		assert_eq!(level.tile_weights()[&Coords::new(0, 2)], 4);
+		let mut level = make_level(".0.X. ");
+		perform(&mut level, [R]);
+		let weights = level.tile_weights();
+		assert!(!weights.contains_key(&Coords::new(0, 0)));
+		assert_eq!(weights[&Coords::new(0, 2)], 1);
+	}
+
+	#[test]
+	fn pits_destroy_objects() {
+		let mut level = make_level(".0.X* ");
+		assert!(level.danger_coords().contains(&Coords::new(0, 2)));
+		perform(&mut level, [R]);
+		assert!(level.object_at(Coords::new(0, 2)).is_none());
+		assert!(
+			level.tile_at(Coords::new(0, 2))
+				== Tile::Floor { portal_color: None }
+		);
+		assert!(!level.has_lost_character());
+		level.undo();
+		assert!(
+			level.object_at(Coords::new(0, 1)) == Some(Object::WOODEN_CRATE)
+		);
+		assert!(level.tile_at(Coords::new(0, 2)) == Tile::Pit);
+		level.redo();
+		assert!(
+			level.tile_at(Coords::new(0, 2))
+				== Tile::Floor { portal_color: None }
+		);
+	}
+
+	#[test]
+	fn characters_fall_into_pits() {
+		let mut level = make_level(".0* .1");
+		perform(&mut level, [R, Z]);
+		assert!(level.character_id_at(Coords::new(0, 1)).is_none());
+		assert!(level.tile_at(Coords::new(0, 1)) == Tile::Pit);
+		assert!(level.has_lost_character());
+		level.undo();
+		assert_eq!(level.character_id_at(Coords::new(0, 0)), Some(Id(0)));
+		assert!(!level.has_lost_character());
+	}
+
+	#[test]
+	fn cracked_floor_collapses_once_left() {
+		let cracked = |stepped_on| Tile::Cracked { stepped_on };
+		let mut level = make_level(".0% . ");
+		perform(&mut level, [R]);
+		assert!(level.tile_at(Coords::new(0, 1)) == cracked(true));
+		perform(&mut level, [R]);
+		assert!(level.tile_at(Coords::new(0, 1)) == Tile::Pit);
+		assert!(level.danger_coords().contains(&Coords::new(0, 1)));
+		level.undo();
+		assert!(level.tile_at(Coords::new(0, 1)) == cracked(true));
+		assert_eq!(level.character_id_at(Coords::new(0, 1)), Some(Id(0)));
+		level.undo();
+		assert!(level.tile_at(Coords::new(0, 1)) == cracked(false));
+	}
+
+	#[test]
+	fn cracked_floor_collapses_under_starting_objects() {
+		let mut level = make_level("%0. ");
+		perform(&mut level, [R]);
+		assert!(level.tile_at(Coords::new(0, 0)) == Tile::Pit);
+		level.undo();
+		assert!(
+			level.tile_at(Coords::new(0, 0))
+				== Tile::Cracked { stepped_on: false }
+		);
+	}
+
+	#[test]
+	fn floating_crates_bridge_water() {
+		let start = ".0.Xw . ";
+		let mut level = make_level(start);
+		assert!(level.danger_coords().contains(&Coords::new(0, 2)));
+		perform(&mut level, [R]);
+		assert_eq!(level, make_level(". .0W . "));
+		assert!(!level.danger_coords().contains(&Coords::new(0, 2)));
+		perform(&mut level, [R]);
+		assert_eq!(level, make_level(". . W0. "));
+		level.undo();
+		level.undo();
+		assert_eq!(level, make_level(start));
+	}
+
+	#[test]
+	fn heavy_props_sink_in_water() {
+		test([R, R], ".0.1.Yw ", ". .0.1w ");
+	}
+
+	#[test]
+	fn characters_cannot_enter_open_water() {
+		test([R], ".0w ", ".0w ");
+		assert_eq!(
+			make_level("# # # # \n# .0w1# \n# # # # ").validate(),
+			[LevelError::ObjectInWater(Coords::new(1, 2))],
+		);
+	}
+
+	#[test]
+	fn stone_bridges_lava() {
+		// Stone blocks take three characters to push.
+		let start = ".0.1.2.Zl . ";
+		let mut level = make_level(start);
+		assert!(level.danger_coords().contains(&Coords::new(0, 4)));
+		perform(&mut level, [R, R, R]);
+		assert_eq!(level, make_level(". .0.1.2L . "));
+		assert!(!level.danger_coords().contains(&Coords::new(0, 4)));
+		perform(&mut level, [R, R, R]);
+		assert_eq!(level, make_level(". . .0.1L2. "));
+		level.undo();
+		level.undo();
+		assert_eq!(level, make_level(start));
+	}
+
+	#[test]
+	fn wooden_crates_burn_in_lava() {
+		test([R], ".0.Xl ", ". .0l ");
+	}
+
+	#[test]
+	fn characters_die_in_lava() {
+		let mut level = make_level(".0l .1");
+		perform(&mut level, [R, Z]);
+		assert!(level.character_id_at(Coords::new(0, 1)).is_none());
+		assert!(
+			level.tile_at(Coords::new(0, 1)) == Tile::Lava { bridged: false }
+		);
+		assert!(level.has_lost_character());
+		assert_eq!(
+			make_level("# # # # \n# .0l1# \n# # # # ").validate(),
+			[LevelError::ObjectInLava(Coords::new(1, 2))],
+		);
+	}
+
+	#[test]
+	fn map_edges_block_pushes() {
+		test([R], "> . .0", "> . .0");
+		// Pushing off an inner edge doesn't wrap around into the next row.
+		test([R], ". .0\n. . ", ". .0\n. . ");
+		test([R], ". .0.X", ". .0.X");
+		test([L], ".0. ", ".0. ");
+	}
+
+	#[test]
+	fn void_blocks_pushes_and_summons() {
+		let mut level = make_level("# . _ .0. # ");
+		assert!(level.summon_tile(Coords::new(0, 3), Offset::LEFT).is_none());
+		assert_eq!(
+			level.summon_tile(Coords::new(0, 3), Offset::RIGHT),
+			Some(Coords::new(0, 4)),
+		);
+		perform(&mut level, [L]);
+		assert!(level.object_at(Coords::new(0, 3)).is_some());
+		assert!(level.object_at(Coords::new(0, 2)).is_none());
+		assert!(make_level("_0> ")
+			.validate()
+			.contains(&LevelError::ObjectInVoid(Coords::new(0, 0))));
+	}
+
+	#[test]
+	fn state_hash_is_stable() {
+		// Saved in replays, so it mustn't change between builds.
+		assert_eq!(make_level(". .0.X. . ").state_hash(), 0x97f770c6439e0083);
+	}
+
+	#[test]
+	fn checksum_identifies_the_starting_level() {
+		let level = make_level(". .0.X. . ");
+		let checksum = level.checksum();
+		let mut played = level.clone();
+		perform(&mut played, [R]);
+		assert_eq!(played.checksum(), checksum);
+		assert_eq!(make_level(". .0.X. . ").checksum(), checksum);
+		assert_ne!(make_level(". .0.Y. . ").checksum(), checksum);
+		let hiding = level
+			.clone()
+			.with_contents([(Coords::new(0, 2), Object::STEEL_CRATE)]);
+		assert_ne!(hiding.checksum(), checksum);
+	}
+
+	#[test]
+	fn mutations_vary_the_starting_layout() {
+		let mut level = make_level(
+			"# # # # # # \n\
+			 # .0. .X# # \n\
+			 # # # # # # ",
+		);
+		perform(&mut level, [R]);
+		let from = Coords::new(1, 3);
+		let to = Coords::new(1, 2);
+		assert_eq!(
+			level.mutations(),
+			[
+				Mutation::AddWall(to),
+				Mutation::MoveProp { from, to },
+				Mutation::RemoveWall(Coords::new(1, 4)),
+			]
+		);
+		let moved = level.mutated(Mutation::MoveProp { from, to });
+		assert_eq!(moved.turn(), 0);
+		assert_eq!(
+			moved,
+			make_level(
+				"# # # # # # \n\
+				 # .0.X. # # \n\
+				 # # # # # # ",
+			)
+		);
+	}
+
+	#[test]
+	fn platforms_carry_objects_back_and_forth() {
+		let start = ".0+ = . ";
+		let mut level = make_level(start);
+		perform(&mut level, [R]);
+		assert_eq!(level, make_level(". = +0. "));
+		perform(&mut level, [Z]);
+		assert_eq!(level, make_level(". +0= . "));
+		let hash = level.state_hash();
+		let read = Level::from_text(&level.to_text()).unwrap();
+		assert_eq!(read.state_hash(), hash);
+		level.undo();
+		level.undo();
+		assert_eq!(level, make_level(start));
+	}
+
+	#[test]
+	fn cannot_push_onto_arriving_platform() {
+		test([R], ".0= + ", ".0+ = ");
+	}
+
+	#[test]
+	fn platforms_circle_looped_tracks() {
+		let mut level = make_level(
+			"+ = .0\n\
+			 = = . ",
+		);
+		for coords in [(1, 0), (1, 1), (0, 1), (0, 0)] {
+			perform(&mut level, [Z]);
+			let (row, col) = coords;
+			assert_eq!(level.platforms()[0].coords(), Coords::new(row, col));
+		}
+		assert!(make_level("+ + .0")
+			.validate()
+			.contains(&LevelError::InvalidTrack(Coords::new(0, 0))));
+	}
+
+	#[test]
+	fn turntables_turn_their_occupants() {
+		let mut level = make_level(")0. ");
+		let id = level.character_id_at(Coords::new(0, 0)).unwrap();
+		perform(&mut level, [Z]);
+		assert_eq!(level.facing(&id), Offset::DOWN);
+		perform(&mut level, [Z]);
+		assert_eq!(level.facing(&id), Offset::LEFT);
+		let mv = history::turns(&level).last().unwrap().change.moves[&id];
+		assert_eq!(mv.from_coords, mv.to_coords);
+		level.undo();
+		assert_eq!(level.facing(&id), Offset::DOWN);
+	}
+
+	#[test]
+	fn turntables_turn_objects_arriving_on_them() {
+		let mut level = make_level(".0( ");
+		perform(&mut level, [R]);
+		let id = level.character_id_at(Coords::new(0, 1)).unwrap();
+		assert_eq!(level.facing(&id), Offset::UP);
+	}
+
+	#[test]
+	fn level_text_round_trips() {
+		let mut level = make_level(
+			"# # # # # # \n\
+			 # . .0.X. # \n\
+			 # . .1. . # \n\
+			 # # . > # # ",
+		);
+		perform(&mut level, [Action::Summon(Offset::LEFT), D]);
+		let text = level.to_text();
+		assert!(text.contains("---"));
+		assert!(text.contains(" portal "));
+		let copy = Level::from_text(&text).unwrap();
+		assert_eq!(copy.to_text(), text);
+		assert_eq!(format!("{copy:?}"), format!("{level:?}"));
+	}
+
+	#[test]
+	fn starting_characters_act_in_color_order() {
+		use crate::level_asset::{
+			LevelAsset, LevelAssetError, StartingCharacter,
+		};
+		let mut asset = LevelAsset {
+			map: "# # # # # \n# . .1. # \n# # # # # ".into(),
+			characters: vec![StartingCharacter {
+				coords: (1, 3),
+				color: 0,
+				angle: std::f32::consts::PI,
+				sliding: false,
+				portal: Some((1, 1)),
+			}],
+			..LevelAsset::default()
+		};
+		let level = asset.to_level();
+		let (id, character) = level.characters_by_id().next().unwrap();
+		assert!(character.color == CharacterColor::Green);
+		assert_eq!(character.portal_coords, Some(Coords::new(1, 1)));
+		assert_eq!(level.facing(id), Offset::LEFT);
+		assert_eq!(level.character_id_at(Coords::new(1, 3)), Some(*id));
+		asset.characters[0].coords = (1, 2);
+		assert!(matches!(
+			asset.validated(),
+			Err(LevelAssetError::Level(errors))
+				if errors == [LevelError::InvalidCharacter(Coords::new(1, 2))],
+		));
+	}
+
+	#[test]
+	fn unversioned_level_files_are_version_1() {
+		use crate::level_asset::{LevelAsset, FORMAT_VERSION};
+		let asset: LevelAsset =
+			ron::de::from_str(r#"(name: "Old", map: "+ = )0% ")"#).unwrap();
+		assert_eq!(asset.version, 1);
+		let asset = asset.migrated().unwrap();
+		assert_eq!(asset.version, FORMAT_VERSION);
+		// Tracks and turntables predate versioning, unlike cracked floor.
+		assert_eq!(asset.map, "+ = )0. \n");
+	}
+
+	#[test]
+	fn practice_levels_start_from_the_current_position() {
+		use crate::level_asset::LevelAsset;
+		let mut level = make_level(
+			"# # # # # # \n\
+			 # . .0.X. # \n\
+			 # . . . . # \n\
+			 # . . > . # \n\
+			 # # # # # # ",
+		);
+		perform(&mut level, [Action::Summon(Offset::LEFT)]);
+		let asset = LevelAsset::practice(&level, None).validated().unwrap();
+		assert!(asset.state.is_some());
+		let practice = asset.to_level();
+		assert_eq!(practice.turn(), 0);
+		assert_eq!(practice.to_text(), level.to_text());
+	}
+
+	#[test]
+	fn level_text_rejects_invalid_states() {
+		let text = "# # # \n# .0# \n# # # \n---\n1 1 portal\n";
+		assert_eq!(
+			Level::from_text(text).unwrap_err(),
+			LevelTextError::InvalidState(5),
+		);
+		let text = "# # # \n# .0# \n# # # \n---\n0 0 sliding\n";
+		assert!(Level::from_text(text).is_err());
+	}
+
+	#[test]
+	fn bundled_levels_have_metadata() {
+		let level = read_level_file("short");
+		assert_eq!(level.meta().name, "Flat");
+		assert_eq!(level.meta().par, Some(5));
+	}
+
+	#[test]
+	fn scores_fall_off_past_par() {
+		let meta = LevelMeta {
+			par: Some(5),
+			..default()
+		};
+		assert_eq!(meta.score(4), LevelMeta::MAX_SCORE);
+		assert_eq!(meta.score(5), LevelMeta::MAX_SCORE);
+		assert_eq!(meta.score(10), LevelMeta::MAX_SCORE / 2);
+		assert_eq!(LevelMeta::default().score(100), LevelMeta::MAX_SCORE);
+	}
+
+	#[test]
+	fn localized_metadata_falls_back_to_base_language() {
+		let meta = LevelMeta {
+			name: "Flat".to_string(),
+			description: Some("Walk to the stairs.".to_string()),
+			translations: [(
+				"fr".to_string(),
+				LevelText {
+					name: Some("Plat".to_string()),
+					description: None,
+				},
+			)]
+			.into(),
+			..default()
+		};
+		assert_eq!(meta.localized_name("fr"), "Plat");
+		assert_eq!(meta.localized_name("fr-CA"), "Plat");
+		assert_eq!(meta.localized_name("de"), "Flat");
+		assert_eq!(
+			meta.localized_description("fr"),
+			Some("Walk to the stairs.")
+		);
+	}
+
+	#[test]
+	fn tiled_maps_are_imported() {
+		let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+			<map orientation="orthogonal" width="4" height="3"
+				tilewidth="16" tileheight="16" infinite="0">
+				<tileset firstgid="1" name="tiles" tilecount="3">
+					<tile id="1" type="wall"/>
+					<tile id="2" type="stairs"/>
+				</tileset>
+				<layer id="1" name="Tiles" width="4" height="3">
+					<data encoding="csv">2,2,2,2,2,1,3,0,2,2,2,2</data>
+				</layer>
+				<objectgroup id="2" name="Objects">
+					<object id="1" type="character" x="16" y="16"/>
+					<object id="2" type="steel_crate" gid="1" x="32" y="32"/>
+				</objectgroup>
+			</map>"#;
+		let map = crate::tiled::import_map(tmx).unwrap();
+		assert_eq!(
+			make_level(&map),
+			make_level("# # # # \n# .0>Y# \n# # # # ")
+		);
+	}
+
+	// Replays
+
+	#[test]
+	fn replaying_causes_reproduces_state() {
+		let mut level = make_level(".0.X. . ");
+		perform(&mut level, [R]);
+		perform(&mut level, [Z]);
+		perform(&mut level, [R]);
+		let mut replayed = level.rewound();
+		assert_eq!(replayed, make_level(".0.X. . "));
+		for turn in history::taken(&level) {
+			let Cause::Actions(actions) = turn.cause else {
+				panic!("unexpected edit");
+			};
+			let actors = replayed
+				.character_ids
+				.iter()
+				.copied()
+				.zip(actions.iter().copied())
+				.collect();
+			replayed.update(actors);
+			assert_eq!(replayed.state_hash(), turn.state_hash);
+		}
+		assert_eq!(replayed, level);
+	}
+
+	#[test]
+	fn object_actions_include_undone_turns() {
+		let mut level = make_level(".0.1. . . ");
+		let first = level.character_id_at(Coords::new(0, 0)).unwrap();
+		let second = level.character_id_at(Coords::new(0, 1)).unwrap();
+		perform(&mut level, [Z, R]);
+		perform(&mut level, [R, Z]);
+		perform(&mut level, [L, Z]);
+		level.undo();
+		assert!(
+			history::object_actions(&level, first)
+				== vec![(0, Z), (1, R), (2, L)]
+		);
+		assert!(
+			history::object_actions(&level, second)
+				== vec![(0, R), (1, Z), (2, Z)]
+		);
+	}
+
+	#[test]
+	fn bundled_levels_are_valid() {
+		for name in ["test", "short", "thin", "large"] {
+			assert_eq!(read_level_file(name).validate(), vec![]);
+		}
+	}
+
+	#[test]
+	fn validation_reports_problems() {
+		assert_eq!(
+			make_level("# # # \n# . \n# # # ").validate(),
+			vec![LevelError::NotRectangular]
+		);
+		assert_eq!(
+			make_level("# # # \n# .X# \n# # # ").validate(),
+			vec![LevelError::NoCharacters]
+		);
+		assert_eq!(
+			make_level("# # # # \n# .0#X# \n# # # # ").validate(),
+			vec![LevelError::ObjectOnWall(Coords::new(1, 2))]
+		);
+		assert!(matches!(
+			make_level("# # # # # \n# .0. .0# \n# # # # # ").validate()[..],
+			[LevelError::DuplicateColor(..)]
+		));
+		assert_eq!(
+			make_level("# # # # # \n# .0# > # \n# # # # # ").validate(),
+			vec![LevelError::UnreachableStairs]
+		);
+		assert_eq!(
+			make_level("> . .0").validate(),
+			vec![
+				LevelError::OpenEdge(Coords::new(0, 0)),
+				LevelError::OpenEdge(Coords::new(0, 1)),
+				LevelError::OpenEdge(Coords::new(0, 2)),
+			]
+		);
+	}
+
+	// Summoning
+
+	#[test]
+	fn summons_are_summoned() {
+		let mut level = make_level(".0. . . ");
+		perform(&mut level, [Action::Summon(Offset::RIGHT)]);
+		let summon_id = level.object_ids_by_coords[&Coords::new(0, 3)];
+		assert!(level.is_summoned(&summon_id));
+		assert!(!level.is_summoned(&Id(0)));
+		level.undo();
+		assert!(!level.is_summoned(&summon_id));
+	}
+
+	// Stuck detection
+
+	#[test]
+	fn boxed_in_character_is_stuck() {
+		let level = make_level(
+			r#"# # # # 
+			   # .0.Z# 
+			   # # # # "#,
+		);
+		assert!(level.is_stuck(&Id(0)));
+	}
+
+	#[test]
+	fn character_that_can_push_is_not_stuck() {
+		let mut level = make_level(
+			r#"# # # # # 
+			   # .0.X. # 
+			   # # # # # "#,
+		);
+		// Rule out summoning and returning by giving 0 a portal elsewhere.
+		level.character_by_id_mut(&Id(0)).portal_coords =
+			Some(Coords::new(0, 0));
+		assert!(!level.is_stuck(&Id(0)));
+	}
+
 	// Blocking
 
 	#[test]
@@ -1286,6 +4233,25 @@ mod tests {
 		test([R, R, L], r#".0.1.2"#, r#".0.1.2"#);
 	}
 
+	#[test]
+	fn opposing_characters_swap_with_rule() {
+		let rules = Rules {
+			swap_places: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R, L], r#".0.1"#, r#".1.0"#);
+	}
+
+	#[test]
+	fn only_adjacent_opposing_characters_swap_with_rule() {
+		// 0 is still blocked by 2, but 1 and 2 trade places.
+		let rules = Rules {
+			swap_places: true,
+			..Rules::default()
+		};
+		test_with_rules(rules, [R, R, L], r#".0.1.2"#, r#".0.2.1"#);
+	}
+
 	#[test]
 	fn orthogonal_team_blocks() {
 		// Although the rightward team is stronger, it's blocked regardless of
@@ -0,0 +1,835 @@
+use std::{
+	collections::{BTreeMap, HashSet},
+	fmt::{self, Display},
+	path::{Path, PathBuf},
+};
+
+use bevy::{
+	asset::{
+		io::{AssetSourceBuilder, Reader},
+		AssetLoader, LoadContext, LoadState,
+	},
+	prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	assets::GameAssets,
+	audio::{self, Soundtrack},
+	campaign::Campaign,
+	level::{
+		self, Character, CharacterColor, Coords, Level, LevelError, LevelMeta,
+		LevelTextError, Object, Rules,
+	},
+	lighting::{LightingOverrides, LightingPreset},
+	locale::{self, LevelText, Translations},
+	pack::{LevelPack, PACK_EXTENSION},
+	replay,
+	states::GameState,
+};
+
+/// The current version of the level file format. Each version's changes are
+/// migrated from the version before it when older files load:
+///
+/// 1. The format as of platform tracks (`=`, `+`) and turntables (`(`, `)`).
+/// 2. Adds cracked floor (`%`, `&`) to maps. In version 1 maps, those were
+///    unknown tiles, which are floor.
+/// 3. Adds water (`w`, `W`) to maps. In version 2 maps, those were unknown
+///    tiles, which are floor.
+/// 4. Adds lava (`l`, `L`) to maps. In version 3 maps, those were unknown
+///    tiles, which are floor.
+pub const FORMAT_VERSION: u32 = 4;
+
+/// A migration from one version of the level file format to the next, which
+/// returns the number of tiles it rewrote.
+type Migration = fn(&mut LevelAsset) -> usize;
+
+/// Migrations from each version of the level file format to the next,
+/// starting from version 1.
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize - 1] =
+	[migrate_v1, migrate_v2, migrate_v3];
+
+/// A level as authored in a `.level.ron` file.
+#[derive(Asset, TypePath, Serialize, Deserialize)]
+pub struct LevelAsset {
+	/// The version of the file format the level was written in. Files without
+	/// one are version 1. See [`FORMAT_VERSION`].
+	#[serde(default = "first_version")]
+	pub version: u32,
+	pub name: String,
+	/// The level's tiles and objects, in the format of [`level::make_level`].
+	pub map: String,
+	/// The starting states of objects and platforms in the map, if any aren't
+	/// in their default states, in the format of [`Level::state_text`].
+	#[serde(default)]
+	pub state: Option<String>,
+	#[serde(default)]
+	pub lighting: LightingPreset,
+	/// Changes to the lighting preset, for this level.
+	#[serde(default)]
+	pub lighting_overrides: LightingOverrides,
+	/// Key of the level's music track, if it overrides its lighting's.
+	#[serde(default)]
+	pub music: Option<String>,
+	/// Key of the level's ambience track, if it overrides its lighting's.
+	#[serde(default)]
+	pub ambience: Option<String>,
+	/// A shortest known solution, in the format of [`replay::parse_turns`].
+	#[serde(default)]
+	pub solution: Option<String>,
+	#[serde(default)]
+	pub tags: LevelTags,
+	/// A sentence or two introducing the level.
+	#[serde(default)]
+	pub description: Option<String>,
+	/// The number of turns a good solution takes. Defaults to the length of
+	/// the solution, if there is one.
+	#[serde(default)]
+	pub par: Option<usize>,
+	/// The name and description in other languages, keyed by language code.
+	#[serde(default)]
+	pub translations: Translations<LevelText>,
+	/// The most portals that can be open at once, if limited. See
+	/// [`Rules::max_open_portals`].
+	#[serde(default)]
+	pub max_open_portals: Option<usize>,
+	/// Props hidden inside objects in the map, as the map symbols of the
+	/// props keyed by the row and column of the objects hiding them. See
+	/// [`Level::with_contents`].
+	#[serde(default)]
+	pub contents: BTreeMap<(i32, i32), char>,
+	/// Characters the level starts with in states a map digit can't give,
+	/// in addition to those in the map.
+	#[serde(default)]
+	pub characters: Vec<StartingCharacter>,
+}
+
+fn first_version() -> u32 {
+	1
+}
+
+impl Default for LevelAsset {
+	fn default() -> LevelAsset {
+		LevelAsset {
+			version: FORMAT_VERSION,
+			name: String::new(),
+			map: String::new(),
+			state: None,
+			lighting: LightingPreset::default(),
+			lighting_overrides: LightingOverrides::default(),
+			music: None,
+			ambience: None,
+			solution: None,
+			tags: LevelTags::default(),
+			description: None,
+			par: None,
+			translations: Translations::default(),
+			max_open_portals: None,
+			contents: BTreeMap::new(),
+			characters: Vec::new(),
+		}
+	}
+}
+
+/// Replaces version 1 map tiles that newer versions gave meaning to with the
+/// floor they stood for.
+fn migrate_v1(asset: &mut LevelAsset) -> usize {
+	floor_tiles(asset, "%&")
+}
+
+/// Replaces version 2 map tiles that newer versions gave meaning to with the
+/// floor they stood for.
+fn migrate_v2(asset: &mut LevelAsset) -> usize {
+	floor_tiles(asset, "wW")
+}
+
+/// Replaces version 3 map tiles that newer versions gave meaning to with the
+/// floor they stood for.
+fn migrate_v3(asset: &mut LevelAsset) -> usize {
+	floor_tiles(asset, "lL")
+}
+
+/// Replaces the map tiles with any of the given `symbols` with floor, and
+/// returns how many it replaced.
+fn floor_tiles(asset: &mut LevelAsset, symbols: &str) -> usize {
+	let mut replaced = 0;
+	let mut map = String::new();
+	for line in asset.map.lines() {
+		// Rows are read after their indentation, alternating between tiles
+		// and objects, as in level::make_level.
+		let row = line.trim_start();
+		map.push_str(&line[..line.len() - row.len()]);
+		for (idx, symbol) in row.chars().enumerate() {
+			if idx % 2 == 0 && symbols.contains(symbol) {
+				replaced += 1;
+				map.push('.');
+			} else {
+				map.push(symbol);
+			}
+		}
+		map.push('\n');
+	}
+	asset.map = map;
+	replaced
+}
+
+/// Descriptive tags for browsing levels.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LevelTags {
+	/// How hard the level is, from 1 up, or 0 if unrated.
+	pub difficulty: u32,
+	/// The mechanics the level requires.
+	pub mechanics: Vec<Mechanic>,
+	pub author: Option<String>,
+}
+
+/// A character a level starts with, in a state of its own.
+#[derive(Serialize, Deserialize)]
+pub struct StartingCharacter {
+	/// The row and column the character starts at, which must be empty in
+	/// the map.
+	pub coords: (i32, i32),
+	/// The character's color, as the digit that would stand for it in a map,
+	/// from 0 for green to 7 for white.
+	pub color: u8,
+	/// The angle the character faces, in radians counterclockwise from
+	/// facing right.
+	#[serde(default)]
+	pub angle: f32,
+	/// Whether the character starts out sliding, as if it had just stepped
+	/// onto ice.
+	#[serde(default)]
+	pub sliding: bool,
+	/// The row and column of the character's open portal, if it starts out
+	/// having summoned its future self and still has to return.
+	#[serde(default)]
+	pub portal: Option<(i32, i32)>,
+}
+
+impl StartingCharacter {
+	/// The character's starting coordinates, the character, and its angle,
+	/// or `None` if its color is unknown.
+	fn placed(&self) -> Option<(Coords, Character, f32)> {
+		if usize::from(self.color) >= CharacterColor::COUNT {
+			return None;
+		}
+		let (row, col) = self.coords;
+		let character = Character {
+			color: CharacterColor::from(self.color),
+			sliding: self.sliding,
+			portal_coords: self.portal.map(|(row, col)| Coords::new(row, col)),
+		};
+		Some((Coords::new(row, col), character, self.angle))
+	}
+}
+
+/// A gameplay mechanic a level can require.
+#[derive(
+	Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug,
+)]
+pub enum Mechanic {
+	Crates,
+	HeavyObjects,
+	Summoning,
+	Returning,
+	Sliding,
+	Platforms,
+	Turntables,
+}
+
+impl Mechanic {
+	pub const ALL: [Mechanic; 7] = [
+		Mechanic::Crates,
+		Mechanic::HeavyObjects,
+		Mechanic::Summoning,
+		Mechanic::Returning,
+		Mechanic::Sliding,
+		Mechanic::Platforms,
+		Mechanic::Turntables,
+	];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Mechanic::Crates => "crates",
+			Mechanic::HeavyObjects => "heavy objects",
+			Mechanic::Summoning => "summoning",
+			Mechanic::Returning => "returning",
+			Mechanic::Sliding => "sliding",
+			Mechanic::Platforms => "platforms",
+			Mechanic::Turntables => "turntables",
+		}
+	}
+}
+
+impl LevelAsset {
+	/// Reads a level file directly, without the asset server.
+	pub fn read(path: &str) -> Result<LevelAsset, LevelAssetError> {
+		let bytes = std::fs::read(path)?;
+		ron::de::from_bytes::<LevelAsset>(&bytes)?.validated()
+	}
+
+	/// This level, migrated to the current [format version](FORMAT_VERSION),
+	/// or an error if it's from a version this game doesn't know. Logs a
+	/// warning if migrating turned any tiles into floor, since a file that
+	/// leaves out its version may have meant them as newer tiles.
+	pub fn migrated(mut self) -> Result<LevelAsset, LevelAssetError> {
+		if !(1..=FORMAT_VERSION).contains(&self.version) {
+			return Err(LevelAssetError::UnsupportedVersion(self.version));
+		}
+		let replaced: usize = MIGRATIONS[self.version as usize - 1..]
+			.iter()
+			.map(|migrate| migrate(&mut self))
+			.sum();
+		if replaced > 0 {
+			warn!(
+				"level \"{}\": migrating from format version {} turned {} \
+				tiles into floor",
+				self.name, self.version, replaced
+			);
+		}
+		self.version = FORMAT_VERSION;
+		Ok(self)
+	}
+
+	/// This level, [migrated](LevelAsset::migrated), or the problems with its
+	/// map if it isn't valid. See [`Level::validate`].
+	pub fn validated(self) -> Result<LevelAsset, LevelAssetError> {
+		let asset = self.migrated()?;
+		let level = asset.start().map_err(LevelAssetError::State)?;
+		let mut errors = level.validate();
+		let map_level = level::make_level(&asset.map);
+		let mut taken = HashSet::new();
+		for character in &asset.characters {
+			let (row, col) = character.coords;
+			let coords = Coords::new(row, col);
+			let free = map_level.in_bounds(coords)
+				&& map_level.object_at(coords).is_none()
+				&& taken.insert(coords);
+			if !free || character.placed().is_none() {
+				errors.push(LevelError::InvalidCharacter(coords));
+			}
+		}
+		for (coords, object) in asset.contents() {
+			let hidden =
+				level.in_bounds(coords) && level.object_at(coords).is_some();
+			if !hidden || object.is_none() {
+				errors.push(LevelError::InvalidContents(coords));
+			}
+		}
+		if errors.is_empty() {
+			Ok(asset)
+		} else {
+			Err(LevelAssetError::Level(errors))
+		}
+	}
+
+	/// The level's map with its [starting characters](LevelAsset::characters)
+	/// added and its [starting state](LevelAsset::state) applied, or an error
+	/// if the state doesn't fit.
+	pub fn start(&self) -> Result<Level, LevelTextError> {
+		let level = level::make_level(&self.map).with_characters(
+			self.characters.iter().filter_map(StartingCharacter::placed),
+		);
+		match &self.state {
+			Some(state) => level.with_state(state),
+			None => Ok(level),
+		}
+	}
+
+	/// A standalone level starting from `level`'s current position, for
+	/// practicing a tricky part of it without replaying the turns before.
+	/// Presentation carries over from `source`, the asset `level` came from,
+	/// if there is one. History and the solution are left behind.
+	pub fn practice(level: &Level, source: Option<&LevelAsset>) -> LevelAsset {
+		let contents = level
+			.iter_level_objects()
+			.filter_map(|level_object| {
+				match level.contents(&level_object.id)? {
+					Object::Prop(descriptor) => {
+						let Coords { row, col } = level_object.coords;
+						Some(((row, col), descriptor.symbol as char))
+					}
+					Object::Character(_) => None,
+				}
+			})
+			.collect();
+		let state = level.state_text();
+		LevelAsset {
+			name: format!(
+				"{} (practice from turn {})",
+				level.meta().name,
+				level.turn(),
+			),
+			map: level.map_text(),
+			state: (!state.is_empty()).then_some(state),
+			lighting: source.map_or_else(default, |source| source.lighting),
+			lighting_overrides: source
+				.map_or_else(default, |source| source.lighting_overrides),
+			music: source.and_then(|source| source.music.clone()),
+			ambience: source.and_then(|source| source.ambience.clone()),
+			tags: LevelTags {
+				author: level.meta().author.clone(),
+				..default()
+			},
+			max_open_portals: level.rules().max_open_portals,
+			contents,
+			..default()
+		}
+	}
+
+	/// The level's contents, with the props they name, if known.
+	fn contents(&self) -> impl Iterator<Item = (Coords, Option<Object>)> + '_ {
+		self.contents.iter().map(|(&(row, col), &symbol)| {
+			let object =
+				u8::try_from(symbol).ok().and_then(Object::prop_with_symbol);
+			(Coords::new(row, col), object)
+		})
+	}
+
+	/// The level's name in `language`. See [`LevelMeta::localized_name`].
+	pub fn localized_name(&self, language: &str) -> &str {
+		locale::resolve(&self.translations, language)
+			.and_then(|text| text.name.as_deref())
+			.unwrap_or(&self.name)
+	}
+
+	/// A fresh copy of this level.
+	pub fn to_level(&self) -> Level {
+		let track = |name: &Option<String>| {
+			let name = name.as_deref()?;
+			let key = audio::track_key(name);
+			if key.is_none() {
+				warn!("level \"{}\" uses unknown track \"{name}\"", self.name);
+			}
+			key
+		};
+		let solution = self.solution.as_deref().and_then(|text| {
+			let solution = replay::parse_turns(text);
+			if solution.is_none() {
+				warn!("level \"{}\" has an invalid solution", self.name);
+			}
+			solution
+		});
+		let level = self
+			.start()
+			.unwrap_or_else(|err| {
+				warn!("level \"{}\" has an invalid state: {err}", self.name);
+				level::make_level(&self.map)
+			})
+			.with_meta(LevelMeta {
+				name: self.name.clone(),
+				author: self.tags.author.clone(),
+				description: self.description.clone(),
+				par: self.par.or(solution.as_ref().map(Vec::len)),
+				translations: self.translations.clone(),
+			})
+			.with_rules(Rules {
+				max_open_portals: self.max_open_portals,
+				..Rules::default()
+			})
+			.with_contents(
+				self.contents()
+					.filter_map(|(coords, object)| Some((coords, object?))),
+			)
+			.with_lighting(
+				self.lighting_overrides.apply(self.lighting.lighting()),
+			)
+			.with_soundtrack(Soundtrack {
+				music: track(&self.music),
+				ambience: track(&self.ambience),
+			});
+		match solution {
+			Some(solution) => level.with_solution(solution),
+			None => level,
+		}
+	}
+}
+
+/// An error reading a [`LevelAsset`].
+#[derive(Debug)]
+pub enum LevelAssetError {
+	Io(std::io::Error),
+	Ron(ron::error::SpannedError),
+	/// The level is from a version of the file format this game doesn't
+	/// know, likely a newer one.
+	UnsupportedVersion(u32),
+	/// The level parsed but isn't playable.
+	Level(Vec<LevelError>),
+	/// The level's starting state doesn't fit its map.
+	State(LevelTextError),
+}
+
+impl Display for LevelAssetError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			LevelAssetError::Io(err) => {
+				write!(f, "could not read level: {err}")
+			}
+			LevelAssetError::Ron(err) => write!(f, "invalid level: {err}"),
+			LevelAssetError::UnsupportedVersion(version) => write!(
+				f,
+				"unsupported level format version {version}; this game \
+				reads versions 1 through {FORMAT_VERSION}"
+			),
+			LevelAssetError::Level(errors) => {
+				write!(f, "invalid level: ")?;
+				for (idx, err) in errors.iter().enumerate() {
+					if idx > 0 {
+						write!(f, "; ")?;
+					}
+					write!(f, "{err}")?;
+				}
+				Ok(())
+			}
+			LevelAssetError::State(err) => write!(f, "invalid level: {err}"),
+		}
+	}
+}
+
+impl std::error::Error for LevelAssetError {}
+
+impl From<std::io::Error> for LevelAssetError {
+	fn from(err: std::io::Error) -> Self {
+		LevelAssetError::Io(err)
+	}
+}
+
+impl From<ron::error::SpannedError> for LevelAssetError {
+	fn from(err: ron::error::SpannedError) -> Self {
+		LevelAssetError::Ron(err)
+	}
+}
+
+/// Loads [`LevelAsset`]s from `.level.ron` files.
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+impl AssetLoader for LevelAssetLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = LevelAssetError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<LevelAsset, LevelAssetError> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		ron::de::from_bytes::<LevelAsset>(&bytes)?.validated()
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["level.ron"]
+	}
+}
+
+/// Pairs the path of a level file in the assets directory with its contents.
+macro_rules! embed_level {
+	($path:literal) => {
+		($path, include_str!(concat!("../assets/", $path)))
+	};
+}
+
+/// The campaign's levels, embedded in the executable in campaign order, as
+/// their paths relative to the assets directory and their contents.
+const EMBEDDED_LEVELS: [(&str, &str); 4] = [
+	embed_level!("levels/test.level.ron"),
+	embed_level!("levels/short.level.ron"),
+	embed_level!("levels/thin.level.ron"),
+	embed_level!("levels/large.level.ron"),
+];
+
+/// The number of [embedded levels](embedded_level).
+pub const EMBEDDED_LEVEL_COUNT: usize = EMBEDDED_LEVELS.len();
+
+/// The `idx`-th of the campaign's levels, from the copy embedded in the
+/// executable, or `None` if there's no such level or it isn't valid. Unlike
+/// the files in the assets directory, embedded levels are always available,
+/// so they stand in for the campaign when its manifest can't be read.
+pub fn embedded_level(idx: usize) -> Option<LevelAsset> {
+	let (path, text) = EMBEDDED_LEVELS.get(idx)?;
+	let asset = ron::from_str::<LevelAsset>(text)
+		.map_err(LevelAssetError::from)
+		.and_then(LevelAsset::validated);
+	match asset {
+		Ok(asset) => Some(asset),
+		Err(err) => {
+			error!("embedded level {path} is invalid: {err}");
+			None
+		}
+	}
+}
+
+/// The path relative to the assets directory of the `idx`-th
+/// [embedded level](embedded_level), if there is one.
+pub fn embedded_level_path(idx: usize) -> Option<&'static str> {
+	EMBEDDED_LEVELS.get(idx).map(|(path, _)| *path)
+}
+
+/// Name of the asset source for levels in the [user levels
+/// directory](user_levels_dir).
+pub const USER_SOURCE: &str = "user";
+
+/// Extensions of the level files the game can load.
+const LEVEL_EXTENSIONS: [&str; 3] = ["level.ron", "tmx", "ldtk"];
+
+/// The directory players can put their own level files in: `causal-oops/levels`
+/// in the platform's user data directory, like `~/.local/share` on Linux. Falls
+/// back to `user_levels` in the working directory if the platform's directory
+/// can't be determined.
+pub fn user_levels_dir() -> PathBuf {
+	let var = |key| std::env::var_os(key).map(PathBuf::from);
+	let data_dir = if cfg!(windows) {
+		var("APPDATA")
+	} else if cfg!(target_os = "macos") {
+		var("HOME").map(|home| home.join("Library/Application Support"))
+	} else {
+		var("XDG_DATA_HOME")
+			.or_else(|| var("HOME").map(|home| home.join(".local/share")))
+	};
+	match data_dir {
+		Some(data_dir) => data_dir.join("causal-oops").join("levels"),
+		None => PathBuf::from("user_levels"),
+	}
+}
+
+/// An asset source for levels in the [user levels directory](user_levels_dir).
+pub fn user_source() -> AssetSourceBuilder {
+	AssetSourceBuilder::platform_default(
+		&user_levels_dir().to_string_lossy(),
+		None,
+	)
+}
+
+/// The names of the files in `dir` with any of the given extensions, sorted
+/// so they're listed in a stable order.
+fn files_with_extensions(
+	dir: &Path,
+	extensions: &[&str],
+) -> std::io::Result<Vec<String>> {
+	let mut files: Vec<String> = std::fs::read_dir(dir)?
+		.filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+		.filter(|name| {
+			extensions
+				.iter()
+				.any(|extension| name.ends_with(&format!(".{extension}")))
+		})
+		.collect();
+	files.sort();
+	Ok(files)
+}
+
+/// The names of the level files in `dir`, sorted so they're listed in a
+/// stable order.
+pub fn level_files(dir: &Path) -> std::io::Result<Vec<String>> {
+	files_with_extensions(dir, &LEVEL_EXTENSIONS)
+}
+
+/// The names of the files in the [user levels directory](user_levels_dir)
+/// with any of the given extensions.
+fn user_files(extensions: &[&str]) -> Vec<String> {
+	let dir = user_levels_dir();
+	match files_with_extensions(&dir, extensions) {
+		Ok(files) => files,
+		Err(err) => {
+			if err.kind() != std::io::ErrorKind::NotFound {
+				warn!("could not read user levels in {}: {err}", dir.display());
+			}
+			Vec::new()
+		}
+	}
+}
+
+/// Handles to the bundled levels, in [campaign](Campaign) order, followed by
+/// any levels found in the [user levels directory](user_levels_dir): first
+/// loose level files, then the levels of each [pack](LevelPack). Levels
+/// [added](Levels::add) while running, like downloaded ones, come last.
+#[derive(Resource)]
+pub struct Levels {
+	handles: Vec<Handle<LevelAsset>>,
+	/// The index of the bundled level being played, if any.
+	current: Option<usize>,
+}
+
+impl Levels {
+	/// Starts loading the campaign's levels and the user's levels. Packs are
+	/// read right away and [added](Campaign::add_pack) to `campaign`.
+	pub fn load(
+		asset_server: &mut AssetServer,
+		level_assets: &mut Assets<LevelAsset>,
+		campaign: &mut Campaign,
+	) -> Self {
+		let level_files = user_files(&LEVEL_EXTENSIONS);
+		if !level_files.is_empty() {
+			info!("found {} user levels", level_files.len());
+		}
+		let mut handles: Vec<Handle<LevelAsset>> =
+			if campaign.level_paths().is_empty() {
+				warn!("no campaign levels to load; using the embedded ones");
+				(0..EMBEDDED_LEVEL_COUNT)
+					.filter_map(embedded_level)
+					.map(|asset| level_assets.add(asset))
+					.collect()
+			} else {
+				campaign
+					.level_paths()
+					.iter()
+					.map(|path| asset_server.load(path.clone()))
+					.collect()
+			};
+		handles.extend(
+			level_files.into_iter().map(|file| {
+				asset_server.load(format!("{USER_SOURCE}://{file}"))
+			}),
+		);
+		for file in user_files(&[PACK_EXTENSION]) {
+			let pack = match LevelPack::read(&user_levels_dir().join(&file)) {
+				Ok(pack) => pack,
+				Err(err) => {
+					warn!("could not load level pack {file}: {err}");
+					continue;
+				}
+			};
+			info!("found level pack \"{}\"", pack.title);
+			campaign.add_pack(
+				pack.title,
+				pack.author,
+				&file,
+				handles.len(),
+				pack.levels.len(),
+			);
+			handles.extend(
+				pack.levels.into_iter().map(|level| level_assets.add(level)),
+			);
+		}
+		Self {
+			handles,
+			current: None,
+		}
+	}
+
+	/// The number of levels, loaded or not.
+	pub fn count(&self) -> usize {
+		self.handles.len()
+	}
+
+	/// Adds a level that's already in `Assets`, returning its index.
+	pub fn add(&mut self, handle: Handle<LevelAsset>) -> usize {
+		self.handles.push(handle);
+		self.handles.len() - 1
+	}
+
+	/// The index of the bundled level being played, if any.
+	pub fn current(&self) -> Option<usize> {
+		self.current
+	}
+
+	/// The ID of the `idx`-th level's asset, if there is such a level.
+	pub fn id(&self, idx: usize) -> Option<AssetId<LevelAsset>> {
+		self.handles.get(idx).map(Handle::id)
+	}
+
+	/// Records which bundled level is being played, if any.
+	pub fn set_current(&mut self, current: Option<usize>) {
+		self.current = current;
+	}
+
+	/// Whether `event` means the file of the bundled level being played was
+	/// modified.
+	///
+	/// Modifications are only detected when Bevy's `file_watcher` feature is
+	/// enabled, e.g. with `cargo run --features bevy/file_watcher`.
+	pub fn is_current_modified(&self, event: &AssetEvent<LevelAsset>) -> bool {
+		let Some(handle) = self.current.and_then(|idx| self.handles.get(idx))
+		else {
+			return false;
+		};
+		event.is_modified(handle)
+	}
+
+	/// Whether every level has either loaded or failed to load. Pack levels
+	/// are added directly rather than loaded, so they're always settled.
+	fn is_settled(
+		&self,
+		asset_server: &AssetServer,
+		level_assets: &Assets<LevelAsset>,
+	) -> bool {
+		self.handles.iter().all(|handle| {
+			level_assets.contains(handle)
+				|| matches!(
+					asset_server.load_state(handle),
+					LoadState::Loaded | LoadState::Failed(_)
+				)
+		})
+	}
+
+	/// The bundled levels that loaded, with their indices.
+	pub fn loaded<'a>(
+		&'a self,
+		level_assets: &'a Assets<LevelAsset>,
+	) -> impl Iterator<Item = (usize, &'a LevelAsset)> {
+		self.handles.iter().enumerate().filter_map(|(idx, handle)| {
+			level_assets.get(handle).map(|asset| (idx, asset))
+		})
+	}
+
+	/// The asset of the bundled level being played, if any.
+	pub fn current_asset<'a>(
+		&self,
+		level_assets: &'a Assets<LevelAsset>,
+	) -> Option<&'a LevelAsset> {
+		level_assets.get(self.handles.get(self.current?)?)
+	}
+
+	/// A fresh copy of the `idx`-th bundled level, if it loaded.
+	pub fn instantiate(
+		&self,
+		idx: usize,
+		level_assets: &Assets<LevelAsset>,
+	) -> Option<Level> {
+		let asset = level_assets.get(self.handles.get(idx)?)?;
+		info!("starting level \"{}\"", asset.name);
+		Some(asset.to_level())
+	}
+}
+
+/// Command-line flag followed by the path of a level file to start in. The
+/// flag may be left out if the path is the first argument.
+pub const LEVEL_FLAG: &str = "--level";
+
+/// A level read from the command line, to start in rather than the first
+/// bundled level. It's played after the bundled levels finish loading, as a
+/// level outside the campaign.
+#[derive(Resource)]
+pub struct StartLevel(pub Option<LevelAsset>);
+
+/// Once game assets and levels are loaded, starts the first level, or the
+/// [start level](StartLevel) if there is one.
+pub fn finish_loading(
+	asset_server: Res<AssetServer>,
+	assets: Res<GameAssets>,
+	mut levels: ResMut<Levels>,
+	mut level_assets: ResMut<Assets<LevelAsset>>,
+	mut start_level: ResMut<StartLevel>,
+	mut level: ResMut<Level>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !assets.is_loaded() || !levels.is_settled(&asset_server, &level_assets) {
+		return;
+	}
+	let first = match start_level.0.take() {
+		Some(asset) => levels.add(level_assets.add(asset)),
+		None => 0,
+	};
+	match levels.instantiate(first, &level_assets) {
+		Some(first_level) => {
+			*level = first_level;
+			levels.set_current(Some(first));
+		}
+		None => warn!("could not load the first level; using a built-in one"),
+	}
+	next_state.set(GameState::SpawningLevel);
+}
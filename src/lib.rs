@@ -0,0 +1,63 @@
+//! The game's modules, shared by the game and tools like `lint-levels`.
+
+pub mod animation;
+pub mod assets;
+pub mod audio;
+#[cfg(feature = "automation")]
+pub mod automation;
+pub mod autopilot;
+pub mod backdrop;
+pub mod branches;
+pub mod browser;
+pub mod bug_report;
+pub mod camera;
+pub mod campaign;
+pub mod check;
+pub mod control;
+pub mod cues;
+pub mod danger;
+pub mod devices;
+pub mod diagnostics;
+pub mod display;
+pub mod emotes;
+pub mod featured;
+pub mod ghost;
+pub mod graphics;
+pub mod grid;
+pub mod hints;
+pub mod history;
+pub mod inspector;
+pub mod ldtk;
+pub mod level;
+pub mod level_asset;
+pub mod lighting;
+pub mod locale;
+pub mod macros;
+pub mod mutators;
+pub mod outro;
+pub mod overview;
+pub mod pack;
+pub mod pause;
+pub mod peek;
+pub mod photo_mode;
+pub mod playtest;
+pub mod pressure;
+pub mod progress;
+pub mod protocol;
+pub mod replay;
+pub mod rng;
+pub mod sandbox;
+pub mod settings;
+pub mod sharing;
+pub mod solution;
+pub mod states;
+pub mod stress;
+pub mod thumbnails;
+pub mod tile_behavior;
+pub mod tiled;
+pub mod timeline;
+pub mod title;
+pub mod trails;
+pub mod turns;
+pub mod tutorials;
+pub mod update;
@@ -0,0 +1,158 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{audio::Soundtrack, backdrop::Sky};
+
+/// Describes how a level is lit.
+#[derive(Clone, Copy)]
+pub struct Lighting {
+	pub ambient_color: Color,
+	pub ambient_brightness: f32,
+	/// Illuminance of the directional light, in lux.
+	pub illuminance: f32,
+	/// Tilt of the directional light, in radians.
+	pub light_angle: f32,
+	pub fog: Option<Fog>,
+	pub sky: Sky,
+	/// A fixed background color to show instead of the sky, if any.
+	pub clear_color: Option<Color>,
+	/// The default music and ambience for levels with this lighting.
+	pub soundtrack: Soundtrack,
+}
+
+/// Linear distance fog.
+#[derive(Clone, Copy)]
+pub struct Fog {
+	pub color: Color,
+	pub start: f32,
+	pub end: f32,
+}
+
+impl Lighting {
+	/// Bright, clear daylight.
+	pub const OUTDOOR: Lighting = Lighting {
+		ambient_color: Color::WHITE,
+		ambient_brightness: 250.0,
+		illuminance: 0.3 * light_consts::lux::AMBIENT_DAYLIGHT,
+		light_angle: -TAU / 16.0,
+		fog: None,
+		sky: Sky {
+			colors: &[
+				// Day
+				Color::srgb(0.45, 0.65, 0.9),
+				// Dusk
+				Color::srgb(0.85, 0.5, 0.35),
+				// Night
+				Color::srgb(0.05, 0.07, 0.2),
+				// Dawn
+				Color::srgb(0.8, 0.55, 0.65),
+			],
+			cycle: Duration::from_secs(240),
+		},
+		clear_color: None,
+		soundtrack: Soundtrack {
+			music: Some("meadow"),
+			ambience: Some("birdsong"),
+		},
+	};
+
+	/// Dim, bluish light with fog.
+	pub const UNDERGROUND: Lighting = Lighting {
+		ambient_color: Color::srgb(0.6, 0.7, 1.0),
+		ambient_brightness: 80.0,
+		illuminance: 0.05 * light_consts::lux::AMBIENT_DAYLIGHT,
+		light_angle: -TAU / 10.0,
+		fog: Some(Fog {
+			color: Color::srgb(0.02, 0.02, 0.05),
+			start: 10.0,
+			end: 30.0,
+		}),
+		sky: Sky {
+			colors: &[
+				Color::srgb(0.08, 0.05, 0.12),
+				Color::srgb(0.04, 0.1, 0.1),
+			],
+			cycle: Duration::from_secs(120),
+		},
+		clear_color: None,
+		soundtrack: Soundtrack {
+			music: Some("caverns"),
+			ambience: Some("dripping"),
+		},
+	};
+
+	/// The rotation of the directional light.
+	pub fn light_rotation(&self) -> Quat {
+		Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), self.light_angle)
+	}
+}
+
+/// A named [`Lighting`], for choosing lighting in level files.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub enum LightingPreset {
+	#[default]
+	Outdoor,
+	Underground,
+}
+
+impl LightingPreset {
+	pub fn lighting(&self) -> Lighting {
+		match self {
+			LightingPreset::Outdoor => Lighting::OUTDOOR,
+			LightingPreset::Underground => Lighting::UNDERGROUND,
+		}
+	}
+}
+
+/// Adjustments to a level's [`LightingPreset`], for giving it its own mood.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct LightingOverrides {
+	/// Brightness of the ambient light.
+	pub ambient_brightness: Option<f32>,
+	/// Tilt of the directional light, in degrees.
+	pub light_angle: Option<f32>,
+	/// A fixed background color, as sRGB components from 0 to 1.
+	pub clear_color: Option<(f32, f32, f32)>,
+}
+
+impl LightingOverrides {
+	/// `lighting` with these overrides applied.
+	pub fn apply(&self, lighting: Lighting) -> Lighting {
+		Lighting {
+			ambient_brightness: self
+				.ambient_brightness
+				.unwrap_or(lighting.ambient_brightness),
+			light_angle: self
+				.light_angle
+				.map_or(lighting.light_angle, f32::to_radians),
+			clear_color: self
+				.clear_color
+				.map(|(r, g, b)| Color::srgb(r, g, b))
+				.or(lighting.clear_color),
+			..lighting
+		}
+	}
+}
+
+impl Default for Lighting {
+	fn default() -> Lighting {
+		Lighting::OUTDOOR
+	}
+}
+
+impl Fog {
+	/// The fog as a camera component.
+	pub fn distance_fog(&self) -> DistanceFog {
+		DistanceFog {
+			color: self.color,
+			falloff: FogFalloff::Linear {
+				start: self.start,
+				end: self.end,
+			},
+			..default()
+		}
+	}
+}
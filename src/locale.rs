@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// The language shown when neither the settings nor the system name one.
+const DEFAULT_LANGUAGE: &str = "en";
+
+/// Strings keyed by language code, like `fr` or `pt-BR`.
+pub type Translations<T> = BTreeMap<String, T>;
+
+/// A level's text in one language. Missing strings fall back to the level's
+/// untranslated text.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct LevelText {
+	pub name: Option<String>,
+	pub description: Option<String>,
+}
+
+/// The language code to show text in: the one in [`Settings`] if set,
+/// otherwise the system's, from the `LANG` environment variable.
+pub fn language(settings: &Settings) -> String {
+	settings
+		.language
+		.clone()
+		.or_else(system_language)
+		.unwrap_or_else(|| DEFAULT_LANGUAGE.to_string())
+}
+
+/// The system language as a language code, from a locale like `pt_BR.UTF-8`.
+fn system_language() -> Option<String> {
+	let locale = std::env::var("LANG").ok()?;
+	let code = locale.split(['.', '@']).next()?.replace('_', "-");
+	(!code.is_empty() && code != "C" && code != "POSIX").then_some(code)
+}
+
+/// The translation for `language` in `translations`, falling back to the base
+/// language without its region, so `pt-BR` finds `pt` if there's no `pt-BR`.
+pub fn resolve<'a, T>(
+	translations: &'a Translations<T>,
+	language: &str,
+) -> Option<&'a T> {
+	translations.get(language).or_else(|| {
+		let (base, _) = language.split_once('-')?;
+		translations.get(base)
+	})
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use crate::{
+	control::{direction_action, Action, ControlEvent},
+	level::Level,
+	photo_mode::HiddenInPhotoMode,
+	update::NextActor,
+};
+
+/// Maximum number of actions in a macro.
+const MAX_MACRO_LEN: usize = 32;
+
+/// A recorded sequence of actions that can be replayed with one key, each
+/// action going to whichever character is next to act.
+#[derive(Resource, Default)]
+pub struct Macro {
+	actions: Vec<Action>,
+	recording: bool,
+	/// The index of the next action to play, while playing.
+	playhead: Option<usize>,
+	/// The character waiting for an action from the macro, if any.
+	next_actor: Option<NextActor>,
+}
+
+/// Starts or stops recording a macro when M is pressed, and plays the
+/// recorded macro when N is pressed.
+pub fn control_macro(keys: Res<ButtonInput<KeyCode>>, mut mac: ResMut<Macro>) {
+	if keys.just_pressed(KeyCode::KeyM) {
+		if mac.recording {
+			mac.recording = false;
+		} else {
+			mac.actions.clear();
+			mac.recording = true;
+			mac.playhead = None;
+		}
+	}
+	if keys.just_pressed(KeyCode::KeyN)
+		&& !mac.recording
+		&& !mac.actions.is_empty()
+	{
+		mac.playhead = Some(0);
+	}
+}
+
+/// Adds the player's actions to the macro while recording.
+pub fn record_macro(
+	mut mac: ResMut<Macro>,
+	mut control_events: EventReader<ControlEvent>,
+) {
+	for control_event in control_events.read() {
+		if !mac.recording {
+			continue;
+		}
+		if let ControlEvent::Act((_, action)) = control_event {
+			mac.actions.push(*action);
+			if mac.actions.len() == MAX_MACRO_LEN {
+				mac.recording = false;
+			}
+		}
+	}
+}
+
+/// While playing, sends the macro's next action for each character as it
+/// becomes the next to act. Playback stops at the end of the macro or as soon
+/// as its next action would be invalid.
+pub fn play_macro(
+	mut mac: ResMut<Macro>,
+	level: Res<Level>,
+	mut next_actors: EventReader<NextActor>,
+	mut control_events: EventWriter<ControlEvent>,
+) {
+	if let Some(next_actor) = next_actors.read().last() {
+		mac.next_actor = Some(*next_actor);
+	}
+	let Some(playhead) = mac.playhead else {
+		return;
+	};
+	let Some(actor) = mac.next_actor else {
+		return;
+	};
+	let Some(&action) = mac.actions.get(playhead) else {
+		mac.playhead = None;
+		return;
+	};
+	if !is_valid(&level, &actor, action) {
+		info!("stopped macro at invalid action {}", playhead + 1);
+		mac.playhead = None;
+		return;
+	}
+	control_events.send(ControlEvent::Act((actor.id, action)));
+	mac.next_actor = None;
+	mac.playhead = Some(playhead + 1);
+}
+
+/// Whether `actor` could take `action` and have it do something: pushes must
+/// not be blocked, summons must have room, and returns must be on a portal.
+fn is_valid(level: &Level, actor: &NextActor, action: Action) -> bool {
+	let allowed = match action {
+		Action::Wait => actor.character.can_wait(),
+		Action::Push(offset) => matches!(
+			direction_action(actor, offset, false),
+			Some(Action::Push(_))
+		),
+		Action::Summon(offset) => matches!(
+			direction_action(actor, offset, true),
+			Some(Action::Summon(_))
+		),
+		Action::Return => actor.character.can_return(),
+	};
+	if !allowed {
+		return false;
+	}
+	let change = level.preview(vec![(actor.id, action)]);
+	match action {
+		Action::Wait => true,
+		// Check for a blocked push rather than a move, since a character on a
+		// platform or turntable moves regardless.
+		Action::Push(_) => !change.blocked.contains(&actor.id),
+		Action::Summon(_) => change.summonings.contains_key(&actor.id),
+		Action::Return => change.returnings.contains_key(&actor.id),
+	}
+}
+
+/// Marker component for the macro status text.
+#[derive(Component)]
+pub struct MacroText;
+
+/// Spawns the (initially empty) macro status text.
+pub fn spawn_macro_text(mut commands: Commands) {
+	commands.spawn((
+		MacroText,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			// Above the mutators text.
+			bottom: Val::Px(24.0),
+			right: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// Shows whether a macro is being recorded or played.
+pub fn update_macro_text(
+	mac: Res<Macro>,
+	mut text_query: Query<&mut Text, With<MacroText>>,
+) {
+	if !mac.is_changed() {
+		return;
+	}
+	let status = match (mac.recording, mac.playhead) {
+		(true, _) => format!(
+			"Recording macro: {}/{MAX_MACRO_LEN} (M to stop)",
+			mac.actions.len()
+		),
+		(false, Some(playhead)) => {
+			format!("Playing macro: {}/{}", playhead, mac.actions.len())
+		}
+		(false, None) if !mac.actions.is_empty() => {
+			format!("Macro: {} actions (N to play)", mac.actions.len())
+		}
+		(false, None) => String::new(),
+	};
+	for mut text in &mut text_query {
+		**text = status.clone();
+	}
+}
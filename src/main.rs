@@ -1,31 +1,117 @@
-use std::f32::consts::TAU;
+use std::path::Path;
 
 use bevy::{
+	core_pipeline::experimental::taa::TemporalAntiAliasPlugin,
+	diagnostic::{
+		EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+		RegisterDiagnostic,
+	},
+	ecs::system::SystemParam,
 	input::{keyboard::KeyboardInput, ButtonState},
 	prelude::*,
-	render::camera::ScalingMode,
 };
 use bevy_easings::EasingsPlugin;
+use rand::Rng;
 
+use assets::{load_pending_meshes, GameAssets};
+#[cfg(feature = "automation")]
+use causal_oops::automation;
+use causal_oops::{
+	animation, assets, audio, autopilot, backdrop, branches, browser,
+	bug_report, camera, campaign, check, control, cues, danger, devices,
+	diagnostics, display, emotes, featured, ghost, graphics, grid, hints,
+	inspector, ldtk, level, level_asset, macros, mutators, outro, overview,
+	pause, peek, photo_mode, playtest, pressure, progress, protocol, rng,
+	sandbox, settings, sharing, solution, states, stress, thumbnails, tiled,
+	timeline, title, trails, turns, tutorials, update,
+};
 use control::ControlEvent;
-use level::{ChangeEvent, Coords, Level, LevelEntity, Object, Tile};
-use materials::Materials;
-use meshes::Meshes;
-use models::{load_gltf_meshes, Models};
+use level::{ChangeEvent, Coords, Level, LevelEntity, Tile};
 use states::GameState;
 use update::NextActor;
 
-mod animation;
-mod control;
-mod level;
-mod materials;
-mod meshes;
-mod models;
-mod states;
-mod update;
+/// How many times per second control and level updates run.
+const SIMULATION_HZ: f64 = 120.0;
 
 fn main() {
-	App::new()
+	let args: Vec<String> = std::env::args().collect();
+	if args.get(1).map(String::as_str) == Some(protocol::SOLVER_FLAG) {
+		let name = args.get(2).map_or("test", String::as_str);
+		let level = match protocol::level_named(name) {
+			Ok(level) => level,
+			Err(err) => {
+				eprintln!("{name}: {err}");
+				std::process::exit(2);
+			}
+		};
+		if let Err(err) = protocol::run(level) {
+			eprintln!("solver protocol failed: {err}");
+			std::process::exit(1);
+		}
+		return;
+	}
+	if args.get(1).map(String::as_str) == Some(check::CHECK_FLAG) {
+		let Some(dir) = args.get(2) else {
+			eprintln!(
+				"usage: {} <dir> [{}]",
+				check::CHECK_FLAG,
+				check::SOLVE_FLAG
+			);
+			std::process::exit(2);
+		};
+		let solve = args.get(3).map(String::as_str) == Some(check::SOLVE_FLAG);
+		match check::run(Path::new(dir), solve) {
+			Ok(true) => {}
+			Ok(false) => std::process::exit(1),
+			Err(err) => {
+				eprintln!("{dir}: {err}");
+				std::process::exit(2);
+			}
+		}
+		return;
+	}
+	// Designers and CI can launch straight into a level file, given either
+	// as the first argument or after the level flag.
+	let start_path = match args.get(1).map(String::as_str) {
+		Some(level_asset::LEVEL_FLAG) => {
+			let Some(path) = args.get(2) else {
+				eprintln!("usage: {} <file>", level_asset::LEVEL_FLAG);
+				std::process::exit(2);
+			};
+			Some(path)
+		}
+		Some(arg) if !arg.starts_with('-') => args.get(1),
+		_ => None,
+	};
+	let start_level = start_path.map(|path| {
+		level_asset::LevelAsset::read(path).unwrap_or_else(|err| {
+			eprintln!("{path}: {err}");
+			std::process::exit(2);
+		})
+	});
+	let mut settings = settings::Settings::load();
+	if settings.display_preset.is_none() {
+		let preset = display::DisplayPreset::detect();
+		info!("detected display preset: {preset:?}");
+		settings.display_preset = Some(preset);
+		settings.save();
+	}
+	let keyboard_bindings = control::KeyboardBindings::profile(
+		&settings.binding_profiles,
+		settings.binding_profile.as_deref(),
+	);
+
+	let mut app = App::new();
+	app
+		// Asset sources must be registered before the asset plugin is added.
+		.register_asset_source(
+			thumbnails::CACHE_SOURCE,
+			thumbnails::cache_source(),
+		)
+		.register_asset_source(
+			level_asset::USER_SOURCE,
+			level_asset::user_source(),
+		)
 		.add_plugins((
 			DefaultPlugins.set(WindowPlugin {
 				primary_window: Some(Window {
@@ -35,233 +121,629 @@ fn main() {
 				..default()
 			}),
 			EasingsPlugin::default(),
+			TemporalAntiAliasPlugin,
+			FrameTimeDiagnosticsPlugin,
+			EntityCountDiagnosticsPlugin,
 		))
+		.register_diagnostic(diagnostics::gameplay_time_diagnostic())
 		.init_state::<GameState>()
-		.add_systems(Startup, setup)
+		.init_asset::<level_asset::LevelAsset>()
+		.init_asset_loader::<level_asset::LevelAssetLoader>()
+		.init_asset_loader::<tiled::TiledLevelLoader>()
+		.init_asset_loader::<ldtk::LdtkLevelLoader>()
+		.init_resource::<diagnostics::GameplayTimer>()
+		.init_resource::<trails::ShowTrails>()
+		.init_resource::<danger::ShowDanger>()
+		.init_resource::<pressure::ShowPressure>()
+		.init_resource::<photo_mode::PhotoMode>()
+		.insert_resource(keyboard_bindings)
+		.init_resource::<control::GamepadBindings>()
+		.init_resource::<control::ActiveDevice>()
+		.init_resource::<sandbox::Sandbox>()
+		.init_resource::<featured::FeaturedRun>()
+		.init_resource::<bug_report::BugReport>()
+		.init_resource::<autopilot::Autopilot>()
+		.init_resource::<ghost::Ghost>()
+		.init_resource::<stress::StressTest>()
+		.init_resource::<macros::Macro>()
+		.init_resource::<solution::SolutionComparison>()
+		.init_resource::<inspector::Inspector>()
+		.init_resource::<browser::LevelBrowser>()
+		.init_resource::<thumbnails::Thumbnails>()
+		.init_resource::<audio::Ducking>()
+		.init_resource::<sharing::Sharing>()
+		.init_resource::<timeline::Timeline>()
+		.init_resource::<branches::Branches>()
+		.init_resource::<tutorials::Tutorials>()
+		.init_resource::<pause::FocusPause>()
+		.init_resource::<outro::Outro>()
+		.add_systems(
+			Startup,
+			(
+				setup,
+				diagnostics::spawn_diagnostics_overlay,
+				hints::spawn_control_hints,
+				hints::spawn_stuck_prompt,
+				hints::spawn_rejection_tooltip,
+				sandbox::spawn_sandbox_palette,
+				mutators::spawn_mutators_text,
+				featured::spawn_featured_text,
+				stress::spawn_stress_test_text,
+				macros::spawn_macro_text,
+				solution::spawn_comparison_panel,
+				turns::spawn_turn_counter,
+				inspector::spawn_history_panel,
+				title::spawn_level_title,
+				timeline::spawn_timeline,
+				playtest::spawn_playtest_text,
+			),
+		)
 		.add_systems(
 			Update,
 			(
-				load_gltf_meshes.run_if(in_state(GameState::Loading)),
-				(spawn_level, lights_cameras_action)
+				(load_pending_meshes, level_asset::finish_loading)
+					.chain()
+					.run_if(in_state(GameState::Loading)),
+				(
+					mutators::apply_mutators,
+					spawn_level,
+					grid::spawn_grid_overlay,
+					audio::play_level_audio,
+					lights_cameras_action,
+				)
 					.chain()
 					.run_if(in_state(GameState::SpawningLevel)),
 				(
-					control::control,
-					update::update,
+					diagnostics::start_gameplay_timer,
 					(
 						animation::animate_returnings,
 						animation::animate_moves,
+						animation::animate_platforms,
 						animation::animate_summonings,
+						animation::animate_creations_and_destructions,
+						animation::animate_recaps,
+						animation::animate_exit,
+						animation::animate_tile_changes,
+						animation::animate_submersions,
 						animation::timed_despawn,
 					),
+					// Let a multi-turn seek's animations land before rebuilding
+					// the level's entities over them.
+					apply_deferred,
+					animation::respawn_after_seek,
 					// Allow adding indicators on newly spawned entities.
 					apply_deferred,
 					animation::add_indicators,
 					// Allow indicators to be added/removed in one frame.
 					apply_deferred,
 					animation::clear_indicators,
+					(emotes::add_emotes, emotes::float_emotes),
 					change_level,
+					diagnostics::stop_gameplay_timer,
 				)
 					.chain()
 					.run_if(in_state(GameState::Playing)),
+				(
+					diagnostics::toggle_diagnostics_overlay,
+					diagnostics::update_diagnostics_overlay,
+					stress::run_stress_test
+						.run_if(in_state(GameState::Playing)),
+					stress::update_stress_test_text,
+					playtest::mutate_level.run_if(in_state(GameState::Playing)),
+				),
+				(
+					hints::update_control_hints,
+					hints::update_stuck_prompt,
+					hints::show_rejection_tooltip,
+					turns::cue_turns.run_if(in_state(GameState::Playing)),
+					title::update_level_title,
+					(cues::cue_moves, cues::announce_actor),
+					audio::apply_mixer,
+				),
+				(
+					(
+						graphics::cycle_anti_aliasing,
+						graphics::apply_anti_aliasing,
+					)
+						.chain(),
+					(
+						display::cycle_display_preset,
+						display::apply_display_preset,
+					)
+						.chain(),
+				),
+				(grid::cycle_grid_overlay, grid::respawn_grid_overlay)
+					.chain()
+					.run_if(in_state(GameState::Playing)),
+				(
+					trails::toggle_trails,
+					trails::draw_trails,
+					camera::frame_characters,
+					danger::toggle_danger_preview,
+					danger::update_danger_preview,
+					pressure::toggle_pressure_readout,
+					pressure::update_pressure_readout,
+					(
+						inspector::inspect_characters,
+						inspector::update_history_panel,
+					)
+						.chain(),
+				)
+					.run_if(in_state(GameState::Playing)),
+				(autopilot::toggle_autopilot, autopilot::draw_scripted_rings)
+					.run_if(in_state(GameState::Playing)),
+				(
+					macros::control_macro.run_if(in_state(GameState::Playing)),
+					macros::update_macro_text,
+				),
+				(
+					overview::toggle_overview_window,
+					overview::sync_overview_camera,
+				)
+					.run_if(in_state(GameState::Playing)),
+				(
+					ghost::toggle_ghost,
+					ghost::track_attempt,
+					ghost::draw_ghost,
+					peek::draw_undo_preview,
+				)
+					.run_if(in_state(GameState::Playing)),
+				(
+					solution::track_completion,
+					solution::watch_optimal_solution,
+					solution::play_optimal_solution,
+					solution::update_comparison_panel,
+					campaign::check_level_complete,
+				)
+					.chain()
+					.run_if(in_state(GameState::Playing)),
+				(sandbox::sandbox, sandbox::update_sandbox_palette)
+					.chain()
+					.run_if(in_state(GameState::Playing)),
+				(
+					featured::track_featured_run
+						.run_if(in_state(GameState::Playing)),
+					outro::play_outro.run_if(in_state(GameState::LevelOutro)),
+				),
+				(
+					bug_report::open_bug_report
+						.run_if(in_state(GameState::Playing)),
+					bug_report::edit_bug_report
+						.run_if(in_state(GameState::BugReport)),
+				),
+				(
+					browser::open_level_browser
+						.run_if(in_state(GameState::Playing)),
+					browser::browse_levels
+						.run_if(in_state(GameState::LevelBrowser)),
+					sharing::open_community_levels
+						.run_if(in_state(GameState::LevelBrowser)),
+					sharing::browse_community_levels
+						.run_if(in_state(GameState::CommunityLevels)),
+					campaign::advance_campaign
+						.run_if(in_state(GameState::LevelComplete)),
+					progress::record_seen_mechanics,
+					thumbnails::request_thumbnails,
+					thumbnails::render_thumbnails
+						.run_if(in_state(GameState::Playing)),
+					timeline::update_timeline
+						.run_if(in_state(GameState::Playing)),
+					branches::track_branches,
+					branches::open_branch_comparison
+						.run_if(in_state(GameState::Playing)),
+					branches::compare_branches
+						.run_if(in_state(GameState::ComparingBranches)),
+					pause::pause_on_focus_loss,
+					pause::open_pause_menu.run_if(in_state(GameState::Playing)),
+					pause::navigate_pause_menu
+						.run_if(in_state(GameState::Paused)),
+					pause::practice_position
+						.run_if(in_state(GameState::Paused)),
+					tutorials::queue_tutorials
+						.run_if(in_state(GameState::Playing)),
+					tutorials::play_tutorial
+						.run_if(in_state(GameState::Tutorial)),
+				),
+				(
+					photo_mode::toggle_photo_mode.run_if(
+						in_state(GameState::Playing)
+							.or(in_state(GameState::PhotoMode)),
+					),
+					photo_mode::control_photo_camera
+						.run_if(in_state(GameState::PhotoMode)),
+				),
+				(
+					devices::handle_gamepad_connections,
+					devices::resume_with_any_device
+						.run_if(in_state(GameState::DeviceDisconnected)),
+				),
+				backdrop::animate_backdrop.run_if(
+					in_state(GameState::Playing)
+						.or(in_state(GameState::PhotoMode)),
+				),
 			),
 		)
+		.add_systems(
+			OnEnter(GameState::PhotoMode),
+			photo_mode::enter_photo_mode,
+		)
+		.add_systems(OnExit(GameState::PhotoMode), photo_mode::exit_photo_mode)
+		.add_systems(
+			OnEnter(GameState::BugReport),
+			bug_report::spawn_bug_report_dialog,
+		)
+		.add_systems(
+			OnExit(GameState::BugReport),
+			bug_report::despawn_bug_report_dialog,
+		)
+		.add_systems(
+			OnEnter(GameState::LevelOutro),
+			(outro::start_outro, audio::play_fanfare),
+		)
+		.add_systems(
+			OnEnter(GameState::LevelComplete),
+			campaign::spawn_level_complete_dialog,
+		)
+		.add_systems(
+			OnExit(GameState::LevelComplete),
+			(campaign::despawn_level_complete_dialog, outro::end_outro),
+		)
+		.add_systems(
+			OnEnter(GameState::DeviceDisconnected),
+			devices::spawn_disconnected_prompt,
+		)
+		.add_systems(
+			OnExit(GameState::DeviceDisconnected),
+			devices::despawn_disconnected_prompt,
+		)
+		.add_systems(
+			OnEnter(GameState::LevelBrowser),
+			browser::spawn_level_browser,
+		)
+		.add_systems(
+			OnExit(GameState::LevelBrowser),
+			browser::despawn_level_browser,
+		)
+		.add_systems(
+			OnEnter(GameState::CommunityLevels),
+			sharing::spawn_community_levels,
+		)
+		.add_systems(
+			OnExit(GameState::CommunityLevels),
+			sharing::despawn_community_levels,
+		)
+		.add_systems(
+			OnEnter(GameState::ComparingBranches),
+			branches::spawn_branch_comparison,
+		)
+		.add_systems(
+			OnExit(GameState::ComparingBranches),
+			branches::despawn_branch_comparison,
+		)
+		.add_systems(OnEnter(GameState::Tutorial), tutorials::spawn_tutorial)
+		.add_systems(OnExit(GameState::Tutorial), tutorials::despawn_tutorial)
+		.add_systems(Last, display::limit_frame_rate)
+		.add_systems(OnEnter(GameState::Paused), pause::spawn_pause_menu)
+		.add_systems(OnExit(GameState::Paused), pause::despawn_pause_menu)
+		// Run input handling and level updates on a fixed timestep rather than
+		// once per render frame. The fixed schedule runs before Update, so
+		// animations respond to changes in the same frame; they aren't
+		// interpolated against the fixed step.
+		.add_systems(
+			FixedUpdate,
+			(
+				control::control,
+				macros::record_macro,
+				macros::play_macro,
+				update::update,
+			)
+				.chain()
+				.run_if(in_state(GameState::Playing)),
+		)
+		.insert_resource(Time::<Fixed>::from_hz(SIMULATION_HZ))
 		.add_event::<NextActor>()
 		.add_event::<ControlEvent>()
+		.add_event::<control::ControlRejected>()
 		.add_event::<ChangeEvent>()
+		.add_event::<update::SeekRecapEvent>()
+		.add_event::<update::LevelStatusEvent>()
+		.add_event::<campaign::PlayLevel>()
+		.add_event::<campaign::LevelCompleted>()
+		.insert_resource(rng::GameRng::from_env())
+		.insert_resource(settings)
+		.insert_resource(progress::Progress::load())
+		.insert_resource(campaign::Campaign::load())
 		.insert_resource(ClearColor(Color::BLACK))
-		.insert_resource(level::test_level())
-		.run();
+		.insert_resource(level_asset::StartLevel(start_level))
+		// Replaced by the first bundled level, or the start level if there is
+		// one, once it loads.
+		.insert_resource(
+			level_asset::embedded_level(0)
+				.expect("embedded levels are valid")
+				.to_level(),
+		);
+	#[cfg(feature = "automation")]
+	app.init_resource::<automation::AutomationLog>()
+		.add_systems(Update, automation::emit_automation_events);
+	app.run();
 }
 
-// Loads and inserts models, meshes, and materials.
+// Loads and inserts game assets and levels.
 fn setup(
 	mut commands: Commands,
 	mut asset_server: ResMut<AssetServer>,
+	mut campaign: ResMut<campaign::Campaign>,
+	mut level_assets: ResMut<Assets<level_asset::LevelAsset>>,
 	mut mesh_assets: ResMut<Assets<Mesh>>,
 	mut material_assets: ResMut<Assets<StandardMaterial>>,
 ) {
-	commands.insert_resource(Models::load(&mut asset_server));
-	commands.insert_resource(Meshes::load(&mut mesh_assets));
-	commands.insert_resource(Materials::load(&mut material_assets));
+	commands.insert_resource(GameAssets::load(
+		&asset_server,
+		&mut mesh_assets,
+		&mut material_assets,
+	));
+	commands.insert_resource(level_asset::Levels::load(
+		&mut asset_server,
+		&mut level_assets,
+		&mut campaign,
+	));
+	commands.insert_resource(audio::AudioTracks::load(&mut asset_server));
 }
 
 fn spawn_level(
 	mut commands: Commands,
 	level: Res<Level>,
-	models: Res<Models>,
-	meshes: Res<Meshes>,
-	materials: Res<Materials>,
+	assets: Res<GameAssets>,
+	mut rng: ResMut<rng::GameRng>,
 ) {
 	// Spawn tile entities.
 	for row in 0..level.height() {
 		for col in 0..level.width() {
 			let tile_coords = Coords::new(row as i32, col as i32);
-			match level.tile_at(tile_coords) {
-				// Assume a fresh level has no open portals.
-				Tile::Floor { .. } => commands.spawn((
-					LevelEntity,
-					SceneRoot(models.floor.clone()),
-					tile_coords.transform(-0.5),
-				)),
-				Tile::Wall => commands.spawn((
-					LevelEntity,
-					SceneRoot(models.wall.clone()),
-					tile_coords.transform(0.5),
-				)),
+			let tile = level.tile_at(tile_coords);
+			// Rotate floor tiles randomly to break up repetition. Assume a
+			// fresh level has no open portals.
+			let quarter_turns = match tile {
+				Tile::Floor { .. } => {
+					rng.stream("tile_variation").gen_range(0..4)
+				}
+				_ => 0,
 			};
+			animation::spawn_tile(
+				&mut commands,
+				&assets,
+				tile,
+				tile_coords,
+				quarter_turns,
+			);
 		}
 	}
 
+	// Spawn platform entities.
+	for (idx, platform) in level.platforms().iter().enumerate() {
+		commands.spawn((
+			LevelEntity,
+			animation::PlatformModel { idx },
+			animation::platform(&assets, platform.coords()),
+		));
+	}
+
 	// Spawn object entities.
 	for level_object in level.iter_level_objects() {
-		let transform = level_object.coords.transform(0.5);
-		match level_object.object {
-			Object::Character(c) => commands
-				.spawn((
-					LevelEntity,
-					animation::Object {
-						id: level_object.id,
-						rotates: true,
-					},
-					transform,
-				))
-				.with_children(|child_builder| {
-					child_builder.spawn((
-						animation::ObjectBody,
-						Mesh3d(meshes.character.clone()),
-						MeshMaterial3d(
-							materials.characters[c.color.idx()].clone(),
-						),
-					));
-				}),
-			Object::WoodenCrate => commands
-				.spawn((
-					LevelEntity,
-					animation::Object {
-						id: level_object.id,
-						rotates: false,
-					},
-					transform,
-				))
-				.with_children(|child_builder| {
-					child_builder.spawn((
-						animation::ObjectBody,
-						SceneRoot(models.wooden_crate.clone()),
-					));
-				}),
-			Object::SteelCrate => commands
-				.spawn((
-					LevelEntity,
-					animation::Object {
-						id: level_object.id,
-						rotates: false,
-					},
-					transform,
-				))
-				.with_children(|child_builder| {
-					child_builder.spawn((
-						animation::ObjectBody,
-						SceneRoot(models.steel_crate.clone()),
-					));
-				}),
-			Object::StoneBlock => commands
-				.spawn((
-					LevelEntity,
-					animation::Object {
-						id: level_object.id,
-						rotates: false,
-					},
-					transform,
-				))
-				.with_children(|child_builder| {
-					child_builder.spawn((
-						animation::ObjectBody,
-						SceneRoot(models.stone_block.clone()),
-					));
-				}),
-		};
+		animation::spawn_object(&mut commands, &assets, level_object);
 	}
 }
 
 fn lights_cameras_action(
 	mut commands: Commands,
 	level: Res<Level>,
+	assets: Res<GameAssets>,
 	mut ambient_light: ResMut<AmbientLight>,
 	mut next_actors: EventWriter<NextActor>,
 	mut next_state: ResMut<NextState<GameState>>,
 ) {
-	// Add a static camera overlooking the level.
-	let offset = Vec3::new(-0.5, 0.5, 1.0);
-	let level_size =
-		Vec3::new(level.width() as f32, level.height() as f32, 0.0);
-	let target = offset + 0.5 * Vec3::new(level_size.x, -level_size.y, 0.0);
-	commands.spawn((
+	// Add a camera overlooking the whole level.
+	let (camera_transform, view_size) = camera::framing(
+		Vec2::ZERO,
+		Vec2::new(level.width() as f32, level.height() as f32) - Vec2::ONE,
+	);
+	let lighting = level.lighting();
+	let mut camera = commands.spawn((
 		LevelEntity,
+		camera::MainCamera,
 		Camera3d::default(),
-		Transform::from_translation(Vec3::new(
-			target.x,
-			-level_size.y,
-			level_size.x.max(level_size.y),
-		))
-		.looking_at(target, Vec3::Z),
+		camera_transform,
 		Projection::Orthographic(OrthographicProjection {
-			scaling_mode: ScalingMode::AutoMin {
-				min_width: level_size.x,
-				min_height: level_size.y,
-			},
+			scaling_mode: camera::scaling_mode(view_size),
 			..OrthographicProjection::default_3d()
 		}),
 	));
+	if let Some(fog) = lighting.fog {
+		camera.insert(fog.distance_fog());
+	}
+	// Add a backdrop behind the level, attached to the camera so it always
+	// fills the view.
+	camera.with_children(|child_builder| {
+		child_builder.spawn(backdrop::backdrop_bundle(&assets));
+	});
 
 	// Add lighting.
-	ambient_light.brightness = 250.0;
+	ambient_light.color = lighting.ambient_color;
+	ambient_light.brightness = lighting.ambient_brightness;
 	commands.spawn((
 		LevelEntity,
 		DirectionalLight {
-			illuminance: 0.3 * light_consts::lux::AMBIENT_DAYLIGHT,
+			illuminance: lighting.illuminance,
 			shadows_enabled: true,
 			..default()
 		},
-		Transform::from_rotation(Quat::from_axis_angle(
-			Vec3::new(1.0, 1.0, 0.0),
-			-TAU / 16.0,
-		)),
+		Transform::from_rotation(lighting.light_rotation()),
 	));
 
 	// Kick off the control loop by sending the first actor, if there is one.
-	if let Some((&id, &character)) = level.characters_by_id().next() {
-		next_actors.send(NextActor { id, character });
+	if let Some(next_actor) = NextActor::nth(&level, 0) {
+		next_actors.send(next_actor);
 	}
 
 	next_state.set(GameState::Playing);
 }
 
+/// Keys that start the first few levels in the list, in order.
+const LEVEL_KEYS: [KeyCode; 9] = [
+	KeyCode::Digit1,
+	KeyCode::Digit2,
+	KeyCode::Digit3,
+	KeyCode::Digit4,
+	KeyCode::Digit5,
+	KeyCode::Digit6,
+	KeyCode::Digit7,
+	KeyCode::Digit8,
+	KeyCode::Digit9,
+];
+
+/// A level to change to.
+#[derive(Clone, Copy)]
+enum LevelChoice {
+	Bundled(usize),
+	Stress,
+	Featured(u64),
+}
+
+/// The sources of requests to change levels.
+#[derive(SystemParam)]
+struct LevelRequests<'w, 's> {
+	keyboard_events: EventReader<'w, 's, KeyboardInput>,
+	level_asset_events:
+		EventReader<'w, 's, AssetEvent<level_asset::LevelAsset>>,
+	play_level_events: EventReader<'w, 's, campaign::PlayLevel>,
+}
+
+/// The play modes that reset or switch when the level changes.
+#[derive(SystemParam)]
+struct LevelModes<'w> {
+	featured_run: ResMut<'w, featured::FeaturedRun>,
+	autopilot: ResMut<'w, autopilot::Autopilot>,
+	ghost: ResMut<'w, ghost::Ghost>,
+	stress_test: ResMut<'w, stress::StressTest>,
+	time: Res<'w, Time>,
+}
+
+/// The bundled levels, their loaded assets, and which are unlocked.
+#[derive(SystemParam)]
+struct BundledLevels<'w> {
+	levels: ResMut<'w, level_asset::Levels>,
+	level_assets: Res<'w, Assets<level_asset::LevelAsset>>,
+	campaign: Res<'w, campaign::Campaign>,
+	progress: Res<'w, progress::Progress>,
+}
+
+/// Changes levels when a level key is pressed or another level is requested,
+/// or restarts the current bundled level when its file is modified. Page Down
+/// and Page Up step through the list of levels. Only unlocked levels can be
+/// changed to, and the stress level (F6) is only available in debug builds.
 fn change_level(
 	mut commands: Commands,
-	mut keyboard_events: EventReader<KeyboardInput>,
+	requests: LevelRequests,
 	mut level: ResMut<Level>,
+	modes: LevelModes,
+	bundled: BundledLevels,
 	mut next_state: ResMut<NextState<GameState>>,
 	level_entities: Query<Entity, With<level::LevelEntity>>,
 ) {
-	for event in keyboard_events.read() {
-		if event.state != ButtonState::Pressed {
-			continue;
+	let LevelRequests {
+		mut keyboard_events,
+		mut level_asset_events,
+		mut play_level_events,
+	} = requests;
+	let LevelModes {
+		mut featured_run,
+		mut autopilot,
+		mut ghost,
+		mut stress_test,
+		time,
+	} = modes;
+	let BundledLevels {
+		mut levels,
+		level_assets,
+		campaign,
+		progress,
+	} = bundled;
+	// Steps `offset` levels along the list from the current one, wrapping.
+	let step = |offset: usize| {
+		let count = levels.count();
+		(count > 0).then(|| {
+			let idx = levels.current().map_or(0, |idx| idx + offset) % count;
+			LevelChoice::Bundled(idx)
+		})
+	};
+	let mut choices: Vec<LevelChoice> = keyboard_events
+		.read()
+		.filter(|event| event.state == ButtonState::Pressed)
+		.filter_map(|event| match event.key_code {
+			KeyCode::PageDown => step(1),
+			KeyCode::PageUp => step(levels.count().saturating_sub(1)),
+			KeyCode::F6 => {
+				cfg!(debug_assertions).then_some(LevelChoice::Stress)
+			}
+			KeyCode::Digit0 => {
+				Some(LevelChoice::Featured(featured::current_week()))
+			}
+			key => LEVEL_KEYS
+				.iter()
+				.position(|level_key| *level_key == key)
+				.filter(|idx| *idx < levels.count())
+				.map(LevelChoice::Bundled),
+		})
+		.collect();
+	choices.extend(
+		play_level_events
+			.read()
+			.map(|event| LevelChoice::Bundled(event.0)),
+	);
+	choices.retain(|choice| match choice {
+		LevelChoice::Bundled(idx) => campaign.is_unlocked(*idx, &progress),
+		_ => true,
+	});
+	if level_asset_events
+		.read()
+		.any(|event| levels.is_current_modified(event))
+	{
+		if let Some(idx) = levels.current() {
+			info!("level file modified; reloading");
+			choices.push(LevelChoice::Bundled(idx));
 		}
-		if let Some(next_level) = match event.key_code {
-			KeyCode::Digit1 => Some(level::test_level()),
-			KeyCode::Digit2 => Some(level::test_level_short()),
-			KeyCode::Digit3 => Some(level::test_level_thin()),
-			KeyCode::Digit4 => Some(level::test_level_large()),
-			_ => None,
-		} {
-			// Despawn any existing level entities.
-			for entity in level_entities.into_iter() {
-				commands.entity(entity).despawn_recursive();
+	}
+	for choice in choices {
+		let next_level = match choice {
+			LevelChoice::Bundled(idx) => {
+				match levels.instantiate(idx, &level_assets) {
+					Some(next_level) => next_level,
+					None => continue,
+				}
 			}
-			// Update the level resource and respawn the level.
-			*level = next_level;
-			next_state.set(GameState::SpawningLevel);
+			LevelChoice::Stress => level::stress_level(),
+			LevelChoice::Featured(week) => featured::featured_level(week),
+		};
+		autopilot.clear();
+		ghost.restart(&level, &next_level, time.elapsed());
+		match choice {
+			LevelChoice::Stress => stress_test.start(),
+			_ => stress_test.stop(),
+		}
+		match choice {
+			LevelChoice::Featured(week) => featured_run.start(week),
+			_ => featured_run.stop(),
+		}
+		levels.set_current(match choice {
+			LevelChoice::Bundled(idx) => Some(idx),
+			_ => None,
+		});
+		// Despawn any existing level entities.
+		for entity in level_entities.into_iter() {
+			commands.entity(entity).despawn_recursive();
 		}
+		// Update the level resource and respawn the level.
+		*level = next_level;
+		next_state.set(GameState::SpawningLevel);
 	}
 }
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	level::{Level, Rules},
+	photo_mode::HiddenInPhotoMode,
+	settings::Settings,
+};
+
+/// A challenge modifier that alters a level's rules when the level starts.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mutator {
+	/// Time only moves forward.
+	NoUndo,
+	/// Crates weigh twice as much as usual.
+	HeavyCrates,
+	/// Summons land next to the summoner instead of as far away as possible.
+	AdjacentSummons,
+	/// Turns with summonings can't be undone while their portals are open.
+	PortalsLockHistory,
+	/// Heavy objects crush wooden crates instead of pushing them.
+	Crushing,
+}
+
+impl Mutator {
+	/// All mutators, in display order.
+	const ALL: [Mutator; 5] = [
+		Mutator::NoUndo,
+		Mutator::HeavyCrates,
+		Mutator::AdjacentSummons,
+		Mutator::PortalsLockHistory,
+		Mutator::Crushing,
+	];
+
+	fn name(self) -> &'static str {
+		match self {
+			Mutator::NoUndo => "No undo",
+			Mutator::HeavyCrates => "Heavy crates",
+			Mutator::AdjacentSummons => "Adjacent summons",
+			Mutator::PortalsLockHistory => "Portals lock history",
+			Mutator::Crushing => "Crushing",
+		}
+	}
+
+	/// Modifies `rules` to include this mutator's effect.
+	fn apply(self, rules: Rules) -> Rules {
+		match self {
+			Mutator::NoUndo => Rules {
+				no_undo: true,
+				..rules
+			},
+			Mutator::HeavyCrates => Rules {
+				heavy_crates: true,
+				..rules
+			},
+			Mutator::AdjacentSummons => Rules {
+				adjacent_summons: true,
+				..rules
+			},
+			Mutator::PortalsLockHistory => Rules {
+				summons_lock_history: true,
+				..rules
+			},
+			Mutator::Crushing => Rules {
+				crushing: true,
+				..rules
+			},
+		}
+	}
+
+	/// The percentage this mutator adds to the score multiplier.
+	fn score_bonus(self) -> u32 {
+		match self {
+			Mutator::NoUndo => 50,
+			Mutator::HeavyCrates => 25,
+			Mutator::AdjacentSummons => 25,
+			Mutator::PortalsLockHistory => 25,
+			// Crushing changes puzzles more than it makes them harder.
+			Mutator::Crushing => 0,
+		}
+	}
+}
+
+/// The distinct mutators among `mutators`, in display order.
+fn active(mutators: &[Mutator]) -> impl Iterator<Item = Mutator> + '_ {
+	Mutator::ALL
+		.into_iter()
+		.filter(|mutator| mutators.contains(mutator))
+}
+
+/// Passes `rules` through each of the `mutators` in turn.
+pub fn apply_all(mutators: &[Mutator], rules: Rules) -> Rules {
+	active(mutators).fold(rules, |rules, mutator| mutator.apply(rules))
+}
+
+/// The factor by which a level's score is multiplied for playing with
+/// `mutators`.
+pub fn score_multiplier(mutators: &[Mutator]) -> f32 {
+	let bonus: u32 = active(mutators).map(Mutator::score_bonus).sum();
+	1.0 + bonus as f32 / 100.0
+}
+
+/// Applies the selected mutators to the level as it starts.
+pub fn apply_mutators(settings: Res<Settings>, mut level: ResMut<Level>) {
+	let rules = apply_all(&settings.mutators, level.rules());
+	if rules != level.rules() {
+		level.set_rules(rules);
+	}
+}
+
+/// Marker component for the active mutators text.
+#[derive(Component)]
+pub struct MutatorsText;
+
+/// Spawns text listing the active mutators and their score multiplier, if any
+/// are selected.
+pub fn spawn_mutators_text(mut commands: Commands, settings: Res<Settings>) {
+	if active(&settings.mutators).next().is_none() {
+		return;
+	}
+	let names: Vec<&str> =
+		active(&settings.mutators).map(Mutator::name).collect();
+	commands.spawn((
+		MutatorsText,
+		HiddenInPhotoMode,
+		Text(format!(
+			"Mutators: {} (score x{:.2})",
+			names.join(", "),
+			score_multiplier(&settings.mutators),
+		)),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(4.0),
+			right: Val::Px(4.0),
+			..default()
+		},
+	));
+}
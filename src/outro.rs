@@ -0,0 +1,292 @@
+use std::{
+	f32::consts::{FRAC_PI_2, TAU},
+	time::Duration,
+};
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_easings::{
+	Ease, EaseFunction, EasingChainComponent, EasingComponent, EasingType,
+};
+
+use crate::{
+	animation::{Object, ObjectBody},
+	camera::{self, MainCamera},
+	level::{CharacterColor, Level, Offset},
+	settings::Settings,
+	states::GameState,
+};
+
+/// How long characters take to turn toward the camera, which also gives the
+/// last turn's animations time to finish.
+const TURN_DURATION: Duration = Duration::from_millis(300);
+
+/// How much later each character starts its pose than the one before it.
+const POSE_STAGGER: Duration = Duration::from_millis(80);
+
+/// How long the outro lasts before the results appear.
+const OUTRO_DURATION: Duration = Duration::from_millis(1600);
+
+/// Number of tiles kept in view around the characters once the camera has
+/// dollied in.
+const DOLLY_MARGIN: f32 = 1.0;
+
+/// How high characters jump in their poses.
+const JUMP_HEIGHT: f32 = 0.35;
+
+/// A character's celebration at the end of a level.
+#[derive(Clone, Copy)]
+enum Pose {
+	/// Two quick hops.
+	Hop,
+	/// A leap with a full turn in the air.
+	Spin,
+	/// A wiggle from side to side.
+	Wiggle,
+}
+
+impl Pose {
+	/// The pose of characters of `color`.
+	fn of(color: CharacterColor) -> Pose {
+		match color.idx() % 3 {
+			0 => Pose::Hop,
+			1 => Pose::Spin,
+			_ => Pose::Wiggle,
+		}
+	}
+
+	/// The steps of this pose for a body resting at `rest`, as the transform
+	/// to ease to, how, and for how long.
+	fn steps(
+		self,
+		rest: Transform,
+	) -> Vec<(Transform, EaseFunction, Duration)> {
+		let up = |height: f32| {
+			rest.with_translation(rest.translation + height * Vec3::Z)
+		};
+		let turned = |angle: f32| {
+			rest.with_rotation(rest.rotation * Quat::from_rotation_z(angle))
+		};
+		let ms = Duration::from_millis;
+		match self {
+			Pose::Hop => [JUMP_HEIGHT, 0.6 * JUMP_HEIGHT]
+				.into_iter()
+				.flat_map(|height| {
+					[
+						(up(height), EaseFunction::QuadraticOut, ms(180)),
+						(rest, EaseFunction::QuadraticIn, ms(180)),
+					]
+				})
+				.collect(),
+			Pose::Spin => {
+				// A full turn is the same rotation as none, so turn in thirds.
+				let peak = up(JUMP_HEIGHT);
+				vec![
+					(peak, EaseFunction::QuadraticOut, ms(150)),
+					(
+						turned(TAU / 3.0).with_translation(peak.translation),
+						EaseFunction::QuadraticInOut,
+						ms(120),
+					),
+					(
+						turned(2.0 * TAU / 3.0)
+							.with_translation(peak.translation),
+						EaseFunction::QuadraticInOut,
+						ms(120),
+					),
+					(rest, EaseFunction::BounceOut, ms(300)),
+				]
+			}
+			Pose::Wiggle => [1.0, -1.0, 1.0, -1.0]
+				.into_iter()
+				.map(|side: f32| {
+					(
+						turned(side * FRAC_PI_2 / 4.0),
+						EaseFunction::QuadraticInOut,
+						ms(120),
+					)
+				})
+				.chain([(rest, EaseFunction::QuadraticOut, ms(120))])
+				.collect(),
+		}
+	}
+}
+
+/// The state of the level outro.
+#[derive(Resource, Default)]
+pub struct Outro {
+	timer: Timer,
+	/// The camera's transform and view size before and after dollying in.
+	dolly: Option<((Transform, Vec2), (Transform, Vec2))>,
+}
+
+/// Starts the outro of a completed level: its characters turn toward the
+/// camera and strike their poses while the camera dollies in on them. With
+/// reduced motion, characters just turn, and the camera stays put.
+pub fn start_outro(
+	mut commands: Commands,
+	level: Res<Level>,
+	settings: Res<Settings>,
+	mut outro: ResMut<Outro>,
+	object_query: Query<(&Object, &Children)>,
+	body_query: Query<&Transform, With<ObjectBody>>,
+	camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
+) {
+	let once = |duration| EasingType::Once { duration };
+	let facing_camera =
+		Transform::from_rotation(Quat::from_rotation_z(Offset::DOWN.angle()));
+	for (idx, (id, character)) in level.characters_by_id().enumerate() {
+		let Some((_, children)) =
+			object_query.iter().find(|(object, _)| object.id == *id)
+		else {
+			continue;
+		};
+		for &child in children {
+			let Ok(from) = body_query.get(child) else {
+				continue;
+			};
+			let mut chain = from
+				.ease_to(
+					facing_camera,
+					EaseFunction::QuadraticInOut,
+					once(TURN_DURATION),
+				)
+				.ease_to(
+					facing_camera,
+					EaseFunction::QuadraticInOut,
+					once(POSE_STAGGER * (idx as u32 + 1)),
+				);
+			if !settings.reduce_motion {
+				for (to, ease_function, duration) in
+					Pose::of(character.color).steps(facing_camera)
+				{
+					chain = chain.ease_to(to, ease_function, once(duration));
+				}
+			}
+			commands
+				.entity(child)
+				.remove::<EasingComponent<Transform>>()
+				.insert(chain);
+		}
+	}
+
+	outro.timer = Timer::new(OUTRO_DURATION, TimerMode::Once);
+	outro.dolly = None;
+	if settings.reduce_motion {
+		return;
+	}
+	let Some((min, max)) = level
+		.iter_level_objects()
+		.filter(|level_object| level_object.object.is_character())
+		.map(|level_object| {
+			let coords = level_object.coords;
+			Vec2::new(coords.col as f32, coords.row as f32)
+		})
+		.fold(None, |bounds: Option<(Vec2, Vec2)>, coords| {
+			Some(bounds.map_or((coords, coords), |(min, max)| {
+				(min.min(coords), max.max(coords))
+			}))
+		})
+	else {
+		return;
+	};
+	let Ok((transform, projection)) = camera_query.get_single() else {
+		return;
+	};
+	let Projection::Orthographic(orthographic) = projection else {
+		return;
+	};
+	let ScalingMode::AutoMin {
+		min_width,
+		min_height,
+	} = orthographic.scaling_mode
+	else {
+		return;
+	};
+	let dollied = camera::framing(
+		min - Vec2::splat(DOLLY_MARGIN),
+		max + Vec2::splat(DOLLY_MARGIN),
+	);
+	outro.dolly =
+		Some(((*transform, Vec2::new(min_width, min_height)), dollied));
+}
+
+/// Dollies the camera in over the outro, then shows the results. Enter or
+/// Escape skips to the results.
+pub fn play_outro(
+	time: Res<Time>,
+	keys: Res<ButtonInput<KeyCode>>,
+	mut outro: ResMut<Outro>,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection),
+		With<MainCamera>,
+	>,
+) {
+	let finished = outro.timer.tick(time.delta()).finished()
+		|| keys.just_pressed(KeyCode::Enter)
+		|| keys.just_pressed(KeyCode::Escape);
+	if let Some(((start, start_size), (end, end_size))) = outro.dolly {
+		let t = if finished {
+			1.0
+		} else {
+			let t = outro.timer.fraction();
+			t * t * (3.0 - 2.0 * t)
+		};
+		for (mut transform, mut projection) in &mut camera_query {
+			transform.translation = start.translation.lerp(end.translation, t);
+			transform.rotation = start.rotation.slerp(end.rotation, t);
+			if let Projection::Orthographic(orthographic) = &mut *projection {
+				orthographic.scaling_mode =
+					camera::scaling_mode(start_size.lerp(end_size, t));
+			}
+		}
+	}
+	if finished {
+		next_state.set(GameState::LevelComplete);
+	}
+}
+
+/// Puts the camera and characters back as they were before the outro, for
+/// playing on after the results.
+pub fn end_outro(
+	mut commands: Commands,
+	level: Res<Level>,
+	mut outro: ResMut<Outro>,
+	object_query: Query<(&Object, &Children)>,
+	body_query: Query<(), With<ObjectBody>>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection),
+		With<MainCamera>,
+	>,
+) {
+	if let Some(((transform, size), _)) = outro.dolly.take() {
+		for (mut camera_transform, mut projection) in &mut camera_query {
+			*camera_transform = transform;
+			if let Projection::Orthographic(orthographic) = &mut *projection {
+				orthographic.scaling_mode = camera::scaling_mode(size);
+			}
+		}
+	}
+	for (object, children) in &object_query {
+		let Some(level_object) =
+			level.iter_level_objects().find(|level_object| {
+				object.rotates && level_object.id == object.id
+			})
+		else {
+			continue;
+		};
+		let rest =
+			Transform::from_rotation(Quat::from_rotation_z(level_object.angle));
+		for &child in children {
+			if body_query.contains(child) {
+				commands
+					.entity(child)
+					.remove::<(
+						EasingComponent<Transform>,
+						EasingChainComponent<Transform>,
+					)>()
+					.insert(rest);
+			}
+		}
+	}
+}
@@ -0,0 +1,90 @@
+use bevy::{
+	prelude::*,
+	render::camera::RenderTarget,
+	window::{WindowRef, WindowResolution},
+};
+
+use crate::{
+	assets::GameAssets,
+	backdrop, camera,
+	level::{Level, LevelEntity},
+};
+
+/// Marker component for the secondary window showing the whole level.
+#[derive(Component)]
+pub struct OverviewWindow;
+
+/// Marker component for the camera rendering to the [`OverviewWindow`].
+#[derive(Component)]
+pub struct OverviewCamera;
+
+/// Opens or closes the overview window when F10 is pressed. While it's open,
+/// the main camera follows the characters.
+pub fn toggle_overview_window(
+	mut commands: Commands,
+	keys: Res<ButtonInput<KeyCode>>,
+	window_query: Query<Entity, With<OverviewWindow>>,
+) {
+	if !keys.just_pressed(KeyCode::F10) {
+		return;
+	}
+	if window_query.is_empty() {
+		commands.spawn((
+			OverviewWindow,
+			Window {
+				title: "Causal Oops: Overview".to_string(),
+				resolution: WindowResolution::new(640.0, 480.0),
+				..default()
+			},
+		));
+	} else {
+		for entity in &window_query {
+			commands.entity(entity).despawn_recursive();
+		}
+	}
+}
+
+/// Keeps a camera framing the whole level in the overview window, if it's
+/// open. The camera is a level entity, so it's respawned with each level.
+pub fn sync_overview_camera(
+	mut commands: Commands,
+	level: Res<Level>,
+	assets: Res<GameAssets>,
+	window_query: Query<Entity, With<OverviewWindow>>,
+	camera_query: Query<Entity, With<OverviewCamera>>,
+) {
+	let Ok(window) = window_query.get_single() else {
+		// The window was closed, possibly by the OS.
+		for entity in &camera_query {
+			commands.entity(entity).despawn_recursive();
+		}
+		return;
+	};
+	if !camera_query.is_empty() {
+		return;
+	}
+	let (transform, view_size) = camera::framing(
+		Vec2::ZERO,
+		Vec2::new(level.width() as f32, level.height() as f32) - Vec2::ONE,
+	);
+	let mut overview_camera = commands.spawn((
+		LevelEntity,
+		OverviewCamera,
+		Camera3d::default(),
+		Camera {
+			target: RenderTarget::Window(WindowRef::Entity(window)),
+			..default()
+		},
+		transform,
+		Projection::Orthographic(OrthographicProjection {
+			scaling_mode: camera::scaling_mode(view_size),
+			..OrthographicProjection::default_3d()
+		}),
+	));
+	if let Some(fog) = level.lighting().fog {
+		overview_camera.insert(fog.distance_fog());
+	}
+	overview_camera.with_children(|child_builder| {
+		child_builder.spawn(backdrop::backdrop_bundle(&assets));
+	});
+}
@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::level_asset::{LevelAsset, LevelAssetError};
+
+/// Extension of level pack files.
+pub const PACK_EXTENSION: &str = "oopspack";
+
+/// A set of levels bundled into a single `.oopspack` file, to be played in
+/// order as a campaign of their own.
+///
+/// A pack is a RON document with the pack's title and author and a list of
+/// levels, each in the same format as a `.level.ron` file:
+///
+/// ```text
+/// (
+///     title: "Sampler",
+///     author: Some("Me"),
+///     levels: [
+///         (name: "First", map: "..."),
+///         (name: "Second", map: "..."),
+///     ],
+/// )
+/// ```
+#[derive(Deserialize)]
+pub struct LevelPack {
+	pub title: String,
+	#[serde(default)]
+	pub author: Option<String>,
+	/// The pack's levels, in the order they're played.
+	pub levels: Vec<LevelAsset>,
+}
+
+impl LevelPack {
+	/// Reads and validates the pack at `path`. The pack is rejected if any of
+	/// its levels is invalid.
+	pub fn read(path: &Path) -> Result<LevelPack, LevelAssetError> {
+		let bytes = std::fs::read(path)?;
+		let pack = ron::de::from_bytes::<LevelPack>(&bytes)?;
+		Ok(LevelPack {
+			levels: pack
+				.levels
+				.into_iter()
+				.map(LevelAsset::validated)
+				.collect::<Result<_, _>>()?,
+			..pack
+		})
+	}
+}
@@ -0,0 +1,210 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+	campaign::PlayLevel,
+	control::KeyboardBindings,
+	level::Level,
+	level_asset::{user_levels_dir, LevelAsset, Levels},
+	settings::Settings,
+	states::GameState,
+};
+
+/// Keys that switch to each key binding profile, starting with the default
+/// bindings.
+const PROFILE_KEYS: [KeyCode; 9] = [
+	KeyCode::Digit1,
+	KeyCode::Digit2,
+	KeyCode::Digit3,
+	KeyCode::Digit4,
+	KeyCode::Digit5,
+	KeyCode::Digit6,
+	KeyCode::Digit7,
+	KeyCode::Digit8,
+	KeyCode::Digit9,
+];
+
+/// Marker component for the pause menu.
+#[derive(Component)]
+pub struct PauseMenu;
+
+/// Whether play was paused because the window lost focus, rather than by the
+/// player, so it should resume when the window regains focus.
+#[derive(Resource, Default)]
+pub struct FocusPause(bool);
+
+/// Pauses when the window loses focus while playing, if the
+/// [setting](Settings::pause_on_focus_loss) is on, and resumes when it regains
+/// focus, unless the player has since left the pause menu some other way.
+pub fn pause_on_focus_loss(
+	mut focus_events: EventReader<WindowFocused>,
+	settings: Res<Settings>,
+	mut focus_pause: ResMut<FocusPause>,
+	state: Res<State<GameState>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	let Some(event) = focus_events.read().last() else {
+		return;
+	};
+	match (event.focused, state.get()) {
+		(false, GameState::Playing) if settings.pause_on_focus_loss => {
+			focus_pause.0 = true;
+			next_state.set(GameState::Paused);
+		}
+		(true, GameState::Paused) if focus_pause.0 => {
+			focus_pause.0 = false;
+			next_state.set(GameState::Playing);
+		}
+		(true, _) => focus_pause.0 = false,
+		_ => {}
+	}
+}
+
+/// Pauses when Tab is pressed.
+pub fn open_pause_menu(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::Tab) {
+		next_state.set(GameState::Paused);
+	}
+}
+
+/// The text of the pause menu, listing the key binding profiles with the one
+/// in use marked.
+fn menu_text(settings: &Settings) -> String {
+	let names = std::iter::once(None).chain(
+		settings
+			.binding_profiles
+			.iter()
+			.map(|profile| Some(profile.name.as_str())),
+	);
+	let mut text = "Paused\n\nKey bindings:\n".to_string();
+	for (idx, name) in names.take(PROFILE_KEYS.len()).enumerate() {
+		let marker = if name == settings.binding_profile.as_deref() {
+			">"
+		} else {
+			" "
+		};
+		let name = name.unwrap_or("Default");
+		text += &format!("{marker} {}: {name}\n", idx + 1);
+	}
+	text +=
+		"\nNumber: switch bindings   Enter: practice from here   Esc: resume";
+	text
+}
+
+/// Spawns the pause menu, and stops game time, so timers like the
+/// [ghost](crate::ghost::Ghost)'s don't count time spent paused.
+pub fn spawn_pause_menu(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	mut time: ResMut<Time<Virtual>>,
+) {
+	time.pause();
+	commands.spawn((
+		PauseMenu,
+		Text(menu_text(&settings)),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Percent(30.0),
+			left: Val::Percent(30.0),
+			width: Val::Percent(40.0),
+			padding: UiRect::all(Val::Px(12.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+	));
+}
+
+/// Despawns the pause menu and restarts game time.
+pub fn despawn_pause_menu(
+	mut commands: Commands,
+	mut time: ResMut<Time<Virtual>>,
+	menu_query: Query<Entity, With<PauseMenu>>,
+) {
+	time.unpause();
+	for entity in &menu_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// Handles pause menu input: a number key switches to that key binding
+/// profile and saves the choice, and Escape or Tab resumes play.
+pub fn navigate_pause_menu(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut settings: ResMut<Settings>,
+	mut keyboard_bindings: ResMut<KeyboardBindings>,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut menu_query: Query<&mut Text, With<PauseMenu>>,
+) {
+	if keys.just_pressed(KeyCode::Escape) || keys.just_pressed(KeyCode::Tab) {
+		next_state.set(GameState::Playing);
+		return;
+	}
+	let Some(idx) = PROFILE_KEYS.iter().position(|key| keys.just_pressed(*key))
+	else {
+		return;
+	};
+	let name = match idx.checked_sub(1) {
+		None => None,
+		Some(idx) => match settings.binding_profiles.get(idx) {
+			Some(profile) => Some(profile.name.clone()),
+			None => return,
+		},
+	};
+	info!("key bindings: {}", name.as_deref().unwrap_or("default"));
+	*keyboard_bindings =
+		KeyboardBindings::profile(&settings.binding_profiles, name.as_deref());
+	settings.binding_profile = name;
+	settings.save();
+	for mut text in &mut menu_query {
+		**text = menu_text(&settings);
+	}
+}
+
+/// Starts a practice level from the current position when Enter is pressed
+/// in the pause menu, so players can retry a tricky part of a level without
+/// replaying it from the start. The practice level is also saved to the
+/// [user levels directory](user_levels_dir), to retry in later sessions.
+pub fn practice_position(
+	keys: Res<ButtonInput<KeyCode>>,
+	level: Res<Level>,
+	mut levels: ResMut<Levels>,
+	mut level_assets: ResMut<Assets<LevelAsset>>,
+	mut play_level_events: EventWriter<PlayLevel>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !keys.just_pressed(KeyCode::Enter) {
+		return;
+	}
+	let source = levels.current_asset(&level_assets);
+	let asset = match LevelAsset::practice(&level, source).validated() {
+		Ok(asset) => asset,
+		Err(err) => {
+			warn!("could not practice from this position: {err}");
+			return;
+		}
+	};
+	let file = format!("practice-{:016x}.level.ron", level.state_hash());
+	let dir = user_levels_dir();
+	let result = std::fs::create_dir_all(&dir)
+		.map_err(|err| err.to_string())
+		.and_then(|()| {
+			ron::ser::to_string_pretty(&asset, default())
+				.map_err(|err| err.to_string())
+		})
+		.and_then(|text| {
+			std::fs::write(dir.join(&file), text).map_err(|err| err.to_string())
+		});
+	match result {
+		Ok(()) => info!("saved practice level {file}"),
+		Err(err) => warn!("could not save practice level {file}: {err}"),
+	}
+	let idx = levels.add(level_assets.add(asset));
+	play_level_events.send(PlayLevel(idx));
+	next_state.set(GameState::Playing);
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{
+	control::GameButton,
+	hints::ControlHint,
+	level::{Level, Object},
+};
+
+/// The key to hold to peek at the previous turn.
+const PEEK_KEY: KeyCode = KeyCode::KeyY;
+
+/// Opacity of peeked objects.
+const PEEK_ALPHA: f32 = 0.35;
+
+/// Radius of a peeked character.
+const PEEK_RADIUS: f32 = 0.3;
+
+/// Side length of a peeked prop, in tiles.
+const PEEK_PROP_SIZE: f32 = 0.6;
+
+/// The color of peeked props and the outlines of tiles undoing would change.
+const PEEK_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+
+/// While the Undo hint is hovered or Y is held, draws the state undoing would
+/// return to over the current one, without undoing: each coordinate undoing
+/// would change is outlined, with a translucent copy of whatever would be
+/// there. See [`Level::undo_preview`].
+pub fn draw_undo_preview(
+	keys: Res<ButtonInput<KeyCode>>,
+	level: Res<Level>,
+	hint_query: Query<(&ControlHint, &Interaction)>,
+	mut gizmos: Gizmos,
+) {
+	let hovering_undo = hint_query.iter().any(|(hint, interaction)| {
+		hint.buttons().contains(&GameButton::Undo)
+			&& *interaction != Interaction::None
+	});
+	if !hovering_undo && !keys.pressed(PEEK_KEY) {
+		return;
+	}
+	let Some(previous) = level.undo_preview() else {
+		return;
+	};
+	let outline_color = PEEK_COLOR.with_alpha(PEEK_ALPHA);
+	for coords in previous.changed_coords(&level.snapshot()) {
+		gizmos.rect(
+			coords.transform(0.01).to_isometry(),
+			Vec2::ONE,
+			outline_color,
+		);
+		let position = coords.transform(0.5).translation;
+		match previous.object_at(coords) {
+			Some(Object::Character(character)) => {
+				gizmos.sphere(
+					Isometry3d::from_translation(position),
+					PEEK_RADIUS,
+					character.color.color().with_alpha(PEEK_ALPHA),
+				);
+			}
+			Some(Object::Prop(_)) => {
+				gizmos.cuboid(
+					Transform::from_translation(position)
+						.with_scale(Vec3::splat(PEEK_PROP_SIZE)),
+					outline_color,
+				);
+			}
+			None => {}
+		}
+	}
+}
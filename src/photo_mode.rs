@@ -0,0 +1,192 @@
+use std::{
+	f32::consts::TAU,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+	prelude::*,
+	render::view::{
+		screenshot::{save_to_disk, Screenshot},
+		ColorGrading,
+	},
+};
+
+use crate::{camera::MainCamera, states::GameState};
+
+/// Camera translation speed, in units per second.
+const MOVE_SPEED: f32 = 4.0;
+/// Camera rotation speed, in radians per second.
+const TURN_SPEED: f32 = TAU / 4.0;
+/// Field-of-view change speed, in radians per second.
+const ZOOM_SPEED: f32 = TAU / 8.0;
+const DEFAULT_FOV: f32 = TAU / 8.0;
+const MIN_FOV: f32 = TAU / 64.0;
+const MAX_FOV: f32 = TAU / 3.0;
+
+/// Marks entities that should be hidden while in photo mode, such as HUD
+/// elements and indicators.
+#[derive(Component)]
+pub struct HiddenInPhotoMode;
+
+/// A color filter to apply while in photo mode.
+#[derive(Clone, Copy, Default)]
+enum PhotoFilter {
+	#[default]
+	None,
+	Noir,
+	Warm,
+	Cool,
+}
+
+impl PhotoFilter {
+	fn next(self) -> PhotoFilter {
+		match self {
+			PhotoFilter::None => PhotoFilter::Noir,
+			PhotoFilter::Noir => PhotoFilter::Warm,
+			PhotoFilter::Warm => PhotoFilter::Cool,
+			PhotoFilter::Cool => PhotoFilter::None,
+		}
+	}
+
+	fn color_grading(self) -> ColorGrading {
+		let mut color_grading = ColorGrading::default();
+		match self {
+			PhotoFilter::None => {}
+			PhotoFilter::Noir => color_grading.global.post_saturation = 0.0,
+			PhotoFilter::Warm => color_grading.global.temperature = 0.4,
+			PhotoFilter::Cool => color_grading.global.temperature = -0.4,
+		}
+		color_grading
+	}
+}
+
+/// State to restore when leaving photo mode.
+#[derive(Resource, Default)]
+pub struct PhotoMode {
+	saved_camera: Option<(Transform, Projection)>,
+	hidden: Vec<(Entity, Visibility)>,
+	filter: PhotoFilter,
+}
+
+/// Enters or leaves photo mode when P is pressed.
+pub fn toggle_photo_mode(
+	keys: Res<ButtonInput<KeyCode>>,
+	state: Res<State<GameState>>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !keys.just_pressed(KeyCode::KeyP) {
+		return;
+	}
+	match state.get() {
+		GameState::Playing => next_state.set(GameState::PhotoMode),
+		GameState::PhotoMode => next_state.set(GameState::Playing),
+		_ => {}
+	}
+}
+
+/// Unlocks the camera and hides the HUD.
+pub fn enter_photo_mode(
+	mut photo_mode: ResMut<PhotoMode>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection, &mut ColorGrading),
+		With<MainCamera>,
+	>,
+	mut hidden_query: Query<(Entity, &mut Visibility), With<HiddenInPhotoMode>>,
+) {
+	for (transform, mut projection, mut color_grading) in &mut camera_query {
+		photo_mode.saved_camera = Some((*transform, projection.clone()));
+		*projection = Projection::Perspective(PerspectiveProjection {
+			fov: DEFAULT_FOV,
+			..default()
+		});
+		*color_grading = photo_mode.filter.color_grading();
+	}
+	for (entity, mut visibility) in &mut hidden_query {
+		photo_mode.hidden.push((entity, *visibility));
+		*visibility = Visibility::Hidden;
+	}
+}
+
+/// Restores the camera and HUD.
+pub fn exit_photo_mode(
+	mut photo_mode: ResMut<PhotoMode>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection, &mut ColorGrading),
+		With<MainCamera>,
+	>,
+	mut visibility_query: Query<&mut Visibility>,
+) {
+	if let Some((saved_transform, saved_projection)) =
+		photo_mode.saved_camera.take()
+	{
+		for (mut transform, mut projection, mut color_grading) in
+			&mut camera_query
+		{
+			*transform = saved_transform;
+			*projection = saved_projection.clone();
+			*color_grading = ColorGrading::default();
+		}
+	}
+	for (entity, saved_visibility) in photo_mode.hidden.drain(..) {
+		// The entity may have been despawned in the meantime.
+		if let Ok(mut visibility) = visibility_query.get_mut(entity) {
+			*visibility = saved_visibility;
+		}
+	}
+}
+
+/// Free camera controls: WASD to move, R/F to rise/fall, arrow keys to turn,
+/// Q/E to roll, -/= to change the field of view, C to cycle filters, and F12
+/// to take a screenshot.
+pub fn control_photo_camera(
+	mut commands: Commands,
+	keys: Res<ButtonInput<KeyCode>>,
+	time: Res<Time>,
+	mut photo_mode: ResMut<PhotoMode>,
+	mut camera_query: Query<
+		(&mut Transform, &mut Projection, &mut ColorGrading),
+		With<MainCamera>,
+	>,
+) {
+	let dt = time.delta_secs();
+	// Sums the opposing effects of two keys.
+	let axis = |negative: KeyCode, positive: KeyCode| {
+		keys.pressed(positive) as i32 as f32
+			- keys.pressed(negative) as i32 as f32
+	};
+	if keys.just_pressed(KeyCode::KeyC) {
+		photo_mode.filter = photo_mode.filter.next();
+	}
+	for (mut transform, mut projection, mut color_grading) in &mut camera_query
+	{
+		let direction = axis(KeyCode::KeyS, KeyCode::KeyW)
+			* *transform.forward()
+			+ axis(KeyCode::KeyA, KeyCode::KeyD) * *transform.right()
+			+ axis(KeyCode::KeyF, KeyCode::KeyR) * Vec3::Z;
+		transform.translation += MOVE_SPEED * dt * direction;
+		transform.rotate_z(
+			TURN_SPEED * dt * axis(KeyCode::ArrowRight, KeyCode::ArrowLeft),
+		);
+		transform.rotate_local_x(
+			TURN_SPEED * dt * axis(KeyCode::ArrowDown, KeyCode::ArrowUp),
+		);
+		transform.rotate_local_z(
+			TURN_SPEED * dt * axis(KeyCode::KeyE, KeyCode::KeyQ),
+		);
+		if let Projection::Perspective(perspective) = &mut *projection {
+			perspective.fov = (perspective.fov
+				+ ZOOM_SPEED * dt * axis(KeyCode::Equal, KeyCode::Minus))
+			.clamp(MIN_FOV, MAX_FOV);
+		}
+		*color_grading = photo_mode.filter.color_grading();
+	}
+	if keys.just_pressed(KeyCode::F12) {
+		let seconds = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_secs())
+			.unwrap_or_default();
+		commands
+			.spawn(Screenshot::primary_window())
+			.observe(save_to_disk(format!("screenshot-{seconds}.png")));
+	}
+}
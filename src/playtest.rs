@@ -0,0 +1,188 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashSet};
+use rand::seq::SliceRandom;
+
+use crate::{
+	autopilot::Autopilot,
+	control::Action,
+	level::{Id, Level, LevelEntity},
+	photo_mode::HiddenInPhotoMode,
+	protocol,
+	rng::GameRng,
+	solution::{self, SolutionError},
+	states::GameState,
+	update::NextActor,
+};
+
+/// How many level states the search for a shortest solution visits before
+/// giving up.
+pub const SEARCH_LIMIT: usize = 20_000;
+
+/// The result of searching for a level's shortest solution.
+pub enum Search {
+	/// The fewest turns that complete the level.
+	Solved(usize),
+	/// Every reachable state was visited without completing the level.
+	Unsolvable,
+	/// The search hit [`SEARCH_LIMIT`].
+	GaveUp,
+}
+
+/// Every combination of the characters' legal actions on the current turn of
+/// `level`, in character order.
+fn joint_actions(level: &Level) -> Vec<Vec<(Id, Action)>> {
+	let mut joint = vec![Vec::new()];
+	for idx in 0..level.character_count() {
+		let Some(actor) = NextActor::nth(level, idx) else {
+			break;
+		};
+		let legal = protocol::legal_actions(&actor);
+		let id = actor.id;
+		joint = joint
+			.into_iter()
+			.flat_map(|actions: Vec<(Id, Action)>| {
+				legal.iter().map(move |action| {
+					let mut actions = actions.clone();
+					actions.push((id, *action));
+					actions
+				})
+			})
+			.collect();
+	}
+	joint
+}
+
+/// Searches breadth-first from the start of `level` for the fewest turns that
+/// complete it.
+///
+/// States are told apart by [state hash](Level::state_hash), which doesn't
+/// cover history, so on levels with time travel, the result is a guide rather
+/// than a proof.
+pub fn shortest_solution(level: &Level) -> Search {
+	let start = level.rewound();
+	if start.is_complete() {
+		return Search::Solved(0);
+	}
+	let mut seen = HashSet::from([start.state_hash()]);
+	let mut frontier = vec![start];
+	let mut turns = 0;
+	loop {
+		turns += 1;
+		let mut next = Vec::new();
+		for level in &frontier {
+			for actors in joint_actions(level) {
+				let mut successor = level.clone();
+				successor.update(actors);
+				if successor.is_complete() {
+					return Search::Solved(turns);
+				}
+				if seen.insert(successor.state_hash()) {
+					if seen.len() >= SEARCH_LIMIT {
+						return Search::GaveUp;
+					}
+					next.push(successor);
+				}
+			}
+		}
+		if next.is_empty() {
+			return Search::Unsolvable;
+		}
+		frontier = next;
+	}
+}
+
+/// Marker component for the playtest report.
+#[derive(Component)]
+pub struct PlaytestText;
+
+/// Spawns the text that reports on the last mutation.
+pub fn spawn_playtest_text(mut commands: Commands) {
+	commands.spawn((
+		PlaytestText,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			// Below the stress test text.
+			top: Val::Px(140.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// What it takes to restart play on a replaced level.
+#[derive(SystemParam)]
+pub struct LevelRestart<'w, 's> {
+	commands: Commands<'w, 's>,
+	autopilot: ResMut<'w, Autopilot>,
+	next_state: ResMut<'w, NextState<GameState>>,
+	level_entities: Query<'w, 's, Entity, With<LevelEntity>>,
+}
+
+impl LevelRestart<'_, '_> {
+	/// Despawns the current level's entities and respawns the level.
+	fn restart(&mut self) {
+		for entity in &self.level_entities {
+			self.commands.entity(entity).despawn_recursive();
+		}
+		self.autopilot.clear();
+		self.next_state.set(GameState::SpawningLevel);
+	}
+}
+
+/// A tool for designers exploring difficulty variants of a puzzle: pressing
+/// F4 restarts the level with a random [mutation](crate::level::Mutation) of
+/// its starting layout that keeps it valid, then reports whether the level's
+/// stored solution still completes it and how few turns the shortest
+/// solution now takes.
+///
+/// The search runs on the spot, so the game stalls until it finishes.
+pub fn mutate_level(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut level: ResMut<Level>,
+	mut rng: ResMut<GameRng>,
+	mut restart: LevelRestart,
+	mut text_query: Query<&mut Text, With<PlaytestText>>,
+) {
+	if !keys.just_pressed(KeyCode::F4) {
+		return;
+	}
+	let mut mutations = level.mutations();
+	mutations.shuffle(rng.stream("playtest"));
+	let mutated = mutations
+		.into_iter()
+		.map(|mutation| (mutation, level.mutated(mutation)))
+		.find(|(_, mutated)| mutated.validate().is_empty());
+	let report = match mutated {
+		Some((mutation, mutated)) => {
+			let stored = match solution::play_solution(mutated.clone()) {
+				Ok(solved) if solved.is_complete() => "still completes it",
+				Ok(_) => "no longer completes it",
+				Err(SolutionError::Missing) => "missing",
+				Err(_) => "no longer fits it",
+			};
+			let shortest = match shortest_solution(&mutated) {
+				Search::Solved(turns) => format!("{turns} turns"),
+				Search::Unsolvable => "none; the level is unsolvable".into(),
+				Search::GaveUp => {
+					format!("not found within {SEARCH_LIMIT} states")
+				}
+			};
+			*level = mutated;
+			restart.restart();
+			format!(
+				"Playtest: {mutation}\nStored solution: {stored}\n\
+				Shortest solution: {shortest}"
+			)
+		}
+		None => "Playtest: no mutation keeps the level valid".to_string(),
+	};
+	info!("{}", report.replace('\n', "; "));
+	for mut text in &mut text_query {
+		**text = report.clone();
+	}
+}
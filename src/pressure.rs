@@ -0,0 +1,78 @@
+use bevy::{
+	pbr::{NotShadowCaster, NotShadowReceiver},
+	prelude::*,
+};
+
+use crate::{
+	assets::{GameAssets, MaterialKind, MeshKind, PRESSURE_LEVELS},
+	level::{ChangeEvent, Level, LevelEntity},
+	photo_mode::HiddenInPhotoMode,
+	states::GameState,
+};
+
+/// Height at which pressure badges are drawn, just above the tallest objects.
+const PRESSURE_HEIGHT: f32 = 1.05;
+
+/// Side length of a pressure badge, in tiles.
+const BADGE_SIZE: f32 = 0.4;
+
+/// Whether the weight on each tile is shown.
+#[derive(Resource, Default)]
+pub struct ShowPressure(bool);
+
+/// Marker component for pressure badge entities.
+#[derive(Component)]
+pub struct PressureBadge;
+
+/// Shows or hides the pressure readout when J is pressed.
+pub fn toggle_pressure_readout(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut show_pressure: ResMut<ShowPressure>,
+) {
+	if keys.just_pressed(KeyCode::KeyJ) {
+		show_pressure.0 = !show_pressure.0;
+	}
+}
+
+/// Recomputes the pressure readout whenever the level changes, a level
+/// starts, or the readout is toggled: each tile with weight on it gets a badge
+/// whose heat color rises with the weight, up to [`PRESSURE_LEVELS`]. See
+/// [`Level::tile_weights`].
+pub fn update_pressure_readout(
+	mut commands: Commands,
+	show_pressure: Res<ShowPressure>,
+	level: Res<Level>,
+	state: Res<State<GameState>>,
+	mut change_events: EventReader<ChangeEvent>,
+	assets: Res<GameAssets>,
+	badge_query: Query<Entity, With<PressureBadge>>,
+) {
+	let changed = change_events.read().count() > 0;
+	if !changed && !show_pressure.is_changed() && !state.is_changed() {
+		return;
+	}
+	for entity in &badge_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	if !show_pressure.0 {
+		return;
+	}
+	for (coords, weight) in level.tile_weights() {
+		if weight <= 0 {
+			continue;
+		}
+		let heat = weight.min(PRESSURE_LEVELS.into()) as u8;
+		commands.spawn((
+			LevelEntity,
+			PressureBadge,
+			HiddenInPhotoMode,
+			Mesh3d(assets.mesh(MeshKind::Highlight)),
+			MeshMaterial3d(assets.material(MaterialKind::Pressure(heat))),
+			NotShadowCaster,
+			NotShadowReceiver,
+			coords
+				.transform(PRESSURE_HEIGHT)
+				.with_scale(Vec3::splat(BADGE_SIZE)),
+		));
+	}
+}
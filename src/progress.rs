@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::level_asset::{LevelAsset, Levels, Mechanic};
+
+/// Where the player's progress is saved.
+const PROGRESS_PATH: &str = "progress.ron";
+
+/// The player's progress through the game, kept across sessions.
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Progress {
+	/// Mechanics required by levels the player has started.
+	pub seen_mechanics: BTreeSet<Mechanic>,
+	/// Paths of the campaign levels the player has completed, along with the
+	/// keys of completed pack levels.
+	pub completed_levels: BTreeSet<String>,
+	/// Mechanics whose tutorials the player has been shown.
+	pub explained_mechanics: BTreeSet<Mechanic>,
+}
+
+impl Progress {
+	/// Loads progress from [`PROGRESS_PATH`], starting fresh if the file is
+	/// missing or invalid.
+	pub fn load() -> Progress {
+		let Ok(text) = std::fs::read_to_string(PROGRESS_PATH) else {
+			return Progress::default();
+		};
+		ron::from_str(&text).unwrap_or_else(|err| {
+			warn!("invalid progress in {PROGRESS_PATH}: {err}");
+			Progress::default()
+		})
+	}
+
+	/// Records that the player completed the level with the given key.
+	pub fn complete_level(&mut self, key: &str) {
+		if self.completed_levels.insert(key.to_string()) {
+			self.save();
+		}
+	}
+
+	/// Records that the player has been shown the tutorials for `mechanics`,
+	/// returning the ones they hadn't been shown before.
+	pub fn explain_mechanics(
+		&mut self,
+		mechanics: impl IntoIterator<Item = Mechanic>,
+	) -> Vec<Mechanic> {
+		let unexplained: Vec<Mechanic> = mechanics
+			.into_iter()
+			.filter(|mechanic| self.explained_mechanics.insert(*mechanic))
+			.collect();
+		if !unexplained.is_empty() {
+			self.save();
+		}
+		unexplained
+	}
+
+	/// Saves progress to [`PROGRESS_PATH`].
+	fn save(&self) {
+		let result = ron::ser::to_string_pretty(self, default())
+			.map_err(|err| err.to_string())
+			.and_then(|text| {
+				std::fs::write(PROGRESS_PATH, text)
+					.map_err(|err| err.to_string())
+			});
+		if let Err(err) = result {
+			warn!("could not save progress to {PROGRESS_PATH}: {err}");
+		}
+	}
+}
+
+/// Marks the mechanics of each bundled level as seen when it starts.
+pub fn record_seen_mechanics(
+	levels: Res<Levels>,
+	level_assets: Res<Assets<LevelAsset>>,
+	mut progress: ResMut<Progress>,
+) {
+	if !levels.is_changed() {
+		return;
+	}
+	let Some(asset) = levels.current_asset(&level_assets) else {
+		return;
+	};
+	let unseen: Vec<Mechanic> = asset
+		.tags
+		.mechanics
+		.iter()
+		.copied()
+		.filter(|mechanic| !progress.seen_mechanics.contains(mechanic))
+		.collect();
+	if !unseen.is_empty() {
+		progress.seen_mechanics.extend(unseen);
+		progress.save();
+	}
+}
@@ -0,0 +1,196 @@
+use std::io::{self, BufRead, Write};
+
+use crate::{
+	control::{direction_action, Action},
+	level::{Id, Level, Offset},
+	level_asset::{LevelAsset, LevelAssetError},
+	replay::{action_code, parse_action_code},
+	update::NextActor,
+};
+
+/// Command-line flag that runs the game headlessly, speaking the
+/// [solver protocol](run) over stdin and stdout.
+pub const SOLVER_FLAG: &str = "--solver";
+
+/// The bundled level with the given `name`, for choosing a level to solve.
+/// Reads the level file directly, since there's no asset server when running
+/// headlessly.
+pub fn level_named(name: &str) -> Result<Level, LevelAssetError> {
+	LevelAsset::read(&format!("assets/levels/{name}.level.ron"))
+		.map(|asset| asset.to_level())
+}
+
+/// Plays `level` headlessly on behalf of an external solver, one line at a
+/// time.
+///
+/// Before each action, the game writes the state for the next character to
+/// act:
+///
+/// ```text
+/// map <rows>
+/// <one line per row, in the test level format>
+/// state <lines>
+/// <one line per object or platform state, in the level text format>
+/// turn <turn number>
+/// actor <character index> <color index>
+/// legal <space-separated action codes>
+/// ```
+///
+/// The map and state lines are the two parts of the [level
+/// text](Level::to_text), so [`Level::from_text`] reads them back into the
+/// current state of the level.
+///
+/// Action codes are the same as in replays: `.` to wait, an arrow (`^`, `<`,
+/// `v`, `>`) to push, `+` and an arrow to summon, and `x` to return. The
+/// solver replies with one line: a legal action code, `undo`, `redo`, or
+/// `quit`. An invalid reply gets an `error <reason>` line, followed by the
+/// same state again. The turn runs once every character has an action.
+pub fn run(mut level: Level) -> io::Result<()> {
+	let mut stdout = io::stdout().lock();
+	let mut lines = io::stdin().lock().lines();
+	let mut queue: Vec<(Id, Action)> = Vec::new();
+	loop {
+		let Some(actor) = NextActor::nth(&level, queue.len()) else {
+			writeln!(stdout, "error no characters")?;
+			return Ok(());
+		};
+		let legal = legal_actions(&actor);
+		write_state(&mut stdout, &level, &actor, queue.len(), &legal)?;
+		stdout.flush()?;
+
+		let Some(line) = lines.next().transpose()? else {
+			return Ok(());
+		};
+		match line.trim() {
+			"quit" => return Ok(()),
+			"undo" => {
+				if level.undo().is_some() {
+					queue.clear();
+				} else {
+					writeln!(stdout, "error nothing to undo")?;
+				}
+			}
+			"redo" => {
+				if level.redo().is_some() {
+					queue.clear();
+				} else {
+					writeln!(stdout, "error nothing to redo")?;
+				}
+			}
+			code => match parse_action_code(code) {
+				Some(action) if legal.contains(&action) => {
+					queue.push((actor.id, action));
+					if queue.len() == level.character_count() {
+						level.update(std::mem::take(&mut queue));
+					}
+				}
+				Some(_) => writeln!(stdout, "error illegal action {code}")?,
+				None => writeln!(stdout, "error unknown command {code}")?,
+			},
+		}
+	}
+}
+
+/// The actions `actor` is able to choose, whether or not they'd succeed.
+pub fn legal_actions(actor: &NextActor) -> Vec<Action> {
+	let mut actions = Vec::new();
+	if actor.character.can_wait() {
+		actions.push(Action::Wait);
+	}
+	for act_button_held in [false, true] {
+		for offset in Offset::DIRECTIONS {
+			match direction_action(actor, offset, act_button_held) {
+				Some(Action::Summon(offset)) if act_button_held => {
+					actions.push(Action::Summon(offset));
+				}
+				Some(Action::Push(offset)) if !act_button_held => {
+					actions.push(Action::Push(offset));
+				}
+				_ => {}
+			}
+		}
+	}
+	if actor.character.can_return() {
+		actions.push(Action::Return);
+	}
+	actions
+}
+
+fn write_state(
+	out: &mut impl Write,
+	level: &Level,
+	actor: &NextActor,
+	actor_idx: usize,
+	legal: &[Action],
+) -> io::Result<()> {
+	writeln!(out, "map {}", level.height())?;
+	write!(out, "{}", level.map_text())?;
+	let states = level.state_text();
+	writeln!(out, "state {}", states.lines().count())?;
+	write!(out, "{states}")?;
+	writeln!(out, "turn {}", level.turn())?;
+	writeln!(out, "actor {actor_idx} {}", actor.character.color.idx())?;
+	let codes: Vec<String> = legal.iter().copied().map(action_code).collect();
+	writeln!(out, "legal {}", codes.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::level::make_level;
+
+	#[test]
+	fn action_codes_round_trip() {
+		let mut actions = vec![Action::Wait, Action::Return];
+		for offset in Offset::DIRECTIONS {
+			actions.push(Action::Push(offset));
+			actions.push(Action::Summon(offset));
+		}
+		for action in actions {
+			assert!(parse_action_code(&action_code(action)) == Some(action));
+		}
+	}
+
+	#[test]
+	fn unknown_action_codes_are_rejected() {
+		for code in ["", "+", "++^", "^^", "X", "undo", "+.", "+x"] {
+			assert!(parse_action_code(code).is_none(), "{code:?}");
+		}
+	}
+
+	#[test]
+	fn state_reads_back_into_the_level() {
+		let mut level = make_level("# # # # # \n# .0. > # \n# # # # # ");
+		let (id, _) = level.characters_by_id().next().unwrap();
+		level.update(vec![(*id, Action::Summon(Offset::RIGHT))]);
+		let actor = NextActor::nth(&level, 0).unwrap();
+		let mut out = Vec::new();
+		write_state(&mut out, &level, &actor, 0, &legal_actions(&actor))
+			.unwrap();
+		let out = String::from_utf8(out).unwrap();
+
+		let mut lines = out.lines();
+		let rows: usize = lines
+			.next()
+			.unwrap()
+			.strip_prefix("map ")
+			.unwrap()
+			.parse()
+			.unwrap();
+		let map: Vec<&str> = lines.by_ref().take(rows).collect();
+		let states: usize = lines
+			.next()
+			.unwrap()
+			.strip_prefix("state ")
+			.unwrap()
+			.parse()
+			.unwrap();
+		// The summoner's open portal is part of the state.
+		assert_ne!(states, 0);
+		let state: Vec<&str> = lines.take(states).collect();
+		let read = make_level(&map.join("\n"))
+			.with_state(&state.join("\n"))
+			.unwrap();
+		assert_eq!(read.to_text(), level.to_text());
+	}
+}
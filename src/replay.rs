@@ -0,0 +1,269 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+	control::Action,
+	history,
+	level::{Cause, Change, Level, Object, Offset},
+};
+
+/// Number of turns between integrity checkpoints in a delta-encoded replay.
+const CHECKPOINT_INTERVAL: usize = 16;
+
+/// A compact record of the turns taken in a level.
+pub enum Replay {
+	/// Just the cause of each turn, which is enough to reproduce the level
+	/// since the simulation is deterministic, along with a state hash every
+	/// [`CHECKPOINT_INTERVAL`] turns to catch any divergence.
+	Deltas {
+		causes: Vec<Cause>,
+		checkpoints: Vec<u64>,
+	},
+	/// Every turn's change verbatim, with its cause, for when replaying the
+	/// causes doesn't reproduce the level.
+	Full(Vec<(Change, Cause)>),
+}
+
+/// A replay failed to reproduce the recorded level state as of `turn`.
+#[derive(Debug)]
+pub struct Desync {
+	pub turn: usize,
+}
+
+impl Replay {
+	/// Records the turns taken so far in `level`. Uses delta encoding if
+	/// replaying the deltas from the start of the level reproduces its current
+	/// state, falling back to recording full changes otherwise.
+	pub fn record(level: &Level) -> Replay {
+		let mut causes = Vec::new();
+		let mut checkpoints = Vec::new();
+		for turn in history::taken(level) {
+			causes.push(turn.cause.clone());
+			if (turn.turn + 1) % CHECKPOINT_INTERVAL == 0 {
+				checkpoints.push(turn.state_hash);
+			}
+		}
+		let deltas = Replay::Deltas {
+			causes,
+			checkpoints,
+		};
+		match deltas.play(level.rewound()) {
+			Ok(replayed) if replayed.state_hash() == level.state_hash() => {
+				deltas
+			}
+			result => {
+				let turn =
+					result.err().map_or(level.turn(), |desync| desync.turn);
+				warn!("replay diverged by turn {turn}; recording full changes");
+				Replay::Full(
+					history::taken(level)
+						.map(|turn| (turn.change.clone(), turn.cause.clone()))
+						.collect(),
+				)
+			}
+		}
+	}
+
+	/// Plays this replay starting from `level`, returning the resulting level.
+	pub fn play(&self, level: Level) -> Result<Level, Desync> {
+		self.play_with(level, |_| {})
+	}
+
+	/// Like [`Replay::play`], but calls `observe` with the level after each
+	/// turn.
+	pub fn play_with(
+		&self,
+		mut level: Level,
+		mut observe: impl FnMut(&Level),
+	) -> Result<Level, Desync> {
+		match self {
+			Replay::Deltas {
+				causes,
+				checkpoints,
+			} => {
+				for (idx, cause) in causes.iter().enumerate() {
+					match cause {
+						Cause::Actions(actions) => {
+							let actors = level
+								.characters_by_id()
+								.map(|(id, _)| *id)
+								.zip(actions.iter().copied())
+								.collect();
+							level.update(actors);
+						}
+						Cause::Edit { coords, object } => {
+							level.edit(*coords, *object);
+						}
+					}
+					let turn = idx + 1;
+					if turn % CHECKPOINT_INTERVAL == 0
+						&& checkpoints.get(turn / CHECKPOINT_INTERVAL - 1)
+							!= Some(&level.state_hash())
+					{
+						return Err(Desync { turn });
+					}
+					observe(&level);
+				}
+			}
+			Replay::Full(changes) => {
+				for (change, cause) in changes {
+					level.replay(change.clone(), cause.clone());
+					observe(&level);
+				}
+			}
+		}
+		Ok(level)
+	}
+
+	/// A plain-text form of this replay, with one line per turn.
+	///
+	/// Delta-encoded turns list each character's action: `.` to wait, an arrow
+	/// (`^`, `<`, `v`, `>`) to push, `+` and an arrow to summon, and `x` to
+	/// return. Edits are shown as `edit (row, col) symbol`, and checkpoints as
+	/// `# hash`.
+	pub fn to_text(&self) -> String {
+		let mut text = String::new();
+		match self {
+			Replay::Deltas {
+				causes,
+				checkpoints,
+			} => {
+				writeln!(text, "deltas").unwrap();
+				for (idx, cause) in causes.iter().enumerate() {
+					let line = match cause {
+						Cause::Actions(actions) => actions
+							.iter()
+							.map(|action| action_code(*action))
+							.collect::<Vec<_>>()
+							.join(" "),
+						Cause::Edit { coords, object } => format!(
+							"edit ({}, {}) {}",
+							coords.row,
+							coords.col,
+							object_code(*object)
+						),
+					};
+					writeln!(text, "{line}").unwrap();
+					let turn = idx + 1;
+					if turn % CHECKPOINT_INTERVAL == 0 {
+						let hash = checkpoints[turn / CHECKPOINT_INTERVAL - 1];
+						writeln!(text, "# {hash:016x}").unwrap();
+					}
+				}
+			}
+			Replay::Full(changes) => {
+				writeln!(text, "full").unwrap();
+				for (change, _) in changes {
+					writeln!(text, "{}", describe_change(change)).unwrap();
+				}
+			}
+		}
+		text
+	}
+}
+
+fn direction_code(offset: Offset) -> &'static str {
+	match offset {
+		Offset::UP => "^",
+		Offset::LEFT => "<",
+		Offset::DOWN => "v",
+		Offset::RIGHT => ">",
+		_ => "?",
+	}
+}
+
+/// The code for `action` used in replay text. See [`Replay::to_text`].
+pub fn action_code(action: Action) -> String {
+	match action {
+		Action::Wait => ".".to_string(),
+		Action::Push(offset) => direction_code(offset).to_string(),
+		Action::Summon(offset) => format!("+{}", direction_code(offset)),
+		Action::Return => "x".to_string(),
+	}
+}
+
+/// Parses an action from its [code](action_code).
+pub fn parse_action_code(code: &str) -> Option<Action> {
+	let direction = |code: &str| {
+		Some(match code {
+			"^" => Offset::UP,
+			"<" => Offset::LEFT,
+			"v" => Offset::DOWN,
+			">" => Offset::RIGHT,
+			_ => return None,
+		})
+	};
+	match code {
+		"." => Some(Action::Wait),
+		"x" => Some(Action::Return),
+		_ => match code.strip_prefix('+') {
+			Some(summon) => direction(summon).map(Action::Summon),
+			None => direction(code).map(Action::Push),
+		},
+	}
+}
+
+/// Parses turns of [action codes](action_code), one turn per line with each
+/// character's action separated by spaces. Blank lines are ignored.
+pub fn parse_turns(text: &str) -> Option<Vec<Vec<Action>>> {
+	text.lines()
+		.filter(|line| !line.trim().is_empty())
+		.map(|line| line.split_whitespace().map(parse_action_code).collect())
+		.collect()
+}
+
+fn object_code(object: Option<Object>) -> String {
+	match object {
+		Some(Object::Character(character)) => character.color.idx().to_string(),
+		Some(Object::Prop(descriptor)) => {
+			(descriptor.symbol as char).to_string()
+		}
+		None => "-".to_string(),
+	}
+}
+
+/// A one-line description of `change`, with entries sorted by object ID.
+fn describe_change(change: &Change) -> String {
+	let mut entries = Vec::new();
+	for (id, returning) in &change.returnings {
+		let coords = returning.returner.coords;
+		entries.push((id.0, format!("#{} returns from {coords:?}", id.0)));
+	}
+	for (id, mv) in &change.moves {
+		entries.push((
+			id.0,
+			format!(
+				"#{} moves {:?} -> {:?}",
+				id.0, mv.from_coords, mv.to_coords
+			),
+		));
+	}
+	for (id, summoning) in &change.summonings {
+		let summon = &summoning.summon;
+		entries.push((
+			id.0,
+			format!(
+				"#{} summons #{} at {:?}",
+				id.0, summon.id.0, summon.coords
+			),
+		));
+	}
+	for (id, created) in &change.creations {
+		let coords = created.coords;
+		entries.push((id.0, format!("#{} created at {coords:?}", id.0)));
+	}
+	for (id, destroyed) in &change.destructions {
+		let coords = destroyed.coords;
+		entries.push((id.0, format!("#{} destroyed at {coords:?}", id.0)));
+	}
+	if entries.is_empty() {
+		return "wait".to_string();
+	}
+	entries.sort_by_key(|(id, _)| *id);
+	entries
+		.into_iter()
+		.map(|(_, entry)| entry)
+		.collect::<Vec<_>>()
+		.join(", ")
+}
@@ -0,0 +1,57 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Environment variable that, if set, overrides the master random seed.
+const SEED_VAR: &str = "CAUSAL_OOPS_SEED";
+
+/// The central source of game randomness. Each consumer draws from its own
+/// named stream, and every stream is derived from a single master seed, so all
+/// randomness can be reproduced from that seed.
+#[derive(Resource)]
+pub struct GameRng {
+	seed: u64,
+	streams: HashMap<&'static str, ChaCha8Rng>,
+}
+
+impl GameRng {
+	pub fn from_seed(seed: u64) -> GameRng {
+		GameRng {
+			seed,
+			streams: HashMap::new(),
+		}
+	}
+
+	/// Seeds from [`SEED_VAR`] if it's set to a valid seed, or from entropy
+	/// otherwise.
+	pub fn from_env() -> GameRng {
+		let seed = std::env::var(SEED_VAR)
+			.ok()
+			.and_then(|seed| seed.parse().ok())
+			.unwrap_or_else(rand::random);
+		GameRng::from_seed(seed)
+	}
+
+	/// The master seed.
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	/// The random stream with the given `name`. Streams are independent, so
+	/// drawing from one doesn't affect the others.
+	pub fn stream(&mut self, name: &'static str) -> &mut ChaCha8Rng {
+		let seed = self.seed;
+		self.streams.entry(name).or_insert_with(|| {
+			let mut rng = ChaCha8Rng::seed_from_u64(seed);
+			rng.set_stream(stream_id(name));
+			rng
+		})
+	}
+}
+
+/// A stable ID for the stream named `name` (the FNV-1a hash of the name).
+fn stream_id(name: &str) -> u64 {
+	name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+	})
+}
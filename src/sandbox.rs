@@ -0,0 +1,167 @@
+use bevy::{ecs::system::SystemParam, prelude::*, window::PrimaryWindow};
+
+use crate::{
+	camera::MainCamera,
+	control::ControlEvent,
+	level::{Character, CharacterColor, Coords, Object},
+	photo_mode::HiddenInPhotoMode,
+};
+
+/// Objects that can be placed in sandbox mode.
+const PALETTE: [Object; 4] = [
+	Object::PROPS[0],
+	Object::PROPS[1],
+	Object::PROPS[2],
+	// The level assigns the actual color when the character is placed.
+	Object::Character(Character {
+		color: CharacterColor::Green,
+		sliding: false,
+		portal_coords: None,
+	}),
+];
+
+/// Sandbox mode state. In sandbox mode, the player can freely place and remove
+/// objects during play.
+#[derive(Resource, Default)]
+pub struct Sandbox {
+	enabled: bool,
+	/// Index into [`PALETTE`] of the object to place.
+	selected: usize,
+}
+
+impl Sandbox {
+	/// Whether sandbox mode is on.
+	pub fn is_enabled(&self) -> bool {
+		self.enabled
+	}
+}
+
+/// Marker component for the sandbox palette text.
+#[derive(Component)]
+pub struct SandboxPaletteText;
+
+/// Spawns the (initially hidden) sandbox palette text.
+pub fn spawn_sandbox_palette(mut commands: Commands) {
+	commands.spawn((
+		SandboxPaletteText,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Px(4.0),
+			right: Val::Px(4.0),
+			..default()
+		},
+		Visibility::Hidden,
+	));
+}
+
+/// Handles sandbox input: F2 toggles sandbox mode, [ and ] cycle through the
+/// palette, left click places the selected object, and right click removes an
+/// object.
+pub fn sandbox(
+	keys: Res<ButtonInput<KeyCode>>,
+	mouse_buttons: Res<ButtonInput<MouseButton>>,
+	mut sandbox: ResMut<Sandbox>,
+	window_query: Query<&Window, With<PrimaryWindow>>,
+	camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+	mut control_events: EventWriter<ControlEvent>,
+) {
+	if keys.just_pressed(KeyCode::F2) {
+		sandbox.enabled = !sandbox.enabled;
+	}
+	if !sandbox.enabled {
+		return;
+	}
+	if keys.just_pressed(KeyCode::BracketLeft) {
+		sandbox.selected =
+			(sandbox.selected + PALETTE.len() - 1) % PALETTE.len();
+	}
+	if keys.just_pressed(KeyCode::BracketRight) {
+		sandbox.selected = (sandbox.selected + 1) % PALETTE.len();
+	}
+
+	let object = if mouse_buttons.just_pressed(MouseButton::Left) {
+		Some(PALETTE[sandbox.selected])
+	} else if mouse_buttons.just_pressed(MouseButton::Right) {
+		None
+	} else {
+		return;
+	};
+	let Some(coords) = cursor_coords(&window_query, &camera_query) else {
+		return;
+	};
+	control_events.send(ControlEvent::Edit { coords, object });
+}
+
+/// The window and camera the cursor is pointing through, for systems that
+/// only need [`Cursor::coords`].
+#[derive(SystemParam)]
+pub struct Cursor<'w, 's> {
+	window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+	camera_query: Query<
+		'w,
+		's,
+		(&'static Camera, &'static GlobalTransform),
+		With<MainCamera>,
+	>,
+}
+
+impl Cursor<'_, '_> {
+	/// The level coordinates of the floor tile under the cursor, if any.
+	pub fn coords(&self) -> Option<Coords> {
+		cursor_coords(&self.window_query, &self.camera_query)
+	}
+}
+
+/// The level coordinates of the floor tile under the cursor, if any.
+pub fn cursor_coords(
+	window_query: &Query<&Window, With<PrimaryWindow>>,
+	camera_query: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) -> Option<Coords> {
+	let cursor_position = window_query.get_single().ok()?.cursor_position()?;
+	let (camera, camera_transform) = camera_query.get_single().ok()?;
+	let ray = camera
+		.viewport_to_world(camera_transform, cursor_position)
+		.ok()?;
+	// Floor tiles' top faces lie in the z = 0 plane.
+	let distance =
+		ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z))?;
+	let point = ray.get_point(distance);
+	Some(Coords::new(-point.y.round() as i32, point.x.round() as i32))
+}
+
+/// Shows the palette while in sandbox mode.
+pub fn update_sandbox_palette(
+	sandbox: Res<Sandbox>,
+	mut text_query: Query<
+		(&mut Text, &mut Visibility),
+		With<SandboxPaletteText>,
+	>,
+) {
+	if !sandbox.is_changed() {
+		return;
+	}
+	for (mut text, mut visibility) in &mut text_query {
+		*visibility = if sandbox.enabled {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+		let selected = match PALETTE[sandbox.selected] {
+			Object::Character(_) => "Character".to_string(),
+			Object::Prop(descriptor) => {
+				format!("{} ({})", descriptor.name, descriptor.summary())
+			}
+		};
+		**text = format!(
+			"Sandbox: {selected} ([/] to change)\n\
+			Left click: place\n\
+			Right click: remove"
+		);
+	}
+}
@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	animation::{MovementStyle, TurnReplay},
+	audio::Mixer,
+	control::BindingProfile,
+	display::DisplayPreset,
+	graphics::AntiAliasing,
+	grid::GridOverlay,
+	mutators::Mutator,
+};
+
+/// Where player settings are read from.
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// Player preferences.
+#[derive(Resource, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+	/// Whether the backdrop cycles through its colors. If false, the backdrop
+	/// stays fixed on its first color, for reduced distraction.
+	pub animated_backdrop: bool,
+	/// Challenge modifiers to apply to each level as it starts.
+	pub mutators: Vec<Mutator>,
+	/// Whether the camera follows the area occupied by characters, rather
+	/// than always framing the whole level. Useful on large, sparse levels.
+	pub dynamic_camera: bool,
+	/// How to smooth jagged edges. Switchable at runtime with F7.
+	pub anti_aliasing: AntiAliasing,
+	/// How tile boundaries are marked on the floor. Switchable at runtime
+	/// with F8.
+	pub grid_overlay: GridOverlay,
+	/// Whether to leave out purely decorative motion, like the ripple through
+	/// nearby tiles when a portal closes.
+	pub reduce_motion: bool,
+	/// How objects travel from tile to tile.
+	pub movement_style: MovementStyle,
+	/// Whether a turn's changes play out all at once or one character at a
+	/// time, to help show how simultaneous actions resolved.
+	pub turn_replay: TurnReplay,
+	/// The language code to show level text in, like `fr` or `pt-BR`. Defaults
+	/// to the system language.
+	pub language: Option<String>,
+	/// Whether to announce the color of each character as it becomes the next
+	/// to act, for players who have trouble telling the characters apart.
+	pub announce_actor: bool,
+	/// The volume of each audio bus: music, ambience, sound effects, and
+	/// interface sounds.
+	pub mixer: Mixer,
+	/// The base URL of the level sharing server, like
+	/// `http://example.com/api`. Level sharing is off unless this is set.
+	pub sharing_endpoint: Option<String>,
+	/// Named sets of key bindings, switchable from the pause menu.
+	pub binding_profiles: Vec<BindingProfile>,
+	/// The name of the key binding profile in use, or `None` for the default
+	/// bindings.
+	pub binding_profile: Option<String>,
+	/// Whether to pause when the game window loses focus, resuming when it
+	/// regains focus.
+	pub pause_on_focus_loss: bool,
+	/// The display preset, chosen by [detection](DisplayPreset::detect) at
+	/// first launch if unset. Switchable at runtime with F5.
+	pub display_preset: Option<DisplayPreset>,
+	/// Whether to queue the last character's action for it and end the turn
+	/// when every other character has acted and it has only one choice, like
+	/// waiting when boxed in.
+	pub auto_end_turn: bool,
+}
+
+impl Default for Settings {
+	fn default() -> Settings {
+		Settings {
+			animated_backdrop: true,
+			mutators: Vec::new(),
+			dynamic_camera: false,
+			anti_aliasing: AntiAliasing::default(),
+			grid_overlay: GridOverlay::default(),
+			reduce_motion: false,
+			movement_style: MovementStyle::default(),
+			turn_replay: TurnReplay::default(),
+			language: None,
+			announce_actor: false,
+			mixer: Mixer::default(),
+			sharing_endpoint: None,
+			binding_profiles: Vec::new(),
+			binding_profile: None,
+			pause_on_focus_loss: true,
+			display_preset: None,
+			auto_end_turn: false,
+		}
+	}
+}
+
+impl Settings {
+	/// The display preset in use.
+	pub fn display_preset(&self) -> DisplayPreset {
+		self.display_preset.unwrap_or_default()
+	}
+
+	/// Loads settings from [`SETTINGS_PATH`], falling back to the defaults if
+	/// the file is missing or invalid.
+	pub fn load() -> Settings {
+		let Ok(text) = std::fs::read_to_string(SETTINGS_PATH) else {
+			return Settings::default();
+		};
+		ron::from_str(&text).unwrap_or_else(|err| {
+			warn!("invalid settings in {SETTINGS_PATH}: {err}");
+			Settings::default()
+		})
+	}
+
+	/// Saves settings to [`SETTINGS_PATH`], so choices made in game, like the
+	/// key binding profile, carry over to later sessions.
+	pub fn save(&self) {
+		let result = ron::ser::to_string_pretty(self, default())
+			.map_err(|err| err.to_string())
+			.and_then(|text| {
+				std::fs::write(SETTINGS_PATH, text)
+					.map_err(|err| err.to_string())
+			});
+		if let Err(err) = result {
+			warn!("could not save settings to {SETTINGS_PATH}: {err}");
+		}
+	}
+}
@@ -0,0 +1,449 @@
+use std::{
+	fmt::{self, Display, Write as _},
+	io::{self, Read, Write},
+	net::TcpStream,
+	time::Duration,
+};
+
+use bevy::{
+	ecs::system::SystemParam,
+	prelude::*,
+	tasks::{block_on, futures_lite::future, IoTaskPool, Task},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	level::Level,
+	level_asset::{user_levels_dir, LevelAsset, LevelAssetError, Levels},
+	replay::Replay,
+	settings::Settings,
+	states::GameState,
+};
+
+/// How long to wait on the sharing server before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An error talking to the sharing server.
+#[derive(Debug)]
+pub enum SharingError {
+	/// The endpoint isn't a URL the client can talk to.
+	Unsupported(String),
+	Io(io::Error),
+	/// The server responded with the given HTTP status code.
+	Status(u16),
+	/// The server's response couldn't be understood.
+	Format(String),
+	/// A downloaded level isn't playable.
+	Level(LevelAssetError),
+}
+
+impl Display for SharingError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SharingError::Unsupported(url) => {
+				write!(f, "unsupported endpoint {url}; only http:// works")
+			}
+			SharingError::Io(err) => write!(f, "connection failed: {err}"),
+			SharingError::Status(status) => {
+				write!(f, "server responded with status {status}")
+			}
+			SharingError::Format(msg) => write!(f, "invalid response: {msg}"),
+			SharingError::Level(err) => write!(f, "{err}"),
+		}
+	}
+}
+
+impl std::error::Error for SharingError {}
+
+impl From<io::Error> for SharingError {
+	fn from(err: io::Error) -> Self {
+		SharingError::Io(err)
+	}
+}
+
+/// The base URL of a level sharing server, like `http://example.com/api`.
+///
+/// The server lists its levels in response to `GET <base>/levels`, as a RON
+/// list of [`SharedLevel`]s, and sends the level file for the level with ID
+/// `<id>` in response to `GET <base>/levels/<id>`. Levels are shared with
+/// `POST <base>/levels`, whose body is a RON struct with the `level`, in the
+/// format of a level file, and optionally a `replay` of a solution, in the
+/// format of [`Replay::to_text`], along with the `checksum` of the level it
+/// was recorded on. See [`Level::checksum`].
+///
+/// Only plain HTTP is supported.
+#[derive(Clone)]
+struct Endpoint {
+	host: String,
+	port: u16,
+	/// The path of the base URL, with a leading slash unless it's empty.
+	path: String,
+}
+
+impl Endpoint {
+	fn parse(url: &str) -> Result<Endpoint, SharingError> {
+		let unsupported = || SharingError::Unsupported(url.to_string());
+		let rest = url.strip_prefix("http://").ok_or_else(unsupported)?;
+		let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+		let (host, port) = match authority.rsplit_once(':') {
+			Some((host, port)) => {
+				(host, port.parse().map_err(|_| unsupported())?)
+			}
+			None => (authority, 80),
+		};
+		if host.is_empty() {
+			return Err(unsupported());
+		}
+		let path = path.trim_end_matches('/');
+		Ok(Endpoint {
+			host: host.to_string(),
+			port,
+			path: if path.is_empty() {
+				String::new()
+			} else {
+				format!("/{path}")
+			},
+		})
+	}
+
+	/// Sends a request for `route` under the base URL, blocking until the
+	/// whole response arrives, and returns the response body.
+	fn request(
+		&self,
+		method: &str,
+		route: &str,
+		body: &str,
+	) -> Result<String, SharingError> {
+		let mut stream = TcpStream::connect((self.host.as_str(), self.port))?;
+		stream.set_read_timeout(Some(TIMEOUT))?;
+		stream.set_write_timeout(Some(TIMEOUT))?;
+		// HTTP/1.0, so the response isn't chunked and ends when the server
+		// closes the connection.
+		write!(
+			stream,
+			"{method} {}{route} HTTP/1.0\r\n\
+			Host: {}\r\n\
+			Content-Type: application/ron\r\n\
+			Content-Length: {}\r\n\
+			\r\n\
+			{body}",
+			self.path,
+			self.host,
+			body.len(),
+		)?;
+		let mut response = Vec::new();
+		stream.read_to_end(&mut response)?;
+		let response = String::from_utf8(response)
+			.map_err(|_| SharingError::Format("not UTF-8".to_string()))?;
+		let (head, body) = response
+			.split_once("\r\n\r\n")
+			.ok_or_else(|| SharingError::Format("no headers".to_string()))?;
+		let status: u16 = head
+			.split_whitespace()
+			.nth(1)
+			.and_then(|status| status.parse().ok())
+			.ok_or_else(|| SharingError::Format("no status".to_string()))?;
+		if !(200..300).contains(&status) {
+			return Err(SharingError::Status(status));
+		}
+		Ok(body.to_string())
+	}
+
+	/// The community levels the server has.
+	fn list(&self) -> Result<Vec<SharedLevel>, SharingError> {
+		let body = self.request("GET", "/levels", "")?;
+		ron::from_str(&body)
+			.map_err(|err| SharingError::Format(err.to_string()))
+	}
+
+	/// Downloads the level with the given ID into the
+	/// [user levels directory](user_levels_dir), returning it.
+	fn download(&self, id: &str) -> Result<LevelAsset, SharingError> {
+		// The ID becomes part of a file name, so keep it to a safe alphabet.
+		if id.is_empty()
+			|| !id
+				.chars()
+				.all(|c| c.is_ascii_alphanumeric() || "-_".contains(c))
+		{
+			return Err(SharingError::Format(format!("invalid level ID {id}")));
+		}
+		let text = self.request("GET", &format!("/levels/{id}"), "")?;
+		let asset = ron::from_str::<LevelAsset>(&text)
+			.map_err(|err| SharingError::Level(err.into()))?
+			.validated()
+			.map_err(SharingError::Level)?;
+		let dir = user_levels_dir();
+		std::fs::create_dir_all(&dir)?;
+		std::fs::write(dir.join(format!("shared-{id}.level.ron")), text)?;
+		Ok(asset)
+	}
+}
+
+/// A level listed by the sharing server.
+#[derive(Deserialize, Clone)]
+pub struct SharedLevel {
+	/// The server's identifier for the level.
+	pub id: String,
+	pub name: String,
+	#[serde(default)]
+	pub author: Option<String>,
+}
+
+/// The body of a request to share a level.
+#[derive(Serialize)]
+struct Upload<'a> {
+	level: &'a LevelAsset,
+	replay: Option<String>,
+	checksum: u64,
+}
+
+/// The result of a request to the sharing server.
+enum Outcome {
+	Listed(Result<Vec<SharedLevel>, SharingError>),
+	Uploaded(Result<(), SharingError>),
+	Downloaded(Result<Box<LevelAsset>, SharingError>),
+}
+
+/// The state of the community levels list.
+#[derive(Resource, Default)]
+pub struct Sharing {
+	/// The levels the server listed when last asked.
+	listing: Vec<SharedLevel>,
+	/// The index of the selected level in the listing.
+	selected: usize,
+	/// What the client is doing or last did.
+	status: String,
+	/// The request in progress, if any. Requests block on the network, so
+	/// they run on the IO task pool, one at a time.
+	task: Option<Task<Outcome>>,
+}
+
+impl Sharing {
+	/// Starts `request` against `endpoint` unless a request is already in
+	/// progress.
+	fn start(
+		&mut self,
+		endpoint: &Endpoint,
+		status: impl Into<String>,
+		request: impl FnOnce(Endpoint) -> Outcome + Send + 'static,
+	) {
+		if self.task.is_some() {
+			return;
+		}
+		let endpoint = endpoint.clone();
+		self.task =
+			Some(IoTaskPool::get().spawn(async move { request(endpoint) }));
+		self.status = status.into();
+	}
+}
+
+/// The endpoint set in `settings`, if sharing is on.
+fn endpoint(settings: &Settings) -> Option<Result<Endpoint, SharingError>> {
+	settings.sharing_endpoint.as_deref().map(Endpoint::parse)
+}
+
+/// Whether level sharing is turned on in `settings`.
+pub fn is_enabled(settings: &Settings) -> bool {
+	settings.sharing_endpoint.is_some()
+}
+
+/// Marker component for the community levels dialog.
+#[derive(Component)]
+pub struct CommunityLevelsDialog;
+
+/// Spawns the community levels dialog and asks the server for its levels.
+pub fn spawn_community_levels(
+	mut commands: Commands,
+	settings: Res<Settings>,
+	mut sharing: ResMut<Sharing>,
+) {
+	commands.spawn((
+		CommunityLevelsDialog,
+		Text::default(),
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			top: Val::Percent(20.0),
+			left: Val::Percent(20.0),
+			width: Val::Percent(60.0),
+			padding: UiRect::all(Val::Px(12.0)),
+			..default()
+		},
+		BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+	));
+	match endpoint(&settings) {
+		Some(Ok(endpoint)) => {
+			sharing.start(&endpoint, "Fetching levels...", |endpoint| {
+				Outcome::Listed(endpoint.list())
+			})
+		}
+		Some(Err(err)) => sharing.status = err.to_string(),
+		None => sharing.status = "Sharing is off".to_string(),
+	}
+}
+
+/// Despawns the community levels dialog.
+pub fn despawn_community_levels(
+	mut commands: Commands,
+	dialog_query: Query<Entity, With<CommunityLevelsDialog>>,
+) {
+	for entity in &dialog_query {
+		commands.entity(entity).despawn_recursive();
+	}
+}
+
+/// Opens the community levels when C is pressed in the level browser, if
+/// sharing is on.
+pub fn open_community_levels(
+	keys: Res<ButtonInput<KeyCode>>,
+	settings: Res<Settings>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if keys.just_pressed(KeyCode::KeyC) && is_enabled(&settings) {
+		next_state.set(GameState::CommunityLevels);
+	}
+}
+
+/// The level list that downloaded levels join and shared levels come from.
+#[derive(SystemParam)]
+pub struct LevelLibrary<'w> {
+	levels: ResMut<'w, Levels>,
+	level_assets: ResMut<'w, Assets<LevelAsset>>,
+}
+
+/// Handles community levels input and finished requests: up and down select
+/// a level, Enter downloads it, U shares the current level (with the
+/// player's solution, if they've completed it), R refreshes the list, and
+/// Escape returns to the level browser.
+pub fn browse_community_levels(
+	keys: Res<ButtonInput<KeyCode>>,
+	settings: Res<Settings>,
+	level: Res<Level>,
+	library: LevelLibrary,
+	mut sharing: ResMut<Sharing>,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut dialog_query: Query<&mut Text, With<CommunityLevelsDialog>>,
+) {
+	let LevelLibrary {
+		mut levels,
+		mut level_assets,
+	} = library;
+	let sharing = &mut *sharing;
+	if let Some(task) = &mut sharing.task {
+		if let Some(outcome) = block_on(future::poll_once(task)) {
+			sharing.task = None;
+			sharing.status = match outcome {
+				Outcome::Listed(Ok(listing)) => {
+					sharing.listing = listing;
+					sharing.selected = 0;
+					String::new()
+				}
+				Outcome::Uploaded(Ok(())) => "Level shared".to_string(),
+				Outcome::Downloaded(Ok(asset)) => {
+					let status = format!(
+						"Downloaded {}; find it in the level browser",
+						asset.name
+					);
+					levels.add(level_assets.add(*asset));
+					status
+				}
+				Outcome::Listed(Err(err))
+				| Outcome::Uploaded(Err(err))
+				| Outcome::Downloaded(Err(err)) => {
+					warn!("level sharing failed: {err}");
+					err.to_string()
+				}
+			};
+		}
+	}
+
+	if keys.just_pressed(KeyCode::Escape) {
+		next_state.set(GameState::LevelBrowser);
+		return;
+	}
+	if keys.just_pressed(KeyCode::ArrowUp) {
+		sharing.selected = sharing.selected.saturating_sub(1);
+	}
+	if keys.just_pressed(KeyCode::ArrowDown) {
+		sharing.selected =
+			(sharing.selected + 1).min(sharing.listing.len().saturating_sub(1));
+	}
+	if let Some(Ok(endpoint)) = endpoint(&settings) {
+		if keys.just_pressed(KeyCode::KeyR) {
+			sharing.start(&endpoint, "Fetching levels...", |endpoint| {
+				Outcome::Listed(endpoint.list())
+			});
+		}
+		if keys.just_pressed(KeyCode::Enter) {
+			if let Some(shared) = sharing.listing.get(sharing.selected) {
+				let id = shared.id.clone();
+				let status = format!("Downloading {}...", shared.name);
+				sharing.start(&endpoint, status, move |endpoint| {
+					Outcome::Downloaded(endpoint.download(&id).map(Box::new))
+				});
+			}
+		}
+		if keys.just_pressed(KeyCode::KeyU) {
+			match levels.current_asset(&level_assets) {
+				Some(asset) => {
+					let replay = level
+						.is_complete()
+						.then(|| Replay::record(&level).to_text());
+					match ron::to_string(&Upload {
+						level: asset,
+						replay,
+						checksum: level.checksum(),
+					}) {
+						Ok(body) => sharing.start(
+							&endpoint,
+							format!("Sharing {}...", asset.name),
+							move |endpoint| {
+								Outcome::Uploaded(
+									endpoint
+										.request("POST", "/levels", &body)
+										.map(|_| ()),
+								)
+							},
+						),
+						Err(err) => sharing.status = err.to_string(),
+					}
+				}
+				None => {
+					sharing.status =
+						"Only levels from files can be shared".to_string()
+				}
+			}
+		}
+	}
+
+	for mut text in &mut dialog_query {
+		**text = community_text(sharing);
+	}
+}
+
+fn community_text(sharing: &Sharing) -> String {
+	let mut text = "Community levels\n\n".to_string();
+	if sharing.listing.is_empty() && sharing.task.is_none() {
+		writeln!(text, "No levels").unwrap();
+	}
+	for (row, shared) in sharing.listing.iter().enumerate() {
+		let marker = if row == sharing.selected { ">" } else { " " };
+		write!(text, "{marker} {}", shared.name).unwrap();
+		if let Some(author) = &shared.author {
+			write!(text, " - by {author}").unwrap();
+		}
+		writeln!(text).unwrap();
+	}
+	if !sharing.status.is_empty() {
+		write!(text, "\n{}\n", sharing.status).unwrap();
+	}
+	write!(
+		text,
+		"\nEnter: download   U: share current level   R: refresh   Esc: back"
+	)
+	.unwrap();
+	text
+}
@@ -0,0 +1,301 @@
+use std::{
+	fmt::{self, Display},
+	time::Duration,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+	level::{ChangeEvent, Level, LevelEntity},
+	photo_mode::HiddenInPhotoMode,
+	states::GameState,
+	update::NextActor,
+};
+
+/// Time between turns while watching the optimal solution.
+const PLAYBACK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Width of the longer of the two bars in the comparison graph.
+const MAX_BAR_WIDTH: f32 = 240.0;
+
+/// Compares the player's turn count with the level's
+/// [optimal solution](Level::solution). Any solution that
+/// [completes](Level::is_complete) the level counts, however it ends up.
+#[derive(Resource, Default)]
+pub struct SolutionComparison {
+	/// The state hash of the start of the level being compared.
+	start_hash: Option<u64>,
+	/// The fewest turns the player has completed the level in, if they have.
+	best_turns: Option<usize>,
+	/// While watching the optimal solution, the time until its next turn and
+	/// that turn's index.
+	playback: Option<(Timer, usize)>,
+}
+
+impl SolutionComparison {
+	/// Whether the optimal solution is being watched.
+	pub fn is_watching(&self) -> bool {
+		self.playback.is_some()
+	}
+
+	/// Whether the comparison should be showing for `level`.
+	fn is_showing(&self, level: &Level) -> bool {
+		self.playback.is_none()
+			&& self.best_turns.is_some()
+			&& level.solution().is_some()
+			&& level.is_complete()
+	}
+}
+
+/// Why a level's optimal solution couldn't be played.
+#[derive(Debug)]
+pub enum SolutionError {
+	/// The level has no known solution.
+	Missing,
+	/// The solution doesn't have an action for each character on the given
+	/// turn.
+	WrongActionCount(usize),
+}
+
+impl Display for SolutionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SolutionError::Missing => write!(f, "level has no solution"),
+			SolutionError::WrongActionCount(idx) => {
+				write!(f, "solution turn {idx} has the wrong number of actions")
+			}
+		}
+	}
+}
+
+/// `level` after playing its optimal solution from `level`.
+pub fn play_solution(mut level: Level) -> Result<Level, SolutionError> {
+	let solution = level.solution().ok_or(SolutionError::Missing)?.to_vec();
+	for (idx, actions) in solution.into_iter().enumerate() {
+		if actions.len() != level.character_count() {
+			return Err(SolutionError::WrongActionCount(idx));
+		}
+		let actors = level
+			.characters_by_id()
+			.map(|(id, _)| *id)
+			.zip(actions)
+			.collect();
+		level.update(actors);
+	}
+	Ok(level)
+}
+
+/// Records the player's turn count whenever they complete the level, keeping
+/// their best result until the level changes.
+pub fn track_completion(
+	level: Res<Level>,
+	mut comparison: ResMut<SolutionComparison>,
+) {
+	if !level.is_changed() {
+		return;
+	}
+	// A level can only be replaced at its start, so only check then.
+	if level.turn() == 0 {
+		let start_hash = level.state_hash();
+		if comparison.start_hash != Some(start_hash) {
+			*comparison = SolutionComparison {
+				start_hash: Some(start_hash),
+				..default()
+			};
+		}
+	}
+	if comparison.playback.is_some() || !level.is_complete() {
+		return;
+	}
+	let turns = level.turn();
+	if comparison.best_turns.is_none_or(|best| turns < best) {
+		comparison.best_turns = Some(turns);
+	}
+}
+
+/// Restarts the level and plays back its optimal solution when V is pressed
+/// while the comparison is showing.
+pub fn watch_optimal_solution(
+	mut commands: Commands,
+	keys: Res<ButtonInput<KeyCode>>,
+	mut level: ResMut<Level>,
+	mut comparison: ResMut<SolutionComparison>,
+	mut next_state: ResMut<NextState<GameState>>,
+	level_entities: Query<Entity, With<LevelEntity>>,
+) {
+	if !keys.just_pressed(KeyCode::KeyV) || !comparison.is_showing(&level) {
+		return;
+	}
+	comparison.playback =
+		Some((Timer::new(PLAYBACK_INTERVAL, TimerMode::Repeating), 0));
+	for entity in &level_entities {
+		commands.entity(entity).despawn_recursive();
+	}
+	*level = level.rewound();
+	next_state.set(GameState::SpawningLevel);
+}
+
+/// Plays the next turn of the optimal solution whenever it's due, while
+/// watching it.
+pub fn play_optimal_solution(
+	time: Res<Time>,
+	mut level: ResMut<Level>,
+	mut comparison: ResMut<SolutionComparison>,
+	mut change_events: EventWriter<ChangeEvent>,
+	mut next_actors: EventWriter<NextActor>,
+) {
+	let Some((timer, turn)) = &mut comparison.playback else {
+		return;
+	};
+	if !timer.tick(time.delta()).just_finished() {
+		return;
+	}
+	let Some(actions) = level
+		.solution()
+		.and_then(|solution| solution.get(*turn))
+		.cloned()
+	else {
+		comparison.playback = None;
+		return;
+	};
+	*turn += 1;
+	let actors = level
+		.characters_by_id()
+		.map(|(id, _)| *id)
+		.zip(actions)
+		.collect();
+	change_events.send(level.update(actors));
+	if let Some(next_actor) = NextActor::nth(&level, 0) {
+		next_actors.send(next_actor);
+	}
+}
+
+/// The two solutions in the comparison graph.
+#[derive(Clone, Copy)]
+enum Entry {
+	Player,
+	Optimal,
+}
+
+impl Entry {
+	fn color(self) -> Color {
+		match self {
+			Entry::Player => Color::srgb(0.9, 0.6, 0.2),
+			Entry::Optimal => Color::srgb(0.3, 0.8, 0.4),
+		}
+	}
+}
+
+/// Marker component for the comparison panel.
+#[derive(Component)]
+pub struct ComparisonPanel;
+
+/// The label of one entry in the comparison graph.
+#[derive(Component)]
+pub struct ComparisonLabel(Entry);
+
+/// The bar of one entry in the comparison graph.
+#[derive(Component)]
+pub struct ComparisonBar(Entry);
+
+/// Spawns the (initially hidden) comparison panel.
+pub fn spawn_comparison_panel(mut commands: Commands) {
+	let font = TextFont {
+		font_size: 16.0,
+		..default()
+	};
+	commands
+		.spawn((
+			ComparisonPanel,
+			HiddenInPhotoMode,
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Px(40.0),
+				left: Val::Percent(30.0),
+				flex_direction: FlexDirection::Column,
+				row_gap: Val::Px(6.0),
+				padding: UiRect::all(Val::Px(12.0)),
+				..default()
+			},
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+			Visibility::Hidden,
+		))
+		.with_children(|panel| {
+			panel.spawn((Text::new("Level complete!"), font.clone()));
+			for entry in [Entry::Player, Entry::Optimal] {
+				panel
+					.spawn(Node {
+						align_items: AlignItems::Center,
+						column_gap: Val::Px(8.0),
+						..default()
+					})
+					.with_children(|row| {
+						row.spawn((
+							ComparisonLabel(entry),
+							Text::default(),
+							font.clone(),
+							Node {
+								width: Val::Px(160.0),
+								..default()
+							},
+						));
+						row.spawn((
+							ComparisonBar(entry),
+							Node {
+								width: Val::Px(0.0),
+								height: Val::Px(14.0),
+								..default()
+							},
+							BackgroundColor(entry.color()),
+						));
+					});
+			}
+			panel.spawn((
+				Text::new("V to watch the optimal solution"),
+				font.clone(),
+			));
+		});
+}
+
+/// Shows the comparison while the level is complete, with a bar for the
+/// player's best turn count and one for the optimal solution's.
+pub fn update_comparison_panel(
+	level: Res<Level>,
+	comparison: Res<SolutionComparison>,
+	mut panel_query: Query<&mut Visibility, With<ComparisonPanel>>,
+	mut label_query: Query<(&mut Text, &ComparisonLabel)>,
+	mut bar_query: Query<(&mut Node, &ComparisonBar)>,
+) {
+	if !level.is_changed() && !comparison.is_changed() {
+		return;
+	}
+	let showing = comparison.is_showing(&level);
+	for mut visibility in &mut panel_query {
+		*visibility = if showing {
+			Visibility::Inherited
+		} else {
+			Visibility::Hidden
+		};
+	}
+	let (Some(best_turns), Some(optimal_turns)) = (
+		comparison.best_turns,
+		level.solution().map(|solution| solution.len()),
+	) else {
+		return;
+	};
+	let turns = |entry| match entry {
+		Entry::Player => best_turns,
+		Entry::Optimal => optimal_turns,
+	};
+	for (mut text, label) in &mut label_query {
+		let name = match label.0 {
+			Entry::Player => "You",
+			Entry::Optimal => "Optimal",
+		};
+		**text = format!("{name}: {} turns", turns(label.0));
+	}
+	let longest = best_turns.max(optimal_turns).max(1) as f32;
+	for (mut node, bar) in &mut bar_query {
+		node.width = Val::Px(MAX_BAR_WIDTH * turns(bar.0) as f32 / longest);
+	}
+}
@@ -6,4 +6,14 @@ pub enum GameState {
 	Loading,
 	SpawningLevel,
 	Playing,
+	Paused,
+	PhotoMode,
+	BugReport,
+	LevelBrowser,
+	CommunityLevels,
+	ComparingBranches,
+	Tutorial,
+	LevelOutro,
+	LevelComplete,
+	DeviceDisconnected,
 }
@@ -0,0 +1,175 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+	diagnostic::{
+		DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin,
+	},
+	prelude::*,
+};
+
+use crate::{
+	control::Action,
+	diagnostics::GAMEPLAY_TIME,
+	level::{ChangeEvent, Level, Offset},
+	photo_mode::HiddenInPhotoMode,
+	update::NextActor,
+};
+
+/// Number of scripted turns in a stress test.
+const STRESS_TURNS: usize = 64;
+
+/// Number of consecutive turns the characters push in the same direction.
+const TURNS_PER_DIRECTION: usize = 8;
+
+/// Time between scripted turns, short enough that animations overlap.
+const TURN_INTERVAL: Duration = Duration::from_millis(150);
+
+/// A benchmark that plays scripted turns on the
+/// [stress level](crate::level::stress_level), measuring how long level
+/// updates, gameplay systems, and frames take.
+#[derive(Resource, Default)]
+pub struct StressTest {
+	run: Option<StressRun>,
+	/// A summary of the last completed run.
+	report: Option<String>,
+}
+
+struct StressRun {
+	timer: Timer,
+	turns: usize,
+	/// Time spent in each scripted level update, in milliseconds.
+	update_times: Vec<f64>,
+	/// Time spent in the gameplay system chain each frame, in milliseconds.
+	gameplay_times: Vec<f64>,
+	/// Duration of each frame, in milliseconds.
+	frame_times: Vec<f64>,
+}
+
+impl StressTest {
+	/// Starts a new run, discarding any previous report.
+	pub fn start(&mut self) {
+		self.run = Some(StressRun {
+			timer: Timer::new(TURN_INTERVAL, TimerMode::Repeating),
+			turns: 0,
+			update_times: Vec::new(),
+			gameplay_times: Vec::new(),
+			frame_times: Vec::new(),
+		});
+		self.report = None;
+	}
+
+	/// Abandons the current run, if any, and clears the report.
+	pub fn stop(&mut self) {
+		self.run = None;
+		self.report = None;
+	}
+}
+
+/// Plays the current run's scripted turns, recording timings as it goes and
+/// producing a report once all turns are taken.
+pub fn run_stress_test(
+	time: Res<Time>,
+	store: Res<DiagnosticsStore>,
+	mut stress_test: ResMut<StressTest>,
+	mut level: ResMut<Level>,
+	mut change_events: EventWriter<ChangeEvent>,
+	mut next_actors: EventWriter<NextActor>,
+) {
+	let Some(run) = &mut stress_test.run else {
+		return;
+	};
+	let latest = |path: &DiagnosticPath| {
+		store.get(path).and_then(|diagnostic| diagnostic.value())
+	};
+	run.gameplay_times.extend(latest(&GAMEPLAY_TIME));
+	run.frame_times
+		.extend(latest(&FrameTimeDiagnosticsPlugin::FRAME_TIME));
+
+	if !run.timer.tick(time.delta()).just_finished() {
+		return;
+	}
+	if run.turns == STRESS_TURNS {
+		let report = format!(
+			"Stress test: {} objects, {} characters, {} turns\n{}\n{}\n{}",
+			level.iter_level_objects().count(),
+			level.character_count(),
+			run.turns,
+			summarize("Update", &run.update_times),
+			summarize("Gameplay", &run.gameplay_times),
+			summarize("Frame", &run.frame_times),
+		);
+		info!("{report}");
+		stress_test.run = None;
+		stress_test.report = Some(report);
+		return;
+	}
+
+	let direction = Offset::DIRECTIONS[(run.turns / TURNS_PER_DIRECTION) % 4];
+	let actors = level
+		.characters_by_id()
+		.map(|(&id, _)| (id, Action::Push(direction)))
+		.collect();
+	let start = Instant::now();
+	let change_event = level.update(actors);
+	run.update_times
+		.push(1000.0 * start.elapsed().as_secs_f64());
+	run.turns += 1;
+	change_events.send(change_event);
+	if let Some(next_actor) = NextActor::nth(&level, 0) {
+		next_actors.send(next_actor);
+	}
+}
+
+/// A one-line summary of the timings in `times_ms`.
+fn summarize(label: &str, times_ms: &[f64]) -> String {
+	if times_ms.is_empty() {
+		return format!("{label}: no samples");
+	}
+	let mean = times_ms.iter().sum::<f64>() / times_ms.len() as f64;
+	let max = times_ms.iter().copied().fold(0.0, f64::max);
+	format!("{label}: mean {mean:.2} ms, max {max:.2} ms")
+}
+
+/// Marker component for the stress test status text.
+#[derive(Component)]
+pub struct StressTestText;
+
+/// Spawns the (initially empty) stress test status text.
+pub fn spawn_stress_test_text(mut commands: Commands) {
+	commands.spawn((
+		StressTestText,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			// Below the diagnostics overlay.
+			top: Val::Px(120.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// Shows the progress of the current run or the last run's report.
+pub fn update_stress_test_text(
+	stress_test: Res<StressTest>,
+	mut text_query: Query<&mut Text, With<StressTestText>>,
+) {
+	if !stress_test.is_changed() {
+		return;
+	}
+	let status = match (&stress_test.run, &stress_test.report) {
+		(Some(run), _) => {
+			format!("Stress test: turn {}/{STRESS_TURNS}", run.turns)
+		}
+		(None, Some(report)) => report.clone(),
+		(None, None) => String::new(),
+	};
+	for mut text in &mut text_query {
+		**text = status.clone();
+	}
+}
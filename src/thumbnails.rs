@@ -0,0 +1,317 @@
+use std::path::Path;
+
+use bevy::{
+	asset::io::AssetSourceBuilder,
+	prelude::*,
+	render::{
+		camera::RenderTarget,
+		render_asset::RenderAssetUsages,
+		render_resource::{
+			Extent3d, TextureDimension, TextureFormat, TextureUsages,
+		},
+		view::screenshot::{save_to_disk, Screenshot},
+	},
+	utils::HashMap,
+};
+
+use crate::{
+	animation,
+	assets::{GameAssets, MaterialKind, MeshKind, ModelKind},
+	camera,
+	level::{Coords, Level, Object},
+	level_asset::LevelAsset,
+};
+
+/// Name of the asset source for cached files.
+pub const CACHE_SOURCE: &str = "cache";
+
+/// Directory of the [cache asset source](CACHE_SOURCE).
+const CACHE_DIR: &str = "cache";
+
+/// Width and height of a thumbnail, in pixels.
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// Width and height of a snapshot of a turn, in pixels.
+const SNAP_SIZE: u32 = 64;
+
+/// Where levels are staged for rendering thumbnails.
+const STAGING_OFFSET: Vec3 = Vec3::new(10_000.0, 0.0, 0.0);
+
+/// Frames to wait after staging a level before capturing it, so its scenes
+/// have time to spawn.
+const SETTLE_FRAMES: u32 = 3;
+
+/// An asset source for files cached between runs, like thumbnails.
+pub fn cache_source() -> AssetSourceBuilder {
+	AssetSourceBuilder::platform_default(CACHE_DIR, None)
+}
+
+/// The path of the cached thumbnail for the level with the given content hash,
+/// relative to the cache directory.
+fn thumbnail_path(hash: u64) -> String {
+	format!("thumbnails/{hash:016x}.png")
+}
+
+/// Thumbnail images of levels, keyed by a hash of their starting state so that
+/// editing a level invalidates its thumbnail.
+///
+/// Each level is rendered once to an offscreen image, which is saved to the
+/// cache directory so later runs can load it rather than rendering it again.
+///
+/// Smaller snapshots of the states the level being played passes through are
+/// rendered the same way, for the timeline, but aren't cached between runs.
+#[derive(Resource, Default)]
+pub struct Thumbnails {
+	images: HashMap<u64, Handle<Image>>,
+	/// The content hash of each level asset, as of its last change.
+	hashes: HashMap<AssetId<LevelAsset>, u64>,
+	/// Levels waiting to be rendered, with their content hashes.
+	queue: Vec<(u64, Level)>,
+	/// Snapshots of level states, keyed by [state hash](Level::state_hash).
+	snaps: HashMap<u64, Handle<Image>>,
+	/// Level states waiting to be snapped, with their state hashes. They're
+	/// rendered ahead of thumbnails, so the timeline keeps up with play.
+	snap_queue: Vec<(u64, Level)>,
+	/// The level being rendered, if any.
+	staging: Option<Staging>,
+}
+
+/// A level spawned away from the level being played, with a camera rendering
+/// it to an image.
+struct Staging {
+	hash: u64,
+	image: Handle<Image>,
+	root: Entity,
+	frames: u32,
+	/// Whether the image is a snapshot rather than a thumbnail.
+	snap: bool,
+}
+
+impl Thumbnails {
+	/// The thumbnail of the level asset with the given ID, if it's been
+	/// rendered or loaded from the cache.
+	pub fn get(&self, id: AssetId<LevelAsset>) -> Option<&Handle<Image>> {
+		self.hashes.get(&id).and_then(|hash| self.images.get(hash))
+	}
+
+	/// Loads the cached thumbnail for the level asset with the given ID or
+	/// queues it for rendering, unless it's already available.
+	fn request(
+		&mut self,
+		id: AssetId<LevelAsset>,
+		level: Level,
+		asset_server: &AssetServer,
+	) {
+		let hash = level.state_hash();
+		self.hashes.insert(id, hash);
+		if self.images.contains_key(&hash)
+			|| self.queue.iter().any(|(queued, _)| *queued == hash)
+		{
+			return;
+		}
+		let path = thumbnail_path(hash);
+		if Path::new(CACHE_DIR).join(&path).exists() {
+			let image = asset_server.load(format!("{CACHE_SOURCE}://{path}"));
+			self.images.insert(hash, image);
+		} else {
+			self.queue.push((hash, level));
+		}
+	}
+
+	/// The snapshot of the level state with the given state hash, if it's
+	/// been rendered.
+	pub fn snap(&self, hash: u64) -> Option<&Handle<Image>> {
+		self.snaps.get(&hash)
+	}
+
+	/// Queues a snapshot of `level`'s current state, whose state hash is
+	/// `hash`, unless it's already snapped or queued.
+	pub fn request_snap(&mut self, hash: u64, level: &Level) {
+		let staged = self
+			.staging
+			.as_ref()
+			.is_some_and(|staging| staging.snap && staging.hash == hash);
+		if staged
+			|| self.snaps.contains_key(&hash)
+			|| self.snap_queue.iter().any(|(queued, _)| *queued == hash)
+		{
+			return;
+		}
+		self.snap_queue.push((hash, level.clone()));
+	}
+}
+
+/// Requests thumbnails for levels as they load or change.
+pub fn request_thumbnails(
+	mut asset_events: EventReader<AssetEvent<LevelAsset>>,
+	asset_server: Res<AssetServer>,
+	level_assets: Res<Assets<LevelAsset>>,
+	mut thumbnails: ResMut<Thumbnails>,
+) {
+	for asset_event in asset_events.read() {
+		// Pack levels are added rather than loaded.
+		let (AssetEvent::Added { id }
+		| AssetEvent::LoadedWithDependencies { id }
+		| AssetEvent::Modified { id }) = asset_event
+		else {
+			continue;
+		};
+		if let Some(asset) = level_assets.get(*id) {
+			thumbnails.request(*id, asset.to_level(), &asset_server);
+		}
+	}
+}
+
+/// Renders queued snapshots and thumbnails, one level at a time. Each level is
+/// staged for a few frames, then thumbnails are captured and saved to the
+/// cache.
+pub fn render_thumbnails(
+	mut commands: Commands,
+	mut thumbnails: ResMut<Thumbnails>,
+	mut images: ResMut<Assets<Image>>,
+	assets: Res<GameAssets>,
+) {
+	let thumbnails = &mut *thumbnails;
+	let Some(staging) = &mut thumbnails.staging else {
+		let (snap, (hash, level)) = match thumbnails.snap_queue.pop() {
+			Some(job) => (true, job),
+			None => match thumbnails.queue.pop() {
+				Some(job) => (false, job),
+				None => return,
+			},
+		};
+		let size = if snap { SNAP_SIZE } else { THUMBNAIL_SIZE };
+		let image = images.add(render_target(size));
+		let root =
+			stage(&mut commands, &level, STAGING_OFFSET, &image, &assets);
+		thumbnails.staging = Some(Staging {
+			hash,
+			image,
+			root,
+			frames: 0,
+			snap,
+		});
+		return;
+	};
+	staging.frames += 1;
+	if staging.frames == SETTLE_FRAMES && !staging.snap {
+		let path = Path::new(CACHE_DIR).join(thumbnail_path(staging.hash));
+		if let Some(dir) = path.parent() {
+			if let Err(err) = std::fs::create_dir_all(dir) {
+				warn!("could not create thumbnail cache: {err}");
+			}
+		}
+		commands
+			.spawn(Screenshot::image(staging.image.clone()))
+			.observe(save_to_disk(path));
+	} else if staging.frames > SETTLE_FRAMES {
+		// The capture (or for a snapshot, the image itself) was rendered last
+		// frame, so the stage is done.
+		commands.entity(staging.root).despawn_recursive();
+		let (hash, image) = (staging.hash, staging.image.clone());
+		if staging.snap {
+			thumbnails.snaps.insert(hash, image);
+		} else {
+			thumbnails.images.insert(hash, image);
+		}
+		thumbnails.staging = None;
+	}
+}
+
+/// A blank square image `size` pixels wide for a camera to render to.
+pub fn render_target(size: u32) -> Image {
+	let size = Extent3d {
+		width: size,
+		height: size,
+		..default()
+	};
+	let mut image = Image::new_fill(
+		size,
+		TextureDimension::D2,
+		&[0, 0, 0, 255],
+		TextureFormat::Bgra8UnormSrgb,
+		RenderAssetUsages::default(),
+	);
+	image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+		| TextureUsages::COPY_DST
+		| TextureUsages::COPY_SRC
+		| TextureUsages::RENDER_ATTACHMENT;
+	image
+}
+
+/// Spawns the tiles and objects of `level` at `offset`, along with a camera
+/// framing them that renders to `image`. Returns the root entity.
+///
+/// Only the level's appearance is staged, without any of the components the
+/// gameplay systems look for, so the offset should be far enough from the
+/// origin to be out of view of the level being played.
+pub fn stage(
+	commands: &mut Commands,
+	level: &Level,
+	offset: Vec3,
+	image: &Handle<Image>,
+	assets: &GameAssets,
+) -> Entity {
+	let (camera_transform, view_size) = camera::framing(
+		Vec2::ZERO,
+		Vec2::new(level.width() as f32, level.height() as f32) - Vec2::ONE,
+	);
+	commands
+		.spawn((Transform::from_translation(offset), Visibility::default()))
+		.with_children(|stage| {
+			stage.spawn((
+				Camera3d::default(),
+				Camera {
+					target: RenderTarget::Image(image.clone()),
+					order: -1,
+					clear_color: ClearColorConfig::Custom(
+						level.lighting().clear_color.unwrap_or(Color::BLACK),
+					),
+					..default()
+				},
+				camera_transform,
+				Projection::Orthographic(OrthographicProjection {
+					scaling_mode: camera::scaling_mode(view_size),
+					..OrthographicProjection::default_3d()
+				}),
+			));
+			for row in 0..level.height() {
+				for col in 0..level.width() {
+					let coords = Coords::new(row as i32, col as i32);
+					let tile = level.tile_at(coords);
+					if let Some((scene, z)) = assets.tile(tile) {
+						stage.spawn((SceneRoot(scene), coords.transform(z)));
+					}
+					if let Some(marker) =
+						animation::tile_marker(assets, tile, coords)
+					{
+						stage.spawn(marker);
+					}
+				}
+			}
+			for platform in level.platforms() {
+				stage.spawn(animation::platform(assets, platform.coords()));
+			}
+			for level_object in level.iter_level_objects() {
+				let transform = level_object.coords.transform(0.5);
+				match level_object.object {
+					Object::Character(character) => stage.spawn((
+						Mesh3d(assets.mesh(MeshKind::Character)),
+						MeshMaterial3d(assets.material(
+							MaterialKind::Character(character.color),
+						)),
+						transform.with_rotation(Quat::from_rotation_z(
+							level_object.angle,
+						)),
+					)),
+					Object::Prop(descriptor) => stage.spawn((
+						SceneRoot(
+							assets.model(ModelKind::Prop(descriptor.model)),
+						),
+						transform,
+					)),
+				};
+			}
+		})
+		.id()
+}
@@ -0,0 +1,301 @@
+use bevy::utils::{HashMap, HashSet};
+
+use crate::level::{
+	Change, Coords, Level, Move, Object, Offset, Tile, TileChange,
+};
+
+/// The points within a turn at which tiles can act.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TurnPhase {
+	/// After returns, before pushes are resolved.
+	PreMove,
+	/// After pushes are resolved and platforms have moved, before summons.
+	PostMove,
+	/// After summons.
+	EndOfTurn,
+}
+
+/// The effect a kind of tile has on a level. At each [`TurnPhase`], every
+/// tile's hook for that phase runs and may produce a [`Change`], which is
+/// applied immediately (so later tiles and phases see its effects) and folded
+/// into the turn's change, making tile effects undoable like any other.
+///
+/// Hooks do nothing by default, so a behavior only needs to implement the
+/// phases it cares about.
+pub trait TileBehavior: Sync {
+	fn pre_move(&self, _level: &Level, _coords: Coords) -> Change {
+		Change::default()
+	}
+
+	fn post_move(&self, _level: &Level, _coords: Coords) -> Change {
+		Change::default()
+	}
+
+	fn end_of_turn(&self, _level: &Level, _coords: Coords) -> Change {
+		Change::default()
+	}
+
+	/// Whether an object moving onto this tile next turn would be destroyed,
+	/// for the danger preview. See [`Level::danger_coords`].
+	fn is_lethal(&self, _level: &Level, _coords: Coords) -> bool {
+		false
+	}
+}
+
+impl dyn TileBehavior {
+	/// Runs this behavior's hook for `phase`.
+	pub fn run(
+		&self,
+		phase: TurnPhase,
+		level: &Level,
+		coords: Coords,
+	) -> Change {
+		match phase {
+			TurnPhase::PreMove => self.pre_move(level, coords),
+			TurnPhase::PostMove => self.post_move(level, coords),
+			TurnPhase::EndOfTurn => self.end_of_turn(level, coords),
+		}
+	}
+}
+
+/// A tile with no effect of its own.
+struct Inert;
+
+impl TileBehavior for Inert {}
+
+/// Starts characters sliding when they step onto the ice, and stops them when
+/// they slide off it.
+struct Ice;
+
+impl TileBehavior for Ice {
+	fn post_move(&self, level: &Level, coords: Coords) -> Change {
+		let mut slide_toggles = HashSet::new();
+		if let Some(id) = level.character_id_at(coords) {
+			if !level.character_by_id(&id).sliding {
+				slide_toggles.insert(id);
+			}
+		}
+		// Sliding characters always move the way they face, so one facing away
+		// from this tile just next to it slid off it.
+		for offset in Offset::DIRECTIONS {
+			let neighbor = coords + offset;
+			if !level.in_bounds(neighbor)
+				|| level.tile_at(neighbor) == Tile::Ice
+			{
+				continue;
+			}
+			if let Some(id) = level.character_id_at(neighbor) {
+				if level.character_by_id(&id).sliding
+					&& level.facing(&id) == offset
+				{
+					slide_toggles.insert(id);
+				}
+			}
+		}
+		Change {
+			slide_toggles,
+			..Change::default()
+		}
+	}
+}
+
+/// Destroys anything that moves into the pit. Props fill it as they fall in,
+/// turning it into floor, while characters are lost.
+struct Pit;
+
+impl TileBehavior for Pit {
+	fn post_move(&self, level: &Level, coords: Coords) -> Change {
+		let mut change = Change::default();
+		if let Some(level_object) = level.level_object_at(coords) {
+			change.destructions.insert(level_object.id, *level_object);
+			if !level_object.object.is_character() {
+				change.tile_changes.insert(
+					coords,
+					TileChange {
+						from: Tile::Pit,
+						to: Tile::Floor { portal_color: None },
+					},
+				);
+			}
+		}
+		change
+	}
+
+	fn is_lethal(&self, _level: &Level, _coords: Coords) -> bool {
+		true
+	}
+}
+
+/// Gives way after use. The floor is marked as stepped on whenever something
+/// stands on it at the start or end of a turn, which catches objects the
+/// level starts with as well as arrivals and summons, and it collapses into a
+/// pit after the moves of a turn that leave it empty. An object stepping on
+/// just as another steps off holds it up until it leaves in turn.
+struct Cracked {
+	stepped_on: bool,
+}
+
+impl Cracked {
+	/// Marks the floor as stepped on if something is standing on it.
+	fn mark(&self, level: &Level, coords: Coords) -> Change {
+		let mut change = Change::default();
+		if !self.stepped_on && level.object_at(coords).is_some() {
+			change.tile_changes.insert(
+				coords,
+				TileChange {
+					from: Tile::Cracked { stepped_on: false },
+					to: Tile::Cracked { stepped_on: true },
+				},
+			);
+		}
+		change
+	}
+}
+
+impl TileBehavior for Cracked {
+	fn pre_move(&self, level: &Level, coords: Coords) -> Change {
+		self.mark(level, coords)
+	}
+
+	fn post_move(&self, level: &Level, coords: Coords) -> Change {
+		let mut change = Change::default();
+		if self.stepped_on && level.object_at(coords).is_none() {
+			change.tile_changes.insert(
+				coords,
+				TileChange {
+					from: Tile::Cracked { stepped_on: true },
+					to: Tile::Pit,
+				},
+			);
+		}
+		change
+	}
+
+	fn end_of_turn(&self, level: &Level, coords: Coords) -> Change {
+		self.mark(level, coords)
+	}
+}
+
+/// Takes in props pushed into open water: those that
+/// [float](crate::level::ObjectDescriptor::floats) bridge it, and the rest
+/// sink. Either way, the prop is gone. Characters can't enter open water.
+struct Water;
+
+impl TileBehavior for Water {
+	fn post_move(&self, level: &Level, coords: Coords) -> Change {
+		let mut change = Change::default();
+		let Some(level_object) = level.level_object_at(coords) else {
+			return change;
+		};
+		change.destructions.insert(level_object.id, *level_object);
+		if let Object::Prop(descriptor) = level_object.object {
+			if descriptor.floats {
+				change.tile_changes.insert(
+					coords,
+					TileChange {
+						from: Tile::Water { bridged: false },
+						to: Tile::Water { bridged: true },
+					},
+				);
+			}
+		}
+		change
+	}
+
+	fn is_lethal(&self, _level: &Level, _coords: Coords) -> bool {
+		true
+	}
+}
+
+/// Destroys anything that moves into the lava. Props that aren't
+/// [flammable](crate::level::ObjectDescriptor::flammable) bridge it, while
+/// flammable props burn up and characters are lost.
+struct Lava;
+
+impl TileBehavior for Lava {
+	fn post_move(&self, level: &Level, coords: Coords) -> Change {
+		let mut change = Change::default();
+		let Some(level_object) = level.level_object_at(coords) else {
+			return change;
+		};
+		change.destructions.insert(level_object.id, *level_object);
+		if let Object::Prop(descriptor) = level_object.object {
+			if !descriptor.flammable {
+				change.tile_changes.insert(
+					coords,
+					TileChange {
+						from: Tile::Lava { bridged: false },
+						to: Tile::Lava { bridged: true },
+					},
+				);
+			}
+		}
+		change
+	}
+
+	fn is_lethal(&self, _level: &Level, _coords: Coords) -> bool {
+		true
+	}
+}
+
+/// Turns whatever stands on the turntable a quarter turn. This happens at the
+/// end of the turn, so objects arriving on it this turn are turned as well,
+/// and the ice sees a sliding character's facing as it arrived.
+struct Turntable {
+	clockwise: bool,
+}
+
+impl TileBehavior for Turntable {
+	fn end_of_turn(&self, level: &Level, coords: Coords) -> Change {
+		let mut moves = HashMap::new();
+		if let Some(level_object) = level.level_object_at(coords) {
+			let facing = level.facing(&level_object.id);
+			moves.insert(
+				level_object.id,
+				Move {
+					from_coords: coords,
+					to_coords: coords,
+					from_angle: level_object.angle,
+					to_angle: facing.quarter_turn(self.clockwise).angle(),
+				},
+			);
+		}
+		Change {
+			moves,
+			..Change::default()
+		}
+	}
+}
+
+impl Tile {
+	/// The registered behavior for this kind of tile.
+	pub fn behavior(&self) -> &'static dyn TileBehavior {
+		match self {
+			// Walls and the void block pushes, portals are handled by
+			// summoning and returning, and platforms move in a phase of their
+			// own, so none of them need their own hooks. Bridged water and
+			// lava are just floor.
+			Tile::Floor { .. }
+			| Tile::Wall
+			| Tile::Stairs
+			| Tile::Void
+			| Tile::Track { .. }
+			| Tile::Water { bridged: true }
+			| Tile::Lava { bridged: true } => &Inert,
+			Tile::Ice => &Ice,
+			Tile::Pit => &Pit,
+			Tile::Turntable { clockwise: true } => {
+				&Turntable { clockwise: true }
+			}
+			Tile::Turntable { clockwise: false } => {
+				&Turntable { clockwise: false }
+			}
+			Tile::Cracked { stepped_on: true } => &Cracked { stepped_on: true },
+			Tile::Cracked { stepped_on: false } => {
+				&Cracked { stepped_on: false }
+			}
+			Tile::Water { bridged: false } => &Water,
+			Tile::Lava { bridged: false } => &Lava,
+		}
+	}
+}
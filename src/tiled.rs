@@ -0,0 +1,312 @@
+use std::fmt::{self, Display};
+
+use bevy::{
+	asset::{io::Reader, AssetLoader, LoadContext},
+	prelude::*,
+	utils::HashMap,
+};
+
+use crate::{
+	level::{CharacterColor, Object},
+	level_asset::{LevelAsset, LevelAssetError},
+};
+
+/// Mask for the tile ID bits of a Tiled GID, excluding the flip flags.
+const GID_MASK: u32 = 0x1fff_ffff;
+
+/// An error importing a Tiled map.
+#[derive(Debug)]
+pub enum TiledError {
+	Io(std::io::Error),
+	/// The map doesn't follow the subset of the TMX format the importer
+	/// understands.
+	Format(String),
+	/// The map imported but isn't a playable level.
+	Level(LevelAssetError),
+}
+
+impl Display for TiledError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TiledError::Io(err) => write!(f, "could not read map: {err}"),
+			TiledError::Format(msg) => write!(f, "invalid map: {msg}"),
+			TiledError::Level(err) => write!(f, "{err}"),
+		}
+	}
+}
+
+impl std::error::Error for TiledError {}
+
+impl From<std::io::Error> for TiledError {
+	fn from(err: std::io::Error) -> Self {
+		TiledError::Io(err)
+	}
+}
+
+fn format_error(msg: impl Into<String>) -> TiledError {
+	TiledError::Format(msg.into())
+}
+
+/// Converts a [Tiled](https://www.mapeditor.org/) map in TMX format into a
+/// [`make_level`](crate::level::make_level) map.
+///
+/// The importer understands orthogonal maps with CSV-encoded tile layers and
+/// tilesets embedded in the map:
+///
+/// - Tiles are mapped by their class (or type, in older versions of Tiled) in
+///   the tileset: `wall`, `stairs`, `void`, `ice`, `pit`, `track`, `platform`
+///   (a platform on its track), `turntable` (turning clockwise),
+///   `counterclockwise_turntable`, `cracked_floor`, `water`, `lava`, or
+///   `floor`. Tiles without a class are floor, and empty cells are walls.
+///   Later layers draw over earlier ones.
+/// - Objects are mapped by their class: `character`, or the name of a prop in
+///   snake case, like `wooden_crate`. Characters get colors in the order they
+///   appear. Objects are placed on the tile containing their top-left corner,
+///   or bottom-left corner for tile objects, as Tiled positions them.
+pub fn import_map(tmx: &str) -> Result<String, TiledError> {
+	let mut width = 0;
+	let mut height = 0;
+	let mut tile_width = 1.0;
+	let mut tile_height = 1.0;
+	// Tile classes by GID, for tiles that have one.
+	let mut classes: HashMap<u32, String> = HashMap::new();
+	let mut first_gid = 0;
+	let mut tiles: Vec<u8> = Vec::new();
+	let mut objects: HashMap<(usize, usize), u8> = HashMap::new();
+	let mut character_count = 0;
+
+	for tag in tags(tmx) {
+		match tag.name {
+			"map" => {
+				if tag.attr("orientation").is_some_and(|o| o != "orthogonal") {
+					return Err(format_error("map is not orthogonal"));
+				}
+				if tag.attr("infinite") == Some("1") {
+					return Err(format_error("infinite maps are unsupported"));
+				}
+				width = tag.parse_attr("width")?;
+				height = tag.parse_attr("height")?;
+				tile_width = tag.parse_attr("tilewidth")?;
+				tile_height = tag.parse_attr("tileheight")?;
+				tiles = vec![b'#'; width * height];
+			}
+			"tileset" => {
+				if tag.attr("source").is_some() {
+					return Err(format_error(
+						"external tilesets are unsupported",
+					));
+				}
+				first_gid = tag.parse_attr("firstgid")?;
+			}
+			"tile" => {
+				let id: u32 = tag.parse_attr("id")?;
+				if let Some(class) = tag.attr("class").or(tag.attr("type")) {
+					classes.insert(first_gid + id, class.to_string());
+				}
+			}
+			"data" => {
+				if tag.attr("encoding") != Some("csv") {
+					return Err(format_error(
+						"tile layers must use CSV encoding",
+					));
+				}
+				for (idx, cell) in tag.text.split(',').enumerate() {
+					if cell.trim().is_empty() {
+						continue;
+					}
+					let gid =
+						cell.trim().parse::<u32>().map_err(|_| {
+							format_error("invalid tile in layer")
+						})? & GID_MASK;
+					if gid == 0 {
+						continue;
+					}
+					let Some(tile) = tiles.get_mut(idx) else {
+						return Err(format_error("tile layer is too large"));
+					};
+					let class =
+						classes.get(&gid).map_or("floor", String::as_str);
+					*tile = tile_symbol(class).ok_or_else(|| {
+						format_error(format!("unknown tile class \"{class}\""))
+					})?;
+				}
+			}
+			"object" => {
+				let class =
+					tag.attr("class").or(tag.attr("type")).unwrap_or("");
+				let symbol = if class == "character" {
+					if character_count == CharacterColor::COUNT {
+						return Err(format_error("too many characters"));
+					}
+					character_count += 1;
+					b'0' + character_count as u8 - 1
+				} else {
+					prop_symbol(class).ok_or_else(|| {
+						format_error(format!(
+							"unknown object class \"{class}\""
+						))
+					})?
+				};
+				let x: f32 = tag.parse_attr("x")?;
+				let y: f32 = tag.parse_attr("y")?;
+				let col = (x / tile_width).floor();
+				let mut row = (y / tile_height).floor();
+				// Tile objects are positioned by their bottom-left corner.
+				if tag.attr("gid").is_some() {
+					row = (y / tile_height).ceil() - 1.0;
+				}
+				if col < 0.0
+					|| row < 0.0 || col as usize >= width
+					|| row as usize >= height
+				{
+					return Err(format_error("object outside the map"));
+				}
+				let coords = (row as usize, col as usize);
+				if objects.insert(coords, symbol).is_some() {
+					return Err(format_error(format!(
+						"more than one object at row {}, column {}",
+						coords.0, coords.1
+					)));
+				}
+			}
+			_ => {}
+		}
+	}
+	if width == 0 || height == 0 {
+		return Err(format_error("map is empty"));
+	}
+
+	let mut map = String::new();
+	for row in 0..height {
+		for col in 0..width {
+			map.push(tiles[row * width + col] as char);
+			map.push(objects.get(&(row, col)).copied().unwrap_or(b' ') as char);
+		}
+		map.push('\n');
+	}
+	Ok(map)
+}
+
+/// The map symbol of the tile with the given class, as listed in
+/// [`import_map`].
+pub fn tile_symbol(class: &str) -> Option<u8> {
+	Some(match class {
+		"floor" => b'.',
+		"wall" => b'#',
+		"stairs" => b'>',
+		"void" => b'_',
+		"ice" => b'~',
+		"pit" => b'*',
+		"track" => b'=',
+		"platform" => b'+',
+		"turntable" => b')',
+		"counterclockwise_turntable" => b'(',
+		"cracked_floor" => b'%',
+		"water" => b'w',
+		"lava" => b'l',
+		_ => return None,
+	})
+}
+
+/// The map symbol of the prop whose name is `class` in snake case.
+pub fn prop_symbol(class: &str) -> Option<u8> {
+	Object::PROPS.into_iter().find_map(|prop| match prop {
+		Object::Prop(descriptor)
+			if descriptor.name.to_lowercase().replace(' ', "_") == class =>
+		{
+			Some(descriptor.symbol)
+		}
+		_ => None,
+	})
+}
+
+/// An XML start tag, with the text up to the next tag.
+struct Tag<'a> {
+	name: &'a str,
+	attrs: Vec<(&'a str, &'a str)>,
+	text: &'a str,
+}
+
+impl<'a> Tag<'a> {
+	fn attr(&self, key: &str) -> Option<&'a str> {
+		self.attrs
+			.iter()
+			.find(|(attr_key, _)| *attr_key == key)
+			.map(|(_, value)| *value)
+	}
+
+	fn parse_attr<T: std::str::FromStr>(
+		&self,
+		key: &str,
+	) -> Result<T, TiledError> {
+		self.attr(key)
+			.and_then(|value| value.parse().ok())
+			.ok_or_else(|| {
+				format_error(format!("missing or invalid {} {key}", self.name))
+			})
+	}
+}
+
+/// The start tags in `xml`, in order. End tags, comments, and declarations
+/// are skipped, and entities in attribute values aren't decoded, which is good
+/// enough for the attributes TMX files use.
+fn tags(xml: &str) -> impl Iterator<Item = Tag<'_>> {
+	xml.split('<').skip(1).filter_map(|chunk| {
+		let (tag, text) = chunk.split_once('>')?;
+		if tag.starts_with(['/', '?', '!']) {
+			return None;
+		}
+		let tag = tag.trim_end_matches('/');
+		let (name, mut rest) =
+			tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+		let mut attrs = Vec::new();
+		while let Some((key, after_key)) = rest.split_once('=') {
+			let after_key = after_key.trim_start();
+			let quote = after_key.chars().next()?;
+			if quote != '"' && quote != '\'' {
+				return None;
+			}
+			let (value, after_value) = after_key[1..].split_once(quote)?;
+			attrs.push((key.trim(), value));
+			rest = after_value;
+		}
+		Some(Tag { name, attrs, text })
+	})
+}
+
+/// Loads Tiled maps as [`LevelAsset`]s, named after their files.
+#[derive(Default)]
+pub struct TiledLevelLoader;
+
+impl AssetLoader for TiledLevelLoader {
+	type Asset = LevelAsset;
+	type Settings = ();
+	type Error = TiledError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		load_context: &mut LoadContext<'_>,
+	) -> Result<LevelAsset, TiledError> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let tmx = String::from_utf8(bytes)
+			.map_err(|_| format_error("map is not UTF-8"))?;
+		let name = load_context
+			.path()
+			.file_stem()
+			.map_or_else(String::new, |stem| stem.to_string_lossy().into());
+		LevelAsset {
+			name,
+			map: import_map(&tmx)?,
+			..default()
+		}
+		.validated()
+		.map_err(TiledError::Level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["tmx"]
+	}
+}
@@ -0,0 +1,155 @@
+use std::iter::once;
+
+use bevy::prelude::*;
+
+use crate::{
+	control::ControlEvent, history, level::Level,
+	photo_mode::HiddenInPhotoMode, thumbnails::Thumbnails,
+};
+
+/// How many turns the timeline shows at once, centered on the current turn
+/// where possible.
+const SPAN: usize = 9;
+
+/// Width and height of each turn's snapshot on the timeline.
+const SNAP_SIZE: Val = Val::Px(48.0);
+
+/// The border of the current turn's snapshot.
+const CURRENT_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// The border of other turns' snapshots.
+const BORDER_COLOR: Color = Color::srgba(0.3, 0.3, 0.3, 0.8);
+
+/// How undone turns that could still be redone are tinted.
+const UNDONE_TINT: Color = Color::srgba(1.0, 1.0, 1.0, 0.4);
+
+/// The state hash of the level at its start, which identifies the snapshot of
+/// turn 0. Later turns' hashes are in the level's history.
+#[derive(Resource, Default)]
+pub struct Timeline {
+	start_hash: Option<u64>,
+}
+
+/// Marker component for the timeline.
+#[derive(Component)]
+pub struct TimelineStrip;
+
+/// A slot on the timeline, showing the snapshot of one turn.
+#[derive(Component)]
+pub struct TimelineSnap {
+	/// The slot's position from the left.
+	slot: usize,
+	/// The turn shown, if any.
+	turn: Option<usize>,
+}
+
+/// Spawns the timeline along the bottom of the screen, with a slot for each
+/// turn in its span. The [display preset](crate::display::DisplayPreset) may
+/// move it.
+pub fn spawn_timeline(mut commands: Commands) {
+	commands
+		.spawn((
+			TimelineStrip,
+			HiddenInPhotoMode,
+			Node {
+				position_type: PositionType::Absolute,
+				bottom: Val::Px(4.0),
+				left: Val::Percent(30.0),
+				width: Val::Percent(40.0),
+				justify_content: JustifyContent::Center,
+				column_gap: Val::Px(4.0),
+				..default()
+			},
+		))
+		.with_children(|strip| {
+			for slot in 0..SPAN {
+				strip.spawn((
+					TimelineSnap { slot, turn: None },
+					Button,
+					ImageNode::default(),
+					Node {
+						width: SNAP_SIZE,
+						height: SNAP_SIZE,
+						border: UiRect::all(Val::Px(2.0)),
+						..default()
+					},
+					BorderColor(BORDER_COLOR),
+					Visibility::Hidden,
+				));
+			}
+		});
+}
+
+/// Snapshots each state the level passes through and shows the snapshots of
+/// the turns around the current one, with undone turns dimmed. Clicking a
+/// snapshot seeks to its turn.
+pub fn update_timeline(
+	level: Res<Level>,
+	mut timeline: ResMut<Timeline>,
+	mut thumbnails: ResMut<Thumbnails>,
+	mut control_events: EventWriter<ControlEvent>,
+	mut snap_query: Query<(
+		&Interaction,
+		&mut TimelineSnap,
+		&mut ImageNode,
+		&mut BorderColor,
+		&mut Visibility,
+	)>,
+) {
+	if level.is_changed() {
+		let hash = level.state_hash();
+		if level.turn() == 0 {
+			timeline.start_hash = Some(hash);
+		}
+		thumbnails.request_snap(hash, &level);
+	}
+
+	let hashes: Vec<Option<u64>> = once(timeline.start_hash)
+		.chain(history::turns(&level).map(|turn| Some(turn.state_hash)))
+		.collect();
+	let first = level
+		.turn()
+		.saturating_sub(SPAN / 2)
+		.min(hashes.len().saturating_sub(SPAN));
+	for (interaction, mut snap, mut image_node, mut border, mut visibility) in
+		&mut snap_query
+	{
+		if *interaction == Interaction::Pressed {
+			if let Some(turn) = snap.turn {
+				control_events.send(ControlEvent::Seek(turn));
+			}
+		}
+		let turn = first + snap.slot;
+		let image = hashes
+			.get(turn)
+			.copied()
+			.flatten()
+			.and_then(|hash| thumbnails.snap(hash));
+		let Some(image) = image else {
+			snap.turn = None;
+			visibility.set_if_neq(Visibility::Hidden);
+			continue;
+		};
+		snap.turn = Some(turn);
+		if image_node.image != *image {
+			image_node.image = image.clone();
+		}
+		let tint = if turn > level.turn() {
+			UNDONE_TINT
+		} else {
+			Color::WHITE
+		};
+		if image_node.color != tint {
+			image_node.color = tint;
+		}
+		let border_color = if turn == level.turn() {
+			CURRENT_COLOR
+		} else {
+			BORDER_COLOR
+		};
+		if border.0 != border_color {
+			border.0 = border_color;
+		}
+		visibility.set_if_neq(Visibility::Inherited);
+	}
+}
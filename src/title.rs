@@ -0,0 +1,59 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+	level::Level, locale, photo_mode::HiddenInPhotoMode, settings::Settings,
+};
+
+/// Marker component for the level title text.
+#[derive(Component)]
+pub struct LevelTitle;
+
+/// Spawns the (initially empty) level title text, above the stuck prompt.
+pub fn spawn_level_title(mut commands: Commands) {
+	commands.spawn((
+		LevelTitle,
+		HiddenInPhotoMode,
+		Text::default(),
+		TextFont {
+			font_size: 14.0,
+			..default()
+		},
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(52.0),
+			left: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// Shows the name, author, par, and description of the level being played, in
+/// the player's [language](locale::language) where translated.
+pub fn update_level_title(
+	level: Res<Level>,
+	settings: Res<Settings>,
+	mut title_query: Query<&mut Text, With<LevelTitle>>,
+) {
+	if !level.is_changed() && !settings.is_changed() {
+		return;
+	}
+	let language = locale::language(&settings);
+	let meta = level.meta();
+	let mut title = meta.localized_name(&language).to_string();
+	if let Some(author) = &meta.author {
+		write!(title, " by {author}").unwrap();
+	}
+	if let Some(par) = meta.par {
+		write!(title, " - par {par}").unwrap();
+	}
+	if let Some(description) = meta.localized_description(&language) {
+		write!(title, "\n{description}").unwrap();
+	}
+	for mut text in &mut title_query {
+		if **text != title {
+			**text = title.clone();
+		}
+	}
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::level::Level;
+
+/// Height above the floor at which trails are drawn.
+const TRAIL_HEIGHT: f32 = 0.05;
+
+/// Opacity of the oldest segment of a trail. Newer segments fade in toward
+/// full opacity.
+const MIN_TRAIL_ALPHA: f32 = 0.15;
+
+/// Whether character trails are shown.
+#[derive(Resource, Default)]
+pub struct ShowTrails(bool);
+
+/// Shows or hides character trails when T is pressed.
+pub fn toggle_trails(
+	keys: Res<ButtonInput<KeyCode>>,
+	mut show_trails: ResMut<ShowTrails>,
+) {
+	if keys.just_pressed(KeyCode::KeyT) {
+		show_trails.0 = !show_trails.0;
+	}
+}
+
+/// Draws a fading line along the path each character has taken.
+pub fn draw_trails(
+	show_trails: Res<ShowTrails>,
+	level: Res<Level>,
+	mut gizmos: Gizmos,
+) {
+	if !show_trails.0 {
+		return;
+	}
+	for trail in level.trails() {
+		let color = trail.color.color();
+		let segment_count = trail.coords.len() - 1;
+		for (idx, segment) in trail.coords.windows(2).enumerate() {
+			let age = 1.0 - (idx + 1) as f32 / segment_count as f32;
+			let alpha = 1.0 - age * (1.0 - MIN_TRAIL_ALPHA);
+			gizmos.line(
+				segment[0].transform(TRAIL_HEIGHT).translation,
+				segment[1].transform(TRAIL_HEIGHT).translation,
+				color.with_alpha(alpha),
+			);
+		}
+	}
+}
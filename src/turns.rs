@@ -0,0 +1,154 @@
+use std::{fmt::Write, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+	audio::{self, AudioTracks},
+	control::{ControlRejected, RejectionReason},
+	level::{ChangeEvent, Level},
+	photo_mode::HiddenInPhotoMode,
+};
+
+/// How long a turn takes to resolve, about as long as its animations.
+const RESOLUTION_DURATION: Duration = Duration::from_millis(300);
+
+/// How long the turn counter takes to fade back from a pulse.
+const PULSE_DURATION: Duration = Duration::from_millis(400);
+
+/// The turn counter's color between pulses.
+const BASE_COLOR: Color = Color::srgb(0.7, 0.7, 0.7);
+
+/// The turn counter's color at the start of a "turn resolved" pulse.
+const RESOLVED_COLOR: Color = Color::srgb(1.0, 0.85, 0.3);
+
+/// The turn counter's color at the start of a "planning" pulse.
+const PLANNING_COLOR: Color = Color::srgb(0.4, 0.7, 1.0);
+
+/// The turn counter's color at the start of a "portal limit reached" pulse.
+const LIMIT_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+
+/// The turn counter, which also shows whether the player is planning the next
+/// turn or watching the last one resolve.
+#[derive(Component, Default)]
+pub struct TurnCounter {
+	/// The turn shown.
+	turn: usize,
+	/// The earliest turn that can be undone back to. See
+	/// [`Level::undo_floor`].
+	undo_floor: usize,
+	/// The number of open portals and the most that can be open, if limited.
+	portals: (usize, Option<usize>),
+	/// Time left until the turn finishes resolving, if it's resolving.
+	resolving: Option<Timer>,
+	/// The fading pulse, with the color it started at.
+	pulse: Option<(Timer, Color)>,
+}
+
+impl TurnCounter {
+	fn pulse(&mut self, color: Color) {
+		self.pulse = Some((Timer::new(PULSE_DURATION, TimerMode::Once), color));
+	}
+
+	fn text(&self) -> String {
+		let phase = if self.resolving.is_some() {
+			"resolving"
+		} else {
+			"planning"
+		};
+		let mut text = format!("Turn {} - {phase}", self.turn);
+		if self.undo_floor > 0 {
+			write!(
+				text,
+				"\nOpen portal: can't undo past turn {}",
+				self.undo_floor
+			)
+			.unwrap();
+		}
+		if let (open, Some(max)) = self.portals {
+			write!(text, "\nPortals: {open}/{max} open").unwrap();
+		}
+		text
+	}
+}
+
+/// Spawns the turn counter in the bottom-right corner.
+pub fn spawn_turn_counter(mut commands: Commands) {
+	let counter = TurnCounter::default();
+	commands.spawn((
+		Text(counter.text()),
+		counter,
+		HiddenInPhotoMode,
+		TextFont {
+			font_size: 16.0,
+			..default()
+		},
+		TextColor(BASE_COLOR),
+		Node {
+			position_type: PositionType::Absolute,
+			bottom: Val::Px(44.0),
+			right: Val::Px(4.0),
+			..default()
+		},
+	));
+}
+
+/// Cues the phases of each turn. When a turn resolves, the turn counter
+/// flashes and a tick plays; once it's done resolving and a new planning round
+/// begins, the counter pulses a different color and a softer chime plays.
+/// Undoing and changing levels update the counter without either cue.
+///
+/// The counter also notes when an open portal has locked earlier turns under
+/// [`Rules::summons_lock_history`](crate::level::Rules::summons_lock_history),
+/// and shows how many portals are open under
+/// [`Rules::max_open_portals`](crate::level::Rules::max_open_portals),
+/// pulsing when a summon is refused for the limit.
+pub fn cue_turns(
+	mut commands: Commands,
+	level: Res<Level>,
+	tracks: Res<AudioTracks>,
+	time: Res<Time>,
+	mut change_events: EventReader<ChangeEvent>,
+	mut rejected_events: EventReader<ControlRejected>,
+	mut counter_query: Query<(&mut TurnCounter, &mut Text, &mut TextColor)>,
+) {
+	let changed = change_events.read().count() > 0;
+	let limit_reached = rejected_events
+		.read()
+		.any(|ControlRejected(reason)| *reason == RejectionReason::PortalLimit);
+	let portals = (level.open_portals(), level.rules().max_open_portals);
+	for (mut counter, mut text, mut color) in &mut counter_query {
+		if changed || level.turn() != counter.turn || portals != counter.portals
+		{
+			if changed && level.turn() > counter.turn {
+				counter.resolving =
+					Some(Timer::new(RESOLUTION_DURATION, TimerMode::Once));
+				counter.pulse(RESOLVED_COLOR);
+				audio::play_cue(&mut commands, &tracks, "tick");
+			} else {
+				counter.resolving = None;
+			}
+			counter.turn = level.turn();
+			counter.undo_floor = level.undo_floor();
+			counter.portals = portals;
+			**text = counter.text();
+		}
+		if limit_reached {
+			counter.pulse(LIMIT_COLOR);
+		}
+		if let Some(resolving) = &mut counter.resolving {
+			if resolving.tick(time.delta()).finished() {
+				counter.resolving = None;
+				counter.pulse(PLANNING_COLOR);
+				audio::play_cue(&mut commands, &tracks, "planning");
+				**text = counter.text();
+			}
+		}
+		if let Some((pulse, from)) = &mut counter.pulse {
+			pulse.tick(time.delta());
+			color.0 = from.mix(&BASE_COLOR, pulse.fraction());
+			if pulse.finished() {
+				counter.pulse = None;
+			}
+		}
+	}
+}
@@ -0,0 +1,398 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+	assets::GameAssets,
+	control::Action,
+	level::{make_level, Coords, Level, Object, Offset, Tile},
+	level_asset::Mechanic,
+	progress::Progress,
+	states::GameState,
+	thumbnails,
+};
+
+/// Where tutorial demos are staged, apart from the level being played and the
+/// other stages. Turns alternate between the two, so the turn being shown and
+/// the next one don't overlap.
+const STAGING_OFFSETS: [Vec3; 2] =
+	[Vec3::new(20_000.0, 0.0, 0.0), Vec3::new(30_000.0, 0.0, 0.0)];
+
+/// Width and height of a demo's view, in pixels.
+const VIEW_PIXELS: u32 = 256;
+
+/// Width and height of a demo's view on screen.
+const VIEW_SIZE: Val = Val::Px(256.0);
+
+/// Time between turns of a demo.
+const STEP_INTERVAL: Duration = Duration::from_millis(700);
+
+/// Frames to wait after staging a turn of a demo before showing it, so its
+/// scenes have time to spawn.
+const SETTLE_FRAMES: u32 = 3;
+
+/// An explanation of a mechanic, with a short demo of it in action.
+struct Tutorial {
+	mechanic: Mechanic,
+	title: &'static str,
+	text: &'static str,
+	/// The demo's map, in the format of [`make_level`].
+	map: &'static str,
+	/// Each character's action on each turn of the demo, in character order.
+	turns: &'static [&'static [Action]],
+}
+
+const TUTORIALS: [Tutorial; 4] = [
+	Tutorial {
+		mechanic: Mechanic::Summoning,
+		title: "Summoning",
+		text: "Hold Act and choose a direction to summon your future self \
+			through a portal. Your future self arrives as far as it can go.",
+		map: ".0. . . ",
+		turns: &[&[Action::Summon(Offset::RIGHT)]],
+	},
+	Tutorial {
+		mechanic: Mechanic::Returning,
+		title: "Returning",
+		text: "Once you've summoned your future self, you have to return \
+			through the portal yourself, to become the self you summoned.",
+		// The summon steps aside so its past self can reach the portal.
+		map: ".0. # \n# . # ",
+		turns: &[
+			&[Action::Summon(Offset::RIGHT)],
+			&[Action::Wait, Action::Push(Offset::DOWN)],
+			&[Action::Push(Offset::RIGHT), Action::Wait],
+			&[Action::Return, Action::Wait],
+		],
+	},
+	Tutorial {
+		mechanic: Mechanic::HeavyObjects,
+		title: "Steel crates",
+		text: "Steel crates are too heavy to push alone. It takes two \
+			characters pushing together to move one.",
+		map: ".0.1.Y. . ",
+		turns: &[
+			&[Action::Push(Offset::RIGHT), Action::Push(Offset::RIGHT)],
+			&[Action::Push(Offset::RIGHT), Action::Push(Offset::RIGHT)],
+		],
+	},
+	Tutorial {
+		mechanic: Mechanic::Sliding,
+		title: "Ice",
+		text: "Characters slide across ice until they reach solid ground or \
+			hit something. While sliding, they can't stop, turn, or push.",
+		map: ".0~ ~ . . ",
+		turns: &[
+			&[Action::Push(Offset::RIGHT)],
+			&[Action::Wait],
+			&[Action::Wait],
+		],
+	},
+];
+
+/// The demo of `mechanic`'s tutorial, if it has one: the level it starts
+/// from and each turn's actions.
+pub fn demo(
+	mechanic: Mechanic,
+) -> Option<(Level, &'static [&'static [Action]])> {
+	TUTORIALS
+		.iter()
+		.find(|tutorial| tutorial.mechanic == mechanic)
+		.map(|tutorial| (make_level(tutorial.map), tutorial.turns))
+}
+
+/// The mechanics with tutorials that `level` involves, found by inspecting
+/// its content: steel crates, ice, and sliding characters are in its layout,
+/// while summoning and returning are found in its solution, or an open portal
+/// for returning.
+pub fn mechanics_in(level: &Level) -> Vec<Mechanic> {
+	let solution_uses = |uses: fn(&Action) -> bool| {
+		level
+			.solution()
+			.is_some_and(|solution| solution.iter().flatten().any(uses))
+	};
+	let has_tile = |tile: Tile| {
+		(0..level.height()).any(|row| {
+			(0..level.width()).any(|col| {
+				level.tile_at(Coords::new(row as i32, col as i32)) == tile
+			})
+		})
+	};
+	let mut mechanics = Vec::new();
+	if solution_uses(|action| matches!(action, Action::Summon(_))) {
+		mechanics.push(Mechanic::Summoning);
+	}
+	if solution_uses(|action| *action == Action::Return)
+		|| level
+			.characters_by_id()
+			.any(|(_, character)| character.portal_coords.is_some())
+	{
+		mechanics.push(Mechanic::Returning);
+	}
+	if level
+		.iter_level_objects()
+		.any(|level_object| level_object.object == Object::STEEL_CRATE)
+	{
+		mechanics.push(Mechanic::HeavyObjects);
+	}
+	if has_tile(Tile::Ice)
+		|| level
+			.characters_by_id()
+			.any(|(_, character)| character.sliding)
+	{
+		mechanics.push(Mechanic::Sliding);
+	}
+	mechanics
+}
+
+/// Tutorials waiting to be shown, and the demo of the one showing.
+#[derive(Resource, Default)]
+pub struct Tutorials {
+	queue: VecDeque<Mechanic>,
+	demo: Option<Demo>,
+}
+
+/// A tutorial's demo, played on a loop.
+struct Demo {
+	start: Level,
+	level: Level,
+	turns: &'static [&'static [Action]],
+	/// The number of turns played since the demo last started over.
+	step: usize,
+	timer: Timer,
+	/// The index of the staging offset to use next.
+	offset_idx: usize,
+	/// The stage root and image of the turn being shown.
+	shown: Option<(Entity, Handle<Image>)>,
+	/// The stage root and image of the next turn to show, with the number of
+	/// frames since it was staged.
+	pending: Option<(Entity, Handle<Image>, u32)>,
+}
+
+/// Marker component for the tutorial popup.
+#[derive(Component)]
+pub struct TutorialPopup;
+
+/// Marker component for the view of a tutorial's demo.
+#[derive(Component)]
+pub struct DemoView;
+
+/// Queues a tutorial for each mechanic with one that a newly started level
+/// involves for the first time, and shows them.
+pub fn queue_tutorials(
+	level: Res<Level>,
+	mut progress: ResMut<Progress>,
+	mut tutorials: ResMut<Tutorials>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !level.is_changed() || level.turn() != 0 {
+		return;
+	}
+	let unexplained = progress.explain_mechanics(mechanics_in(&level));
+	if !unexplained.is_empty() {
+		tutorials.queue.extend(unexplained);
+		next_state.set(GameState::Tutorial);
+	}
+}
+
+/// Spawns the popup for the next queued tutorial, if there is one, and starts
+/// its demo. Returns whether there was one.
+fn show_next_tutorial(
+	commands: &mut Commands,
+	tutorials: &mut Tutorials,
+) -> bool {
+	let Some(mechanic) = tutorials.queue.pop_front() else {
+		return false;
+	};
+	let Some(tutorial) = TUTORIALS
+		.iter()
+		.find(|tutorial| tutorial.mechanic == mechanic)
+	else {
+		return show_next_tutorial(commands, tutorials);
+	};
+	if let Some((start, turns)) = demo(mechanic) {
+		tutorials.demo = Some(Demo {
+			level: start.clone(),
+			start,
+			turns,
+			step: 0,
+			timer: Timer::new(STEP_INTERVAL, TimerMode::Repeating),
+			offset_idx: 0,
+			shown: None,
+			pending: None,
+		});
+	}
+	commands
+		.spawn((
+			TutorialPopup,
+			Node {
+				position_type: PositionType::Absolute,
+				top: Val::Percent(20.0),
+				left: Val::Percent(30.0),
+				width: Val::Percent(40.0),
+				padding: UiRect::all(Val::Px(12.0)),
+				border: UiRect::all(Val::Px(2.0)),
+				row_gap: Val::Px(8.0),
+				flex_direction: FlexDirection::Column,
+				align_items: AlignItems::Center,
+				..default()
+			},
+			BorderColor(Color::srgb(1.0, 0.85, 0.3)),
+			BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.9)),
+		))
+		.with_children(|popup| {
+			popup.spawn((
+				Text::new(tutorial.title),
+				TextFont {
+					font_size: 20.0,
+					..default()
+				},
+			));
+			popup.spawn((
+				DemoView,
+				ImageNode::default(),
+				Node {
+					width: VIEW_SIZE,
+					height: VIEW_SIZE,
+					..default()
+				},
+			));
+			popup.spawn((
+				Text::new(format!("{}\n\nEnter: continue", tutorial.text)),
+				TextFont {
+					font_size: 16.0,
+					..default()
+				},
+			));
+		});
+	true
+}
+
+/// Despawns the tutorial popup and its demo's stages.
+fn close_tutorial(
+	commands: &mut Commands,
+	tutorials: &mut Tutorials,
+	popup_query: &Query<Entity, With<TutorialPopup>>,
+) {
+	for entity in popup_query {
+		commands.entity(entity).despawn_recursive();
+	}
+	if let Some(demo) = tutorials.demo.take() {
+		let pending = demo.pending.map(|(root, _, _)| root);
+		for root in demo.shown.map(|(root, _)| root).into_iter().chain(pending)
+		{
+			commands.entity(root).despawn_recursive();
+		}
+	}
+}
+
+/// Shows the first queued tutorial.
+pub fn spawn_tutorial(
+	mut commands: Commands,
+	mut tutorials: ResMut<Tutorials>,
+	mut next_state: ResMut<NextState<GameState>>,
+) {
+	if !show_next_tutorial(&mut commands, &mut tutorials) {
+		next_state.set(GameState::Playing);
+	}
+}
+
+/// Despawns the tutorial being shown, and forgets any still queued.
+pub fn despawn_tutorial(
+	mut commands: Commands,
+	mut tutorials: ResMut<Tutorials>,
+	popup_query: Query<Entity, With<TutorialPopup>>,
+) {
+	close_tutorial(&mut commands, &mut tutorials, &popup_query);
+	tutorials.queue.clear();
+}
+
+/// What it takes to render a turn of a demo.
+#[derive(SystemParam)]
+pub struct DemoStage<'w> {
+	images: ResMut<'w, Assets<Image>>,
+	assets: Res<'w, GameAssets>,
+}
+
+/// The tutorial popup and the view of its demo.
+#[derive(SystemParam)]
+pub struct TutorialNodes<'w, 's> {
+	popup_query: Query<'w, 's, Entity, With<TutorialPopup>>,
+	view_query: Query<'w, 's, &'static mut ImageNode, With<DemoView>>,
+}
+
+/// Plays the demo of the tutorial being shown, restaging it after each turn,
+/// and moves on to the next tutorial, or back to the level, when Enter or
+/// Escape is pressed.
+pub fn play_tutorial(
+	mut commands: Commands,
+	time: Res<Time>,
+	keys: Res<ButtonInput<KeyCode>>,
+	mut tutorials: ResMut<Tutorials>,
+	stage: DemoStage,
+	mut next_state: ResMut<NextState<GameState>>,
+	nodes: TutorialNodes,
+) {
+	let DemoStage { mut images, assets } = stage;
+	let TutorialNodes {
+		popup_query,
+		mut view_query,
+	} = nodes;
+	if keys.just_pressed(KeyCode::Enter) || keys.just_pressed(KeyCode::Escape) {
+		close_tutorial(&mut commands, &mut tutorials, &popup_query);
+		if !show_next_tutorial(&mut commands, &mut tutorials) {
+			next_state.set(GameState::Playing);
+		}
+		return;
+	}
+	let Some(demo) = &mut tutorials.demo else {
+		return;
+	};
+	// Show the next turn once it's settled, in place of the last one.
+	if let Some((root, image, frames)) = &mut demo.pending {
+		*frames += 1;
+		if *frames >= SETTLE_FRAMES {
+			for mut view in &mut view_query {
+				view.image = image.clone();
+			}
+			if let Some((shown, _)) = demo.shown.take() {
+				commands.entity(shown).despawn_recursive();
+			}
+			demo.shown = Some((*root, image.clone()));
+			demo.pending = None;
+		}
+		return;
+	}
+	// Stage the start right away, and each turn after that on a timer.
+	if demo.shown.is_some() && !demo.timer.tick(time.delta()).just_finished() {
+		return;
+	}
+	if demo.shown.is_some() {
+		match demo.turns.get(demo.step) {
+			Some(actions) => {
+				let actors = demo
+					.level
+					.characters_by_id()
+					.map(|(id, _)| *id)
+					.zip(actions.iter().copied())
+					.collect();
+				demo.level.update(actors);
+				demo.step += 1;
+			}
+			None => {
+				demo.level = demo.start.clone();
+				demo.step = 0;
+			}
+		}
+	}
+	let image = images.add(thumbnails::render_target(VIEW_PIXELS));
+	let root = thumbnails::stage(
+		&mut commands,
+		&demo.level,
+		STAGING_OFFSETS[demo.offset_idx],
+		&image,
+		&assets,
+	);
+	demo.pending = Some((root, image, 0));
+	demo.offset_idx = 1 - demo.offset_idx;
+}
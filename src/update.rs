@@ -1,8 +1,11 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 
 use crate::{
+	autopilot::Autopilot,
 	control::{Action, ControlEvent},
-	level::{ChangeEvent, Character, Id, Level},
+	level::{ChangeEvent, Character, Coords, Id, Level, Offset},
+	protocol::legal_actions,
+	settings::Settings,
 };
 
 /// The next character to act.
@@ -10,6 +13,39 @@ use crate::{
 pub struct NextActor {
 	pub id: Id,
 	pub character: Character,
+	pub facing: Offset,
+	/// Whether waiting is the actor's only meaningful option. See
+	/// [`Level::is_stuck`].
+	pub stuck: bool,
+}
+
+impl NextActor {
+	/// The `idx`-th character in `level` as the next actor, if there is one.
+	pub fn nth(level: &Level, idx: usize) -> Option<NextActor> {
+		level
+			.characters_by_id()
+			.nth(idx)
+			.map(|(&id, &character)| NextActor {
+				id,
+				character,
+				facing: level.facing(&id),
+				stuck: level.is_stuck(&id),
+			})
+	}
+}
+
+/// Sent after a seek of more than one turn, listing the coordinates whose
+/// contents differ from before the seek.
+#[derive(Event)]
+pub struct SeekRecapEvent {
+	pub changed_coords: Vec<Coords>,
+}
+
+/// Sent when the level's win prerequisites become met, or unmet again, as by
+/// an undo. See [`Level::is_complete`].
+#[derive(Event)]
+pub struct LevelStatusEvent {
+	pub complete: bool,
 }
 
 /// Local state for the update system, to store queued actions.
@@ -19,24 +55,36 @@ pub struct UpdateState {
 	queue: Vec<(Id, Action)>,
 }
 
-/// Consumes control events to update the level and produces change events.
+/// The events besides [`ChangeEvent`]s that the [`update`] system sends.
+#[derive(SystemParam)]
+pub struct UpdateNotices<'w> {
+	next_actors: EventWriter<'w, NextActor>,
+	recap_events: EventWriter<'w, SeekRecapEvent>,
+	status_events: EventWriter<'w, LevelStatusEvent>,
+}
+
+/// Consumes control events to update the level and produces change events,
+/// along with status events when the level's status changes.
 pub fn update(
 	mut state: Local<UpdateState>,
 	mut level: ResMut<Level>,
+	mut autopilot: ResMut<Autopilot>,
+	settings: Res<Settings>,
 	mut control_events: EventReader<ControlEvent>,
-	mut next_actors: EventWriter<NextActor>,
 	mut change_events: EventWriter<ChangeEvent>,
+	mut notices: UpdateNotices,
 ) {
+	let complete = level.is_complete();
 	for control_event in control_events.read() {
 		match control_event {
 			ControlEvent::Act(character_action) => {
-				state.queue.push(*character_action);
-				// If all characters have queued actions, execute the turn.
-				if state.queue.len() == level.character_count() {
-					let actions = Vec::from_iter(state.queue.drain(..));
-					let change_event = level.update(actions);
-					change_events.send(change_event);
-				}
+				queue_action(
+					&mut state,
+					&mut level,
+					&mut autopilot,
+					&mut change_events,
+					*character_action,
+				);
 			}
 			ControlEvent::Undo => {
 				if let Some(change) = level.undo() {
@@ -50,12 +98,178 @@ pub fn update(
 					change_events.send(change_event);
 				}
 			}
+			ControlEvent::Edit { coords, object } => {
+				if let Some(change_event) = level.edit(*coords, *object) {
+					state.queue.clear();
+					change_events.send(change_event);
+				}
+			}
+			ControlEvent::UndoAll
+			| ControlEvent::RedoAll
+			| ControlEvent::Seek(_) => {
+				let target = match *control_event {
+					ControlEvent::UndoAll => 0,
+					ControlEvent::Seek(turn) => turn,
+					_ => usize::MAX,
+				};
+				let before = level.snapshot();
+				let mut turns = 0;
+				while level.turn() != target {
+					let seek = if level.turn() > target {
+						Level::undo
+					} else {
+						Level::redo
+					};
+					let Some(change_event) = seek(&mut level) else {
+						break;
+					};
+					change_events.send(change_event);
+					turns += 1;
+				}
+				if turns > 0 {
+					state.queue.clear();
+				}
+				if turns > 1 {
+					notices.recap_events.send(SeekRecapEvent {
+						changed_coords: level.changed_coords(&before),
+					});
+				}
+			}
+		}
+		// Let the autopilot act for any scripted characters next in line, and
+		// if enabled, end the turn for a last character with no real choice.
+		while let Some(character_action) = autopilot
+			.scripted_action(&level, state.queue.len())
+			.or_else(|| {
+				settings
+					.auto_end_turn
+					.then(|| forced_last_action(&level, &state.queue))
+					.flatten()
+			}) {
+			queue_action(
+				&mut state,
+				&mut level,
+				&mut autopilot,
+				&mut change_events,
+				character_action,
+			);
 		}
 		// Send the next actor to the control and animation systems.
-		let (&id, &character) = level
-			.characters_by_id()
-			.nth(state.queue.len())
+		let next_actor = NextActor::nth(&level, state.queue.len())
 			.expect("character out of bounds");
-		next_actors.send(NextActor { id, character });
+		notices.next_actors.send(next_actor);
+	}
+	if level.is_complete() != complete {
+		notices.status_events.send(LevelStatusEvent {
+			complete: !complete,
+		});
+	}
+}
+
+/// The last character to act and its only choice, if the actions in `queue`
+/// cover every other character: its one [legal action](legal_actions), like
+/// continuing to slide, or waiting when it's [stuck](Level::is_stuck) and no
+/// push would succeed alongside the queued actions. Turns with nothing queued
+/// yet are left alone, so a lone character is never moved on its own.
+fn forced_last_action(
+	level: &Level,
+	queue: &[(Id, Action)],
+) -> Option<(Id, Action)> {
+	if queue.is_empty() || queue.len() + 1 != level.character_count() {
+		return None;
+	}
+	let actor = NextActor::nth(level, queue.len())?;
+	let legal = legal_actions(&actor);
+	if let [action] = legal[..] {
+		return Some((actor.id, action));
+	}
+	// Being stuck assumes no other character moves, but the queued actions
+	// might clear the way for a push.
+	let can_push = legal.iter().any(|&action| {
+		let Action::Push(_) = action else {
+			return false;
+		};
+		let mut actors = queue.to_vec();
+		actors.push((actor.id, action));
+		!level.preview(actors).blocked.contains(&actor.id)
+	});
+	(actor.stuck && !can_push && legal.contains(&Action::Wait))
+		.then_some((actor.id, Action::Wait))
+}
+
+/// Queues a character's action, executing the turn once every character has
+/// an action queued.
+fn queue_action(
+	state: &mut UpdateState,
+	level: &mut Level,
+	autopilot: &mut Autopilot,
+	change_events: &mut EventWriter<ChangeEvent>,
+	character_action: (Id, Action),
+) {
+	state.queue.push(character_action);
+	if state.queue.len() == level.character_count() {
+		let actions = Vec::from_iter(state.queue.drain(..));
+		autopilot.record(level, &actions);
+		change_events.send(level.update(actions));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::level::make_level;
+
+	/// The ID of the `idx`-th character in `level`.
+	fn nth_id(level: &Level, idx: usize) -> Id {
+		NextActor::nth(level, idx).unwrap().id
+	}
+
+	#[test]
+	fn sliding_last_actor_continues_sliding() {
+		let level = make_level("# # # # # \n# .0~b~ # \n# # # # # ");
+		let id = nth_id(&level, 1);
+		let facing = level.facing(&id);
+		assert!(
+			forced_last_action(&level, &[(nth_id(&level, 0), Action::Wait)])
+				== Some((id, Action::Push(facing)))
+		);
+	}
+
+	#[test]
+	fn stuck_last_actor_waits() {
+		let level = make_level("# # # # # \n# .1# .0# \n# # # # # ");
+		let queue = [(nth_id(&level, 0), Action::Wait)];
+		let id = nth_id(&level, 1);
+		assert!(forced_last_action(&level, &queue) == Some((id, Action::Wait)));
+	}
+
+	#[test]
+	fn last_actor_is_not_forced_when_the_way_clears() {
+		let level = make_level(
+			"# # # # \n\
+			 # .0. # \n\
+			 # .1# # \n\
+			 # # # # ",
+		);
+		let id = nth_id(&level, 1);
+		// Boxed in while the other character stays put...
+		let queue = [(nth_id(&level, 0), Action::Wait)];
+		assert!(forced_last_action(&level, &queue) == Some((id, Action::Wait)));
+		// ...but free to follow it once it steps aside.
+		let queue = [(nth_id(&level, 0), Action::Push(Offset::RIGHT))];
+		assert!(forced_last_action(&level, &queue).is_none());
+	}
+
+	#[test]
+	fn last_actor_with_choices_is_not_forced() {
+		let level = make_level("# # # # # \n# .0.1. # \n# # # # # ");
+		let queue = [(nth_id(&level, 0), Action::Wait)];
+		assert!(forced_last_action(&level, &queue).is_none());
+	}
+
+	#[test]
+	fn lone_actor_is_not_forced() {
+		let level = make_level("# # # # \n# .a~ # \n# # # # ");
+		assert!(forced_last_action(&level, &[]).is_none());
 	}
 }